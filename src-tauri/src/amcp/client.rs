@@ -83,8 +83,23 @@ impl AmcpClient {
 
         // Check if multi-line response
         if code >= 200 && code < 300 {
-            // Success with possible data
-            if response_line.ends_with(" OK") || message.is_empty() {
+            // 201 carries exactly one data line (e.g. VERSION's version string).
+            // It must be consumed here, or it is read as the next command's reply.
+            if code == 201 {
+                let mut line = String::new();
+                reader.read_line(&mut line).await.map_err(|e| {
+                    AmcpError::Receive(format!("Failed to read data: {}", e))
+                })?;
+                let line = line.trim_end();
+                return Ok(AmcpResponse {
+                    code,
+                    message: message.to_string(),
+                    data: if line.is_empty() { None } else { Some(line.to_string()) },
+                });
+            }
+
+            // Success with possible data; only 200 is followed by a data block
+            if code != 200 || message.is_empty() {
                 return Ok(AmcpResponse {
                     code,
                     message: message.to_string(),
@@ -2,6 +2,7 @@
 // High-level wrappers for common AMCP commands
 
 use super::{AmcpClient, AmcpError, AmcpResponse};
+use crate::ident::SlateClips;
 use serde::{Deserialize, Serialize};
 
 /// System information from CasparCG INFO SYSTEM command
@@ -14,6 +15,7 @@ pub struct SystemInfo {
 // Test pattern layers - high numbers to avoid conflicts with production content
 const TEST_FILL_LAYER: u32 = 20;
 const TEST_KEY_LAYER: u32 = 19;
+const TEST_AUDIO_LAYER: u32 = 18;
 
 impl AmcpClient {
     // ═══════════════════════════════════════════════════════════════
@@ -21,29 +23,58 @@ impl AmcpClient {
     // Load fill/key test patterns for visual verification
     // ═══════════════════════════════════════════════════════════════
 
-    /// Start a channel test by putting a solid colour fill on the channel.
+    /// Start a channel test by playing the channel's identification slate:
+    /// the key still on the key layer, keyed onto the fill still above it with
+    /// `MIXER KEYER`, so fill and key outputs both show which channel this is.
     ///
     /// This deliberately does NOT use the HTML identifier: CasparCG's CEF/HTML
     /// producer crashes the server outright on some setups (the process vanishes
     /// right after the PLAY is acknowledged), which made "Test" kill the server
-    /// and show nothing. The colour producer is a plain, CEF-free path that
-    /// reliably reaches the SDI output, so Test answers the only question it needs
-    /// to — "is this channel actually producing output?" — without risking the
-    /// server. The HTML identifier still renders in the in-app Preview tab, which
-    /// uses the system webview rather than CasparCG's CEF.
+    /// and show nothing. Stills go through the image producer, a plain CEF-free
+    /// path. Without a slate (none could be written) the channel gets the old
+    /// solid colour, which at least proves it is producing output.
     ///
-    /// `test_server_url` is unused now but kept in the signature for callers.
+    /// `audio` is a tone clip looped on the layer below, so routing can be
+    /// checked by ear or on a meter at the same time.
     pub async fn start_channel_test(
         &self,
         channel: u32,
-        _test_server_url: &str,
+        slate: Option<&SlateClips>,
+        audio: Option<&str>,
     ) -> Result<(), AmcpError> {
-        // Magenta — unmistakably a test signal, and easy to spot on a scope.
-        let cmd = format!("PLAY {}-{} #FFFF00FF", channel, TEST_FILL_LAYER);
+        let mut commands = match slate {
+            Some(slate) => vec![
+                format!("PLAY {}-{} \"{}\"", channel, TEST_KEY_LAYER, slate.key),
+                format!("MIXER {}-{} KEYER 1", channel, TEST_KEY_LAYER),
+                format!("PLAY {}-{} \"{}\"", channel, TEST_FILL_LAYER, slate.fill),
+            ],
+            // Magenta — unmistakably a test signal, and easy to spot on a scope.
+            None => vec![format!("PLAY {}-{} #FFFF00FF", channel, TEST_FILL_LAYER)],
+        };
+        if let Some(audio) = audio {
+            commands.push(format!("PLAY {}-{} \"{}\" LOOP", channel, TEST_AUDIO_LAYER, audio));
+        }
+        for cmd in commands {
+            let response = self.send_command(&cmd).await?;
+            if !response.is_success() {
+                return Err(AmcpError::Protocol(format!(
+                    "Failed to load test pattern: {} {}",
+                    response.code, response.message
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Loop an AV sync clip on the channel's fill layer in place of the
+    /// slate. Stopped like any channel test.
+    pub async fn start_av_sync(&self, channel: u32, clip: &str) -> Result<(), AmcpError> {
+        let cmd = format!("PLAY {}-{} \"{}\" LOOP", channel, TEST_FILL_LAYER, clip);
         let response = self.send_command(&cmd).await?;
         if !response.is_success() {
             return Err(AmcpError::Protocol(format!(
-                "Failed to load test pattern: {} {}",
+                "Failed to play AV sync clip: {} {}",
                 response.code, response.message
             )));
         }
@@ -57,9 +88,16 @@ impl AmcpClient {
         let clear_fill = format!("CLEAR {}-{}", channel, TEST_FILL_LAYER);
         self.send_command(&clear_fill).await?;
 
-        // Clear layer 19 (key)
+        // Clear layer 19 (key), and its keyer so the layer above is not left
+        // keyed by an empty layer
         let clear_key = format!("CLEAR {}-{}", channel, TEST_KEY_LAYER);
         self.send_command(&clear_key).await?;
+        let reset_keyer = format!("MIXER {}-{} KEYER 0", channel, TEST_KEY_LAYER);
+        self.send_command(&reset_keyer).await?;
+
+        // Clear layer 18 (tone)
+        let clear_audio = format!("CLEAR {}-{}", channel, TEST_AUDIO_LAYER);
+        self.send_command(&clear_audio).await?;
 
         Ok(())
     }
@@ -73,6 +111,42 @@ impl AmcpClient {
         Ok(())
     }
 
+    // ═══════════════════════════════════════════════════════════════
+    // TEMPLATE (CG) COMMANDS
+    // Load and refresh an HTML template on a channel-layer
+    // ═══════════════════════════════════════════════════════════════
+
+    /// `CG ADD` `template` (a name or URL) on CG layer 1 and play it, with
+    /// `data` as its initial data
+    pub async fn cg_add(&self, channel: u32, layer: u32, template: &str, data: &str) -> Result<(), AmcpError> {
+        let cmd = format!("CG {}-{} ADD 1 {} 1 {}", channel, layer, quote(template), quote(data));
+        self.cg(&cmd).await
+    }
+
+    /// `CG UPDATE` the template on CG layer 1 with `data`
+    pub async fn cg_update(&self, channel: u32, layer: u32, data: &str) -> Result<(), AmcpError> {
+        let cmd = format!("CG {}-{} UPDATE 1 {}", channel, layer, quote(data));
+        self.cg(&cmd).await
+    }
+
+    /// Remove every template from the channel-layer
+    pub async fn cg_clear(&self, channel: u32, layer: u32) -> Result<(), AmcpError> {
+        self.cg(&format!("CG {}-{} CLEAR", channel, layer)).await
+    }
+
+    async fn cg(&self, cmd: &str) -> Result<(), AmcpError> {
+        let response = self.send_command(cmd).await?;
+        if !response.is_success() {
+            return Err(AmcpError::Protocol(format!(
+                "{} failed: {} {}",
+                cmd.split(' ').take(3).collect::<Vec<_>>().join(" "),
+                response.code,
+                response.message
+            )));
+        }
+        Ok(())
+    }
+
     // ═══════════════════════════════════════════════════════════════
     // INFO AND VERSION COMMANDS
     // ═══════════════════════════════════════════════════════════════
@@ -237,6 +311,17 @@ fn extract_xml_value(xml: &str, tag: &str) -> Option<String> {
     Some(xml[start..end].trim().to_string())
 }
 
+/// An AMCP string parameter: quoted, with backslashes and quotes escaped and
+/// line breaks as `\n`, so JSON or XML data stays one parameter on one line
+fn quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -259,4 +344,13 @@ mod tests {
         assert_eq!(extract_xml_value(xml, "version"), Some("2.5.0".to_string()));
         assert_eq!(extract_xml_value(xml, "channels"), Some("2".to_string()));
     }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("lower-third"), "\"lower-third\"");
+        assert_eq!(
+            quote("{\"f0\": \"C:\\\\x\"}\r\n"),
+            "\"{\\\"f0\\\": \\\"C:\\\\\\\\x\\\"}\\n\""
+        );
+    }
 }
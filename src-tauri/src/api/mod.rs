@@ -0,0 +1,8 @@
+// REST control API
+// Token-authenticated HTTP access to the server, profiles, DeckLink and AMCP
+
+mod routes;
+mod server;
+
+pub use routes::*;
+pub use server::*;
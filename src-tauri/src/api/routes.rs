@@ -0,0 +1,419 @@
+// REST API routes
+// Handlers over the shared control operations, behind bearer-token auth
+
+use std::sync::Arc;
+
+use axum::{
+    body::Bytes,
+    extract::{ws::WebSocketUpgrade, Path, Query, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::config::GlobalConfig;
+use crate::control::Control;
+use crate::decklink;
+use crate::events::{events_socket, EventsQuery};
+
+/// OpenAPI 3 description of every route below
+pub const OPENAPI: &str = include_str!("openapi.json");
+
+/// An error reply: `{"error": "..."}` with a status code
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+fn bad_request(message: String) -> ApiError {
+    ApiError(StatusCode::BAD_REQUEST, message)
+}
+
+fn conflict(message: String) -> ApiError {
+    ApiError(StatusCode::CONFLICT, message)
+}
+
+/// 403 for a route that changes anything when the GUI was started read-only
+fn writable(control: &Control) -> Result<(), ApiError> {
+    control.writable().map_err(|e| ApiError(StatusCode::FORBIDDEN, e))
+}
+
+type ApiResult = Result<Json<Value>, ApiError>;
+
+fn reply<T: serde::Serialize>(value: T) -> ApiResult {
+    serde_json::to_value(value)
+        .map(Json)
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// The token a route accepts, and whether it may come as `?token=` — only for
+/// the event WebSocket, as browsers cannot set headers on one.
+#[derive(Clone)]
+struct Auth {
+    token: Arc<str>,
+    query: bool,
+}
+
+/// Build the control API. Everything under `/api/v1` except `openapi.json`
+/// needs `Authorization: Bearer <token>`.
+pub fn router(control: Control, token: String) -> Router {
+    let token: Arc<str> = token.into();
+    let header_auth = Auth {
+        token: token.clone(),
+        query: false,
+    };
+    let query_auth = Auth { token, query: true };
+    let events = Router::new()
+        .route("/events", get(events))
+        .route_layer(middleware::from_fn_with_state(query_auth, require_token))
+        .with_state(control.clone());
+    let protected = Router::new()
+        .route("/status", get(status))
+        .route("/server/start", post(start_server))
+        .route("/server/stop", post(stop_server))
+        .route("/server/auto-start/cancel", post(cancel_auto_start))
+        .route("/profiles", get(list_profiles))
+        .route("/profiles/{name}", get(get_profile).put(put_profile))
+        .route("/profiles/{name}/activate", post(activate_profile))
+        .route("/decklink/devices", get(decklink_devices))
+        .route("/decklink/devices/{index}/status", get(decklink_status))
+        .route("/system/versions", get(system_versions))
+        .route("/resources", get(resources))
+        .route("/amcp", post(amcp))
+        .route_layer(middleware::from_fn_with_state(header_auth, require_token))
+        .with_state(control);
+
+    Router::new().nest(
+        "/api/v1",
+        protected.merge(events).route("/openapi.json", get(openapi)),
+    )
+}
+
+async fn require_token(State(auth): State<Auth>, request: Request, next: Next) -> Response {
+    let header = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    let query = request
+        .uri()
+        .query()
+        .filter(|_| auth.query)
+        .and_then(|q| q.split('&').find_map(|pair| pair.strip_prefix("token=")));
+    match header.or(query) {
+        Some(presented) if tokens_match(presented, &auth.token) => next.run(request).await,
+        _ => ApiError(StatusCode::UNAUTHORIZED, "Missing or wrong API token".to_string())
+            .into_response(),
+    }
+}
+
+/// Compare without returning early, so timing says nothing about the token
+fn tokens_match(presented: &str, token: &str) -> bool {
+    presented.len() == token.len()
+        && presented
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn openapi() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "application/json")], OPENAPI)
+}
+
+async fn status(State(control): State<Control>) -> ApiResult {
+    reply(control.status().await)
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StartRequest {
+    profile: Option<String>,
+}
+
+async fn start_server(State(control): State<Control>, body: Bytes) -> ApiResult {
+    writable(&control)?;
+    // The body is optional: without one the active profile starts.
+    let request: StartRequest = if body.is_empty() {
+        StartRequest::default()
+    } else {
+        serde_json::from_slice(&body).map_err(|e| bad_request(e.to_string()))?
+    };
+    let name = control
+        .start_profile(request.profile.as_deref())
+        .await
+        .map_err(conflict)?;
+    reply(json!({ "started": name }))
+}
+
+async fn stop_server(State(control): State<Control>) -> ApiResult {
+    writable(&control)?;
+    control.stop_server().await.map_err(conflict)?;
+    reply(json!({ "stopped": true }))
+}
+
+async fn cancel_auto_start(State(control): State<Control>) -> ApiResult {
+    writable(&control)?;
+    reply(json!({ "cancelled": control.cancel_auto_start() }))
+}
+
+async fn list_profiles(State(control): State<Control>) -> ApiResult {
+    reply(control.list_profiles().await.map_err(bad_request)?)
+}
+
+/// 404 unless `name` is a saved profile
+async fn existing(control: &Control, name: &str) -> Result<(), ApiError> {
+    let profiles = control.list_profiles().await.map_err(bad_request)?;
+    if profiles.iter().any(|p| p == name) {
+        Ok(())
+    } else {
+        Err(ApiError(StatusCode::NOT_FOUND, format!("Profile '{}' not found", name)))
+    }
+}
+
+async fn get_profile(State(control): State<Control>, Path(name): Path<String>) -> ApiResult {
+    existing(&control, &name).await?;
+    reply(control.load_profile(&name).await.map_err(bad_request)?)
+}
+
+async fn put_profile(
+    State(control): State<Control>,
+    Path(name): Path<String>,
+    Json(config): Json<GlobalConfig>,
+) -> ApiResult {
+    writable(&control)?;
+    control.save_profile(&name, config).await.map_err(bad_request)?;
+    reply(json!({ "saved": name }))
+}
+
+async fn activate_profile(State(control): State<Control>, Path(name): Path<String>) -> ApiResult {
+    writable(&control)?;
+    existing(&control, &name).await?;
+    let restarted = control.activate_profile(&name).await.map_err(conflict)?;
+    reply(json!({ "active": name, "restarted": restarted }))
+}
+
+/// Run a DeckLink driver call off the runtime; a failure is a 400
+async fn driver<T: Send + 'static>(
+    call: impl FnOnce() -> Result<T, decklink::DeckLinkError> + Send + 'static,
+) -> Result<T, ApiError> {
+    tokio::task::spawn_blocking(call)
+        .await
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(|e| bad_request(e.to_string()))
+}
+
+async fn decklink_devices() -> ApiResult {
+    reply(driver(decklink::list_devices).await?)
+}
+
+async fn decklink_status(Path(index): Path<u32>) -> ApiResult {
+    reply(driver(move || decklink::get_device_status(index)).await?)
+}
+
+async fn system_versions(State(control): State<Control>) -> ApiResult {
+    reply(control.system_versions().await)
+}
+
+#[derive(Debug, Deserialize)]
+struct ResourcesQuery {
+    since: Option<DateTime<Utc>>,
+}
+
+async fn resources(
+    State(control): State<Control>,
+    Query(query): Query<ResourcesQuery>,
+) -> ApiResult {
+    reply(control.resources(query.since))
+}
+
+async fn events(
+    State(control): State<Control>,
+    Query(query): Query<EventsQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    events_socket(upgrade, query, control.events.clone())
+}
+
+#[derive(Debug, Deserialize)]
+struct AmcpRequest {
+    command: String,
+}
+
+async fn amcp(State(control): State<Control>, Json(request): Json<AmcpRequest>) -> ApiResult {
+    writable(&control)?;
+    control
+        .ensure_amcp()
+        .await
+        .map_err(|e| ApiError(StatusCode::BAD_GATEWAY, e))?;
+    let response = control
+        .amcp_command(&request.command)
+        .await
+        .map_err(|e| ApiError(StatusCode::BAD_GATEWAY, e))?;
+    reply(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GuiSettings;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn control(caspar_dir: &std::path::Path) -> Control {
+        let settings = GuiSettings {
+            caspar_path: Some(caspar_dir.display().to_string()),
+            ..GuiSettings::default()
+        };
+        Control::for_tests(settings)
+    }
+
+    fn call(app: &Router, uri: &str, token: Option<&str>) -> (StatusCode, Value) {
+        let mut request = Request::builder().uri(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let request = request.body(Body::empty()).unwrap();
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let response = app.clone().oneshot(request).await.unwrap();
+            let status = response.status();
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+        })
+    }
+
+    #[test]
+    fn requests_need_the_token_and_reach_the_profiles() {
+        let dir = std::env::temp_dir().join(format!("caspar-api-test-{}", std::process::id()));
+        let profiles = dir.join("caspar-gui-profiles");
+        std::fs::create_dir_all(&profiles).unwrap();
+        GlobalConfig::new("Studio A").save_to_file(&profiles.join("studio-a.json")).unwrap();
+        let app = router(control(&dir), "secret".to_string());
+
+        assert_eq!(call(&app, "/api/v1/profiles", None).0, StatusCode::UNAUTHORIZED);
+        assert_eq!(call(&app, "/api/v1/profiles", Some("wrong")).0, StatusCode::UNAUTHORIZED);
+
+        let (status, body) = call(&app, "/api/v1/profiles", Some("secret"));
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!(["studio-a"]));
+
+        let (status, body) = call(&app, "/api/v1/profiles/studio-a", Some("secret"));
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["name"], "Studio A");
+        assert_eq!(
+            call(&app, "/api/v1/profiles/missing", Some("secret")).0,
+            StatusCode::NOT_FOUND
+        );
+
+        let (status, body) = call(&app, "/api/v1/status", Some("secret"));
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["running"], false);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    // Reading still works on a read-only launch; changing anything is refused.
+    #[test]
+    fn a_read_only_gui_refuses_changes() {
+        let app = router(
+            Control {
+                read_only: true,
+                ..control(&std::env::temp_dir())
+            },
+            "secret".to_string(),
+        );
+        assert_eq!(call(&app, "/api/v1/status", Some("secret")).0, StatusCode::OK);
+        let profile = serde_json::to_string(&GlobalConfig::new("Studio A")).unwrap();
+        for (method, uri, body) in [
+            ("POST", "/api/v1/server/start", ""),
+            ("POST", "/api/v1/server/stop", ""),
+            ("POST", "/api/v1/server/auto-start/cancel", ""),
+            ("PUT", "/api/v1/profiles/studio-a", profile.as_str()),
+            ("POST", "/api/v1/profiles/studio-a/activate", ""),
+            ("POST", "/api/v1/amcp", r#"{"command":"INFO"}"#),
+        ] {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::AUTHORIZATION, "Bearer secret")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let status = tokio::runtime::Runtime::new()
+                .unwrap()
+                .block_on(app.clone().oneshot(request))
+                .unwrap()
+                .status();
+            assert_eq!(status, StatusCode::FORBIDDEN, "{method} {uri}");
+        }
+    }
+
+    // A dashboard connects with the token in the query, gets the latest
+    // matching event replayed, then live ones, filtered by topic.
+    #[test]
+    fn event_socket_replays_then_streams_matching_topics() {
+        let control = control(&std::env::temp_dir());
+        let bus = control.events.clone();
+        bus.publish("diagnostic", json!({ "id": "old" }));
+        bus.publish("caspar-log", json!({ "text": "hidden" }));
+        bus.publish("diagnostic", json!({ "id": "latest" }));
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let addr = rt.block_on(async {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let app = router(control, "secret".to_string());
+            tokio::spawn(async move { axum::serve(listener, app).await.ok() });
+            addr
+        });
+
+        let url = format!("ws://{addr}/api/v1/events?topics=diagnostic,server-*&replay=1");
+        assert!(tungstenite::connect(&url).is_err(), "connected without a token");
+
+        let (mut socket, _) = tungstenite::connect(format!("{url}&token=secret")).unwrap();
+        let mut next = || -> Value {
+            serde_json::from_str(socket.read().unwrap().to_text().unwrap()).unwrap()
+        };
+        assert_eq!(next()["payload"]["id"], "latest");
+
+        bus.publish("caspar-log", json!({ "text": "still hidden" }));
+        bus.publish("server-exit", json!({ "code": 3 }));
+        let live = next();
+        assert_eq!(live["topic"], "server-exit");
+        assert_eq!(live["seq"], 5);
+    }
+
+    // The description is public and must cover every route the router serves.
+    #[test]
+    fn openapi_is_public_and_lists_every_route() {
+        let app = router(control(&std::env::temp_dir()), "secret".to_string());
+        let (status, spec) = call(&app, "/api/v1/openapi.json", None);
+        assert_eq!(status, StatusCode::OK);
+        let paths = spec["paths"].as_object().unwrap();
+        for path in [
+            "/status",
+            "/server/start",
+            "/server/stop",
+            "/server/auto-start/cancel",
+            "/profiles",
+            "/profiles/{name}",
+            "/profiles/{name}/activate",
+            "/decklink/devices",
+            "/decklink/devices/{index}/status",
+            "/system/versions",
+            "/resources",
+            "/amcp",
+            "/events",
+        ] {
+            assert!(paths.contains_key(path), "{path} missing from openapi.json");
+        }
+    }
+}
@@ -0,0 +1,134 @@
+// REST API server
+// Binds, serves and shuts down the control API
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::Router;
+use serde::Serialize;
+use tokio::sync::RwLock;
+
+use super::router;
+use crate::config::ApiSettings;
+use crate::control::Control;
+use crate::ports;
+
+/// A settings-driven HTTP listener: the control API, or the metrics endpoint
+#[derive(Debug, Default)]
+pub struct HttpListener {
+    /// Where it is listening (None if not running)
+    addr: Option<SocketAddr>,
+    /// Handle to shut down the server
+    shutdown_tx: Option<tokio::sync::oneshot::Sender<()>>,
+}
+
+impl HttpListener {
+    pub fn addr(&self) -> Option<SocketAddr> {
+        self.addr
+    }
+}
+
+pub type HttpListenerState = Arc<RwLock<HttpListener>>;
+
+/// The control API's listener
+pub type ApiServerState = HttpListenerState;
+
+/// The API settings plus where it is actually listening, for the GUI
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiStatus {
+    #[serde(flatten)]
+    pub settings: ApiSettings,
+    /// Listening address while running
+    pub address: Option<String>,
+}
+
+/// A fresh random bearer token: 32 bytes from the OS random source, as 64
+/// hex digits
+pub fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes).map_err(|e| format!("Cannot generate an API token: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Serve `app` on `bind` until `stop_listener`. `what` names it in errors.
+pub async fn start_listener(
+    state: HttpListenerState,
+    bind: &str,
+    app: Router,
+    what: &str,
+) -> Result<SocketAddr, String> {
+    if state.read().await.addr.is_some() {
+        return Err(format!("{} is already running", what));
+    }
+    let addr: SocketAddr = bind
+        .parse()
+        .map_err(|e| format!("Invalid {} address '{}': {}", what, bind, e))?;
+
+    let listener = tokio::net::TcpListener::bind(addr).await.map_err(|e| {
+        let owner = ports::owner(ports::Protocol::Tcp, addr.port())
+            .map(|owner| format!(" — in use by {}", owner))
+            .unwrap_or_default();
+        format!("Failed to bind the {} to {}: {}{}", what, addr, e, owner)
+    })?;
+    let actual = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to get local address: {}", e))?;
+
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    tokio::spawn(async move {
+        axum::serve(listener, app)
+            .with_graceful_shutdown(async {
+                let _ = shutdown_rx.await;
+            })
+            .await
+            .ok();
+    });
+
+    let mut server = state.write().await;
+    server.addr = Some(actual);
+    server.shutdown_tx = Some(shutdown_tx);
+    Ok(actual)
+}
+
+/// Stop a listener if it is running
+pub async fn stop_listener(state: HttpListenerState) {
+    let mut server = state.write().await;
+    if let Some(tx) = server.shutdown_tx.take() {
+        let _ = tx.send(());
+    }
+    server.addr = None;
+}
+
+/// Start the control API on `settings.bind`. Errors if it is already running,
+/// has no token, or the address cannot be bound.
+pub async fn start_api(
+    state: ApiServerState,
+    settings: &ApiSettings,
+    control: Control,
+) -> Result<SocketAddr, String> {
+    let token = settings
+        .token
+        .clone()
+        .filter(|t| !t.is_empty())
+        .ok_or_else(|| "Control API has no token".to_string())?;
+    start_listener(state, &settings.bind, router(control, token), "control API").await
+}
+
+/// Stop the control API if it is running
+pub async fn stop_api(state: ApiServerState) {
+    stop_listener(state).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_long_and_distinct() {
+        let a = generate_token().unwrap();
+        let b = generate_token().unwrap();
+        assert_eq!(a.len(), 64);
+        assert!(a.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(a, b);
+    }
+}
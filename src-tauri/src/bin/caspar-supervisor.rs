@@ -0,0 +1,212 @@
+// CasparCG headless supervisor
+// Launches and supervises CasparCG from a profile, without the GUI
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+use caspar_server_gui_lib::{
+    DiagnosticsEngine, EventSink, GlobalConfig, GuiSettings, Launcher, LauncherEvent, LogLevel,
+    LogStore,
+};
+
+const USAGE: &str = "\
+Usage: caspar-supervisor --profile <profile.json> [options]
+
+Writes casparcg.config from the profile, then launches and supervises
+casparcg.exe and the media scanner with the profile's restart policy.
+Stops both cleanly on Ctrl-C or SIGTERM.
+
+Options:
+  --profile <file>      Profile JSON saved by the GUI (required)
+  --caspar-dir <dir>    CasparCG installation directory. Defaults to the
+                        directory above the profile's caspar-gui-profiles
+                        folder, then the GUI's configured path.
+  --log-dir <dir>       Also write rotating log files to this directory
+  --crash-dir <dir>     Where crash reports go (default: the GUI's)
+  --quiet               Do not print console lines to stdout
+  -h, --help            Show this help
+
+Exit status: 0 after Ctrl-C/SIGTERM or a clean server exit the policy left
+to stand, 1 when the supervisor stood down after a failure, 2 on bad usage.";
+
+/// How often the main task checks for a stand-down
+const POLL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Default)]
+struct Args {
+    profile: Option<PathBuf>,
+    caspar_dir: Option<PathBuf>,
+    log_dir: Option<PathBuf>,
+    crash_dir: Option<PathBuf>,
+    quiet: bool,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut parsed = Args::default();
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| {
+            args.next()
+                .map(PathBuf::from)
+                .ok_or_else(|| format!("{flag} needs a value"))
+        };
+        match arg.as_str() {
+            "--profile" => parsed.profile = Some(value("--profile")?),
+            "--caspar-dir" => parsed.caspar_dir = Some(value("--caspar-dir")?),
+            "--log-dir" => parsed.log_dir = Some(value("--log-dir")?),
+            "--crash-dir" => parsed.crash_dir = Some(value("--crash-dir")?),
+            "--quiet" => parsed.quiet = true,
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("unknown argument '{other}'")),
+        }
+    }
+    Ok(Some(parsed))
+}
+
+/// The GUI keeps profiles in `<caspar>/caspar-gui-profiles/`, so a profile from
+/// there names its own installation.
+fn install_dir_of(profile: &Path) -> Option<PathBuf> {
+    let folder = profile.parent()?;
+    if folder.file_name()? != "caspar-gui-profiles" {
+        return None;
+    }
+    let dir = folder.parent()?;
+    dir.join("casparcg.exe").exists().then(|| dir.to_path_buf())
+}
+
+/// Deliver "SIGTERM"/"Ctrl-C" (and, on Windows, console close and shutdown) on
+/// `tx`. Each listener is its own task as there is no select! here.
+fn listen_for_shutdown(tx: tokio::sync::mpsc::Sender<&'static str>) {
+    let ctrl_c = tx.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            let _ = ctrl_c.send("Ctrl-C").await;
+        }
+    });
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        if let Ok(mut term) = signal(SignalKind::terminate()) {
+            tokio::spawn(async move {
+                term.recv().await;
+                let _ = tx.send("SIGTERM").await;
+            });
+        }
+    }
+    #[cfg(windows)]
+    {
+        use tokio::signal::windows::{ctrl_close, ctrl_shutdown};
+        if let Ok(mut close) = ctrl_close() {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                close.recv().await;
+                let _ = tx.send("console close").await;
+            });
+        }
+        if let Ok(mut shutdown) = ctrl_shutdown() {
+            tokio::spawn(async move {
+                shutdown.recv().await;
+                let _ = tx.send("system shutdown").await;
+            });
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("caspar-supervisor: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let Some(profile_path) = args.profile else {
+        eprintln!("caspar-supervisor: --profile is required\n\n{USAGE}");
+        return ExitCode::from(2);
+    };
+    let config = match GlobalConfig::load_from_file(&profile_path) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("caspar-supervisor: cannot load {}: {e}", profile_path.display());
+            return ExitCode::from(2);
+        }
+    };
+    let Some(dir) = args
+        .caspar_dir
+        .or_else(|| install_dir_of(&profile_path))
+        .or_else(|| GuiSettings::load().caspar_path.map(PathBuf::from))
+    else {
+        eprintln!("caspar-supervisor: no CasparCG directory — pass --caspar-dir");
+        return ExitCode::from(2);
+    };
+
+    // Console lines go to stdout (the journal, or the service wrapper's log);
+    // diagnostics and crash reports to stderr so they stand out.
+    let quiet = args.quiet;
+    let sink: EventSink = Arc::new(move |event: LauncherEvent| match event {
+        LauncherEvent::Log(entry) if !quiet => println!("{}", entry.to_line()),
+        LauncherEvent::Diagnostic(d) => {
+            eprintln!("diagnostic: {} — {} {}", d.title, d.explanation, d.suggested_action)
+        }
+        LauncherEvent::CrashReport(path) => eprintln!("crash report: {}", path.display()),
+        _ => {}
+    });
+    let (logs, log_error) = LogStore::with_log_dir(args.log_dir);
+    let (diagnostics, rule_errors) =
+        DiagnosticsEngine::with_user_rules(GuiSettings::diagnostic_rules_path());
+    let launcher = Arc::new(Launcher::new(
+        logs,
+        diagnostics,
+        args.crash_dir.or_else(GuiSettings::crash_reports_dir),
+        sink,
+    ));
+    if let Some(e) = log_error {
+        launcher.log(LogLevel::Warning, e);
+    }
+    for e in rule_errors {
+        launcher.log(LogLevel::Warning, format!("Diagnostic rules: {e}"));
+    }
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("caspar-supervisor: cannot start the async runtime: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    runtime.block_on(async {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+        listen_for_shutdown(tx);
+
+        launcher.log(
+            LogLevel::Info,
+            format!("supervising profile '{}' in {}", config.name, dir.display()),
+        );
+        if let Err(e) = launcher.start(config, dir).await {
+            launcher.log(LogLevel::Error, e);
+            return ExitCode::FAILURE;
+        }
+
+        loop {
+            if let Ok(Some(signal)) = tokio::time::timeout(POLL, rx.recv()).await {
+                launcher.log(LogLevel::Info, format!("{signal} — stopping CasparCG"));
+                let _ = launcher.stop().await;
+                return ExitCode::SUCCESS;
+            }
+            if !launcher.is_supervising() {
+                // Stood down on its own: clean only if the server exited 0.
+                let clean = launcher
+                    .exits
+                    .recent()
+                    .last()
+                    .is_some_and(|e| e.code == Some(0) && e.action == "stand");
+                return if clean { ExitCode::SUCCESS } else { ExitCode::FAILURE };
+            }
+        }
+    })
+}
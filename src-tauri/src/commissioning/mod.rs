@@ -0,0 +1,8 @@
+// Commissioning
+// A guided walk through every output of the active profile, with an exported report
+
+mod report;
+mod session;
+
+pub use report::*;
+pub use session::*;
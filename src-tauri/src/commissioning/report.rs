@@ -0,0 +1,266 @@
+// Commissioning report
+// The session's checks with the hardware inventory and versions, as Markdown or HTML
+
+use chrono::{DateTime, Local, Utc};
+use serde::Deserialize;
+
+use super::{CheckResult, Commissioning, PlayedVia};
+use crate::control::Control;
+use crate::decklink::{self, DeckLinkDevice};
+use crate::system::{self, SystemVersions};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Html => "html",
+        }
+    }
+}
+
+/// What the outputs were checked on
+#[derive(Debug, Clone)]
+pub struct Inventory {
+    pub versions: SystemVersions,
+    /// Empty with the reason if the driver could not be asked
+    pub devices: Result<Vec<DeckLinkDevice>, String>,
+    pub address: Option<String>,
+}
+
+impl Inventory {
+    pub async fn collect(control: &Control) -> Self {
+        Self {
+            versions: control.system_versions().await,
+            devices: tokio::task::spawn_blocking(|| decklink::list_devices().map_err(|e| e.to_string()))
+                .await
+                .unwrap_or_else(|e| Err(e.to_string())),
+            address: system::network::primary_ip(),
+        }
+    }
+}
+
+/// A headed table, the unit both formats are written from
+struct Table {
+    heading: String,
+    columns: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+}
+
+fn time(at: DateTime<Utc>) -> String {
+    at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn result_text(result: Option<CheckResult>) -> &'static str {
+    match result {
+        Some(CheckResult::Pass) => "Pass",
+        Some(CheckResult::Fail) => "FAIL",
+        Some(CheckResult::Skipped) => "Skipped",
+        None => "Not checked",
+    }
+}
+
+fn tables(session: &Commissioning, inventory: &Inventory, generated: DateTime<Utc>) -> Vec<Table> {
+    let (passed, failed, skipped, unchecked) = session.tally();
+    let or_unknown = |v: &Option<String>| v.clone().unwrap_or_else(|| "not found".to_string());
+    let mut tables = vec![
+        Table {
+            heading: "Session".to_string(),
+            columns: &["", ""],
+            rows: vec![
+                vec!["Profile".to_string(), session.profile.clone()],
+                vec!["Operator".to_string(), session.operator.clone()],
+                vec!["Started".to_string(), time(session.started)],
+                vec![
+                    "Finished".to_string(),
+                    session.finished.map(time).unwrap_or_else(|| "not finished".to_string()),
+                ],
+                vec!["Report written".to_string(), time(generated)],
+                vec![
+                    "Result".to_string(),
+                    format!("{passed} passed, {failed} failed, {skipped} skipped, {unchecked} not checked"),
+                ],
+            ],
+        },
+        Table {
+            heading: "Software".to_string(),
+            columns: &["", "Version"],
+            rows: vec![
+                vec![
+                    "CasparCG".to_string(),
+                    inventory.versions.caspar_version.clone().unwrap_or_else(|| "not connected".to_string()),
+                ],
+                vec!["DeckLink driver".to_string(), or_unknown(&inventory.versions.decklink_version)],
+                vec!["NDI".to_string(), or_unknown(&inventory.versions.ndi_version)],
+                vec!["Media scanner".to_string(), or_unknown(&inventory.versions.scanner_version)],
+                vec![
+                    "Launcher".to_string(),
+                    format!("{} ({})", env!("CARGO_PKG_VERSION"), std::env::consts::OS),
+                ],
+                vec!["Host address".to_string(), or_unknown(&inventory.address)],
+            ],
+        },
+    ];
+
+    let labels = &session.config.decklink.devices;
+    tables.push(Table {
+        heading: "DeckLink devices".to_string(),
+        columns: &["#", "Model", "Label", "Persistent ID", "Outputs", "Audio channels"],
+        rows: match &inventory.devices {
+            Ok(devices) => devices
+                .iter()
+                .map(|d| {
+                    let label = labels
+                        .iter()
+                        .find(|l| l.persistent_id == d.persistent_id)
+                        .and_then(|l| l.label.clone())
+                        .or_else(|| d.device_label.clone())
+                        .unwrap_or_default();
+                    vec![
+                        d.index.to_string(),
+                        d.model_name.clone(),
+                        label,
+                        d.persistent_id.clone(),
+                        d.output_connectors.join(", "),
+                        d.max_audio_channels.to_string(),
+                    ]
+                })
+                .collect(),
+            Err(e) => vec![vec![String::new(), format!("Could not list devices: {e}")]],
+        },
+    });
+
+    for (i, channel) in session.config.caspar.channels.iter().enumerate() {
+        let number = i as u32 + 1;
+        tables.push(Table {
+            heading: format!("Channel {number} — {}", channel.video_mode.display_name()),
+            columns: &["Check", "Result", "Played", "Checked", "Notes"],
+            rows: session
+                .steps
+                .iter()
+                .filter(|s| s.channel == number)
+                .map(|s| {
+                    vec![
+                        s.title.clone(),
+                        result_text(s.result).to_string(),
+                        match s.played {
+                            Some(PlayedVia::Amcp) => "Channel test",
+                            Some(PlayedVia::OutputTest) => "Direct output test",
+                            None => "",
+                        }
+                        .to_string(),
+                        s.checked.map(time).unwrap_or_default(),
+                        s.notes.clone(),
+                    ]
+                })
+                .collect(),
+        });
+    }
+    tables
+}
+
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// The report for `session`, written at `generated`
+pub fn render_report(
+    session: &Commissioning,
+    inventory: &Inventory,
+    format: ReportFormat,
+    generated: DateTime<Utc>,
+) -> String {
+    let title = format!("Commissioning report — {}", session.profile);
+    let tables = tables(session, inventory, generated);
+    let mut out = String::new();
+    match format {
+        ReportFormat::Markdown => {
+            out.push_str(&format!("# {}\n", markdown_cell(&title)));
+            for table in tables {
+                out.push_str(&format!("\n## {}\n\n", markdown_cell(&table.heading)));
+                out.push_str(&format!("| {} |\n", table.columns.join(" | ")));
+                out.push_str(&format!("|{}\n", "---|".repeat(table.columns.len())));
+                for row in table.rows {
+                    let cells: Vec<String> = row.iter().map(|c| markdown_cell(c)).collect();
+                    out.push_str(&format!("| {} |\n", cells.join(" | ")));
+                }
+            }
+        }
+        ReportFormat::Html => {
+            out.push_str(&format!(
+                "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n\
+                 <style>\n  body {{ font: 14px/1.4 system-ui, sans-serif; margin: 2em; }}\n  \
+                 table {{ border-collapse: collapse; margin-bottom: 1.5em; }}\n  \
+                 th, td {{ border: 1px solid #bbb; padding: 4px 8px; text-align: left; vertical-align: top; }}\n  \
+                 th {{ background: #eee; }}\n  td.fail {{ color: #b00; font-weight: 600; }}\n\
+                 </style>\n</head>\n<body>\n<h1>{0}</h1>\n",
+                html_escape(&title)
+            ));
+            for table in tables {
+                out.push_str(&format!("<h2>{}</h2>\n<table>\n<tr>", html_escape(&table.heading)));
+                for column in table.columns {
+                    out.push_str(&format!("<th>{}</th>", html_escape(column)));
+                }
+                out.push_str("</tr>\n");
+                for row in table.rows {
+                    out.push_str("<tr>");
+                    for cell in row {
+                        let class = if cell == result_text(Some(CheckResult::Fail)) { " class=\"fail\"" } else { "" };
+                        out.push_str(&format!("<td{class}>{}</td>", html_escape(&cell).replace('\n', "<br>")));
+                    }
+                    out.push_str("</tr>\n");
+                }
+                out.push_str("</table>\n");
+            }
+            out.push_str("</body>\n</html>\n");
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Channel, Consumer, DeckLinkConsumer, GlobalConfig};
+
+    #[test]
+    fn reports_checks_and_inventory_in_both_formats() {
+        let mut config = GlobalConfig::new("Studio A");
+        config.caspar.channels = vec![Channel {
+            consumers: vec![Consumer::DeckLink(DeckLinkConsumer::default())],
+            ..Default::default()
+        }];
+        let mut session = Commissioning::new("Studio A", "Sam", config, Utc::now());
+        session.record(1, CheckResult::Fail, "router 14 | no <signal>", Utc::now()).unwrap();
+        let inventory = Inventory {
+            versions: SystemVersions {
+                decklink_version: Some("12.9".to_string()),
+                ..Default::default()
+            },
+            devices: Err("no driver".to_string()),
+            address: None,
+        };
+
+        let markdown = render_report(&session, &inventory, ReportFormat::Markdown, Utc::now());
+        assert!(markdown.starts_with("# Commissioning report — Studio A\n"));
+        assert!(markdown.contains("| DeckLink driver | 12.9 |"));
+        assert!(markdown.contains("| Channel 1 — DeckLink 1 fill | FAIL |"));
+        assert!(markdown.contains("router 14 \\| no <signal>"));
+        assert!(markdown.contains("0 passed, 1 failed, 0 skipped"));
+
+        let html = render_report(&session, &inventory, ReportFormat::Html, Utc::now());
+        assert!(html.contains("<td class=\"fail\">FAIL</td>"));
+        assert!(html.contains("router 14 | no &lt;signal&gt;"));
+        assert!(html.contains("Could not list devices: no driver"));
+    }
+}
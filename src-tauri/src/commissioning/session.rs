@@ -0,0 +1,331 @@
+// Commissioning session
+// Steps through every channel and output of a profile, playing its identification and recording the checks
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{AudioIdentMode, Consumer, DeckLinkKeyer, GlobalConfig};
+use crate::control::{Control, TestTarget};
+use crate::decklink;
+use crate::logs::LogLevel;
+
+/// Topic the session is published on whenever it changes
+pub const COMMISSIONING_TOPIC: &str = "commissioning";
+
+/// Owner of the tests a commissioning session plays
+pub const COMMISSIONING_OWNER: &str = "commissioning";
+
+/// What a step checks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Check {
+    /// The channel's slate and tone, wherever it goes
+    Identify,
+    /// A DeckLink output's fill (1-based device)
+    Fill { device: u32 },
+    /// The key a DeckLink consumer sends on a second device
+    Key { device: u32 },
+    /// One stereo pair embedded in a DeckLink output (1-based pair)
+    AudioPair { device: u32, pair: u32 },
+    Ndi { name: String },
+    Screen { device: u32 },
+    SystemAudio,
+}
+
+/// How a step's identification was put on the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlayedVia {
+    /// The channel test through the running server
+    Amcp,
+    /// The direct DeckLink output test, with CasparCG stopped
+    OutputTest,
+}
+
+/// The operator's verdict on a step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckResult {
+    Pass,
+    Fail,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CommissioningStep {
+    /// 1-based channel the step belongs to
+    pub channel: u32,
+    pub check: Check,
+    pub title: String,
+    /// What the operator should see or hear
+    pub instructions: String,
+    pub played: Option<PlayedVia>,
+    pub result: Option<CheckResult>,
+    pub notes: String,
+    /// When the result was recorded
+    pub checked: Option<DateTime<Utc>>,
+}
+
+/// A walk through every output of a profile
+#[derive(Debug, Clone, Serialize)]
+pub struct Commissioning {
+    pub profile: String,
+    pub operator: String,
+    pub started: DateTime<Utc>,
+    pub finished: Option<DateTime<Utc>>,
+    /// The step last played or recorded
+    pub current: Option<usize>,
+    pub steps: Vec<CommissioningStep>,
+    /// The profile as it was when the session started
+    #[serde(skip)]
+    pub config: GlobalConfig,
+}
+
+/// The steps for `config`: per channel, its identification, then each
+/// consumer's outputs, key and embedded audio pairs
+pub fn steps_for(config: &GlobalConfig) -> Vec<CommissioningStep> {
+    let step = |channel: u32, check: Check, title: String, instructions: String| CommissioningStep {
+        channel,
+        check,
+        title,
+        instructions,
+        played: None,
+        result: None,
+        notes: String::new(),
+        checked: None,
+    };
+    let tone = config.ident.audio != AudioIdentMode::Off;
+    let pairs = config.ident.tracks.count().div_ceil(2);
+    let mut steps = Vec::new();
+    for (i, channel) in config.caspar.channels.iter().enumerate() {
+        let c = i as u32 + 1;
+        steps.push(step(
+            c,
+            Check::Identify,
+            format!("Channel {c} identification"),
+            format!(
+                "The slate names channel {c} and {}{}",
+                channel.video_mode.display_name(),
+                if tone { ", with tone on every audio output" } else { "" }
+            ),
+        ));
+        for consumer in &channel.consumers {
+            match consumer {
+                Consumer::DeckLink(d) => {
+                    steps.push(step(
+                        c,
+                        Check::Fill { device: d.device },
+                        format!("Channel {c} — DeckLink {} fill", d.device),
+                        format!("DeckLink {} fill shows the channel {c} slate at the router", d.device),
+                    ));
+                    let key = match (d.key_device, &d.keyer) {
+                        (Some(key), _) => Some(key),
+                        (None, DeckLinkKeyer::ExternalSeparateDevice) => Some(d.device + 1),
+                        _ => None,
+                    };
+                    if let Some(key) = key {
+                        steps.push(step(
+                            c,
+                            Check::Key { device: key },
+                            format!("Channel {c} — DeckLink {key} key"),
+                            format!("DeckLink {key} shows the slate's key: white where the fill is opaque"),
+                        ));
+                    }
+                    if tone && d.embedded_audio {
+                        for pair in 1..=pairs {
+                            let (left, right) = (pair * 2 - 1, pair * 2);
+                            let heard = match config.ident.audio {
+                                AudioIdentMode::Lineup => format!(
+                                    "continuous 1 kHz at {} dBFS on tracks {left} and {right}",
+                                    config.ident.lineup_dbfs
+                                ),
+                                _ => format!(
+                                    "{pair} short break(s), then one on track {left} (left) and two on track {right} (right)"
+                                ),
+                            };
+                            steps.push(step(
+                                c,
+                                Check::AudioPair { device: d.device, pair },
+                                format!("Channel {c} — DeckLink {} audio {left}/{right}", d.device),
+                                format!("Embedded pair {pair} at the router: {heard}"),
+                            ));
+                        }
+                    }
+                }
+                Consumer::Ndi(n) => steps.push(step(
+                    c,
+                    Check::Ndi { name: n.name.clone() },
+                    format!("Channel {c} — NDI \"{}\"", n.name),
+                    format!("The NDI source \"{}\" is on the network with the channel {c} slate", n.name),
+                )),
+                Consumer::Screen(s) => steps.push(step(
+                    c,
+                    Check::Screen { device: s.device },
+                    format!("Channel {c} — screen {}", s.device),
+                    format!("Screen {} shows the channel {c} slate", s.device),
+                )),
+                Consumer::SystemAudio(_) => steps.push(step(
+                    c,
+                    Check::SystemAudio,
+                    format!("Channel {c} — system audio"),
+                    "The tone plays on this machine's audio output".to_string(),
+                )),
+            }
+        }
+    }
+    steps
+}
+
+impl Commissioning {
+    pub fn new(profile: &str, operator: &str, config: GlobalConfig, now: DateTime<Utc>) -> Self {
+        Self {
+            profile: profile.to_string(),
+            operator: operator.to_string(),
+            started: now,
+            finished: None,
+            current: None,
+            steps: steps_for(&config),
+            config,
+        }
+    }
+
+    fn step(&self, index: usize) -> Result<&CommissioningStep, String> {
+        self.steps.get(index).ok_or_else(|| format!("No commissioning step {}", index + 1))
+    }
+
+    /// Put step `index`'s identification on its output: the channel test
+    /// while the server is running, else the direct output test for a
+    /// DeckLink fill or key. Whatever the session played before is stopped.
+    pub async fn play(&mut self, index: usize, control: &Control) -> Result<(), String> {
+        let step = self.step(index)?.clone();
+        stop_played(control).await;
+
+        let connected = control.amcp_client.lock().await.is_connected();
+        let (target, label, via) = if connected {
+            let channel = step.channel;
+            let slates = control.channel_slates(&self.config).await;
+            let audio = control.channel_audio(&self.config).await;
+            let at = channel as usize - 1;
+            let tone = audio.get(at).and_then(|a| a.as_deref());
+            control
+                .amcp_client
+                .lock()
+                .await
+                .start_channel_test(channel, slates.get(at), tone)
+                .await
+                .map_err(|e| e.to_string())?;
+            (TestTarget::Channel { channel }, "identification", PlayedVia::Amcp)
+        } else {
+            let (device, mode, label) = match step.check {
+                Check::Fill { device } => (device, 0, "SDI fill"),
+                Check::Key { device } => (device, 1, "SDI key"),
+                _ => return Err(format!("Start CasparCG to play {}", step.title)),
+            };
+            tokio::task::spawn_blocking(move || decklink::output_test_start(device, mode))
+                .await
+                .map_err(|e| e.to_string())?
+                .map_err(|e| e.to_string())?;
+            (TestTarget::Output { device }, label, PlayedVia::OutputTest)
+        };
+        control.begin_test(target, label, COMMISSIONING_OWNER, None).await;
+
+        self.steps[index].played = Some(via);
+        self.current = Some(index);
+        Ok(())
+    }
+
+    /// Record the operator's verdict on step `index`
+    pub fn record(&mut self, index: usize, result: CheckResult, notes: &str, now: DateTime<Utc>) -> Result<(), String> {
+        self.step(index)?;
+        let step = &mut self.steps[index];
+        step.result = Some(result);
+        step.notes = notes.trim().to_string();
+        step.checked = Some(now);
+        self.current = Some(index);
+        Ok(())
+    }
+
+    /// Stop what the session is playing and mark it finished
+    pub async fn finish(&mut self, control: &Control, now: DateTime<Utc>) {
+        stop_played(control).await;
+        self.finished.get_or_insert(now);
+    }
+
+    /// Steps passed, failed, skipped and not yet checked
+    pub fn tally(&self) -> (usize, usize, usize, usize) {
+        let count = |r: Option<CheckResult>| self.steps.iter().filter(|s| s.result == r).count();
+        (
+            count(Some(CheckResult::Pass)),
+            count(Some(CheckResult::Fail)),
+            count(Some(CheckResult::Skipped)),
+            count(None),
+        )
+    }
+
+    /// Publish the session for the GUI
+    pub fn publish(&self, control: &Control) {
+        if let Ok(payload) = serde_json::to_value(self) {
+            control.events.publish(COMMISSIONING_TOPIC, payload);
+        }
+    }
+}
+
+/// Stop every test a commissioning session started
+pub async fn stop_played(control: &Control) {
+    for session in control.tests.list() {
+        if session.owner == COMMISSIONING_OWNER {
+            if let Err(e) = control.stop_test(session.target).await {
+                control.launcher.log(LogLevel::Warning, format!("commissioning: {e}"));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AudioTracks, Channel, DeckLinkConsumer, NdiConsumer};
+
+    #[test]
+    fn steps_walk_each_channel_output_key_and_pair() {
+        let mut config = GlobalConfig::new("Studio A");
+        config.ident.audio = AudioIdentMode::Ident;
+        config.ident.tracks = AudioTracks::Eight;
+        config.caspar.channels = vec![
+            Channel {
+                consumers: vec![Consumer::DeckLink(DeckLinkConsumer {
+                    device: 1,
+                    key_device: Some(2),
+                    ..Default::default()
+                })],
+                ..Default::default()
+            },
+            Channel {
+                consumers: vec![Consumer::Ndi(NdiConsumer::default())],
+                ..Default::default()
+            },
+        ];
+        let titles: Vec<String> = steps_for(&config).into_iter().map(|s| s.title).collect();
+        assert_eq!(
+            titles,
+            [
+                "Channel 1 identification",
+                "Channel 1 — DeckLink 1 fill",
+                "Channel 1 — DeckLink 2 key",
+                "Channel 1 — DeckLink 1 audio 1/2",
+                "Channel 1 — DeckLink 1 audio 3/4",
+                "Channel 1 — DeckLink 1 audio 5/6",
+                "Channel 1 — DeckLink 1 audio 7/8",
+                "Channel 2 identification",
+                "Channel 2 — NDI \"CasparCG\"",
+            ]
+        );
+
+        let mut session = Commissioning::new("Studio A", "Sam", config, Utc::now());
+        session.record(1, CheckResult::Pass, " ok ", Utc::now()).unwrap();
+        session.record(2, CheckResult::Fail, "no key on router 14", Utc::now()).unwrap();
+        assert!(session.record(9, CheckResult::Pass, "", Utc::now()).is_err());
+        assert_eq!(session.steps[1].notes, "ok");
+        assert_eq!(session.tally(), (1, 1, 0, 7));
+    }
+}
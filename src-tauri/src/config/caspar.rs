@@ -31,6 +31,8 @@ pub fn parse_caspar_xml(xml: &str) -> Result<CasparConfig, CasparXmlError> {
     let mut in_tcp = false;
     let mut in_amcp = false;
     let mut in_media_server = false;
+    let mut in_osc = false;
+    let mut in_osc_client = false;
     let mut current_element = String::new();
     let mut current_channel: Option<Channel> = None;
     let mut current_consumer: Option<ConsumerBuilder> = None;
@@ -86,6 +88,19 @@ pub fn parse_caspar_xml(xml: &str) -> Result<CasparConfig, CasparXmlError> {
                             config.amcp.media_server = Some(MediaServer::default());
                         }
                     }
+                    "osc" => {
+                        in_osc = true;
+                        config.osc.get_or_insert_with(OscConfig::default);
+                    }
+                    "predefined-client" if in_osc => {
+                        in_osc_client = true;
+                        if let Some(ref mut osc) = config.osc {
+                            osc.predefined_clients.push(OscClient {
+                                address: String::new(),
+                                port: 0,
+                            });
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -116,6 +131,8 @@ pub fn parse_caspar_xml(xml: &str) -> Result<CasparConfig, CasparXmlError> {
                     "tcp" => in_tcp = false,
                     "amcp" => in_amcp = false,
                     "media-server" => in_media_server = false,
+                    "osc" => in_osc = false,
+                    "predefined-client" => in_osc_client = false,
                     _ => {}
                 }
             }
@@ -163,6 +180,26 @@ pub fn parse_caspar_xml(xml: &str) -> Result<CasparConfig, CasparXmlError> {
                             _ => {}
                         }
                     }
+                } else if in_osc {
+                    if let Some(ref mut osc) = config.osc {
+                        if in_osc_client {
+                            if let Some(client) = osc.predefined_clients.last_mut() {
+                                match current_element.as_str() {
+                                    "address" => client.address = text,
+                                    "port" => client.port = text.parse().unwrap_or(0),
+                                    _ => {}
+                                }
+                            }
+                        } else {
+                            match current_element.as_str() {
+                                "default-port" => osc.default_port = text.parse().ok(),
+                                "disable-send-to-amcp-clients" => {
+                                    osc.disable_send_to_amcp_clients = Some(text == "true")
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
                 } else {
                     // Root level elements
                     match current_element.as_str() {
@@ -270,6 +307,32 @@ pub fn generate_caspar_xml(config: &CasparConfig) -> Result<String, CasparXmlErr
         writer.write_event(Event::End(BytesEnd::new("amcp")))?;
     }
 
+    // OSC section
+    if let Some(ref osc) = config.osc {
+        writer.write_event(Event::Start(BytesStart::new("osc")))?;
+        if let Some(port) = osc.default_port {
+            write_element(&mut writer, "default-port", &port.to_string())?;
+        }
+        if let Some(disable) = osc.disable_send_to_amcp_clients {
+            write_element(
+                &mut writer,
+                "disable-send-to-amcp-clients",
+                if disable { "true" } else { "false" },
+            )?;
+        }
+        if !osc.predefined_clients.is_empty() {
+            writer.write_event(Event::Start(BytesStart::new("predefined-clients")))?;
+            for client in &osc.predefined_clients {
+                writer.write_event(Event::Start(BytesStart::new("predefined-client")))?;
+                write_element(&mut writer, "address", &client.address)?;
+                write_element(&mut writer, "port", &client.port.to_string())?;
+                writer.write_event(Event::End(BytesEnd::new("predefined-client")))?;
+            }
+            writer.write_event(Event::End(BytesEnd::new("predefined-clients")))?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("osc")))?;
+    }
+
     writer.write_event(Event::End(BytesEnd::new("configuration")))?;
 
     let result = writer.into_inner().into_inner();
@@ -669,4 +732,29 @@ mod tests {
             other => panic!("expected an ndi consumer, got {other:?}"),
         }
     }
+
+    #[test]
+    fn test_roundtrip_preserves_osc_clients() {
+        let original = CasparConfig {
+            osc: Some(OscConfig {
+                default_port: Some(6250),
+                disable_send_to_amcp_clients: Some(true),
+                predefined_clients: vec![OscClient {
+                    address: "127.0.0.1".to_string(),
+                    port: 6251,
+                }],
+            }),
+            ..CasparConfig::default()
+        };
+
+        let xml = generate_caspar_xml(&original).expect("generate");
+        let parsed = parse_caspar_xml(&xml).expect("parse");
+
+        let osc = parsed.osc.expect("osc section");
+        assert_eq!(osc.default_port, Some(6250));
+        assert_eq!(osc.disable_send_to_amcp_clients, Some(true));
+        assert_eq!(osc.predefined_clients, original.osc.unwrap().predefined_clients);
+        // The client's <port> must not leak into the AMCP controller's.
+        assert_eq!(parsed.controllers.tcp.port, 5250);
+    }
 }
@@ -39,6 +39,345 @@ pub struct DeckLinkConfig {
     pub devices: Vec<DeckLinkDeviceConfig>,
 }
 
+/// Hung-server watchdog settings. The supervisor only sees a process exit; the
+/// watchdog catches a server that is alive but deadlocked, and kills it so the
+/// normal restart path (and its crash-loop guard) takes over.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchdogConfig {
+    /// Off by default: a busy server can be slow to answer, so opt in per site
+    #[serde(default)]
+    pub enabled: bool,
+    /// Seconds between heartbeats
+    #[serde(default = "default_watchdog_interval")]
+    pub interval_secs: u64,
+    /// Seconds an AMCP ping may take before it counts as missed
+    #[serde(default = "default_watchdog_timeout")]
+    pub timeout_secs: u64,
+    /// Consecutive missed heartbeats before the server is declared hung
+    #[serde(default = "default_watchdog_missed")]
+    pub missed_threshold: u32,
+    /// Misses are not counted until the server has answered once or this many
+    /// seconds have passed since launch (DeckLink and CEF start-up is slow)
+    #[serde(default = "default_watchdog_grace")]
+    pub startup_grace_secs: u64,
+    /// Loopback UDP port to receive CasparCG's OSC on. When set, a channel that
+    /// stops sending per-frame OSC counts as a missed heartbeat too.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub osc_port: Option<u16>,
+}
+
+fn default_watchdog_interval() -> u64 {
+    5
+}
+
+fn default_watchdog_timeout() -> u64 {
+    3
+}
+
+fn default_watchdog_missed() -> u32 {
+    3
+}
+
+fn default_watchdog_grace() -> u64 {
+    60
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: default_watchdog_interval(),
+            timeout_secs: default_watchdog_timeout(),
+            missed_threshold: default_watchdog_missed(),
+            startup_grace_secs: default_watchdog_grace(),
+            osc_port: None,
+        }
+    }
+}
+
+/// When the supervisor relaunches a server that has exited
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartMode {
+    /// Never relaunch; any exit stands (rehearsal and bench machines)
+    Never,
+    /// Relaunch on a crash or restart request, not on a clean or fatal exit
+    OnFailure,
+    /// Relaunch on every exit, clean ones included (unattended channels)
+    Always,
+}
+
+/// Server supervision policy. The defaults reproduce the stock
+/// casparcg_auto_restart.bat behaviour plus a crash-loop guard.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupervisorConfig {
+    #[serde(default = "default_restart_mode")]
+    pub restart: RestartMode,
+    /// Seconds between checks of the server and scanner processes
+    #[serde(default = "default_supervisor_tick")]
+    pub tick_secs: u64,
+    /// Pause before the first relaunch after a crash
+    #[serde(default = "default_backoff")]
+    pub backoff_secs: f64,
+    /// Each consecutive crash multiplies the pause by this (1 keeps it fixed)
+    #[serde(default = "default_backoff_multiplier")]
+    pub backoff_multiplier: f64,
+    /// Upper bound on the pause between relaunches
+    #[serde(default = "default_backoff_max")]
+    pub backoff_max_secs: f64,
+    /// Give up after more than this many crashes within `crash_window_secs`
+    /// (0 retries indefinitely)
+    #[serde(default = "default_max_crashes")]
+    pub max_crashes: u32,
+    /// Rolling window for `max_crashes`. A server that stayed up this long also
+    /// resets the backoff.
+    #[serde(default = "default_crash_window")]
+    pub crash_window_secs: u64,
+    /// Exit codes from 0 up to (not including) this are a clean or fatal
+    /// shutdown (e.g. a config CasparCG refuses), which `on_failure` leaves to
+    /// stand. Negative Windows crash codes are always crashes.
+    #[serde(default = "default_fatal_below")]
+    pub fatal_below: i32,
+    /// Exit codes that ask for a restart. They relaunch after `backoff_secs`
+    /// and do not count as crashes (CasparCG uses 5).
+    #[serde(default = "default_restart_request_codes")]
+    pub restart_request_codes: Vec<i32>,
+}
+
+fn default_restart_mode() -> RestartMode {
+    RestartMode::OnFailure
+}
+
+fn default_supervisor_tick() -> u64 {
+    3
+}
+
+fn default_backoff() -> f64 {
+    2.0
+}
+
+fn default_backoff_multiplier() -> f64 {
+    2.0
+}
+
+fn default_backoff_max() -> f64 {
+    60.0
+}
+
+fn default_max_crashes() -> u32 {
+    3
+}
+
+fn default_crash_window() -> u64 {
+    60
+}
+
+fn default_fatal_below() -> i32 {
+    5
+}
+
+fn default_restart_request_codes() -> Vec<i32> {
+    vec![5]
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            restart: default_restart_mode(),
+            tick_secs: default_supervisor_tick(),
+            backoff_secs: default_backoff(),
+            backoff_multiplier: default_backoff_multiplier(),
+            backoff_max_secs: default_backoff_max(),
+            max_crashes: default_max_crashes(),
+            crash_window_secs: default_crash_window(),
+            fatal_below: default_fatal_below(),
+            restart_request_codes: default_restart_request_codes(),
+        }
+    }
+}
+
+/// A launched process whose resources are sampled
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceProcess {
+    /// casparcg.exe and its CEF subprocesses
+    Server,
+    /// The media scanner and its children
+    Scanner,
+}
+
+/// A sampled quantity, summed over the process tree
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceMetric {
+    /// CPU over the last interval; 100 is one full core
+    CpuPercent,
+    /// Resident memory (working set on Windows)
+    RssBytes,
+    Threads,
+    /// Open handles on Windows, file descriptors on Linux
+    Handles,
+}
+
+/// Raise an alert when `metric` of `process` stays above `above` for
+/// `for_secs`, e.g. RSS above 6 GB for 5 minutes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResourceAlertRule {
+    pub process: ResourceProcess,
+    pub metric: ResourceMetric,
+    pub above: f64,
+    #[serde(default)]
+    pub for_secs: u64,
+}
+
+/// Periodic CPU, memory, thread and handle sampling of the server and scanner
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceConfig {
+    #[serde(default = "default_resources_enabled")]
+    pub enabled: bool,
+    /// Seconds between samples
+    #[serde(default = "default_resource_interval")]
+    pub interval_secs: u64,
+    #[serde(default)]
+    pub alerts: Vec<ResourceAlertRule>,
+}
+
+fn default_resources_enabled() -> bool {
+    true
+}
+
+fn default_resource_interval() -> u64 {
+    5
+}
+
+impl Default for ResourceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_resources_enabled(),
+            interval_secs: default_resource_interval(),
+            alerts: Vec::new(),
+        }
+    }
+}
+
+/// What the pre-flight port check does when a port the profile names is taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PortConflictAction {
+    /// Refuse to start, naming the process that holds the port
+    Block,
+    /// Move to a free port and write that into casparcg.config
+    Reassign,
+}
+
+/// Pre-flight policy for the ports a profile names. The scanner port is the
+/// launcher's own choice and is always reassigned.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortPolicy {
+    /// The AMCP port. Blocks by default: every controller is set up for it.
+    #[serde(default = "default_amcp_conflict")]
+    pub amcp: PortConflictAction,
+    /// The watchdog's OSC port, which only the launcher listens on
+    #[serde(default = "default_osc_conflict")]
+    pub osc: PortConflictAction,
+}
+
+fn default_amcp_conflict() -> PortConflictAction {
+    PortConflictAction::Block
+}
+
+fn default_osc_conflict() -> PortConflictAction {
+    PortConflictAction::Reassign
+}
+
+impl Default for PortPolicy {
+    fn default() -> Self {
+        Self {
+            amcp: default_amcp_conflict(),
+            osc: default_osc_conflict(),
+        }
+    }
+}
+
+/// Which media scanner serves CasparCG's `<media-server>`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScannerMode {
+    /// The scanner executable shipped with CasparCG
+    #[default]
+    External,
+    /// The launcher's own scanner, for installs without the Node one
+    BuiltIn,
+}
+
+/// Media scanner selection
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScannerConfig {
+    #[serde(default)]
+    pub mode: ScannerMode,
+}
+
+/// What the channel test plays on the audio layer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioIdentMode {
+    /// Interrupted tones naming each track
+    #[default]
+    Ident,
+    /// Continuous 1 kHz on every track
+    Lineup,
+    /// Pictures only
+    Off,
+}
+
+/// How many embedded audio tracks the identification covers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AudioTracks {
+    #[default]
+    #[serde(rename = "stereo")]
+    Stereo,
+    #[serde(rename = "8ch")]
+    Eight,
+    #[serde(rename = "16ch")]
+    Sixteen,
+}
+
+impl AudioTracks {
+    pub fn count(self) -> u32 {
+        match self {
+            AudioTracks::Stereo => 2,
+            AudioTracks::Eight => 8,
+            AudioTracks::Sixteen => 16,
+        }
+    }
+}
+
+/// Audio played alongside the channel test's identification slates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentConfig {
+    #[serde(default)]
+    pub audio: AudioIdentMode,
+    #[serde(default)]
+    pub tracks: AudioTracks,
+    /// Tone level in dBFS (sine peak); EBU line-up is -18
+    #[serde(default = "default_lineup_dbfs")]
+    pub lineup_dbfs: f64,
+}
+
+fn default_lineup_dbfs() -> f64 {
+    -18.0
+}
+
+impl Default for IdentConfig {
+    fn default() -> Self {
+        Self {
+            audio: AudioIdentMode::default(),
+            tracks: AudioTracks::default(),
+            lineup_dbfs: default_lineup_dbfs(),
+        }
+    }
+}
+
 /// Global configuration format that wraps everything
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GlobalConfig {
@@ -55,6 +394,24 @@ pub struct GlobalConfig {
     /// DeckLink device configuration
     #[serde(default)]
     pub decklink: DeckLinkConfig,
+    /// Hung-server watchdog
+    #[serde(default)]
+    pub watchdog: WatchdogConfig,
+    /// Restart policy for the launched server
+    #[serde(default)]
+    pub supervisor: SupervisorConfig,
+    /// Resource sampling and threshold alerts for the launched processes
+    #[serde(default)]
+    pub resources: ResourceConfig,
+    /// What to do when a port the profile names is taken at launch
+    #[serde(default)]
+    pub ports: PortPolicy,
+    /// Which media scanner to run
+    #[serde(default)]
+    pub scanner: ScannerConfig,
+    /// Channel test audio
+    #[serde(default)]
+    pub ident: IdentConfig,
 }
 
 impl Default for GlobalConfig {
@@ -66,6 +423,12 @@ impl Default for GlobalConfig {
             modified: Utc::now(),
             caspar: CasparConfig::default(),
             decklink: DeckLinkConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            supervisor: SupervisorConfig::default(),
+            resources: ResourceConfig::default(),
+            ports: PortPolicy::default(),
+            scanner: ScannerConfig::default(),
+            ident: IdentConfig::default(),
         }
     }
 }
@@ -81,6 +444,12 @@ impl GlobalConfig {
             modified: now,
             caspar: CasparConfig::default(),
             decklink: DeckLinkConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            supervisor: SupervisorConfig::default(),
+            resources: ResourceConfig::default(),
+            ports: PortPolicy::default(),
+            scanner: ScannerConfig::default(),
+            ident: IdentConfig::default(),
         }
     }
 
@@ -154,6 +523,20 @@ fn allowed_values() -> serde_json::Value {
             DeckLinkLatency::Default,
         ]),
         "decklink.devices[].duplex_mode": ["full", "half"],
+        "supervisor.restart": allowed_strings(&[
+            RestartMode::Never,
+            RestartMode::OnFailure,
+            RestartMode::Always,
+        ]),
+        "ports.amcp": allowed_strings(&[PortConflictAction::Block, PortConflictAction::Reassign]),
+        "ports.osc": allowed_strings(&[PortConflictAction::Block, PortConflictAction::Reassign]),
+        "scanner.mode": allowed_strings(&[ScannerMode::External, ScannerMode::BuiltIn]),
+        "ident.audio": allowed_strings(&[
+            AudioIdentMode::Ident,
+            AudioIdentMode::Lineup,
+            AudioIdentMode::Off,
+        ]),
+        "ident.tracks": allowed_strings(&[AudioTracks::Stereo, AudioTracks::Eight, AudioTracks::Sixteen]),
     })
 }
 
@@ -172,15 +555,171 @@ pub struct GuiSettings {
     /// Last AMCP port
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_port: Option<u16>,
-    /// Whether server was running when GUI last closed
+    /// Whether the server was running at the last start, stop or stand-down.
+    /// Written by the backend; a set flag starts `last_profile` on launch.
     #[serde(default)]
     pub last_server_was_running: bool,
+    /// Whether AMCP was connected to `last_host` when last connected or
+    /// disconnected by hand, so the GUI reconnects on launch
+    #[serde(default)]
+    pub last_amcp_connected: bool,
+    /// Start `last_profile` on launch even if the server was stopped
+    #[serde(default)]
+    pub auto_start: bool,
+    /// Seconds an operator has to cancel the start on launch
+    #[serde(default = "default_auto_start_delay")]
+    pub auto_start_delay_secs: u64,
+    /// Stop a channel or DeckLink output test this long after it started.
+    /// Tests run until stopped when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_timeout_secs: Option<u64>,
     /// Window width
     #[serde(skip_serializing_if = "Option::is_none")]
     pub window_width: Option<u32>,
     /// Window height
     #[serde(skip_serializing_if = "Option::is_none")]
     pub window_height: Option<u32>,
+    /// Remote control HTTP API
+    #[serde(default)]
+    pub api: ApiSettings,
+    /// Prometheus metrics endpoint
+    #[serde(default)]
+    pub metrics: MetricsSettings,
+    /// Template development server
+    #[serde(default)]
+    pub template_dev: TemplateDevSettings,
+}
+
+fn default_auto_start_delay() -> u64 {
+    10
+}
+
+/// The opt-in REST control API. Every request except the OpenAPI description
+/// needs `Authorization: Bearer <token>`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApiSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Address to listen on. Loopback by default; `0.0.0.0:<port>` to reach it
+    /// from automation or a Stream Deck on the network.
+    #[serde(default = "default_api_bind")]
+    pub bind: String,
+    /// Bearer token. Generated when the API is first enabled without one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+fn default_api_bind() -> String {
+    "127.0.0.1:8750".to_string()
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: default_api_bind(),
+            token: None,
+        }
+    }
+}
+
+/// The opt-in Prometheus `/metrics` endpoint, on its own listener so it can
+/// be scraped without the control API's token.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MetricsSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Address to listen on. Loopback by default; `0.0.0.0:<port>` for a
+    /// Prometheus server elsewhere on the network.
+    #[serde(default = "default_metrics_bind")]
+    pub bind: String,
+}
+
+fn default_metrics_bind() -> String {
+    "127.0.0.1:9750".to_string()
+}
+
+impl Default for MetricsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: default_metrics_bind(),
+        }
+    }
+}
+
+/// How a saved template is brought back on air
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TemplateReload {
+    /// `CG CLEAR` then `CG ADD` with the test data: picks up every change
+    #[default]
+    Readd,
+    /// `CG UPDATE` with the test data, for templates that reload themselves
+    Update,
+}
+
+/// The template development server: serves a workspace over HTTP and reloads
+/// the template in CasparCG whenever a file in it is saved
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TemplateDevSettings {
+    /// Folder the templates are written in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+    /// Folder under the workspace to serve, e.g. `dist`. The workspace itself
+    /// when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serve_dir: Option<String>,
+    /// Run in the workspace before each reload, e.g. `npm run build`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_command: Option<String>,
+    /// Entry page, relative to the served folder
+    #[serde(default = "default_template_entry")]
+    pub template: String,
+    #[serde(default = "default_template_channel")]
+    pub channel: u32,
+    #[serde(default = "default_template_layer")]
+    pub layer: u32,
+    #[serde(default)]
+    pub reload: TemplateReload,
+    /// Test data sent with every ADD and UPDATE (JSON or XML)
+    #[serde(default)]
+    pub data: String,
+    /// Address to serve on. CasparCG is given the loopback URL.
+    #[serde(default = "default_template_bind")]
+    pub bind: String,
+}
+
+fn default_template_entry() -> String {
+    "index.html".to_string()
+}
+
+fn default_template_channel() -> u32 {
+    1
+}
+
+fn default_template_layer() -> u32 {
+    10
+}
+
+fn default_template_bind() -> String {
+    "127.0.0.1:8760".to_string()
+}
+
+impl Default for TemplateDevSettings {
+    fn default() -> Self {
+        Self {
+            workspace: None,
+            serve_dir: None,
+            build_command: None,
+            template: default_template_entry(),
+            channel: default_template_channel(),
+            layer: default_template_layer(),
+            reload: TemplateReload::default(),
+            data: String::new(),
+            bind: default_template_bind(),
+        }
+    }
 }
 
 impl Default for GuiSettings {
@@ -191,16 +730,78 @@ impl Default for GuiSettings {
             last_host: Some("localhost".to_string()),
             last_port: Some(5250),
             last_server_was_running: false,
+            last_amcp_connected: false,
+            auto_start: false,
+            auto_start_delay_secs: default_auto_start_delay(),
+            test_timeout_secs: None,
             window_width: None,
             window_height: None,
+            api: ApiSettings::default(),
+            metrics: MetricsSettings::default(),
+            template_dev: TemplateDevSettings::default(),
         }
     }
 }
 
+/// Set by `--settings`, before anything reads the settings
+static SETTINGS_PATH: std::sync::OnceLock<std::path::PathBuf> = std::sync::OnceLock::new();
+
 impl GuiSettings {
+    /// The per-user directory the GUI keeps its state in
+    fn default_dir() -> Option<std::path::PathBuf> {
+        dirs::config_dir().map(|p| p.join("caspar-server-gui"))
+    }
+
     /// Get the path to the GUI settings file
     pub fn settings_path() -> Option<std::path::PathBuf> {
-        dirs::config_dir().map(|p| p.join("caspar-server-gui").join("settings.json"))
+        SETTINGS_PATH
+            .get()
+            .cloned()
+            .or_else(|| Self::default_dir().map(|dir| dir.join("settings.json")))
+    }
+
+    /// Read and write settings at `path` for the rest of the process. The
+    /// logs, crash reports and diagnostic rules move next to it. Returns false
+    /// if a path was already set.
+    pub fn use_settings_path(path: std::path::PathBuf) -> bool {
+        SETTINGS_PATH.set(path).is_ok()
+    }
+
+    /// Lock file naming the running GUI. Always in the per-user directory, so
+    /// a launch with other settings still finds it.
+    pub fn instance_lock_path() -> Option<std::path::PathBuf> {
+        Self::default_dir().map(|dir| dir.join("instance.lock"))
+    }
+
+    /// Directory the launcher writes its own rotating log files to
+    pub fn logs_dir() -> Option<std::path::PathBuf> {
+        Self::settings_path().and_then(|p| p.parent().map(|dir| dir.join("logs")))
+    }
+
+    /// Directory crash report bundles are written to
+    pub fn crash_reports_dir() -> Option<std::path::PathBuf> {
+        Self::settings_path().and_then(|p| p.parent().map(|dir| dir.join("crash-reports")))
+    }
+
+    /// Directory exported reports (e.g. media compatibility) are written to
+    pub fn reports_dir() -> Option<std::path::PathBuf> {
+        Self::settings_path().and_then(|p| p.parent().map(|dir| dir.join("reports")))
+    }
+
+    /// Directory the media library caches scanner thumbnails in
+    pub fn thumbnail_cache_dir() -> Option<std::path::PathBuf> {
+        Self::settings_path().and_then(|p| p.parent().map(|dir| dir.join("thumbnails")))
+    }
+
+    /// Saved test data sets for the template preview, by template
+    pub fn template_data_path() -> Option<std::path::PathBuf> {
+        Self::settings_path().and_then(|p| p.parent().map(|dir| dir.join("template-data.json")))
+    }
+
+    /// Site-specific diagnostic rules, merged over the built-in catalogue
+    pub fn diagnostic_rules_path() -> Option<std::path::PathBuf> {
+        Self::settings_path()
+            .and_then(|p| p.parent().map(|dir| dir.join("diagnostic-rules.json")))
     }
 
     /// Load settings from file
@@ -268,4 +869,25 @@ mod tests {
         assert_eq!(loaded.name, "Test Profile");
         assert_eq!(loaded.version, cfg.version);
     }
+
+    #[test]
+    fn restart_modes_are_documented_in_snake_case() {
+        let value = GlobalConfig::new("Test Profile").documented_value().unwrap();
+        assert_eq!(
+            value["_allowed"]["supervisor.restart"],
+            serde_json::json!(["never", "on_failure", "always"])
+        );
+    }
+
+    #[test]
+    fn profiles_without_a_supervisor_section_get_the_defaults() {
+        let mut value = serde_json::to_value(GlobalConfig::new("Old Profile")).unwrap();
+        value.as_object_mut().unwrap().remove("supervisor");
+        value["watchdog"] = serde_json::json!({ "enabled": true });
+        let loaded: GlobalConfig = serde_json::from_value(value).unwrap();
+        assert_eq!(loaded.supervisor.restart, RestartMode::OnFailure);
+        assert_eq!(loaded.supervisor.restart_request_codes, vec![5]);
+        assert_eq!(loaded.supervisor.max_crashes, 3);
+        assert_eq!(loaded.watchdog.interval_secs, 5);
+    }
 }
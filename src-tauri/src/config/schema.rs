@@ -158,6 +158,79 @@ impl VideoMode {
             VideoMode::P2160_6000 => "2160p60 (4K)",
         }
     }
+
+    /// Width and height of the channel's frame
+    pub fn resolution(&self) -> (u32, u32) {
+        match self {
+            VideoMode::Pal | VideoMode::P576_2500 => (720, 576),
+            VideoMode::Ntsc => (720, 486),
+            VideoMode::P720_2398
+            | VideoMode::P720_2400
+            | VideoMode::P720_2500
+            | VideoMode::P720_5000
+            | VideoMode::P720_2997
+            | VideoMode::P720_5994
+            | VideoMode::P720_3000
+            | VideoMode::P720_6000 => (1280, 720),
+            VideoMode::P1080_2398
+            | VideoMode::P1080_2400
+            | VideoMode::I1080_5000
+            | VideoMode::I1080_5994
+            | VideoMode::I1080_6000
+            | VideoMode::P1080_2500
+            | VideoMode::P1080_2997
+            | VideoMode::P1080_3000
+            | VideoMode::P1080_5000
+            | VideoMode::P1080_5994
+            | VideoMode::P1080_6000 => (1920, 1080),
+            VideoMode::P1556_2398 | VideoMode::P1556_2400 | VideoMode::P1556_2500 => (2048, 1556),
+            VideoMode::P2160_2398
+            | VideoMode::P2160_2400
+            | VideoMode::P2160_2500
+            | VideoMode::P2160_2997
+            | VideoMode::P2160_3000
+            | VideoMode::P2160_5000
+            | VideoMode::P2160_5994
+            | VideoMode::P2160_6000 => (3840, 2160),
+        }
+    }
+
+    /// Whole frames per second; an interlaced mode sends twice as many fields
+    pub fn frame_rate(&self) -> f64 {
+        let (num, den) = self.frame_rate_ratio();
+        f64::from(num) / f64::from(den)
+    }
+
+    /// `frame_rate` as an exact numerator and denominator
+    pub fn frame_rate_ratio(&self) -> (u32, u32) {
+        match self {
+            VideoMode::Pal | VideoMode::I1080_5000 => (25, 1),
+            VideoMode::Ntsc | VideoMode::I1080_5994 => (30000, 1001),
+            VideoMode::I1080_6000 => (30, 1),
+            VideoMode::P720_2398 | VideoMode::P1080_2398 | VideoMode::P1556_2398 | VideoMode::P2160_2398 => {
+                (24000, 1001)
+            }
+            VideoMode::P720_2400 | VideoMode::P1080_2400 | VideoMode::P1556_2400 | VideoMode::P2160_2400 => (24, 1),
+            VideoMode::P576_2500
+            | VideoMode::P720_2500
+            | VideoMode::P1080_2500
+            | VideoMode::P1556_2500
+            | VideoMode::P2160_2500 => (25, 1),
+            VideoMode::P720_2997 | VideoMode::P1080_2997 | VideoMode::P2160_2997 => (30000, 1001),
+            VideoMode::P720_3000 | VideoMode::P1080_3000 | VideoMode::P2160_3000 => (30, 1),
+            VideoMode::P720_5000 | VideoMode::P1080_5000 | VideoMode::P2160_5000 => (50, 1),
+            VideoMode::P720_5994 | VideoMode::P1080_5994 | VideoMode::P2160_5994 => (60000, 1001),
+            VideoMode::P720_6000 | VideoMode::P1080_6000 | VideoMode::P2160_6000 => (60, 1),
+        }
+    }
+
+    /// Whether the channel outputs fields rather than whole frames
+    pub fn interlaced(&self) -> bool {
+        matches!(
+            self,
+            VideoMode::Pal | VideoMode::Ntsc | VideoMode::I1080_5000 | VideoMode::I1080_5994 | VideoMode::I1080_6000
+        )
+    }
 }
 
 /// DeckLink latency mode
@@ -412,6 +485,26 @@ pub struct AmcpConfig {
     pub media_server: Option<MediaServer>,
 }
 
+/// A fixed OSC destination CasparCG always sends to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OscClient {
+    pub address: String,
+    pub port: u16,
+}
+
+/// OSC configuration. CasparCG sends channel and layer state over UDP to AMCP
+/// clients and to every predefined client, once per frame.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct OscConfig {
+    /// Port OSC is sent to on connected AMCP clients
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_send_to_amcp_clients: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub predefined_clients: Vec<OscClient>,
+}
+
 /// Main CasparCG configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CasparConfig {
@@ -423,6 +516,8 @@ pub struct CasparConfig {
     pub controllers: Controllers,
     #[serde(default)]
     pub amcp: AmcpConfig,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub osc: Option<OscConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub log_level: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -451,6 +546,7 @@ impl Default for CasparConfig {
             }],
             controllers: Controllers::default(),
             amcp: AmcpConfig::default(),
+            osc: None,
             log_level: None,
             log_categories: None,
             force_deinterlace: None,
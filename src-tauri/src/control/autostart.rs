@@ -0,0 +1,231 @@
+// Auto-start on launch
+// Restarts the last profile after a reboot, with a cancellable countdown
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use super::Control;
+use crate::events::TopicFilter;
+use crate::logs::LogLevel;
+
+/// Topic of the countdown events on the event bus
+pub const AUTO_START_TOPIC: &str = "auto-start";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoStartPhase {
+    /// Waiting `remaining_secs` before starting
+    Countdown,
+    Started,
+    Cancelled,
+    Failed,
+}
+
+/// One step of the auto-start, published on `AUTO_START_TOPIC`
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoStartStatus {
+    pub profile: String,
+    pub phase: AutoStartPhase,
+    pub remaining_secs: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The countdown in progress, if any, shared by the GUI and the API
+#[derive(Debug, Default)]
+pub struct AutoStart {
+    cancelled: AtomicBool,
+    status: Mutex<Option<AutoStartStatus>>,
+}
+
+impl AutoStart {
+    /// The latest step, for a view that missed the events
+    pub fn status(&self) -> Option<AutoStartStatus> {
+        self.status.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn counting_down(&self) -> bool {
+        matches!(self.status(), Some(s) if s.phase == AutoStartPhase::Countdown)
+    }
+}
+
+impl Control {
+    /// Record whether the server is running, and the profile it runs, so a
+    /// reboot can bring it back
+    pub async fn remember_running(&self, running: bool, profile: Option<&str>) {
+        let mut settings = self.gui_settings.lock().await;
+        if settings.last_server_was_running == running
+            && profile.is_none_or(|p| settings.last_profile.as_deref() == Some(p))
+        {
+            return;
+        }
+        settings.last_server_was_running = running;
+        if let Some(profile) = profile {
+            settings.last_profile = Some(profile.to_string());
+        }
+        if let Err(e) = settings.save() {
+            self.launcher
+                .log(LogLevel::Warning, format!("cannot save the server state: {e}"));
+        }
+    }
+
+    /// Clear the running flag whenever the supervisor stands down. Runs until
+    /// the event bus closes.
+    pub async fn track_stand_downs(self) {
+        use tokio::sync::broadcast::error::RecvError;
+        let (_, mut rx) = self.events.subscribe(&TopicFilter::parse("server-stood-down"), 0);
+        loop {
+            match rx.recv().await {
+                Ok(_) => self.remember_running(false, None).await,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+
+    /// The profile to start on launch: `last_profile`, when the server was
+    /// running at the last start/stop or `auto_start` is on
+    pub async fn auto_start_profile(&self) -> Option<String> {
+        let settings = self.gui_settings.lock().await;
+        settings.caspar_path.as_ref()?;
+        if settings.last_server_was_running || settings.auto_start {
+            settings.last_profile.clone()
+        } else {
+            None
+        }
+    }
+
+    /// Count down `auto_start_delay_secs`, publishing every second, then start
+    /// the profile through the same path as the Start button — unless
+    /// `cancel_auto_start` is called first. A read-only launch only says why
+    /// it does not.
+    pub async fn run_auto_start(&self) {
+        let Some(profile) = self.auto_start_profile().await else {
+            return;
+        };
+        if let Err(e) = self.writable() {
+            self.launcher
+                .log(LogLevel::Info, format!("not starting profile '{profile}' on launch: {e}"));
+            return;
+        }
+        let delay = self.gui_settings.lock().await.auto_start_delay_secs;
+        self.auto_start.cancelled.store(false, Ordering::Release);
+        self.launcher.log(
+            LogLevel::Info,
+            format!("starting profile '{profile}' in {delay}s — cancel to keep the server stopped"),
+        );
+
+        for remaining in (1..=delay).rev() {
+            self.publish_auto_start(&profile, AutoStartPhase::Countdown, remaining, None);
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            if self.auto_start.cancelled.load(Ordering::Acquire) {
+                self.launcher.log(LogLevel::Info, "auto-start cancelled");
+                self.remember_running(false, None).await;
+                self.publish_auto_start(&profile, AutoStartPhase::Cancelled, 0, None);
+                return;
+            }
+        }
+
+        match self.start_profile(Some(&profile)).await {
+            Ok(_) => self.publish_auto_start(&profile, AutoStartPhase::Started, 0, None),
+            Err(e) => {
+                self.launcher
+                    .log(LogLevel::Error, format!("auto-start of '{profile}' failed: {e}"));
+                self.publish_auto_start(&profile, AutoStartPhase::Failed, 0, Some(e));
+            }
+        }
+    }
+
+    /// Abort a countdown in progress. Returns whether there was one.
+    pub fn cancel_auto_start(&self) -> bool {
+        self.auto_start.counting_down() && !self.auto_start.cancelled.swap(true, Ordering::AcqRel)
+    }
+
+    fn publish_auto_start(
+        &self,
+        profile: &str,
+        phase: AutoStartPhase,
+        remaining_secs: u64,
+        error: Option<String>,
+    ) {
+        let status = AutoStartStatus {
+            profile: profile.to_string(),
+            phase,
+            remaining_secs,
+            error,
+        };
+        let payload = serde_json::to_value(&status).unwrap_or_default();
+        *self.auto_start.status.lock().unwrap_or_else(|e| e.into_inner()) = Some(status);
+        self.events.publish(AUTO_START_TOPIC, payload);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GuiSettings;
+
+    // The running flag is already clear, so nothing here writes settings.json.
+    #[test]
+    fn counts_down_and_can_be_cancelled() {
+        let control = Control::for_tests(GuiSettings {
+            caspar_path: Some(std::env::temp_dir().display().to_string()),
+            last_profile: Some("studio-a".to_string()),
+            auto_start: true,
+            auto_start_delay_secs: 5,
+            ..GuiSettings::default()
+        });
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            assert_eq!(control.auto_start_profile().await.as_deref(), Some("studio-a"));
+            assert!(!control.cancel_auto_start(), "nothing to cancel yet");
+
+            let (_, mut rx) = control.events.subscribe(&TopicFilter::parse(AUTO_START_TOPIC), 0);
+            let task = tokio::spawn({
+                let control = control.clone();
+                async move { control.run_auto_start().await }
+            });
+            let first = rx.recv().await.unwrap();
+            assert_eq!(first.payload["phase"], "countdown");
+            assert_eq!(first.payload["remaining_secs"], 5);
+            assert!(control.cancel_auto_start());
+            assert!(!control.cancel_auto_start(), "only once");
+            task.await.unwrap();
+
+            let last = control.auto_start.status().unwrap();
+            assert_eq!(last.phase, AutoStartPhase::Cancelled);
+            assert!(!control.launcher.should_run());
+        });
+    }
+
+    #[test]
+    fn a_read_only_launch_does_not_count_down() {
+        let control = Control {
+            read_only: true,
+            ..Control::for_tests(GuiSettings {
+                caspar_path: Some(std::env::temp_dir().display().to_string()),
+                last_profile: Some("studio-a".to_string()),
+                auto_start: true,
+                ..GuiSettings::default()
+            })
+        };
+        tokio::runtime::Runtime::new().unwrap().block_on(control.run_auto_start());
+        assert!(control.auto_start.status().is_none());
+        assert!(!control.launcher.should_run());
+    }
+
+    #[test]
+    fn a_stopped_server_stays_stopped() {
+        let control = Control::for_tests(GuiSettings {
+            caspar_path: Some("C:/CasparCG".to_string()),
+            last_profile: Some("studio-a".to_string()),
+            ..GuiSettings::default()
+        });
+        let profile = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(control.auto_start_profile());
+        assert_eq!(profile, None);
+    }
+}
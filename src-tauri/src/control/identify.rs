@@ -0,0 +1,94 @@
+// Channel identification
+// Slates and tones for a profile's channels, written into its media folder
+
+use std::path::PathBuf;
+
+use super::Control;
+use crate::config::{AudioIdentMode, GlobalConfig};
+use crate::decklink;
+use crate::ident;
+use crate::logs::LogLevel;
+
+impl Control {
+    /// The media folder of `config` on this machine
+    pub async fn media_dir(&self, config: &GlobalConfig) -> Result<PathBuf, String> {
+        // CasparCG resolves relative paths against its own directory.
+        Ok(self.caspar_dir().await?.join(&config.caspar.paths.media))
+    }
+
+    /// Write identification slates for every channel of `config` into its
+    /// media folder. DeckLink labels come from the profile, else from the cards.
+    async fn write_channel_slates(&self, config: &GlobalConfig) -> Result<Vec<ident::SlateClips>, String> {
+        let media = self.media_dir(config).await?;
+        // Enumerating the cards goes through the driver; keep it off the runtime.
+        let devices = tokio::task::spawn_blocking(|| decklink::list_devices().unwrap_or_default())
+            .await
+            .map_err(|e| e.to_string())?;
+        let labels = devices
+            .into_iter()
+            .filter_map(|device| {
+                let label = config
+                    .decklink
+                    .devices
+                    .iter()
+                    .find(|d| d.persistent_id == device.persistent_id)
+                    .and_then(|d| d.label.clone())
+                    .or(device.device_label)?;
+                Some((device.index, label))
+            })
+            .collect();
+        let infos = ident::SlateInfo::for_profile(config, &labels, chrono::Local::now());
+        tokio::task::spawn_blocking(move || ident::write_slates(&infos, &media))
+            .await
+            .map_err(|e| e.to_string())?
+    }
+
+    /// Slates for `config`, or none (the plain colour test) if they cannot be
+    /// written, e.g. when the media folder is on another machine
+    pub async fn channel_slates(&self, config: &GlobalConfig) -> Vec<ident::SlateClips> {
+        self.write_channel_slates(config).await.unwrap_or_else(|e| {
+            self.launcher.log(
+                LogLevel::Warning,
+                format!("identification slates not written ({e}) — testing with a plain colour"),
+            );
+            Vec::new()
+        })
+    }
+
+    /// The tone clip each channel's test plays, per the profile's `ident.audio`.
+    /// None for channels with no audio output, or everywhere if the files
+    /// cannot be written.
+    pub async fn channel_audio(&self, config: &GlobalConfig) -> Vec<Option<String>> {
+        if config.ident.audio == AudioIdentMode::Off {
+            return Vec::new();
+        }
+        let written = match self.media_dir(config).await {
+            Ok(media) => {
+                let (channels, ident) = (config.caspar.channels.clone(), config.ident.clone());
+                tokio::task::spawn_blocking(move || ident::write_audio(&channels, &ident, &media))
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|r| r)
+            }
+            Err(e) => Err(e),
+        };
+        match written {
+            Ok(clips) => clips
+                .into_iter()
+                .map(|c| {
+                    c.map(|c| match config.ident.audio {
+                        AudioIdentMode::Lineup => c.lineup,
+                        _ => c.ident,
+                    })
+                })
+                .collect(),
+            Err(e) => {
+                self.launcher.log(
+                    LogLevel::Warning,
+                    format!("audio idents not written ({e}) — testing without tone"),
+                );
+                Vec::new()
+            }
+        }
+    }
+}
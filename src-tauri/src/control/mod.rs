@@ -0,0 +1,11 @@
+// Control
+// Operations shared by the Tauri commands and the REST API
+
+mod autostart;
+mod identify;
+mod ops;
+mod testing;
+
+pub use autostart::*;
+pub use ops::*;
+pub use testing::*;
@@ -0,0 +1,336 @@
+// Control operations
+// Server, profile, system and AMCP actions without a Tauri handle
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use crate::amcp::{AmcpClient, AmcpResponse};
+use crate::config::{GlobalConfig, GuiSettings};
+use crate::events::EventBus;
+use crate::resources::ResourceSeries;
+use crate::supervisor::{ExitRecord, Launcher, LauncherEvent};
+use crate::system::{self, SystemVersions};
+
+use super::{AutoStart, TestSessions};
+
+/// Handles to the app's shared state. Cheap to clone: the Tauri commands build
+/// one per call from `AppState`, the API server keeps its own.
+#[derive(Clone)]
+pub struct Control {
+    pub amcp_client: Arc<Mutex<AmcpClient>>,
+    pub gui_settings: Arc<Mutex<GuiSettings>>,
+    pub launcher: Arc<Launcher>,
+    /// Everything the launcher reports, for the event WebSocket
+    pub events: Arc<EventBus>,
+    /// The start-on-launch countdown
+    pub auto_start: Arc<AutoStart>,
+    /// Channel and DeckLink output tests that are running
+    pub tests: Arc<TestSessions>,
+    /// Started with `--read-only`: nothing is started, stopped or saved
+    pub read_only: bool,
+}
+
+/// Snapshot of the launcher for status readouts
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerStatus {
+    /// casparcg.exe is running
+    pub running: bool,
+    /// The supervisor is still watching it (false once it has stood down)
+    pub supervising: bool,
+    /// Profile the latest Start launched
+    pub profile: Option<String>,
+    /// Profile selected in the GUI settings, started by `start_profile(None)`
+    pub active_profile: Option<String>,
+    pub amcp_connected: bool,
+    pub last_exit: Option<ExitRecord>,
+}
+
+/// Whether `name` can be used as a profile file stem: no path separators, no
+/// parent references, nothing hidden.
+pub fn valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && !name.contains(['/', '\\', ':'])
+        && !name.chars().any(char::is_control)
+}
+
+impl Control {
+    /// A control over `settings` with an idle launcher and no AMCP connection
+    #[cfg(test)]
+    pub(crate) fn for_tests(settings: GuiSettings) -> Control {
+        Control {
+            amcp_client: Arc::new(Mutex::new(AmcpClient::new())),
+            gui_settings: Arc::new(Mutex::new(settings)),
+            launcher: Arc::new(Launcher::new(
+                crate::logs::LogStore::default(),
+                crate::diagnostics::DiagnosticsEngine::default(),
+                None,
+                Arc::new(|_| {}),
+            )),
+            events: Default::default(),
+            auto_start: Default::default(),
+            tests: Default::default(),
+            read_only: false,
+        }
+    }
+
+    /// Refuse an operation that changes anything on a read-only launch
+    pub fn writable(&self) -> Result<(), String> {
+        if self.read_only {
+            Err("The GUI was started read-only".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The CasparCG installation directory from the GUI settings
+    pub async fn caspar_dir(&self) -> Result<PathBuf, String> {
+        self.gui_settings
+            .lock()
+            .await
+            .caspar_path
+            .clone()
+            .map(PathBuf::from)
+            .ok_or_else(|| "CasparCG path is not set — complete setup first".to_string())
+    }
+
+    /// Write `config` as casparcg.config and launch the server from the
+    /// configured installation directory, supervised with its restart policy.
+    /// `profile` is the saved profile it came from, remembered with the running
+    /// state for the next launch. Refused while a direct output test holds
+    /// one of its cards.
+    pub async fn start_server(&self, config: GlobalConfig, profile: Option<&str>) -> Result<(), String> {
+        self.check_tests_for_start(&config)?;
+        let dir = self.caspar_dir().await?;
+        self.launcher.start(config, dir).await?;
+        self.remember_running(true, profile).await;
+        Ok(())
+    }
+
+    /// Start the saved profile `name`, or the active profile when `None`.
+    /// Returns the name started.
+    pub async fn start_profile(&self, name: Option<&str>) -> Result<String, String> {
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => self
+                .gui_settings
+                .lock()
+                .await
+                .last_profile
+                .clone()
+                .ok_or_else(|| "No active profile — name one to start".to_string())?,
+        };
+        let config = self.load_profile(&name).await?;
+        self.start_server(config, Some(&name)).await?;
+        Ok(name)
+    }
+
+    pub async fn stop_server(&self) -> Result<(), String> {
+        let result = self.launcher.stop().await;
+        self.remember_running(false, None).await;
+        result
+    }
+
+    pub async fn server_running(&self) -> bool {
+        self.launcher.is_running().await
+    }
+
+    /// Resource samples of the server and scanner taken after `since`
+    pub fn resources(&self, since: Option<DateTime<Utc>>) -> ResourceSeries {
+        self.launcher.resources.series(since)
+    }
+
+    pub async fn status(&self) -> ServerStatus {
+        ServerStatus {
+            running: self.launcher.is_running().await,
+            supervising: self.launcher.is_supervising(),
+            profile: self.launcher.profile_name(),
+            active_profile: self.gui_settings.lock().await.last_profile.clone(),
+            amcp_connected: self.amcp_client.lock().await.is_connected(),
+            last_exit: self.launcher.exits.recent().pop(),
+        }
+    }
+
+    async fn profiles_dir(&self) -> Result<PathBuf, String> {
+        self.gui_settings
+            .lock()
+            .await
+            .profiles_dir()
+            .ok_or_else(|| "CasparCG path not set".to_string())
+    }
+
+    async fn profile_path(&self, name: &str) -> Result<PathBuf, String> {
+        if !valid_profile_name(name) {
+            return Err(format!("Invalid profile name '{}'", name));
+        }
+        Ok(self.profiles_dir().await?.join(format!("{}.json", name)))
+    }
+
+    /// Names of the profiles in the profiles directory
+    pub async fn list_profiles(&self) -> Result<Vec<String>, String> {
+        let profiles_dir = self.profiles_dir().await?;
+        if !profiles_dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let entries = std::fs::read_dir(&profiles_dir)
+            .map_err(|e| format!("Failed to read profiles directory: {}", e))?;
+
+        let profiles: Vec<String> = entries
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                e.path()
+                    .extension()
+                    .map(|ext| ext == "json")
+                    .unwrap_or(false)
+            })
+            .filter_map(|e| {
+                e.path()
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .map(|s| s.to_string())
+            })
+            .collect();
+
+        Ok(profiles)
+    }
+
+    /// The profile a launch named: a file stem, else the display name of one
+    /// of the saved profiles
+    pub async fn find_profile(&self, name: &str) -> Result<String, String> {
+        let profiles = self.list_profiles().await?;
+        if profiles.iter().any(|p| p == name) {
+            return Ok(name.to_string());
+        }
+        for profile in profiles {
+            if let Ok(config) = self.load_profile(&profile).await {
+                if config.name.eq_ignore_ascii_case(name) {
+                    return Ok(profile);
+                }
+            }
+        }
+        Err(format!("Profile '{}' not found", name))
+    }
+
+    /// The profile name of `path` when it is in the profiles directory
+    pub async fn profile_at(&self, path: &Path) -> Option<String> {
+        let dir = self.profiles_dir().await.ok()?;
+        let in_dir = path.parent().is_some_and(|parent| {
+            parent == dir
+                || parent
+                    .canonicalize()
+                    .is_ok_and(|p| dir.canonicalize().is_ok_and(|d| p == d))
+        });
+        let name = path.file_stem()?.to_str()?;
+        (in_dir && valid_profile_name(name)).then(|| name.to_string())
+    }
+
+    pub async fn load_profile(&self, name: &str) -> Result<GlobalConfig, String> {
+        let path = self.profile_path(name).await?;
+        if !path.exists() {
+            return Err(format!("Profile '{}' not found", name));
+        }
+        GlobalConfig::load_from_file(&path).map_err(|e| format!("Failed to load config: {}", e))
+    }
+
+    /// The active profile, else the one the latest Start launched
+    pub async fn active_config(&self) -> Result<GlobalConfig, String> {
+        let active = self.gui_settings.lock().await.last_profile.clone();
+        let name = active
+            .or_else(|| self.launcher.profile_name())
+            .ok_or_else(|| "No active profile".to_string())?;
+        self.load_profile(&name).await
+    }
+
+    pub async fn save_profile(&self, name: &str, mut config: GlobalConfig) -> Result<(), String> {
+        let path = self.profile_path(name).await?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create profiles directory: {}", e))?;
+        }
+        config.touch();
+        config
+            .save_to_file(&path)
+            .map_err(|e| format!("Failed to save config: {}", e))
+    }
+
+    /// Make `name` the active profile and tell the GUI to load it. Running
+    /// tests are stopped, and a running server is restarted on it; returns
+    /// whether that happened.
+    pub async fn activate_profile(&self, name: &str) -> Result<bool, String> {
+        let config = self.load_profile(name).await?;
+        self.release_tests("profile switch").await;
+        {
+            let mut settings = self.gui_settings.lock().await;
+            settings.last_profile = Some(name.to_string());
+            settings
+                .save()
+                .map_err(|e| format!("Failed to save settings: {}", e))?;
+        }
+        self.launcher
+            .emit(LauncherEvent::ProfileActivated(name.to_string()));
+
+        if !self.launcher.is_running().await {
+            return Ok(false);
+        }
+        self.launcher.stop().await?;
+        self.start_server(config, Some(name)).await?;
+        Ok(true)
+    }
+
+    /// Versions of everything installed, with CasparCG's own when connected
+    pub async fn system_versions(&self) -> SystemVersions {
+        let mut versions = system::collect_system_info(self.launcher.scanner_endpoint()).await;
+
+        // Try to get CasparCG version if connected
+        let client = self.amcp_client.lock().await;
+        if client.is_connected() {
+            if let Ok(version) = client.version().await {
+                versions.caspar_version = Some(version);
+            }
+        }
+
+        versions
+    }
+
+    /// Send a raw AMCP command on the shared connection
+    pub async fn amcp_command(&self, command: &str) -> Result<AmcpResponse, String> {
+        let client = self.amcp_client.lock().await;
+        client.send_command(command).await.map_err(|e| e.to_string())
+    }
+
+    /// Connect the shared AMCP client to the last-used host and port if it is
+    /// not connected already.
+    pub async fn ensure_amcp(&self) -> Result<(), String> {
+        let mut client = self.amcp_client.lock().await;
+        if client.is_connected() {
+            return Ok(());
+        }
+        let (host, port) = {
+            let settings = self.gui_settings.lock().await;
+            (
+                settings.last_host.clone().unwrap_or_else(|| "localhost".to_string()),
+                settings.last_port.unwrap_or(5250),
+            )
+        };
+        client.connect(&host, port).await.map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_names_cannot_leave_the_profiles_directory() {
+        assert!(valid_profile_name("Studio A - Main Playout"));
+        assert!(valid_profile_name("rehearsal_2"));
+        for name in ["", "../settings", "a/b", "a\\b", ".hidden", "C:evil", "line\nbreak"] {
+            assert!(!valid_profile_name(name), "{name:?} accepted");
+        }
+    }
+}
@@ -0,0 +1,324 @@
+// Test sessions
+// Every channel and DeckLink output test that is running, who started it and when it stops
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::Control;
+use crate::config::{Consumer, DeckLinkKeyer, GlobalConfig};
+use crate::decklink;
+use crate::logs::LogLevel;
+
+/// Topic the session list is published on whenever it changes
+pub const TEST_SESSIONS_TOPIC: &str = "test-sessions";
+
+/// What a test occupies. Starting a test on a target replaces the one there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TestTarget {
+    /// The AMCP test layers of a channel (1-based)
+    Channel { channel: u32 },
+    /// A DeckLink output driven directly through the SDK (1-based device)
+    Output { device: u32 },
+}
+
+impl std::fmt::Display for TestTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TestTarget::Channel { channel } => write!(f, "channel {channel}"),
+            TestTarget::Output { device } => write!(f, "DeckLink {device} output"),
+        }
+    }
+}
+
+/// A running test
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TestSession {
+    pub id: u64,
+    pub target: TestTarget,
+    /// What is playing, e.g. "identification", "AV sync", "SDI fill"
+    pub label: String,
+    /// Who started it: "GUI", "commissioning", …
+    pub owner: String,
+    pub started: DateTime<Utc>,
+    /// When it is stopped automatically, if ever
+    pub deadline: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default)]
+struct Sessions {
+    running: Vec<TestSession>,
+    next_id: u64,
+}
+
+/// The running tests, shared by the GUI, the API and the expiry sweep
+#[derive(Debug, Default)]
+pub struct TestSessions {
+    inner: Mutex<Sessions>,
+}
+
+impl TestSessions {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Sessions> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Record a test on `target`, replacing any there
+    pub fn begin(
+        &self,
+        target: TestTarget,
+        label: &str,
+        owner: &str,
+        timeout: Option<Duration>,
+        now: DateTime<Utc>,
+    ) -> TestSession {
+        let mut sessions = self.lock();
+        sessions.next_id += 1;
+        let session = TestSession {
+            id: sessions.next_id,
+            target,
+            label: label.to_string(),
+            owner: owner.to_string(),
+            started: now,
+            deadline: timeout.and_then(|t| chrono::Duration::from_std(t).ok()).map(|t| now + t),
+        };
+        sessions.running.retain(|s| s.target != target);
+        sessions.running.push(session.clone());
+        session
+    }
+
+    /// Forget the test on `target`
+    pub fn end(&self, target: TestTarget) -> Option<TestSession> {
+        let mut sessions = self.lock();
+        let at = sessions.running.iter().position(|s| s.target == target)?;
+        Some(sessions.running.remove(at))
+    }
+
+    pub fn list(&self) -> Vec<TestSession> {
+        self.lock().running.clone()
+    }
+
+    /// Forget and return the tests whose deadline has passed
+    pub fn take_expired(&self, now: DateTime<Utc>) -> Vec<TestSession> {
+        let mut sessions = self.lock();
+        let (expired, running) = std::mem::take(&mut sessions.running)
+            .into_iter()
+            .partition(|s| s.deadline.is_some_and(|d| d <= now));
+        sessions.running = running;
+        expired
+    }
+
+    /// Forget and return every test
+    pub fn take_all(&self) -> Vec<TestSession> {
+        std::mem::take(&mut self.lock().running)
+    }
+
+    /// Direct output tests on any DeckLink `config`'s channels play to
+    pub fn holding(&self, config: &GlobalConfig) -> Vec<TestSession> {
+        let devices = profile_devices(config);
+        self.lock()
+            .running
+            .iter()
+            .filter(|s| matches!(s.target, TestTarget::Output { device } if devices.contains(&device)))
+            .cloned()
+            .collect()
+    }
+}
+
+/// The DeckLink devices (1-based) `config` opens: fill and key of every
+/// DeckLink consumer. A separate-device keyer without a `key_device` keys on
+/// the next device.
+pub fn profile_devices(config: &GlobalConfig) -> Vec<u32> {
+    let mut devices: Vec<u32> = config
+        .caspar
+        .channels
+        .iter()
+        .flat_map(|channel| &channel.consumers)
+        .filter_map(|consumer| match consumer {
+            Consumer::DeckLink(d) => {
+                let key = match (d.key_device, &d.keyer) {
+                    (Some(key), _) => Some(key),
+                    (None, DeckLinkKeyer::ExternalSeparateDevice) => Some(d.device + 1),
+                    _ => None,
+                };
+                Some([Some(d.device), key])
+            }
+            _ => None,
+        })
+        .flatten()
+        .flatten()
+        .collect();
+    devices.sort_unstable();
+    devices.dedup();
+    devices
+}
+
+impl Control {
+    /// Record a test that has just been started, stopping it after the
+    /// configured timeout unless `timeout` says otherwise
+    pub async fn begin_test(
+        &self,
+        target: TestTarget,
+        label: &str,
+        owner: &str,
+        timeout: Option<Duration>,
+    ) -> TestSession {
+        let timeout = match timeout {
+            Some(t) => Some(t),
+            None => self.gui_settings.lock().await.test_timeout_secs.map(Duration::from_secs),
+        };
+        let session = self.tests.begin(target, label, owner, timeout, Utc::now());
+        self.publish_tests();
+        session
+    }
+
+    /// Forget a test that has just been stopped
+    pub fn end_test(&self, target: TestTarget) {
+        if self.tests.end(target).is_some() {
+            self.publish_tests();
+        }
+    }
+
+    /// Stop the test on `target` and forget it
+    pub async fn stop_test(&self, target: TestTarget) -> Result<(), String> {
+        self.tests.end(target);
+        self.publish_tests();
+        self.stop_target(target).await
+    }
+
+    /// Stop every test, e.g. on a profile switch or exit
+    pub async fn release_tests(&self, why: &str) {
+        let sessions = self.tests.take_all();
+        if sessions.is_empty() {
+            return;
+        }
+        self.publish_tests();
+        for session in sessions {
+            self.stop_session(&session, why).await;
+        }
+    }
+
+    /// Refuse a start while a direct output test holds a card `config` uses:
+    /// CasparCG could not open it
+    pub fn check_tests_for_start(&self, config: &GlobalConfig) -> Result<(), String> {
+        let holding = self.tests.holding(config);
+        if holding.is_empty() {
+            return Ok(());
+        }
+        let held: Vec<String> = holding
+            .iter()
+            .map(|s| format!("{} ({} by {} at {})", s.target, s.label, s.owner, s.started.format("%H:%M:%S")))
+            .collect();
+        Err(format!(
+            "A direct output test is holding {} — stop it before starting CasparCG",
+            held.join(", ")
+        ))
+    }
+
+    /// Stop tests past their deadline, once a second. Runs until the app exits.
+    pub async fn expire_tests(self) {
+        loop {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            let expired = self.tests.take_expired(Utc::now());
+            if expired.is_empty() {
+                continue;
+            }
+            self.publish_tests();
+            for session in expired {
+                self.stop_session(&session, "timed out").await;
+            }
+        }
+    }
+
+    async fn stop_session(&self, session: &TestSession, why: &str) {
+        let (level, text) = match self.stop_target(session.target).await {
+            Ok(()) => (LogLevel::Info, format!("stopped {} on {} ({why})", session.label, session.target)),
+            Err(e) => (
+                LogLevel::Warning,
+                format!("could not stop {} on {} ({why}): {e}", session.label, session.target),
+            ),
+        };
+        self.launcher.log(level, text);
+    }
+
+    async fn stop_target(&self, target: TestTarget) -> Result<(), String> {
+        match target {
+            TestTarget::Channel { channel } => {
+                let client = self.amcp_client.lock().await;
+                // A server that has gone has taken its layers with it
+                if !client.is_connected() {
+                    return Ok(());
+                }
+                client.stop_channel_test(channel).await.map_err(|e| e.to_string())
+            }
+            TestTarget::Output { device } => tokio::task::spawn_blocking(move || decklink::output_test_stop(device))
+                .await
+                .map_err(|e| e.to_string())?
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    fn publish_tests(&self) {
+        let payload = serde_json::to_value(self.tests.list()).unwrap_or_default();
+        self.events.publish(TEST_SESSIONS_TOPIC, payload);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Channel, DeckLinkConsumer};
+
+    #[test]
+    fn tracks_replaces_and_expires_tests() {
+        let tests = TestSessions::default();
+        let now = Utc::now();
+        let channel = TestTarget::Channel { channel: 1 };
+        let output = TestTarget::Output { device: 2 };
+        tests.begin(channel, "identification", "GUI", None, now);
+        let av = tests.begin(channel, "AV sync", "GUI", None, now);
+        tests.begin(output, "SDI fill", "commissioning", Some(Duration::from_secs(60)), now);
+        assert_eq!(tests.list().len(), 2);
+        assert_eq!(tests.list()[0], av);
+
+        assert!(tests.take_expired(now + chrono::Duration::seconds(59)).is_empty());
+        let expired = tests.take_expired(now + chrono::Duration::seconds(60));
+        assert_eq!(expired.iter().map(|s| s.target).collect::<Vec<_>>(), [output]);
+        assert_eq!(tests.end(channel).map(|s| s.id), Some(av.id));
+        assert!(tests.list().is_empty());
+    }
+
+    #[test]
+    fn an_output_test_on_a_profile_card_holds_the_start() {
+        let mut config = GlobalConfig::new("Studio A");
+        config.caspar.channels = vec![Channel {
+            consumers: vec![Consumer::DeckLink(DeckLinkConsumer {
+                device: 1,
+                key_device: Some(3),
+                ..Default::default()
+            })],
+            ..Default::default()
+        }];
+        assert_eq!(profile_devices(&config), [1, 3]);
+        let mut separate = config.clone();
+        separate.caspar.channels[0].consumers = vec![Consumer::DeckLink(DeckLinkConsumer {
+            device: 2,
+            keyer: DeckLinkKeyer::ExternalSeparateDevice,
+            ..Default::default()
+        })];
+        assert_eq!(profile_devices(&separate), [2, 3]);
+
+        let tests = TestSessions::default();
+        tests.begin(TestTarget::Output { device: 2 }, "SDI fill", "GUI", None, Utc::now());
+        tests.begin(TestTarget::Channel { channel: 1 }, "identification", "GUI", None, Utc::now());
+        assert!(tests.holding(&config).is_empty());
+        tests.begin(TestTarget::Output { device: 3 }, "SDI key", "GUI", None, Utc::now());
+        assert_eq!(tests.holding(&config)[0].target, TestTarget::Output { device: 3 });
+        assert_eq!(
+            serde_json::to_value(TestTarget::Output { device: 3 }).unwrap(),
+            serde_json::json!({ "kind": "output", "device": 3 })
+        );
+    }
+}
@@ -0,0 +1,225 @@
+// Crash report bundle
+// The report's contents, and how they are laid out in the zip
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::config::GlobalConfig;
+use crate::diagnostics::Diagnostic;
+use crate::logs::{LogEntry, LogSource};
+use crate::supervisor::ExitRecord;
+use crate::system::SystemVersions;
+
+/// Everything gathered for one report. Sections that could not be collected
+/// are left empty rather than failing the report — a partial report beats none.
+#[derive(Debug, Clone)]
+pub struct CrashReport {
+    /// Why the report was made ("crashed 4 times in 60s", "requested by user")
+    pub reason: String,
+    pub created: DateTime<Utc>,
+    /// The newest console lines from each process
+    pub logs: Vec<(LogSource, Vec<LogEntry>)>,
+    /// The casparcg.config the server was started with
+    pub casparcg_config: Option<String>,
+    /// The profile the server was started from
+    pub profile: Option<GlobalConfig>,
+    pub system: Option<SystemVersions>,
+    /// DeckLink devices and their live status (see `decklink_snapshot`)
+    pub decklink: serde_json::Value,
+    pub exits: Vec<ExitRecord>,
+    pub diagnostics: Vec<Diagnostic>,
+    /// CasparCG's own log files from `paths.log`: (file name, contents)
+    pub caspar_logs: Vec<(String, Vec<u8>)>,
+}
+
+/// Top-level summary written as `report.json`
+#[derive(Serialize)]
+struct Summary<'a> {
+    reason: &'a str,
+    created: DateTime<Utc>,
+    launcher_version: &'a str,
+    os: &'a str,
+    files: Vec<String>,
+}
+
+impl CrashReport {
+    pub fn new(reason: impl Into<String>) -> Self {
+        Self {
+            reason: reason.into(),
+            created: Utc::now(),
+            logs: Vec::new(),
+            casparcg_config: None,
+            profile: None,
+            system: None,
+            decklink: serde_json::Value::Null,
+            exits: Vec::new(),
+            diagnostics: Vec::new(),
+            caspar_logs: Vec::new(),
+        }
+    }
+
+    /// `crash-20250101-120000.zip`, in UTC so reports sort by time. Attempts
+    /// after the first add `-2`, `-3`… for reports made in the same second.
+    pub fn file_name(&self, attempt: u32) -> String {
+        let stamp = self.created.format("%Y%m%d-%H%M%S");
+        match attempt {
+            0 | 1 => format!("crash-{stamp}.zip"),
+            n => format!("crash-{stamp}-{n}.zip"),
+        }
+    }
+
+    /// Write the report into `dir` under the first free `file_name`, never
+    /// over an earlier report. Blocking: call it off the async runtime.
+    pub fn write_zip_in(&self, dir: &Path) -> Result<PathBuf, CrashReportError> {
+        let mut attempt = 1;
+        loop {
+            let path = dir.join(self.file_name(attempt));
+            match self.write_zip(&path) {
+                Err(CrashReportError::Io(e)) if e.kind() == std::io::ErrorKind::AlreadyExists && attempt < 100 => {
+                    attempt += 1;
+                }
+                result => return result.map(|()| path),
+            }
+        }
+    }
+
+    /// The report's files as (path in zip, contents)
+    fn entries(&self) -> Result<Vec<(String, Vec<u8>)>, CrashReportError> {
+        let mut files = Vec::new();
+        for (source, entries) in &self.logs {
+            let mut text = String::new();
+            for entry in entries {
+                text.push_str(&entry.to_line());
+                text.push('\n');
+            }
+            files.push((format!("logs/{}.log", source.as_str()), text.into_bytes()));
+        }
+        if let Some(xml) = &self.casparcg_config {
+            files.push(("casparcg.config".to_string(), xml.clone().into_bytes()));
+        }
+        if let Some(profile) = &self.profile {
+            files.push(("profile.json".to_string(), serde_json::to_vec_pretty(profile)?));
+        }
+        if let Some(system) = &self.system {
+            files.push(("system.json".to_string(), serde_json::to_vec_pretty(system)?));
+        }
+        if !self.decklink.is_null() {
+            files.push(("decklink.json".to_string(), serde_json::to_vec_pretty(&self.decklink)?));
+        }
+        files.push(("exits.json".to_string(), serde_json::to_vec_pretty(&self.exits)?));
+        files.push((
+            "diagnostics.json".to_string(),
+            serde_json::to_vec_pretty(&self.diagnostics)?,
+        ));
+        for (name, contents) in &self.caspar_logs {
+            files.push((format!("caspar-log/{name}"), contents.clone()));
+        }
+        Ok(files)
+    }
+
+    /// Write the report as a zip at `path`, with a `report.json` index first.
+    /// Fails if a file is already there.
+    pub fn write_zip(&self, path: &Path) -> Result<(), CrashReportError> {
+        let files = self.entries()?;
+        let summary = Summary {
+            reason: &self.reason,
+            created: self.created,
+            launcher_version: env!("CARGO_PKG_VERSION"),
+            os: std::env::consts::OS,
+            files: files.iter().map(|(name, _)| name.clone()).collect(),
+        };
+
+        let mut zip = ZipWriter::new(std::fs::File::create_new(path)?);
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        zip.start_file("report.json", options)?;
+        zip.write_all(&serde_json::to_vec_pretty(&summary)?)?;
+        for (name, contents) in files {
+            zip.start_file(name, options)?;
+            zip.write_all(&contents)?;
+        }
+        zip.finish()?;
+        Ok(())
+    }
+}
+
+/// Errors that can occur when writing a crash report
+#[derive(Debug, thiserror::Error)]
+pub enum CrashReportError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Zip error: {0}")]
+    Zip(#[from] zip::result::ZipError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::LogLevel;
+    use std::io::Read;
+
+    #[test]
+    fn writes_every_section_into_the_zip() {
+        let mut report = CrashReport::new("crashed 4 times in 60s");
+        report.logs.push((
+            LogSource::Server,
+            vec![LogEntry {
+                seq: 7,
+                source: LogSource::Server,
+                level: LogLevel::Error,
+                timestamp: Utc::now(),
+                text: "Could not enable primary video output.".to_string(),
+            }],
+        ));
+        report.casparcg_config = Some("<configuration/>".to_string());
+        report.profile = Some(GlobalConfig::new("Studio A"));
+        report.decklink = serde_json::json!({ "devices": [] });
+        report.exits.push(ExitRecord {
+            timestamp: Utc::now(),
+            code: Some(-1073741819),
+            hung: false,
+            uptime_secs: 4.2,
+            action: "give_up".to_string(),
+            reason: "crash".to_string(),
+        });
+        report.caspar_logs.push(("caspar_2025-01-01.log".to_string(), b"boot\n".to_vec()));
+
+        let path = std::env::temp_dir().join(format!("crash-report-test-{}.zip", std::process::id()));
+        report.write_zip(&path).unwrap();
+
+        let mut zip = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let mut read = |name: &str| {
+            let mut text = String::new();
+            zip.by_name(name).unwrap().read_to_string(&mut text).unwrap();
+            text
+        };
+        assert!(read("report.json").contains("crashed 4 times"));
+        assert!(read("logs/server.log").contains("[server] [error] Could not enable"));
+        assert_eq!(read("casparcg.config"), "<configuration/>");
+        assert!(read("profile.json").contains("Studio A"));
+        assert!(read("exits.json").contains("-1073741819"));
+        assert_eq!(read("caspar-log/caspar_2025-01-01.log"), "boot\n");
+        assert!(zip.by_name("system.json").is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn reports_in_the_same_second_do_not_overwrite() {
+        let dir = std::env::temp_dir().join(format!("crash-report-names-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let report = CrashReport::new("crashed");
+        let first = report.write_zip_in(&dir).unwrap();
+        let second = report.write_zip_in(&dir).unwrap();
+        assert_eq!(first.file_name().unwrap().to_string_lossy(), report.file_name(1));
+        assert_eq!(second.file_name().unwrap().to_string_lossy(), report.file_name(2));
+        assert!(report.file_name(2).ends_with("-2.zip"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
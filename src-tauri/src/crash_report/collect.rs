@@ -0,0 +1,95 @@
+// Crash report collection
+// Gathers the parts of a report that live outside the launcher's own state
+
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// CasparCG's newest log files to include, and how much of each (the tail).
+const CASPAR_LOG_FILES: usize = 3;
+const CASPAR_LOG_MAX_BYTES: u64 = 4 * 1024 * 1024;
+
+/// The newest `.log` files in CasparCG's log directory, trimmed to their last
+/// few megabytes. A missing or unreadable directory yields nothing.
+pub fn caspar_log_files(dir: &Path) -> Vec<(String, Vec<u8>)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut logs: Vec<_> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "log"))
+        .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+        .collect();
+    logs.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+
+    logs.into_iter()
+        .take(CASPAR_LOG_FILES)
+        .filter_map(|(_, path)| {
+            let name = path.file_name()?.to_string_lossy().into_owned();
+            Some((name, read_tail(&path, CASPAR_LOG_MAX_BYTES).ok()?))
+        })
+        .collect()
+}
+
+fn read_tail(path: &Path, max: u64) -> std::io::Result<Vec<u8>> {
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    if len > max {
+        file.seek(SeekFrom::Start(len - max))?;
+    }
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// DeckLink devices and each one's live status. Errors are recorded in place —
+/// a driver that cannot be queried is itself worth reporting.
+pub fn decklink_snapshot() -> serde_json::Value {
+    match crate::decklink::list_devices() {
+        Ok(devices) => {
+            let statuses: Vec<serde_json::Value> = devices
+                .iter()
+                .map(|d| match crate::decklink::get_device_status(d.index) {
+                    Ok(status) => serde_json::json!({ "index": d.index, "status": status }),
+                    Err(e) => serde_json::json!({ "index": d.index, "error": e.to_string() }),
+                })
+                .collect();
+            serde_json::json!({
+                "driver_version": crate::decklink::get_driver_version().ok().flatten(),
+                "devices": devices,
+                "statuses": statuses,
+            })
+        }
+        Err(e) => serde_json::json!({ "error": e.to_string() }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn takes_the_newest_log_files_tail_first() {
+        let dir = std::env::temp_dir().join(format!("crash-report-logs-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for day in 1..=4 {
+            std::fs::write(dir.join(format!("caspar_2025-01-0{day}.log")), format!("day {day}\n"))
+                .unwrap();
+            // Distinct modification times, oldest first.
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        std::fs::write(dir.join("notes.txt"), "not a log").unwrap();
+
+        let logs = caspar_log_files(&dir);
+        let names: Vec<_> = logs.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(
+            names,
+            ["caspar_2025-01-04.log", "caspar_2025-01-03.log", "caspar_2025-01-02.log"]
+        );
+
+        let big = dir.join("big.log");
+        std::fs::write(&big, "x".repeat(100) + "END").unwrap();
+        assert_eq!(read_tail(&big, 3).unwrap(), b"END");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,8 @@
+// Crash report module
+// Bundles logs, configuration and hardware state into a zip for engineering
+
+mod bundle;
+mod collect;
+
+pub use bundle::*;
+pub use collect::*;
@@ -0,0 +1,462 @@
+// Diagnostics engine
+// Runs log entries and exit codes through the rule catalogue and keeps recent findings
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{merge_rules, parse_rules, CompiledRule, RuleError, Severity, BUILTIN_RULES};
+use crate::logs::{LogEntry, LogSource};
+
+/// Diagnostics kept for the GUI to show after a reload.
+const MAX_RECENT: usize = 200;
+
+/// What made a rule fire
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DiagnosticTrigger {
+    /// A console line
+    LogLine {
+        source: LogSource,
+        seq: u64,
+        text: String,
+    },
+    /// The server process exiting
+    ExitCode { code: i32 },
+    /// Something the launcher observed, such as the watchdog declaring a hang.
+    /// `last_output` is the server's console tail at that moment.
+    Event {
+        event: String,
+        reason: String,
+        last_output: Vec<String>,
+    },
+}
+
+/// A diagnosed problem, emitted to the GUI as a `diagnostic` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub rule_id: String,
+    pub severity: Severity,
+    pub title: String,
+    pub explanation: String,
+    pub likely_cause: String,
+    pub suggested_action: String,
+    pub trigger: DiagnosticTrigger,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Outcome of (re)loading the catalogue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleLoadSummary {
+    /// Active rules after merging
+    pub rule_count: usize,
+    /// User rules file that was looked for, whether or not it exists
+    pub user_rules_path: Option<String>,
+    /// Rules that were skipped, and why
+    pub errors: Vec<String>,
+}
+
+/// Thread-safe rule matcher. Uses a std mutex for the same reason as the log
+/// store: it is fed from the blocking console reader threads.
+#[derive(Debug)]
+pub struct DiagnosticsEngine {
+    user_rules_path: Option<PathBuf>,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    rules: Vec<CompiledRule>,
+    last_fired: HashMap<String, Instant>,
+    recent: VecDeque<Diagnostic>,
+}
+
+/// Load the built-in catalogue and overlay the user's rules file, if any.
+pub fn load_rules(user_path: Option<&Path>) -> (Vec<CompiledRule>, Vec<String>) {
+    let mut errors = Vec::new();
+    let builtin = match parse_rules(BUILTIN_RULES) {
+        Ok((rules, errs)) => {
+            errors.extend(errs.iter().map(|e| format!("built-in: {e}")));
+            rules
+        }
+        Err(e) => {
+            errors.push(format!("built-in: {e}"));
+            Vec::new()
+        }
+    };
+
+    let user = match user_path.filter(|p| p.exists()) {
+        Some(path) => match std::fs::read_to_string(path)
+            .map_err(RuleError::from)
+            .and_then(|json| parse_rules(&json))
+        {
+            Ok((rules, errs)) => {
+                errors.extend(errs.iter().map(|e| format!("{}: {e}", path.display())));
+                rules
+            }
+            Err(e) => {
+                errors.push(format!("{}: {e}", path.display()));
+                Vec::new()
+            }
+        },
+        None => Vec::new(),
+    };
+
+    (merge_rules(builtin, user), errors)
+}
+
+impl DiagnosticsEngine {
+    pub fn new(rules: Vec<CompiledRule>) -> Self {
+        Self {
+            user_rules_path: None,
+            inner: Mutex::new(Inner {
+                rules,
+                ..Inner::default()
+            }),
+        }
+    }
+
+    /// An engine with the built-in rules plus those in `user_rules_path`.
+    /// Bad rules are skipped; the problems with the user file are returned for
+    /// the caller to log.
+    pub fn with_user_rules(user_rules_path: Option<PathBuf>) -> (Self, Vec<String>) {
+        let (rules, errors) = load_rules(user_rules_path.as_deref());
+        let engine = Self {
+            user_rules_path,
+            ..Self::new(rules)
+        };
+        (engine, errors)
+    }
+
+    /// Re-read the user rules file, keeping cooldowns and history.
+    pub fn reload(&self) -> RuleLoadSummary {
+        let (rules, errors) = load_rules(self.user_rules_path.as_deref());
+        let rule_count = rules.len();
+        self.lock().rules = rules;
+        RuleLoadSummary {
+            rule_count,
+            user_rules_path: self.user_rules_path.as_ref().map(|p| p.display().to_string()),
+            errors,
+        }
+    }
+
+    /// Diagnose one log entry. At most one diagnostic per line: the first
+    /// matching rule in catalogue order wins.
+    pub fn check_line(&self, entry: &LogEntry) -> Option<Diagnostic> {
+        self.check_line_at(entry, Instant::now())
+    }
+
+    fn check_line_at(&self, entry: &LogEntry, now: Instant) -> Option<Diagnostic> {
+        let mut inner = self.lock();
+        let (id, rendered) = inner.rules.iter().find_map(|rule| {
+            if !rule.applies_to(entry.source) {
+                return None;
+            }
+            let captures = rule.regex.as_ref()?.captures(&entry.text)?;
+            let names: Vec<(&str, &str)> = rule
+                .regex
+                .as_ref()?
+                .capture_names()
+                .flatten()
+                .filter_map(|name| captures.name(name).map(|m| (name, m.as_str())))
+                .collect();
+            Some((rule.rule.id.clone(), render(rule, &names)))
+        })?;
+        let trigger = DiagnosticTrigger::LogLine {
+            source: entry.source,
+            seq: entry.seq,
+            text: entry.text.clone(),
+        };
+        inner.fire(&id, rendered, trigger, entry.timestamp, now)
+    }
+
+    /// Diagnose a server exit. Every rule listing `code` fires.
+    pub fn check_exit(&self, code: i32) -> Vec<Diagnostic> {
+        self.check_exit_at(code, Instant::now())
+    }
+
+    fn check_exit_at(&self, code: i32, now: Instant) -> Vec<Diagnostic> {
+        let shown = format_exit_code(code);
+        let mut inner = self.lock();
+        let matched: Vec<(String, Rendered)> = inner
+            .rules
+            .iter()
+            .filter(|rule| rule.matches_exit(code))
+            .map(|rule| (rule.rule.id.clone(), render(rule, &[("code", shown.as_str())])))
+            .collect();
+        matched
+            .into_iter()
+            .filter_map(|(id, rendered)| {
+                inner.fire(&id, rendered, DiagnosticTrigger::ExitCode { code }, Utc::now(), now)
+            })
+            .collect()
+    }
+
+    /// Diagnose a launcher event. Every rule listing `event` fires.
+    pub fn check_event(&self, event: &str, reason: &str, last_output: Vec<String>) -> Vec<Diagnostic> {
+        let now = Instant::now();
+        let mut inner = self.lock();
+        let matched: Vec<(String, Rendered)> = inner
+            .rules
+            .iter()
+            .filter(|rule| rule.rule.events.iter().any(|e| e == event))
+            .map(|rule| (rule.rule.id.clone(), render(rule, &[("reason", reason)])))
+            .collect();
+        matched
+            .into_iter()
+            .filter_map(|(id, rendered)| {
+                let trigger = DiagnosticTrigger::Event {
+                    event: event.to_string(),
+                    reason: reason.to_string(),
+                    last_output: last_output.clone(),
+                };
+                inner.fire(&id, rendered, trigger, Utc::now(), now)
+            })
+            .collect()
+    }
+
+    /// Diagnostics raised so far, oldest first
+    pub fn recent(&self) -> Vec<Diagnostic> {
+        self.lock().recent.iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        let mut inner = self.lock();
+        inner.recent.clear();
+        inner.last_fired.clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Inner {
+    /// Record a match unless the rule is still cooling down from its last one.
+    fn fire(
+        &mut self,
+        id: &str,
+        rendered: Rendered,
+        trigger: DiagnosticTrigger,
+        timestamp: DateTime<Utc>,
+        now: Instant,
+    ) -> Option<Diagnostic> {
+        let cooldown = Duration::from_secs(rendered.cooldown_secs);
+        if let Some(last) = self.last_fired.get(id) {
+            if !cooldown.is_zero() && now.duration_since(*last) < cooldown {
+                return None;
+            }
+        }
+        self.last_fired.insert(id.to_string(), now);
+
+        let diagnostic = Diagnostic {
+            rule_id: id.to_string(),
+            severity: rendered.severity,
+            title: rendered.title,
+            explanation: rendered.explanation,
+            likely_cause: rendered.likely_cause,
+            suggested_action: rendered.suggested_action,
+            trigger,
+            timestamp,
+        };
+        if self.recent.len() == MAX_RECENT {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(diagnostic.clone());
+        Some(diagnostic)
+    }
+}
+
+/// A rule's text with its placeholders filled in
+struct Rendered {
+    severity: Severity,
+    cooldown_secs: u64,
+    title: String,
+    explanation: String,
+    likely_cause: String,
+    suggested_action: String,
+}
+
+fn render(rule: &CompiledRule, values: &[(&str, &str)]) -> Rendered {
+    let fill = |text: &str| {
+        values.iter().fold(text.to_string(), |acc, (name, value)| {
+            acc.replace(&format!("{{{name}}}"), value)
+        })
+    };
+    Rendered {
+        severity: rule.rule.severity,
+        cooldown_secs: rule.cooldown_secs(),
+        title: fill(&rule.rule.title),
+        explanation: fill(&rule.rule.explanation),
+        likely_cause: fill(&rule.rule.likely_cause),
+        suggested_action: fill(&rule.rule.suggested_action),
+    }
+}
+
+/// Exit codes as Windows tools show them: NTSTATUS crash codes in hex.
+pub fn format_exit_code(code: i32) -> String {
+    if code < 0 {
+        format!("0x{:08X}", code as u32)
+    } else {
+        code.to_string()
+    }
+}
+
+impl Default for DiagnosticsEngine {
+    fn default() -> Self {
+        Self::with_user_rules(None).0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::LogStore;
+
+    /// Console excerpts captured from CasparCG 2.4/2.5 and the media scanner.
+    const CAPTURED: &[(LogSource, &str, Option<&str>)] = &[
+        (
+            LogSource::Server,
+            "[2024-11-02 18:04:11.532] [error]   Could not enable primary video output.",
+            Some("decklink-output-in-use"),
+        ),
+        (
+            LogSource::Server,
+            "[2024-11-02 18:04:11.533] [error]   decklink_consumer[1|1080i5000] Failed to enable external keyer.",
+            Some("decklink-no-keyer"),
+        ),
+        (
+            LogSource::Server,
+            "[2024-11-02 18:04:09.870] [info]    Initialized OpenGL 4.6.14761 Compatibility Profile Context 30.0.13025.1000 ATI Technologies Inc.",
+            Some("amd-gpu-mixer"),
+        ),
+        (
+            LogSource::Server,
+            "[2024-11-02 18:04:09.870] [info]    Initialized OpenGL 4.6.0 NVIDIA 551.23 NVIDIA Corporation",
+            None,
+        ),
+        (
+            LogSource::Server,
+            "[2024-11-02 18:10:42.101] [error]   [ERROR:gpu_process_host.cc(991)] GPU process isn't usable. Goodbye.",
+            Some("html-gpu-process"),
+        ),
+        (
+            LogSource::Server,
+            "[2024-11-02 18:12:03.004] [error]   File not found: AMB",
+            Some("media-file-not-found"),
+        ),
+        (
+            LogSource::Scanner,
+            r#"{"level":50,"time":1730567051000,"msg":"listen EADDRINUSE: address already in use 127.0.0.1:8010"}"#,
+            Some("scanner-port-in-use"),
+        ),
+        (
+            LogSource::Server,
+            "[2024-11-02 18:04:08.112] [error]   bind: Only one usage of each socket address (protocol/network address/port) is normally permitted",
+            Some("amcp-port-in-use"),
+        ),
+        (
+            LogSource::Scanner,
+            "Could not enable primary video output",
+            None,
+        ),
+    ];
+
+    #[test]
+    fn builtin_catalogue_compiles() {
+        let (rules, errors) = load_rules(None);
+        assert!(errors.is_empty(), "{errors:?}");
+        assert!(rules.len() >= 10);
+        let mut ids: Vec<&str> = rules.iter().map(|r| r.rule.id.as_str()).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), rules.len(), "rule ids must be unique");
+    }
+
+    #[test]
+    fn matches_captured_log_excerpts() {
+        let store = LogStore::default();
+        for (source, raw, expected) in CAPTURED {
+            let engine = DiagnosticsEngine::default();
+            let entry = store.record(*source, raw);
+            let fired = engine.check_line(&entry).map(|d| d.rule_id);
+            assert_eq!(fired.as_deref(), *expected, "line: {raw}");
+        }
+    }
+
+    #[test]
+    fn cooldown_suppresses_repeats() {
+        let store = LogStore::default();
+        let engine = DiagnosticsEngine::default();
+        let entry = store.record(
+            LogSource::Server,
+            "[2024-11-02 18:04:11.532] [error]   Could not enable primary video output.",
+        );
+        let start = Instant::now();
+        assert!(engine.check_line_at(&entry, start).is_some());
+        assert!(engine.check_line_at(&entry, start + Duration::from_secs(5)).is_none());
+        assert!(engine.check_line_at(&entry, start + Duration::from_secs(61)).is_some());
+        assert_eq!(engine.recent().len(), 2);
+    }
+
+    #[test]
+    fn explains_windows_crash_codes() {
+        let engine = DiagnosticsEngine::default();
+        // 0xC0000005 as Rust reports it from ExitStatus::code() on Windows.
+        let fired = engine.check_exit(-1073741819);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].rule_id, "exit-access-violation");
+        assert!(fired[0].explanation.contains("0xC0000005"));
+        assert!(engine.check_exit(0).is_empty());
+    }
+
+    #[test]
+    fn explains_a_watchdog_hang() {
+        let engine = DiagnosticsEngine::default();
+        let fired = engine.check_event(
+            "watchdog-hung",
+            "AMCP not answering: no reply within 3s",
+            vec!["[info] last line".to_string()],
+        );
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].rule_id, "server-hung");
+        assert!(fired[0].explanation.contains("no reply within 3s"));
+    }
+
+    #[test]
+    fn user_rules_override_extend_and_disable() {
+        let dir = std::env::temp_dir().join(format!("caspar-diag-rules-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("diagnostic-rules.json");
+        std::fs::write(
+            &path,
+            r#"{ "rules": [
+                { "id": "decklink-no-keyer", "disabled": true },
+                { "id": "site-router-timeout", "severity": "error",
+                  "title": "Router {name} timed out", "pattern": "router (?P<name>\\w+) timeout" },
+                { "id": "broken", "pattern": "(" }
+            ] }"#,
+        )
+        .unwrap();
+
+        let (engine, errors) = DiagnosticsEngine::with_user_rules(Some(path));
+        assert_eq!(errors.len(), 1, "the bad pattern is returned, not fatal");
+        let summary = engine.reload();
+        assert_eq!(summary.errors.len(), 1, "the bad pattern is reported, not fatal");
+
+        let store = LogStore::default();
+        let keyer = store.record(LogSource::Server, "[2024-11-02 18:04:11.533] [error]   Failed to enable external keyer.");
+        assert!(engine.check_line(&keyer).is_none());
+
+        let router = store.record(LogSource::Launcher, "router MAIN timeout");
+        let fired = engine.check_line(&router).unwrap();
+        assert_eq!(fired.rule_id, "site-router-timeout");
+        assert_eq!(fired.title, "Router MAIN timed out");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,8 @@
+// Diagnostics module
+// Matches console output and exit codes against known CasparCG failure signatures
+
+mod engine;
+mod rule;
+
+pub use engine::*;
+pub use rule::*;
@@ -0,0 +1,173 @@
+// Diagnostic rules
+// The rule file format, and its compiled, ready-to-match form
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::logs::LogSource;
+
+/// The catalogue shipped with the launcher. Site-specific rules are merged over
+/// it from the user's rules file (see `GuiSettings::diagnostic_rules_path`).
+pub const BUILTIN_RULES: &str = include_str!("rules.json");
+
+/// Seconds a rule stays quiet after firing when the rule does not say otherwise,
+/// so a line CasparCG repeats every frame raises one diagnostic, not thousands.
+const DEFAULT_COOLDOWN_SECS: u64 = 60;
+
+/// How serious a diagnosed problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Expected or benign — explained so nobody chases it
+    Info,
+    /// Degraded, or likely to fail later
+    Warning,
+    /// Something is not working
+    Error,
+    /// The server cannot run until this is fixed
+    Critical,
+}
+
+/// One rule as written in a rules file. A rule matches a console line
+/// (`pattern`, optionally limited to `sources`), a server exit code
+/// (`exit_codes`), a launcher event (`events`), or any combination.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticRule {
+    /// Stable identifier; a user rule with the same id replaces the built-in one
+    pub id: String,
+    #[serde(default = "default_severity")]
+    pub severity: Severity,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub explanation: String,
+    #[serde(default)]
+    pub likely_cause: String,
+    #[serde(default)]
+    pub suggested_action: String,
+    /// Regular expression tested against each log entry's text. Named groups
+    /// can be quoted in the text fields as `{name}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Only test lines from these sources (all sources when absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sources: Option<Vec<LogSource>>,
+    /// Server exit codes this rule explains. Windows NTSTATUS codes may be
+    /// written unsigned (3221225477) or signed (-1073741819); `{code}` in the
+    /// text fields is replaced by the code as reported.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exit_codes: Vec<i64>,
+    /// Launcher events this rule explains (e.g. `watchdog-hung`); `{reason}`
+    /// in the text fields is replaced by the event's reason
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<String>,
+    /// Seconds to stay quiet after firing (default 60; 0 fires every time)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cooldown_secs: Option<u64>,
+    /// Switch a built-in rule off by redefining its id with `"disabled": true`
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+fn default_severity() -> Severity {
+    Severity::Warning
+}
+
+/// Top level of a rules file. Keys starting with `_` are documentation.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RuleFile {
+    #[serde(default)]
+    pub rules: Vec<DiagnosticRule>,
+}
+
+/// A rule ready to match, with its pattern compiled once at load
+#[derive(Debug, Clone)]
+pub struct CompiledRule {
+    pub rule: DiagnosticRule,
+    pub regex: Option<Regex>,
+}
+
+impl CompiledRule {
+    pub fn compile(rule: DiagnosticRule) -> Result<Self, RuleError> {
+        let regex = match &rule.pattern {
+            Some(pattern) => Some(Regex::new(pattern).map_err(|e| RuleError::Pattern {
+                id: rule.id.clone(),
+                message: e.to_string(),
+            })?),
+            None => None,
+        };
+        if regex.is_none() && rule.exit_codes.is_empty() && rule.events.is_empty() {
+            return Err(RuleError::NoTrigger(rule.id));
+        }
+        Ok(Self { rule, regex })
+    }
+
+    pub fn cooldown_secs(&self) -> u64 {
+        self.rule.cooldown_secs.unwrap_or(DEFAULT_COOLDOWN_SECS)
+    }
+
+    /// Whether `source` is one this rule listens to
+    pub fn applies_to(&self, source: LogSource) -> bool {
+        match &self.rule.sources {
+            Some(sources) if !sources.is_empty() => sources.contains(&source),
+            _ => true,
+        }
+    }
+
+    /// Whether `code` is one of this rule's exit codes, in either signed form
+    pub fn matches_exit(&self, code: i32) -> bool {
+        let signed = code as i64;
+        let unsigned = code as u32 as i64;
+        self.rule
+            .exit_codes
+            .iter()
+            .any(|&c| c == signed || c == unsigned)
+    }
+}
+
+/// Parse a rules file and compile every rule in it. Bad rules are reported and
+/// skipped individually so one typo does not disable the whole catalogue.
+pub fn parse_rules(json: &str) -> Result<(Vec<CompiledRule>, Vec<RuleError>), RuleError> {
+    let file: RuleFile = serde_json::from_str(json)?;
+    let mut compiled = Vec::new();
+    let mut errors = Vec::new();
+    for rule in file.rules {
+        if rule.disabled {
+            // Kept so the merge can see the id; dropped after merging.
+            compiled.push(CompiledRule { rule, regex: None });
+            continue;
+        }
+        match CompiledRule::compile(rule) {
+            Ok(rule) => compiled.push(rule),
+            Err(e) => errors.push(e),
+        }
+    }
+    Ok((compiled, errors))
+}
+
+/// Overlay `user` rules on `base`: same id replaces in place, new ids append,
+/// and disabled rules are removed.
+pub fn merge_rules(base: Vec<CompiledRule>, user: Vec<CompiledRule>) -> Vec<CompiledRule> {
+    let mut merged = base;
+    for rule in user {
+        match merged.iter_mut().find(|r| r.rule.id == rule.rule.id) {
+            Some(existing) => *existing = rule,
+            None => merged.push(rule),
+        }
+    }
+    merged.retain(|r| !r.rule.disabled);
+    merged
+}
+
+/// Errors that can occur when loading diagnostic rules
+#[derive(Debug, thiserror::Error)]
+pub enum RuleError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON parse error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("rule '{id}': invalid pattern: {message}")]
+    Pattern { id: String, message: String },
+    #[error("rule '{0}' has no pattern, exit codes or events")]
+    NoTrigger(String),
+}
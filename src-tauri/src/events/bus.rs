@@ -0,0 +1,143 @@
+// Event bus
+// Broadcasts topic-tagged JSON events and keeps the latest for replay
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use super::TopicFilter;
+use crate::supervisor::{EventSink, LauncherEvent};
+
+/// Events kept for replay to a new subscriber
+pub const REPLAY_CAPACITY: usize = 500;
+
+/// How far a subscriber may fall behind before it misses events
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// One event as published: the payload is the topic's own type (a `LogEntry`
+/// for `caspar-log`, a `Diagnostic` for `diagnostic`, …) encoded as JSON.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BusEvent {
+    /// Increases by one per event, so a gap shows a subscriber missed some
+    pub seq: u64,
+    pub topic: String,
+    pub timestamp: DateTime<Utc>,
+    pub payload: serde_json::Value,
+}
+
+struct History {
+    next_seq: u64,
+    events: VecDeque<BusEvent>,
+}
+
+/// Fan-out of backend events. The Tauri emitter is one subscriber and the
+/// WebSocket endpoint another.
+pub struct EventBus {
+    tx: broadcast::Sender<BusEvent>,
+    history: Mutex<History>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            tx,
+            history: Mutex::new(History {
+                next_seq: 1,
+                events: VecDeque::with_capacity(REPLAY_CAPACITY),
+            }),
+        }
+    }
+
+    pub fn publish(&self, topic: &str, payload: serde_json::Value) -> BusEvent {
+        // Sent under the history lock, so a subscriber's replay and live stream
+        // meet without a gap or a duplicate.
+        let mut history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        let event = BusEvent {
+            seq: history.next_seq,
+            topic: topic.to_string(),
+            timestamp: Utc::now(),
+            payload,
+        };
+        history.next_seq += 1;
+        if history.events.len() == REPLAY_CAPACITY {
+            history.events.pop_front();
+        }
+        history.events.push_back(event.clone());
+        let _ = self.tx.send(event.clone()); // no subscribers is fine
+        event
+    }
+
+    /// Subscribe to live events, with up to `replay` of the latest matching
+    /// `filter` to catch up on first (oldest first). The receiver sees every
+    /// topic; apply the filter to what it yields.
+    pub fn subscribe(
+        &self,
+        filter: &TopicFilter,
+        replay: usize,
+    ) -> (Vec<BusEvent>, broadcast::Receiver<BusEvent>) {
+        let history = self.history.lock().unwrap_or_else(|e| e.into_inner());
+        let rx = self.tx.subscribe();
+        let mut past: Vec<BusEvent> = history
+            .events
+            .iter()
+            .rev()
+            .filter(|e| filter.matches(&e.topic))
+            .take(replay)
+            .cloned()
+            .collect();
+        past.reverse();
+        (past, rx)
+    }
+
+    /// A launcher sink that publishes every `LauncherEvent` under its name
+    pub fn launcher_sink(self: &Arc<Self>) -> EventSink {
+        let bus = self.clone();
+        Arc::new(move |event: LauncherEvent| {
+            bus.publish(event.name(), event.payload());
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn replays_the_latest_matching_events_then_streams_live() {
+        let bus = EventBus::new();
+        for i in 0..5 {
+            bus.publish("caspar-log", json!({ "text": i }));
+        }
+        bus.publish("diagnostic", json!({ "id": "gpu" }));
+
+        let (past, mut rx) = bus.subscribe(&TopicFilter::parse("caspar-log"), 2);
+        let texts: Vec<_> = past.iter().map(|e| e.payload["text"].clone()).collect();
+        assert_eq!(texts, vec![json!(3), json!(4)]);
+
+        let live = bus.publish("server-exit", json!(null));
+        assert_eq!(rx.try_recv().unwrap(), live);
+        assert_eq!(live.seq, 7);
+    }
+
+    #[test]
+    fn replay_keeps_only_the_newest() {
+        let bus = EventBus::new();
+        for i in 0..REPLAY_CAPACITY + 10 {
+            bus.publish("caspar-log", json!(i));
+        }
+        let (past, _) = bus.subscribe(&TopicFilter::default(), usize::MAX);
+        assert_eq!(past.len(), REPLAY_CAPACITY);
+        assert_eq!(past[0].payload, json!(10));
+    }
+}
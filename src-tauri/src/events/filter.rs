@@ -0,0 +1,46 @@
+// Topic filter
+// Selects bus events by topic name, with trailing-* prefixes
+
+/// A comma-separated list of topics. `server-*` matches every topic starting
+/// with `server-`; an empty list or `*` matches everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TopicFilter {
+    patterns: Vec<String>,
+}
+
+impl TopicFilter {
+    pub fn parse(list: &str) -> Self {
+        let patterns = list
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+            .collect();
+        Self { patterns }
+    }
+
+    pub fn matches(&self, topic: &str) -> bool {
+        self.patterns.is_empty()
+            || self.patterns.iter().any(|p| match p.strip_suffix('*') {
+                Some(prefix) => topic.starts_with(prefix),
+                None => p == topic,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_names_and_prefixes() {
+        let filter = TopicFilter::parse("diagnostic, server-*");
+        assert!(filter.matches("diagnostic"));
+        assert!(filter.matches("server-exit"));
+        assert!(filter.matches("server-started"));
+        assert!(!filter.matches("caspar-log"));
+
+        assert!(TopicFilter::parse("").matches("caspar-log"));
+        assert!(TopicFilter::parse("*").matches("caspar-log"));
+    }
+}
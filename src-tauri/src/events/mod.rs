@@ -0,0 +1,10 @@
+// Event bus
+// One stream of backend events for the GUI, the WebSocket and anything else
+
+mod bus;
+mod filter;
+mod ws;
+
+pub use bus::*;
+pub use filter::*;
+pub use ws::*;
@@ -0,0 +1,64 @@
+// Event WebSocket
+// Streams bus events to remote dashboards as JSON text frames
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::response::Response;
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+
+use super::{EventBus, TopicFilter};
+
+/// Replay on connect when the client does not say
+const DEFAULT_REPLAY: usize = 50;
+
+/// Ping an idle connection this often, which is also how a closed one is noticed
+const KEEPALIVE: Duration = Duration::from_secs(30);
+
+/// `?topics=caspar-log,server-*&replay=100`
+#[derive(Debug, Default, Deserialize)]
+pub struct EventsQuery {
+    #[serde(default)]
+    pub topics: Option<String>,
+    #[serde(default)]
+    pub replay: Option<usize>,
+}
+
+/// Upgrade to a WebSocket that sends each matching `BusEvent` as one JSON text
+/// frame: first the replayed ones, then live ones. Messages from the client are
+/// ignored.
+pub fn events_socket(upgrade: WebSocketUpgrade, query: EventsQuery, bus: Arc<EventBus>) -> Response {
+    let filter = TopicFilter::parse(query.topics.as_deref().unwrap_or(""));
+    let replay = query.replay.unwrap_or(DEFAULT_REPLAY);
+    upgrade.on_upgrade(move |socket| stream_events(socket, bus, filter, replay))
+}
+
+async fn stream_events(mut socket: WebSocket, bus: Arc<EventBus>, filter: TopicFilter, replay: usize) {
+    let (past, mut rx) = bus.subscribe(&filter, replay);
+    for event in past {
+        if send(&mut socket, &event).await.is_err() {
+            return;
+        }
+    }
+    loop {
+        let sent = match tokio::time::timeout(KEEPALIVE, rx.recv()).await {
+            Ok(Ok(event)) if filter.matches(&event.topic) => send(&mut socket, &event).await,
+            Ok(Ok(_)) => Ok(()),
+            // Fell behind: the gap in `seq` tells the client what it missed.
+            Ok(Err(RecvError::Lagged(_))) => Ok(()),
+            Ok(Err(RecvError::Closed)) => break,
+            Err(_) => socket.send(Message::Ping(Default::default())).await,
+        };
+        if sent.is_err() {
+            break; // the client went away
+        }
+    }
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+async fn send(socket: &mut WebSocket, event: &super::BusEvent) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(event).unwrap_or_default();
+    socket.send(Message::Text(text.into())).await
+}
@@ -0,0 +1,233 @@
+// Channel data for test pages
+// JSON describing the active profile's channels, so the identifier can show real context
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Serialize;
+
+use crate::config::{Consumer, GlobalConfig, VideoMode};
+use crate::control::Control;
+use crate::decklink::{self, DeckLinkDevice};
+use crate::system;
+
+/// A DeckLink card a consumer names. Model, ID and label are missing when the
+/// card is not in this machine.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CardInfo {
+    pub index: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persistent_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_name: Option<String>,
+    /// The profile's label for the card, else the card's own
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// A consumer as configured, with the cards it drives resolved
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsumerInfo {
+    #[serde(flatten)]
+    pub consumer: Consumer,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub card: Option<CardInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_card: Option<CardInfo>,
+}
+
+/// Everything a test page needs to say which channel it is on
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelInfo {
+    /// 1-based, as in AMCP
+    pub channel: usize,
+    pub profile: String,
+    pub video_mode: VideoMode,
+    pub video_mode_name: String,
+    pub width: u32,
+    pub height: u32,
+    pub frame_rate: f64,
+    pub interlaced: bool,
+    pub consumers: Vec<ConsumerInfo>,
+    /// This machine's primary IPv4
+    pub host_ip: Option<String>,
+}
+
+fn card_info(index: u32, config: &GlobalConfig, devices: &[DeckLinkDevice]) -> CardInfo {
+    let device = devices.iter().find(|d| d.index == index);
+    let label = device.and_then(|device| {
+        config
+            .decklink
+            .devices
+            .iter()
+            .find(|d| d.persistent_id == device.persistent_id)
+            .and_then(|d| d.label.clone())
+            .or_else(|| device.device_label.clone())
+    });
+    CardInfo {
+        index,
+        persistent_id: device.map(|d| d.persistent_id.clone()),
+        model_name: device.map(|d| d.model_name.clone()),
+        label,
+    }
+}
+
+/// Every channel of `config`, with DeckLink consumers matched to `devices`
+pub fn channel_infos(config: &GlobalConfig, devices: &[DeckLinkDevice], host_ip: Option<String>) -> Vec<ChannelInfo> {
+    config
+        .caspar
+        .channels
+        .iter()
+        .enumerate()
+        .map(|(i, channel)| {
+            let (width, height) = channel.video_mode.resolution();
+            let consumers = channel
+                .consumers
+                .iter()
+                .map(|consumer| {
+                    let (card, key_card) = match consumer {
+                        Consumer::DeckLink(d) => (
+                            Some(card_info(d.device, config, devices)),
+                            d.key_device.map(|k| card_info(k, config, devices)),
+                        ),
+                        _ => (None, None),
+                    };
+                    ConsumerInfo {
+                        consumer: consumer.clone(),
+                        card,
+                        key_card,
+                    }
+                })
+                .collect();
+            ChannelInfo {
+                channel: i + 1,
+                profile: config.name.clone(),
+                video_mode: channel.video_mode.clone(),
+                video_mode_name: channel.video_mode.display_name().to_string(),
+                width,
+                height,
+                frame_rate: channel.video_mode.frame_rate(),
+                interlaced: channel.video_mode.interlaced(),
+                consumers,
+                host_ip: host_ip.clone(),
+            }
+        })
+        .collect()
+}
+
+/// `/api/channels` and `/api/channels/{n}` for the active profile
+pub fn router(control: Control) -> Router {
+    Router::new()
+        .route("/api/channels", get(list))
+        .route("/api/channels/{n}", get(one))
+        .with_state(control)
+}
+
+async fn active_channels(control: &Control) -> Result<Vec<ChannelInfo>, Response> {
+    let config = control
+        .active_config()
+        .await
+        .map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, e).into_response())?;
+    // Enumerating the cards goes through the driver; keep it off the runtime.
+    let (devices, host_ip) = tokio::task::spawn_blocking(|| {
+        (decklink::list_devices().unwrap_or_default(), system::network::primary_ip())
+    })
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response())?;
+    Ok(channel_infos(&config, &devices, host_ip))
+}
+
+async fn list(State(control): State<Control>) -> Response {
+    match active_channels(&control).await {
+        Ok(channels) => Json(channels).into_response(),
+        Err(response) => response,
+    }
+}
+
+async fn one(State(control): State<Control>, Path(n): Path<usize>) -> Response {
+    let channels = match active_channels(&control).await {
+        Ok(channels) => channels,
+        Err(response) => return response,
+    };
+    match n.checked_sub(1).and_then(|i| channels.into_iter().nth(i)) {
+        Some(channel) => Json(channel).into_response(),
+        None => (StatusCode::NOT_FOUND, format!("No channel {} in the active profile", n)).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Channel, DeckLinkConsumer, DeckLinkDeviceConfig, NdiConsumer};
+
+    fn device(index: u32, id: &str, label: Option<&str>) -> DeckLinkDevice {
+        DeckLinkDevice {
+            index,
+            persistent_id: id.to_string(),
+            model_name: "DeckLink Duo 2".to_string(),
+            display_name: format!("DeckLink Duo 2 ({index})"),
+            device_label: label.map(str::to_string),
+            supports_duplex: true,
+            duplex_mode: None,
+            input_connectors: Vec::new(),
+            output_connectors: Vec::new(),
+            supports_internal_keying: true,
+            supports_external_keying: true,
+            supports_capture: true,
+            supports_playback: true,
+            max_audio_channels: 16,
+        }
+    }
+
+    #[test]
+    fn resolves_cards_and_serialises_consumers_flat() {
+        let mut config = GlobalConfig::new("Studio A");
+        config.decklink.devices.push(DeckLinkDeviceConfig {
+            persistent_id: "a1".to_string(),
+            model_name: "DeckLink Duo 2".to_string(),
+            label: Some("Graphics Fill".to_string()),
+            duplex_mode: None,
+            connector_mapping: None,
+        });
+        config.caspar.channels = vec![Channel {
+            video_mode: VideoMode::I1080_5000,
+            consumers: vec![
+                Consumer::DeckLink(DeckLinkConsumer {
+                    device: 1,
+                    key_device: Some(2),
+                    ..Default::default()
+                }),
+                Consumer::Ndi(NdiConsumer::default()),
+            ],
+        }];
+        let devices = [device(1, "a1", Some("Card label")), device(2, "b2", Some("Graphics Key"))];
+        let infos = channel_infos(&config, &devices, Some("10.0.0.5".to_string()));
+
+        let json = serde_json::to_value(&infos[0]).unwrap();
+        assert_eq!(json["channel"], 1);
+        assert_eq!(json["video_mode"], "1080i5000");
+        assert_eq!((json["width"].as_u64(), json["interlaced"].as_bool()), (Some(1920), Some(true)));
+        assert_eq!(json["host_ip"], "10.0.0.5");
+        let decklink = &json["consumers"][0];
+        assert_eq!(decklink["type"], "decklink");
+        assert_eq!(decklink["device"], 1);
+        // The profile's label wins over the card's own
+        assert_eq!(decklink["card"]["label"], "Graphics Fill");
+        assert_eq!(decklink["card"]["persistent_id"], "a1");
+        assert_eq!(decklink["key_card"]["label"], "Graphics Key");
+        assert_eq!(json["consumers"][1]["name"], "CasparCG");
+        assert!(json["consumers"][1].get("card").is_none());
+
+        // A card that is not in this machine keeps just its index
+        let infos = channel_infos(&config, &[], None);
+        let bare = CardInfo {
+            index: 1,
+            persistent_id: None,
+            model_name: None,
+            label: None,
+        };
+        assert_eq!(infos[0].consumers[0].card, Some(bare));
+    }
+}
@@ -1,6 +1,9 @@
 // HTTP server for serving test patterns to CasparCG
 // CasparCG's CEF browser needs HTTP access to templates - it cannot use file:// or asset:// protocols
 
+mod channels;
+mod templates;
+
 use axum::{
     Router,
     routing::get,
@@ -14,6 +17,9 @@ use tokio::sync::RwLock;
 use tower_http::services::ServeDir;
 use tower_http::cors::{CorsLayer, Any};
 
+use crate::control::Control;
+use crate::ports;
+
 /// State for the test HTTP server
 #[derive(Debug)]
 pub struct TestServer {
@@ -60,12 +66,15 @@ pub fn create_test_server_state() -> TestServerState {
 
 /// Start the test HTTP server
 ///
-/// Serves files from the test/ directory at the application root.
+/// Serves files from the test/ directory at the application root, the
+/// active profile's channels as JSON under /api/channels, and its HTML
+/// templates in a preview harness under /templates.
 /// Returns the port the server is running on.
 pub async fn start_server(
     state: TestServerState,
     preferred_port: Option<u16>,
     test_dir: PathBuf,
+    control: Control,
 ) -> Result<u16, String> {
     // Check if already running
     {
@@ -91,24 +100,25 @@ pub async fn start_server(
     // builds this router), so use fallback_service — the documented way to serve
     // a ServeDir at the root with the request path intact (so GET /index.html
     // resolves to <test_dir>/index.html).
-    let app = Router::new()
+    let app = channels::router(control.clone())
+        .merge(templates::router(control))
         .fallback_service(ServeDir::new(&test_dir))
         .layer(cors);
 
-    // Try the preferred port, then fallback to random
-    let port = preferred_port.unwrap_or(9966);
+    // Try the preferred port, then fallback to random. Probe with the same
+    // wildcard bind as the launch pre-flight: a loopback bind alone can succeed
+    // on a port another service holds on 0.0.0.0.
+    let preferred = preferred_port.unwrap_or(9966);
+    let port = if ports::is_free(ports::Protocol::Tcp, preferred) {
+        preferred
+    } else {
+        ports::free_port(ports::Protocol::Tcp).unwrap_or(0)
+    };
     let addr = SocketAddr::from(([127, 0, 0, 1], port));
 
-    let listener = match tokio::net::TcpListener::bind(addr).await {
-        Ok(l) => l,
-        Err(_) => {
-            // Fallback to any available port
-            let addr = SocketAddr::from(([127, 0, 0, 1], 0));
-            tokio::net::TcpListener::bind(addr)
-                .await
-                .map_err(|e| format!("Failed to bind to any port: {}", e))?
-        }
-    };
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("Failed to bind to any port: {}", e))?;
 
     let actual_port = listener
         .local_addr()
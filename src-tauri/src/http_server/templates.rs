@@ -0,0 +1,274 @@
+// Template preview
+// The profile's HTML templates, a harness page implementing CasparCG's template API, and saved test data
+
+use std::collections::BTreeMap;
+use std::path::{Component, Path as FsPath, PathBuf};
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::{Path, Request, State};
+use axum::http::{header, StatusCode, Uri};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tower_http::services::ServeDir;
+
+use crate::config::GuiSettings;
+use crate::control::Control;
+use crate::scanner;
+
+/// The harness page. `{{TEMPLATE}}` is replaced with the template's path as a
+/// JSON string.
+const HARNESS: &str = include_str!("template_harness.html");
+
+/// A named payload for a template, JSON or legacy `<templateData>` XML, sent
+/// as-is to `update()`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DataSet {
+    pub name: String,
+    pub data: String,
+}
+
+/// Saved data sets by template path, relative to the template folder
+type DataStore = BTreeMap<String, Vec<DataSet>>;
+
+#[derive(Clone)]
+struct TemplateRoutes {
+    control: Control,
+    /// Where data sets are kept; `template-data.json` beside the settings
+    store: Option<PathBuf>,
+    /// Serialises read-modify-write of the store
+    store_lock: Arc<Mutex<()>>,
+}
+
+/// `/templates` (the HTML templates of the active profile),
+/// `/templates/preview/{path}` (the harness), `/templates/files/{path}` (the
+/// template folder) and `/templates/data/{path}` (saved data sets, GET/PUT)
+pub fn router(control: Control) -> Router {
+    routes(control, GuiSettings::template_data_path())
+}
+
+fn routes(control: Control, store: Option<PathBuf>) -> Router {
+    Router::new()
+        .route("/templates", get(list))
+        .route("/templates/preview/{*path}", get(preview))
+        .route("/templates/files/{*path}", get(file))
+        .route("/templates/data/{*path}", get(get_data).put(put_data))
+        .with_state(TemplateRoutes {
+            control,
+            store,
+            store_lock: Default::default(),
+        })
+}
+
+/// The active profile's template folder. CasparCG resolves a relative path
+/// against its own directory.
+async fn template_dir(control: &Control) -> Result<PathBuf, Response> {
+    let config = control
+        .active_config()
+        .await
+        .map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, e).into_response())?;
+    let caspar = control.gui_settings.lock().await.caspar_path.clone();
+    let caspar = caspar.ok_or_else(|| {
+        (StatusCode::SERVICE_UNAVAILABLE, "CasparCG path is not set").into_response()
+    })?;
+    Ok(PathBuf::from(caspar).join(&config.caspar.paths.template))
+}
+
+/// Whether `path` stays inside the folder it is joined to
+fn is_relative_inside(path: &str) -> bool {
+    !path.is_empty() && FsPath::new(path).components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Every `.html`/`.htm` under `dir`, as `/`-separated relative paths
+fn html_templates(dir: &FsPath) -> Vec<String> {
+    scanner::walk(dir, &["html", "htm"])
+        .into_iter()
+        .filter_map(|file| {
+            let relative = file.path.strip_prefix(dir).ok()?;
+            let parts: Vec<_> = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+            Some(parts.join("/"))
+        })
+        .collect()
+}
+
+/// The harness for `template`, its path embedded as a script string
+fn harness_page(template: &str) -> String {
+    let quoted = serde_json::to_string(template).unwrap_or_default().replace("</", "<\\/");
+    HARNESS.replace("{{TEMPLATE}}", &quoted)
+}
+
+fn load_store(path: &FsPath) -> DataStore {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save_store(path: &FsPath, store: &DataStore) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let text = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+async fn list(State(routes): State<TemplateRoutes>) -> Response {
+    let dir = match template_dir(&routes.control).await {
+        Ok(dir) => dir,
+        Err(response) => return response,
+    };
+    match tokio::task::spawn_blocking(move || html_templates(&dir)).await {
+        Ok(templates) => Json(templates).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+async fn preview(State(routes): State<TemplateRoutes>, Path(path): Path<String>) -> Response {
+    if !is_relative_inside(&path) {
+        return (StatusCode::BAD_REQUEST, "Template path must be inside the template folder").into_response();
+    }
+    let dir = match template_dir(&routes.control).await {
+        Ok(dir) => dir,
+        Err(response) => return response,
+    };
+    if !dir.join(&path).is_file() {
+        return (StatusCode::NOT_FOUND, format!("No template {} in {}", path, dir.display())).into_response();
+    }
+    Html(harness_page(&path)).into_response()
+}
+
+/// A file under the template folder, with the request path (still encoded)
+/// below `/templates/files` handed to `ServeDir`
+async fn file(State(routes): State<TemplateRoutes>, request: Request) -> Response {
+    let dir = match template_dir(&routes.control).await {
+        Ok(dir) => dir,
+        Err(response) => return response,
+    };
+    let (mut parts, body) = request.into_parts();
+    let rest = parts.uri.path().strip_prefix("/templates/files").unwrap_or("/");
+    let uri = match parts.uri.query() {
+        Some(query) => format!("{rest}?{query}"),
+        None => rest.to_string(),
+    };
+    parts.uri = match uri.parse::<Uri>() {
+        Ok(uri) => uri,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    match ServeDir::new(dir).try_call(Request::from_parts(parts, body)).await {
+        // Edits show on the next load, as they do in CasparCG
+        Ok(mut response) => {
+            response
+                .headers_mut()
+                .insert(header::CACHE_CONTROL, header::HeaderValue::from_static("no-store"));
+            response.map(Body::new).into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+async fn get_data(State(routes): State<TemplateRoutes>, Path(path): Path<String>) -> Response {
+    let Some(store) = routes.store else {
+        return Json(Vec::<DataSet>::new()).into_response();
+    };
+    let _guard = routes.store_lock.lock().await;
+    Json(load_store(&store).remove(&path).unwrap_or_default()).into_response()
+}
+
+/// Replace the template's data sets. An empty list forgets the template.
+async fn put_data(
+    State(routes): State<TemplateRoutes>,
+    Path(path): Path<String>,
+    Json(sets): Json<Vec<DataSet>>,
+) -> Response {
+    if let Err(e) = routes.control.writable() {
+        return (StatusCode::FORBIDDEN, e).into_response();
+    }
+    let Some(store_path) = routes.store else {
+        return (StatusCode::SERVICE_UNAVAILABLE, "No settings folder to save data sets in").into_response();
+    };
+    let _guard = routes.store_lock.lock().await;
+    let mut store = load_store(&store_path);
+    if sets.is_empty() {
+        store.remove(&path);
+    } else {
+        store.insert(path, sets);
+    }
+    match save_store(&store_path, &store) {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GlobalConfig;
+    use tower::ServiceExt;
+
+    fn call(app: &Router, method: &str, uri: &str, body: Option<&str>) -> (StatusCode, String) {
+        let request = axum::http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.unwrap_or("").to_string()))
+            .unwrap();
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let response = app.clone().oneshot(request).await.unwrap();
+            let status = response.status();
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, String::from_utf8_lossy(&bytes).to_string())
+        })
+    }
+
+    #[test]
+    fn serves_templates_harness_and_data_sets() {
+        let root = std::env::temp_dir().join(format!("caspar-template-preview-{}", std::process::id()));
+        let templates = root.join("template").join("lower third");
+        std::fs::create_dir_all(&templates).unwrap();
+        std::fs::write(templates.join("index.html"), b"<script>function update(d){}</script>").unwrap();
+        std::fs::write(templates.join("style.css"), b"body{}").unwrap();
+
+        let mut config = GlobalConfig::new("Studio A");
+        config.caspar.paths.template = "template/".to_string();
+        let profiles = root.join("caspar-gui-profiles");
+        std::fs::create_dir_all(&profiles).unwrap();
+        config.save_to_file(&profiles.join("Studio A.json")).unwrap();
+        let settings = GuiSettings {
+            caspar_path: Some(root.to_string_lossy().to_string()),
+            last_profile: Some("Studio A".to_string()),
+            ..Default::default()
+        };
+        let control = Control::for_tests(settings);
+        let app = routes(control, Some(root.join("template-data.json")));
+
+        let (status, body) = call(&app, "GET", "/templates", None);
+        assert_eq!((status, body.as_str()), (StatusCode::OK, r#"["lower third/index.html"]"#));
+
+        let (status, body) = call(&app, "GET", "/templates/preview/lower%20third/index.html", None);
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains(r#"const TEMPLATE = "lower third/index.html";"#));
+        assert_eq!(call(&app, "GET", "/templates/preview/missing.html", None).0, StatusCode::NOT_FOUND);
+        assert_eq!(call(&app, "GET", "/templates/preview/../secret.html", None).0, StatusCode::BAD_REQUEST);
+
+        let (status, body) = call(&app, "GET", "/templates/files/lower%20third/style.css", None);
+        assert_eq!((status, body.as_str()), (StatusCode::OK, "body{}"));
+
+        let sets = r#"[{"name":"Jane","data":"{\"f0\":\"Jane Smith\"}"}]"#;
+        let uri = "/templates/data/lower%20third/index.html";
+        assert_eq!(call(&app, "PUT", uri, Some(sets)).0, StatusCode::NO_CONTENT);
+        assert_eq!(call(&app, "GET", uri, None), (StatusCode::OK, sets.to_string()));
+        assert_eq!(call(&app, "PUT", uri, Some("[]")).0, StatusCode::NO_CONTENT);
+        assert_eq!(call(&app, "GET", uri, None).1, "[]");
+
+        let read_only = Control {
+            read_only: true,
+            ..Control::for_tests(GuiSettings::default())
+        };
+        let app = routes(read_only, Some(root.join("template-data.json")));
+        assert_eq!(call(&app, "PUT", uri, Some(sets)).0, StatusCode::FORBIDDEN);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
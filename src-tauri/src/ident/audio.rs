@@ -0,0 +1,234 @@
+// Audio identification
+// 1 kHz line-up and GLITS-style interrupted tones naming each embedded track, as WAV
+
+use std::path::Path;
+
+use super::slate::SLATE_FOLDER;
+use crate::config::{Channel, Consumer, IdentConfig};
+
+/// CasparCG mixes at 48 kHz
+pub const SAMPLE_RATE: u32 = 48_000;
+const TONE_HZ: f64 = 1000.0;
+
+/// One identification cycle. A whole number of tone periods, so the clip
+/// loops without a click.
+const CYCLE: f64 = 4.0;
+/// The pair count: one short break per stereo pair number
+const PAIR_BREAK: f64 = 0.1;
+const PAIR_PERIOD: f64 = 0.2;
+/// Then left or right, as in GLITS: one longer break for left, two for right
+const SIDE_START: f64 = 2.0;
+const SIDE_BREAK: f64 = 0.25;
+const SIDE_PERIOD: f64 = 0.5;
+/// Fade at each break edge, so breaks do not click
+const RAMP: f64 = 0.002;
+
+/// The breaks in 1-based `track`'s tone within a cycle, as (start, length)
+/// in seconds. Tracks 1/2 are pair 1, 3/4 pair 2 and so on: the pair number
+/// is counted in short breaks from the top of the cycle, then the odd track
+/// of the pair breaks once (left) and the even one twice (right). On a plain
+/// stereo feed that is GLITS's one-left, two-right.
+pub fn track_breaks(track: u32) -> Vec<(f64, f64)> {
+    let pair = track.div_ceil(2);
+    let sides = if track % 2 == 1 { 1 } else { 2 };
+    (0..pair)
+        .map(|i| (i as f64 * PAIR_PERIOD, PAIR_BREAK))
+        .chain((0..sides).map(|i| (SIDE_START + i as f64 * SIDE_PERIOD, SIDE_BREAK)))
+        .collect()
+}
+
+/// Tone gain at `t` seconds into the cycle, given its breaks
+fn gain(t: f64, breaks: &[(f64, f64)]) -> f64 {
+    // A break at the top of the cycle fades in from the end of the previous one
+    breaks
+        .iter()
+        .flat_map(|&(start, len)| [(start, len), (start + CYCLE, len)])
+        .map(|(start, len)| {
+            if t < start {
+                ((start - t) / RAMP).min(1.0)
+            } else if t <= start + len {
+                0.0
+            } else {
+                ((t - start - len) / RAMP).min(1.0)
+            }
+        })
+        .fold(1.0, f64::min)
+}
+
+/// One cycle of 16-bit samples per track: each in its ident pattern, or
+/// continuous tone for line-up
+fn cycle(tracks: u32, level_dbfs: f64, ident: bool) -> Vec<Vec<i16>> {
+    let peak = 10f64.powf(level_dbfs.min(0.0) / 20.0) * i16::MAX as f64;
+    let samples = (CYCLE * SAMPLE_RATE as f64) as usize;
+    let tone: Vec<f64> = (0..samples)
+        .map(|n| (std::f64::consts::TAU * TONE_HZ * n as f64 / SAMPLE_RATE as f64).sin() * peak)
+        .collect();
+    (1..=tracks)
+        .map(|track| {
+            let breaks = if ident { track_breaks(track) } else { Vec::new() };
+            tone.iter()
+                .enumerate()
+                .map(|(n, s)| (s * gain(n as f64 / SAMPLE_RATE as f64, &breaks)).round() as i16)
+                .collect()
+        })
+        .collect()
+}
+
+/// A 16-bit PCM WAV of the given tracks, interleaved. More than two tracks
+/// are written as WAVE_FORMAT_EXTENSIBLE with no speaker mask: discrete
+/// tracks, which is what embedded audio is.
+pub fn wav(tracks: &[Vec<i16>]) -> Vec<u8> {
+    let channels = tracks.len() as u16;
+    let frames = tracks.first().map_or(0, Vec::len);
+    let block = channels as u32 * 2;
+    let data_len = frames as u32 * block;
+    let extensible = channels > 2;
+    let fmt_len: u32 = if extensible { 40 } else { 16 };
+
+    let mut out = Vec::with_capacity(data_len as usize + 68);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(4 + 8 + fmt_len + 8 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&fmt_len.to_le_bytes());
+    out.extend_from_slice(&(if extensible { 0xFFFEu16 } else { 1 }).to_le_bytes());
+    out.extend_from_slice(&channels.to_le_bytes());
+    out.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    out.extend_from_slice(&(SAMPLE_RATE * block).to_le_bytes());
+    out.extend_from_slice(&(block as u16).to_le_bytes());
+    out.extend_from_slice(&16u16.to_le_bytes());
+    if extensible {
+        out.extend_from_slice(&22u16.to_le_bytes());
+        out.extend_from_slice(&16u16.to_le_bytes());
+        out.extend_from_slice(&0u32.to_le_bytes());
+        // KSDATAFORMAT_SUBTYPE_PCM
+        out.extend_from_slice(&[
+            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38, 0x9B, 0x71,
+        ]);
+    }
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+    for n in 0..frames {
+        for track in tracks {
+            out.extend_from_slice(&track[n].to_le_bytes());
+        }
+    }
+    out
+}
+
+/// Whether anything on the channel carries its audio
+fn has_audio_output(channel: &Channel) -> bool {
+    channel.consumers.iter().any(|c| match c {
+        Consumer::DeckLink(d) => d.embedded_audio,
+        Consumer::Ndi(_) | Consumer::SystemAudio(_) => true,
+        Consumer::Screen(_) => false,
+    })
+}
+
+/// The CasparCG names of a channel's written audio
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioClips {
+    /// Every track, each interrupted in its own pattern
+    pub ident: String,
+    /// Every track, continuous tone
+    pub lineup: String,
+}
+
+/// Write each channel's audio into `media_dir/caspar-ident`: the ident and
+/// line-up across all tracks, plus one mono ident per track for checking a
+/// single leg. Channels with no output carrying audio get none.
+pub fn write_audio(
+    channels: &[Channel],
+    config: &IdentConfig,
+    media_dir: &Path,
+) -> Result<Vec<Option<AudioClips>>, String> {
+    let dir = media_dir.join(SLATE_FOLDER);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let tracks = config.tracks.count();
+    let ident = cycle(tracks, config.lineup_dbfs, true);
+    let lineup = cycle(tracks, config.lineup_dbfs, false);
+    let write = |file: String, data: Vec<u8>| {
+        let path = dir.join(file);
+        std::fs::write(&path, data).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    };
+
+    let mut clips = Vec::new();
+    for (i, channel) in channels.iter().enumerate() {
+        if !has_audio_output(channel) {
+            clips.push(None);
+            continue;
+        }
+        let name = format!("channel-{}", i + 1);
+        write(format!("{name}-ident.wav"), wav(&ident))?;
+        write(format!("{name}-lineup.wav"), wav(&lineup))?;
+        for (t, track) in ident.iter().enumerate() {
+            write(format!("{name}-track-{}.wav", t + 1), wav(std::slice::from_ref(track)))?;
+        }
+        let clip = format!("{}/{}", SLATE_FOLDER, name).to_uppercase();
+        clips.push(Some(AudioClips {
+            ident: format!("{clip}-IDENT"),
+            lineup: format!("{clip}-LINEUP"),
+        }));
+    }
+    Ok(clips)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AudioTracks, DeckLinkConsumer, ScreenConsumer};
+
+    #[test]
+    fn tracks_break_in_pair_then_side_pattern() {
+        assert_eq!(track_breaks(1), [(0.0, 0.1), (2.0, 0.25)]);
+        assert_eq!(track_breaks(2), [(0.0, 0.1), (2.0, 0.25), (2.5, 0.25)]);
+        let last = track_breaks(16);
+        assert_eq!(last.len(), 8 + 2);
+        // Every pair count ends before the side breaks start
+        assert!(last[7].0 + last[7].1 < SIDE_START);
+
+        let one = cycle(2, -18.0, true);
+        let peak = |track: &[i16], from: f64, to: f64| {
+            let (a, b) = ((from * 48_000.0) as usize, (to * 48_000.0) as usize);
+            track[a..b].iter().map(|s| s.unsigned_abs()).max().unwrap()
+        };
+        // -18 dBFS peak is 4125 of 32767
+        assert!((4100..=4130).contains(&peak(&one[0], 0.5, 1.5)));
+        assert_eq!(peak(&one[0], 2.01, 2.24), 0);
+        assert!(peak(&one[0], 2.3, 2.5) > 4000);
+        assert_eq!(peak(&one[1], 2.51, 2.74), 0);
+        assert!(peak(&cycle(2, -18.0, false)[1], 2.51, 2.74) > 4000);
+    }
+
+    #[test]
+    fn writes_audio_for_channels_that_carry_it() {
+        let config = IdentConfig {
+            tracks: AudioTracks::Eight,
+            ..Default::default()
+        };
+        let channels = [
+            Channel {
+                consumers: vec![Consumer::DeckLink(DeckLinkConsumer::default())],
+                ..Default::default()
+            },
+            Channel {
+                consumers: vec![Consumer::Screen(ScreenConsumer::default())],
+                ..Default::default()
+            },
+        ];
+        let media = std::env::temp_dir().join(format!("caspar-audio-ident-{}", std::process::id()));
+        let clips = write_audio(&channels, &config, &media).unwrap();
+        assert_eq!(clips[0].as_ref().unwrap().ident, "CASPAR-IDENT/CHANNEL-1-IDENT");
+        assert_eq!(clips[1], None);
+
+        let dir = media.join(SLATE_FOLDER);
+        let ident = std::fs::read(dir.join("channel-1-ident.wav")).unwrap();
+        assert_eq!(&ident[..4], b"RIFF");
+        assert_eq!(u16::from_le_bytes([ident[22], ident[23]]), 8);
+        assert_eq!(ident.len(), 68 + 4 * 48_000 * 8 * 2);
+        let track = std::fs::read(dir.join("channel-1-track-8.wav")).unwrap();
+        assert_eq!(u16::from_le_bytes([track[22], track[23]]), 1);
+        assert!(!dir.join("channel-2-ident.wav").exists());
+        let _ = std::fs::remove_dir_all(&media);
+    }
+}
@@ -0,0 +1,272 @@
+// AV sync clip
+// A looping AVI with a flash frame and a coincident beep, for measuring lip-sync offsets
+
+use std::path::Path;
+
+use super::audio::SAMPLE_RATE;
+use super::raster::{text_height, Canvas, Rgba};
+use super::slate::SLATE_FOLDER;
+use crate::config::{IdentConfig, VideoMode};
+
+/// Loop length. The flash sits in the middle, clear of the loop point, so an
+/// offset of up to a second either way reads unambiguously.
+const LOOP_SECONDS: u32 = 2;
+const BEEP_HZ: f64 = 1000.0;
+
+const CELL: Rgba = Rgba(60, 60, 66, 255);
+const CURRENT: Rgba = Rgba(235, 235, 235, 255);
+const FLASH_CELL: Rgba = Rgba(255, 200, 0, 255);
+const MUTED: Rgba = Rgba(150, 150, 160, 255);
+
+/// The frames in one loop at `mode`'s frame rate, and which is the flash
+pub fn sync_frames(mode: &VideoMode) -> (u32, u32) {
+    let (num, den) = mode.frame_rate_ratio();
+    let frames = LOOP_SECONDS * num.div_ceil(den);
+    (frames, frames / 2)
+}
+
+/// The audio sample frame `frame` starts on. Whole-second loops of every
+/// broadcast rate are a whole number of samples, so the loop stays exact.
+fn sample_at(frame: u32, (num, den): (u32, u32)) -> usize {
+    (u64::from(SAMPLE_RATE) * u64::from(frame) * u64::from(den) / u64::from(num)) as usize
+}
+
+/// One frame of the loop: black with a strip of one cell per frame, the
+/// current one lit, or solid white on the flash frame
+fn render_frame(mode: &VideoMode, frame: u32, frames: u32, flash: u32) -> Canvas {
+    let (width, height) = mode.resolution();
+    if frame == flash {
+        return Canvas::new(width, height, Rgba::WHITE);
+    }
+    let mut canvas = Canvas::new(width, height, Rgba::BLACK);
+    let unit = (height / 216).max(1);
+    let margin = width / 20;
+    let cell = ((width - 2 * margin) / frames).max(1);
+    let gap = cell / 4;
+    let strip_height = height / 12;
+    let strip_y = (height - strip_height) / 2;
+    for k in 0..frames {
+        let color = match k {
+            k if k == frame => CURRENT,
+            k if k == flash => FLASH_CELL,
+            _ => CELL,
+        };
+        canvas.fill_rect(margin + k * cell, strip_y, cell - gap, strip_height, color);
+    }
+
+    let scale = unit * 2;
+    let step = text_height(scale) + 2 * scale;
+    let top = strip_y - 3 * step;
+    canvas.text(margin, top, scale, &format!("AV SYNC  {}", mode.display_name()), CURRENT);
+    canvas.text(margin, top + step, scale, &format!("FRAME {} / {}", frame + 1, frames), MUTED);
+    canvas.text(
+        margin,
+        strip_y + strip_height + step,
+        unit,
+        &format!("FLASH AND 1 KHZ BEEP ON FRAME {}", flash + 1),
+        MUTED,
+    );
+    canvas
+}
+
+/// The beep on every track, exactly the flash frame long, interleaved 16-bit
+fn render_audio(mode: &VideoMode, frames: u32, flash: u32, ident: &IdentConfig) -> Vec<i16> {
+    let rate = mode.frame_rate_ratio();
+    let tracks = ident.tracks.count() as usize;
+    let peak = 10f64.powf(ident.lineup_dbfs.min(0.0) / 20.0) * i16::MAX as f64;
+    let (start, end) = (sample_at(flash, rate), sample_at(flash + 1, rate));
+    let mut samples = vec![0; sample_at(frames, rate) * tracks];
+    for n in start..end {
+        let t = (n - start) as f64 / SAMPLE_RATE as f64;
+        let s = ((std::f64::consts::TAU * BEEP_HZ * t).sin() * peak).round() as i16;
+        samples[n * tracks..(n + 1) * tracks].fill(s);
+    }
+    samples
+}
+
+/// An AVI chunk, padded to an even length
+fn chunk(out: &mut Vec<u8>, id: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(id);
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(data);
+    if data.len() % 2 == 1 {
+        out.push(0);
+    }
+}
+
+fn list(out: &mut Vec<u8>, kind: &[u8; 4], body: &[u8]) {
+    let mut data = kind.to_vec();
+    data.extend_from_slice(body);
+    chunk(out, b"LIST", &data);
+}
+
+fn le(out: &mut Vec<u8>, values: &[u32]) {
+    for v in values {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+}
+
+/// An AVI of PNG frames (`MPNG`, which ffmpeg and so CasparCG decode) and
+/// 16-bit PCM, one audio chunk per frame
+fn avi(width: u32, height: u32, (num, den): (u32, u32), tracks: u16, frames: &[(Vec<u8>, Vec<u8>)]) -> Vec<u8> {
+    let block = u32::from(tracks) * 2;
+    let audio_frames: u32 = frames.iter().map(|(_, pcm)| pcm.len() as u32 / block).sum();
+    let max_video = frames.iter().map(|(png, _)| png.len()).max().unwrap_or(0) as u32;
+    let max_audio = frames.iter().map(|(_, pcm)| pcm.len()).max().unwrap_or(0) as u32;
+
+    let mut movi = b"movi".to_vec();
+    let mut index = Vec::new();
+    for (png, pcm) in frames {
+        for (id, data) in [(b"00dc", png), (b"01wb", pcm)] {
+            index.extend_from_slice(id);
+            // AVIIF_KEYFRAME, then the offset from "movi"
+            le(&mut index, &[0x10, movi.len() as u32, data.len() as u32]);
+            chunk(&mut movi, id, data);
+        }
+    }
+
+    let mut avih = Vec::new();
+    le(
+        &mut avih,
+        &[
+            (1_000_000u64 * u64::from(den) / u64::from(num)) as u32,
+            0,
+            0,
+            // AVIF_HASINDEX | AVIF_ISINTERLEAVED
+            0x110,
+            frames.len() as u32,
+            0,
+            2,
+            max_video.max(max_audio) + 8,
+            width,
+            height,
+            0,
+            0,
+            0,
+            0,
+        ],
+    );
+
+    let mut video = Vec::new();
+    let mut strh = b"vidsMPNG".to_vec();
+    le(&mut strh, &[0, 0, 0, den, num, 0, frames.len() as u32, max_video, u32::MAX, 0]);
+    le(&mut strh, &[0, (height << 16) | width]);
+    chunk(&mut video, b"strh", &strh);
+    let mut strf = Vec::new();
+    le(&mut strf, &[40, width, height, (32 << 16) | 1]);
+    strf.extend_from_slice(b"MPNG");
+    le(&mut strf, &[width * height * 4, 0, 0, 0, 0]);
+    chunk(&mut video, b"strf", &strf);
+
+    let mut audio = Vec::new();
+    let mut strh = b"auds".to_vec();
+    le(&mut strh, &[0, 0, 0, 0, block, SAMPLE_RATE * block, 0, audio_frames, max_audio, u32::MAX, block, 0, 0]);
+    chunk(&mut audio, b"strh", &strh);
+    let mut strf = Vec::new();
+    strf.extend_from_slice(&1u16.to_le_bytes());
+    strf.extend_from_slice(&tracks.to_le_bytes());
+    le(&mut strf, &[SAMPLE_RATE, SAMPLE_RATE * block]);
+    for v in [block as u16, 16, 0] {
+        strf.extend_from_slice(&v.to_le_bytes());
+    }
+    chunk(&mut audio, b"strf", &strf);
+
+    let mut hdrl = Vec::new();
+    chunk(&mut hdrl, b"avih", &avih);
+    list(&mut hdrl, b"strl", &video);
+    list(&mut hdrl, b"strl", &audio);
+
+    let mut body = b"AVI ".to_vec();
+    list(&mut body, b"hdrl", &hdrl);
+    chunk(&mut body, b"LIST", &movi);
+    chunk(&mut body, b"idx1", &index);
+    let mut out = Vec::with_capacity(body.len() + 8);
+    chunk(&mut out, b"RIFF", &body);
+    out
+}
+
+/// File stem for `mode`, from its CasparCG name: `avsync-1080i5000`
+fn clip_stem(mode: &VideoMode) -> String {
+    let name = serde_json::to_value(mode)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_lowercase))
+        .unwrap_or_default();
+    format!("avsync-{name}")
+}
+
+/// Render the AV sync loop for `mode` into `media_dir/caspar-ident` and
+/// return its CasparCG name. The beep is on every track of `ident`'s layout,
+/// at its line-up level.
+pub fn write_av_sync(mode: &VideoMode, ident: &IdentConfig, media_dir: &Path) -> Result<String, String> {
+    let (width, height) = mode.resolution();
+    let rate = mode.frame_rate_ratio();
+    let (frames, flash) = sync_frames(mode);
+    let tracks = ident.tracks.count() as usize;
+    let audio = render_audio(mode, frames, flash, ident);
+
+    let mut chunks = Vec::with_capacity(frames as usize);
+    for frame in 0..frames {
+        let png = render_frame(mode, frame, frames, flash).png()?;
+        let pcm = audio[sample_at(frame, rate) * tracks..sample_at(frame + 1, rate) * tracks]
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+        chunks.push((png, pcm));
+    }
+
+    let dir = media_dir.join(SLATE_FOLDER);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let stem = clip_stem(mode);
+    let path = dir.join(format!("{stem}.avi"));
+    std::fs::write(&path, avi(width, height, rate, tracks as u16, &chunks))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(format!("{}/{}", SLATE_FOLDER, stem).to_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loops_are_whole_frames_and_samples() {
+        assert_eq!(sync_frames(&VideoMode::I1080_5000), (50, 25));
+        assert_eq!(sync_frames(&VideoMode::P1080_5994), (120, 60));
+        // 120 frames at 60000/1001 is exactly 96096 samples
+        assert_eq!(sample_at(120, VideoMode::P1080_5994.frame_rate_ratio()), 96_096);
+
+        let ident = IdentConfig::default();
+        let audio = render_audio(&VideoMode::Pal, 50, 25, &ident);
+        let beep: Vec<usize> = (0..audio.len() / 2).filter(|&n| audio[n * 2] != 0).collect();
+        // The beep is within the flash frame's 1920 samples, and fills it
+        assert!(*beep.first().unwrap() >= 25 * 1920);
+        assert!(*beep.last().unwrap() < 26 * 1920);
+        assert!(beep.len() > 1800);
+    }
+
+    #[test]
+    fn writes_an_avi_with_video_and_audio_streams() {
+        let media = std::env::temp_dir().join(format!("caspar-avsync-{}", std::process::id()));
+        let clip = write_av_sync(&VideoMode::Pal, &IdentConfig::default(), &media).unwrap();
+        assert_eq!(clip, "CASPAR-IDENT/AVSYNC-PAL");
+
+        let avi = std::fs::read(media.join(SLATE_FOLDER).join("avsync-pal.avi")).unwrap();
+        assert_eq!((&avi[..4], &avi[8..12]), (&b"RIFF"[..], &b"AVI "[..]));
+        assert_eq!(u32::from_le_bytes(avi[4..8].try_into().unwrap()) as usize, avi.len() - 8);
+        let find = |needle: &[u8]| avi.windows(needle.len()).position(|w| w == needle).unwrap();
+        let strh = find(b"vidsMPNG");
+        let word = |at: usize| u32::from_le_bytes(avi[at..at + 4].try_into().unwrap());
+        // dwScale / dwRate, then dwLength
+        assert_eq!((word(strh + 20), word(strh + 24), word(strh + 32)), (1, 25, 50));
+
+        // The first video chunk is a PNG the size of the channel
+        let first = find(b"00dc");
+        let png = &avi[first + 8..first + 8 + word(first + 4) as usize];
+        let reader = png::Decoder::new(std::io::Cursor::new(png)).read_info().unwrap();
+        assert_eq!((reader.info().width, reader.info().height), (720, 576));
+        // One index entry per video and audio chunk
+        let idx = find(b"idx1");
+        assert_eq!(word(idx + 4), 50 * 2 * 16);
+        let _ = std::fs::remove_dir_all(&media);
+    }
+}
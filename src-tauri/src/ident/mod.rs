@@ -0,0 +1,12 @@
+// Channel identification
+// Slates, audio idents and the AV sync loop, generated in Rust and written into the media folder
+
+mod audio;
+mod avsync;
+mod font;
+mod raster;
+mod slate;
+
+pub use audio::*;
+pub use avsync::*;
+pub use slate::*;
@@ -0,0 +1,92 @@
+// Raster drawing
+// An RGBA frame with filled rectangles and bitmap text, saved as PNG
+
+use super::font::{glyph, GLYPH_HEIGHT, GLYPH_WIDTH};
+
+/// An 8-bit RGBA colour
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgba(pub u8, pub u8, pub u8, pub u8);
+
+impl Rgba {
+    pub const TRANSPARENT: Rgba = Rgba(0, 0, 0, 0);
+    pub const BLACK: Rgba = Rgba(0, 0, 0, 255);
+    pub const WHITE: Rgba = Rgba(255, 255, 255, 255);
+}
+
+/// A frame to draw on, top-left origin
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32, background: Rgba) -> Self {
+        let pixels = [background.0, background.1, background.2, background.3]
+            .repeat(width as usize * height as usize);
+        Self { width, height, pixels }
+    }
+
+    /// Fill a rectangle, clipped to the frame
+    pub fn fill_rect(&mut self, x: u32, y: u32, width: u32, height: u32, color: Rgba) {
+        let x_end = x.saturating_add(width).min(self.width);
+        let y_end = y.saturating_add(height).min(self.height);
+        let px = [color.0, color.1, color.2, color.3];
+        for row in y.min(y_end)..y_end {
+            let start = (row as usize * self.width as usize + x.min(x_end) as usize) * 4;
+            let end = (row as usize * self.width as usize + x_end as usize) * 4;
+            for chunk in self.pixels[start..end].chunks_exact_mut(4) {
+                chunk.copy_from_slice(&px);
+            }
+        }
+    }
+
+    /// Draw `text` with its top-left at `x`, `y`, each font pixel `scale`
+    /// pixels square. Returns the width drawn.
+    pub fn text(&mut self, x: u32, y: u32, scale: u32, text: &str, color: Rgba) -> u32 {
+        let scale = scale.max(1);
+        let mut cursor = x;
+        for c in text.chars() {
+            for (col, bits) in glyph(c).iter().enumerate() {
+                for row in 0..GLYPH_HEIGHT {
+                    if bits & (1 << row) != 0 {
+                        self.fill_rect(cursor + col as u32 * scale, y + row * scale, scale, scale, color);
+                    }
+                }
+            }
+            cursor += (GLYPH_WIDTH + 1) * scale;
+        }
+        text_width(text, scale)
+    }
+
+    /// Encode as an 8-bit RGBA PNG
+    pub fn png(&self) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_compression(png::Compression::Fast);
+        let mut writer = encoder
+            .write_header()
+            .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+        writer
+            .write_image_data(&self.pixels)
+            .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+        writer
+            .finish()
+            .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+        Ok(out)
+    }
+}
+
+/// Width of `text` drawn at `scale`, without the trailing gap
+pub fn text_width(text: &str, scale: u32) -> u32 {
+    let chars = text.chars().count() as u32;
+    (chars * (GLYPH_WIDTH + 1)).saturating_sub(1) * scale.max(1)
+}
+
+/// Height of a line of text drawn at `scale`
+pub fn text_height(scale: u32) -> u32 {
+    GLYPH_HEIGHT * scale.max(1)
+}
@@ -0,0 +1,248 @@
+// Identification slates
+// Per-channel fill and key stills naming the channel, profile, mode and outputs
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::{DateTime, Local};
+
+use super::raster::{text_height, text_width, Canvas, Rgba};
+use crate::config::{Consumer, GlobalConfig, VideoMode};
+
+/// Folder under `paths.media` the slates are written to
+pub const SLATE_FOLDER: &str = "caspar-ident";
+
+/// Per-channel accent colours, so neighbouring monitors are told apart at a
+/// glance. Channel 1 keeps the magenta the colour-only test used.
+const ACCENTS: [Rgba; 6] = [
+    Rgba(255, 0, 255, 255),
+    Rgba(0, 200, 255, 255),
+    Rgba(255, 200, 0, 255),
+    Rgba(0, 220, 90, 255),
+    Rgba(255, 110, 0, 255),
+    Rgba(120, 120, 255, 255),
+];
+
+const PANEL: Rgba = Rgba(24, 24, 28, 255);
+const TEXT: Rgba = Rgba(235, 235, 235, 255);
+const MUTED: Rgba = Rgba(150, 150, 160, 255);
+
+/// What a channel's slate says
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlateInfo {
+    /// 1-based, as in AMCP
+    pub channel: usize,
+    pub profile: String,
+    pub video_mode: VideoMode,
+    /// One line per consumer, e.g. `DeckLink 1 "Graphics Fill" + key 2`
+    pub consumers: Vec<String>,
+    /// Label of the channel's first DeckLink output, when it has one
+    pub device_label: Option<String>,
+    pub timestamp: DateTime<Local>,
+}
+
+impl SlateInfo {
+    /// One per channel of `config`. `labels` holds the DeckLink device labels
+    /// by the 1-based index consumers refer to.
+    pub fn for_profile(config: &GlobalConfig, labels: &HashMap<u32, String>, now: DateTime<Local>) -> Vec<Self> {
+        config
+            .caspar
+            .channels
+            .iter()
+            .enumerate()
+            .map(|(i, channel)| Self {
+                channel: i + 1,
+                profile: config.name.clone(),
+                video_mode: channel.video_mode.clone(),
+                consumers: channel.consumers.iter().map(|c| consumer_line(c, labels)).collect(),
+                device_label: channel.consumers.iter().find_map(|c| match c {
+                    Consumer::DeckLink(d) => labels.get(&d.device).cloned(),
+                    _ => None,
+                }),
+                timestamp: now,
+            })
+            .collect()
+    }
+}
+
+fn consumer_line(consumer: &Consumer, labels: &HashMap<u32, String>) -> String {
+    match consumer {
+        Consumer::DeckLink(d) => {
+            let mut line = format!("DeckLink {}", d.device);
+            if let Some(label) = labels.get(&d.device) {
+                line.push_str(&format!(" \"{label}\""));
+            }
+            if let Some(key) = d.key_device {
+                line.push_str(&format!(" + key {key}"));
+            }
+            line
+        }
+        Consumer::Ndi(n) => format!("NDI \"{}\"", n.name),
+        Consumer::Screen(s) => format!("Screen {}", s.device),
+        Consumer::SystemAudio(_) => "System audio".to_string(),
+    }
+}
+
+/// A rendered slate: the fill with its alpha, and the alpha as a white-on-black
+/// key for CasparCG's layer keyer and external keyers
+#[derive(Debug, Clone)]
+pub struct Slate {
+    pub fill: Canvas,
+    pub key: Canvas,
+}
+
+/// Render `info` at the channel's native resolution. The slate is a card over
+/// the middle of the frame; around it the fill is transparent and the key
+/// black, so a keyed output shows the card over programme.
+pub fn render_slate(info: &SlateInfo) -> Slate {
+    let (width, height) = info.video_mode.resolution();
+    let accent = ACCENTS[(info.channel.max(1) - 1) % ACCENTS.len()];
+    let mut fill = Canvas::new(width, height, Rgba::TRANSPARENT);
+    let mut key = Canvas::new(width, height, Rgba::BLACK);
+
+    let (card_x, card_y) = (width / 10, height / 8);
+    let (card_w, card_h) = (width - 2 * card_x, height - 2 * card_y);
+    fill.fill_rect(card_x, card_y, card_w, card_h, PANEL);
+    key.fill_rect(card_x, card_y, card_w, card_h, Rgba::WHITE);
+    let stripe = (height / 60).max(4);
+    fill.fill_rect(card_x, card_y, card_w, stripe, accent);
+    fill.fill_rect(card_x, card_y + card_h - stripe, card_w, stripe, accent);
+
+    // Font pixels scale with the raster: 7 px glyphs, 5 px per pixel at 1080
+    let unit = (height / 216).max(1);
+    let margin = card_w / 20;
+    let x = card_x + margin;
+    let max_chars = |scale: u32| ((card_w - 2 * margin) / ((5 + 1) * scale)) as usize;
+    let mut y = card_y + stripe + margin / 2;
+
+    let title = format!("CH {}", info.channel);
+    let title_scale = unit * 6;
+    fill.text(x, y, title_scale, &title, accent);
+    if let Some(label) = &info.device_label {
+        let label_scale = unit * 2;
+        let label_x = x + text_width(&title, title_scale) + 2 * label_scale * 6;
+        let room = ((card_x + card_w - margin).saturating_sub(label_x) / (6 * label_scale)) as usize;
+        let label_y = y + text_height(title_scale) - text_height(label_scale);
+        fill.text(label_x, label_y, label_scale, &truncate(label, room), TEXT);
+    }
+    y += text_height(title_scale) + 6 * unit;
+
+    let line_scale = (unit * 3 / 2).max(1);
+    let line_step = text_height(line_scale) + 2 * line_scale;
+    let lines = [
+        (format!("{}  {}x{}", info.video_mode.display_name(), width, height), TEXT),
+        (info.profile.clone(), TEXT),
+    ];
+    for (line, color) in lines {
+        fill.text(x, y, line_scale, &truncate(&line, max_chars(line_scale)), color);
+        y += line_step;
+    }
+    let small = unit;
+    let small_step = text_height(small) + 2 * small;
+    let bottom = card_y + card_h - stripe - margin / 2 - small_step;
+    for consumer in &info.consumers {
+        if y + small_step > bottom {
+            break;
+        }
+        fill.text(x, y, small, &truncate(consumer, max_chars(small)), MUTED);
+        y += small_step;
+    }
+    fill.text(x, bottom, small, &info.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(), MUTED);
+
+    Slate { fill, key }
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        let kept: String = text.chars().take(max_chars.saturating_sub(3)).collect();
+        format!("{kept}...")
+    }
+}
+
+/// The CasparCG names of a channel's written slate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlateClips {
+    pub fill: String,
+    pub key: String,
+}
+
+/// Render and write every slate into `media_dir/caspar-ident`, replacing the
+/// previous ones, and return what to PLAY for each channel
+pub fn write_slates(infos: &[SlateInfo], media_dir: &Path) -> Result<Vec<SlateClips>, String> {
+    let dir = media_dir.join(SLATE_FOLDER);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let mut clips = Vec::new();
+    for info in infos {
+        let slate = render_slate(info);
+        let name = format!("channel-{}", info.channel);
+        for (file, canvas) in [(format!("{name}.png"), &slate.fill), (format!("{name}-key.png"), &slate.key)] {
+            let path = dir.join(file);
+            std::fs::write(&path, canvas.png()?)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        }
+        let clip = format!("{}/{}", SLATE_FOLDER, name).to_uppercase();
+        clips.push(SlateClips {
+            key: format!("{clip}-KEY"),
+            fill: clip,
+        });
+    }
+    Ok(clips)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Channel, DeckLinkConsumer, NdiConsumer};
+
+    #[test]
+    fn renders_and_writes_a_slate_per_channel() {
+        let mut config = GlobalConfig::new("Studio A");
+        config.caspar.channels = vec![
+            Channel {
+                video_mode: VideoMode::I1080_5000,
+                consumers: vec![Consumer::DeckLink(DeckLinkConsumer {
+                    device: 2,
+                    key_device: Some(3),
+                    ..Default::default()
+                })],
+            },
+            Channel {
+                video_mode: VideoMode::Pal,
+                consumers: vec![Consumer::Ndi(NdiConsumer::default())],
+            },
+        ];
+        let labels = HashMap::from([(2, "Graphics Fill".to_string())]);
+        let infos = SlateInfo::for_profile(&config, &labels, Local::now());
+        assert_eq!(infos[0].consumers, ["DeckLink 2 \"Graphics Fill\" + key 3"]);
+        assert_eq!(infos[0].device_label.as_deref(), Some("Graphics Fill"));
+        assert_eq!(infos[1].device_label, None);
+
+        let media = std::env::temp_dir().join(format!("caspar-slates-{}", std::process::id()));
+        let clips = write_slates(&infos, &media).unwrap();
+        assert_eq!(clips[1].fill, "CASPAR-IDENT/CHANNEL-2");
+        assert_eq!(clips[1].key, "CASPAR-IDENT/CHANNEL-2-KEY");
+
+        let decode = |file: &str| {
+            let png = std::fs::read(media.join("caspar-ident").join(file)).unwrap();
+            let mut reader = png::Decoder::new(std::io::Cursor::new(png)).read_info().unwrap();
+            let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+            let frame = reader.next_frame(&mut pixels).unwrap();
+            let at = move |x: usize, y: usize| {
+                let i = (y * frame.width as usize + x) * 4;
+                [pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3]]
+            };
+            ((frame.width, frame.height), at)
+        };
+        let (size, fill) = decode("channel-1.png");
+        let (_, key) = decode("channel-1-key.png");
+        assert_eq!(size, (1920, 1080));
+        // Outside the card: transparent fill, black key. Inside: opaque, white.
+        assert_eq!((fill(10, 10), key(10, 10)), ([0, 0, 0, 0], [0, 0, 0, 255]));
+        assert_eq!((fill(960, 540)[3], key(960, 540)), (255, [255; 4]));
+        assert_eq!(decode("channel-2.png").0, (720, 576));
+        let _ = std::fs::remove_dir_all(&media);
+    }
+}
@@ -0,0 +1,148 @@
+// Launch arguments
+// Parses the GUI's command line for kiosk shortcuts and file associations
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+pub const USAGE: &str = "\
+Usage: caspar-server-gui [options] [profile.json]
+
+Options:
+  --profile <name|file>  Select a saved profile by name, or open a profile
+                         JSON file. A bare .json path does the same.
+  --start                Start the server on launch, without the
+                         start-on-launch countdown
+  --minimized            Start with the window minimized
+  --settings <file>      Use this settings.json instead of the per-user one.
+                         Logs and crash reports go next to it.
+  --read-only            Show everything but refuse to save, start, stop or
+                         send commands
+  -h, --help             Show this help
+
+When the GUI is already running, the arguments are handed to it and this
+launch exits.";
+
+/// The profile a launch names
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum ProfileArg {
+    /// A profile in the profiles folder, by file stem or display name
+    Name(String),
+    /// A profile JSON anywhere on disk
+    File(PathBuf),
+}
+
+impl ProfileArg {
+    /// A value with a path separator or a `.json` extension is a file, made
+    /// absolute so it still resolves when forwarded to another process.
+    fn from_arg(value: &str) -> Self {
+        let looks_like_file = value.contains(['/', '\\'])
+            || Path::new(value)
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        if looks_like_file {
+            ProfileArg::File(absolute(Path::new(value)))
+        } else {
+            ProfileArg::Name(value.to_string())
+        }
+    }
+}
+
+/// What one invocation of the GUI asked for
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaunchArgs {
+    pub profile: Option<ProfileArg>,
+    pub start: bool,
+    pub minimized: bool,
+    pub settings: Option<PathBuf>,
+    pub read_only: bool,
+}
+
+impl LaunchArgs {
+    /// Parse the arguments after the program name. `Ok(None)` asks for help.
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut parsed = LaunchArgs::default();
+        while let Some(arg) = args.next() {
+            let mut value = |flag: &str| args.next().ok_or_else(|| format!("{flag} needs a value"));
+            let profile = match arg.as_str() {
+                "--profile" => Some(ProfileArg::from_arg(&value("--profile")?)),
+                "--settings" => {
+                    parsed.settings = Some(absolute(Path::new(&value("--settings")?)));
+                    None
+                }
+                "--start" => {
+                    parsed.start = true;
+                    None
+                }
+                "--minimized" => {
+                    parsed.minimized = true;
+                    None
+                }
+                "--read-only" => {
+                    parsed.read_only = true;
+                    None
+                }
+                "-h" | "--help" => return Ok(None),
+                other if other.starts_with('-') => {
+                    return Err(format!("unknown argument '{other}'"))
+                }
+                // A file opened with the app, e.g. a double-clicked profile
+                file => Some(ProfileArg::File(absolute(Path::new(file)))),
+            };
+            if let Some(profile) = profile {
+                if parsed.profile.is_some() {
+                    return Err("only one profile can be given".to_string());
+                }
+                parsed.profile = Some(profile);
+            }
+        }
+        Ok(Some(parsed))
+    }
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<LaunchArgs>, String> {
+        LaunchArgs::parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn parses_a_kiosk_shortcut() {
+        let args = parse(&["--profile", "Studio A", "--start", "--minimized"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(args.profile, Some(ProfileArg::Name("Studio A".to_string())));
+        assert!(args.start && args.minimized && !args.read_only);
+        assert_eq!(args.settings, None);
+    }
+
+    #[test]
+    fn files_are_told_from_names_and_made_absolute() {
+        let args = parse(&["studio-a.json", "--read-only"]).unwrap().unwrap();
+        let Some(ProfileArg::File(path)) = args.profile else {
+            panic!("expected a file, got {:?}", args.profile);
+        };
+        assert!(path.is_absolute() && path.ends_with("studio-a.json"));
+        assert!(args.read_only);
+
+        let args = parse(&["--profile", "profiles/Studio A.JSON"]).unwrap().unwrap();
+        assert!(matches!(args.profile, Some(ProfileArg::File(_))));
+        let args = parse(&["--settings", "kiosk.json"]).unwrap().unwrap();
+        assert!(args.settings.unwrap().is_absolute());
+    }
+
+    #[test]
+    fn rejects_bad_usage() {
+        assert_eq!(parse(&["--help"]), Ok(None));
+        assert!(parse(&["--profile"]).unwrap_err().contains("needs a value"));
+        assert!(parse(&["--strat"]).unwrap_err().contains("--strat"));
+        assert!(parse(&["--profile", "A", "b.json"]).is_err());
+    }
+}
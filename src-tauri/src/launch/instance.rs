@@ -0,0 +1,207 @@
+// Single instance
+// The first launch listens on loopback; later launches hand it their arguments
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use super::LaunchArgs;
+
+/// How long a second launch waits on the first before taking over
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Where the running instance listens, written to the lock file. The token
+/// keeps other local processes from feeding it arguments.
+#[derive(Debug, Serialize, Deserialize)]
+struct Owner {
+    port: u16,
+    token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Forward {
+    token: String,
+    args: LaunchArgs,
+}
+
+/// The outcome of `claim`
+pub enum Instance {
+    /// This is the only instance; serve the launches that follow it
+    Primary(PrimaryInstance),
+    /// Another instance is running and has taken the arguments
+    Forwarded,
+}
+
+/// The lock and listener of the running instance
+pub struct PrimaryInstance {
+    listener: TcpListener,
+    token: String,
+}
+
+/// Become the single instance, or hand `args` to the one already running. A
+/// lock whose owner no longer answers is stale (a crash or power cut) and is
+/// taken over.
+pub fn claim(lock: &Path, args: &LaunchArgs) -> io::Result<Instance> {
+    if let Some(dir) = lock.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    // A few rounds, as two launches at once may race for the lock
+    for _ in 0..3 {
+        if let Some(owner) = read_owner(lock) {
+            if forward(&owner, args).is_ok() {
+                return Ok(Instance::Forwarded);
+            }
+            let _ = std::fs::remove_file(lock);
+        }
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let owner = Owner {
+            port: listener.local_addr()?.port(),
+            token: crate::api::generate_token().map_err(io::Error::other)?,
+        };
+        match write_new(lock, &owner) {
+            Ok(()) => {
+                return Ok(Instance::Primary(PrimaryInstance {
+                    listener,
+                    token: owner.token,
+                }))
+            }
+            // Another launch got there first: forward to it next round
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Err(io::Error::other(format!(
+        "cannot claim the instance lock {}",
+        lock.display()
+    )))
+}
+
+impl PrimaryInstance {
+    /// Accept forwarded launches on a background thread, delivering their
+    /// arguments on the returned channel
+    pub fn serve(self) -> UnboundedReceiver<LaunchArgs> {
+        let (tx, rx) = unbounded_channel();
+        std::thread::spawn(move || {
+            for stream in self.listener.incoming().flatten() {
+                if receive(stream, &self.token, &tx).is_err() && tx.is_closed() {
+                    break;
+                }
+            }
+        });
+        rx
+    }
+
+    /// The port written to the lock, for `release`
+    pub fn port(&self) -> Option<u16> {
+        self.listener.local_addr().ok().map(|a| a.port())
+    }
+}
+
+/// Remove the lock on exit, unless another instance has since taken it over
+pub fn release(lock: &Path, port: Option<u16>) {
+    if read_owner(lock).is_some_and(|owner| Some(owner.port) == port) {
+        let _ = std::fs::remove_file(lock);
+    }
+}
+
+fn read_owner(lock: &Path) -> Option<Owner> {
+    serde_json::from_str(&std::fs::read_to_string(lock).ok()?).ok()
+}
+
+fn write_new(lock: &Path, owner: &Owner) -> io::Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(lock)?;
+    file.write_all(serde_json::to_string(owner)?.as_bytes())
+}
+
+/// Send `args` as one JSON line and wait for the owner's "ok"
+fn forward(owner: &Owner, args: &LaunchArgs) -> io::Result<()> {
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, owner.port));
+    let mut stream = TcpStream::connect_timeout(&addr, FORWARD_TIMEOUT)?;
+    stream.set_read_timeout(Some(FORWARD_TIMEOUT))?;
+    let message = Forward {
+        token: owner.token.clone(),
+        args: args.clone(),
+    };
+    writeln!(stream, "{}", serde_json::to_string(&message)?)?;
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    if reply.trim() == "ok" {
+        Ok(())
+    } else {
+        Err(io::Error::other("the instance lock belongs to something else"))
+    }
+}
+
+fn receive(stream: TcpStream, token: &str, tx: &UnboundedSender<LaunchArgs>) -> io::Result<()> {
+    stream.set_read_timeout(Some(FORWARD_TIMEOUT))?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    let message: Forward = serde_json::from_str(&line)?;
+    if message.token != token {
+        return Err(io::Error::other("wrong instance token"));
+    }
+    tx.send(message.args)
+        .map_err(|_| io::Error::other("the app is shutting down"))?;
+    writeln!(&stream, "ok")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::launch::ProfileArg;
+
+    fn lock(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("caspar-instance-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("instance.lock")
+    }
+
+    #[test]
+    fn second_launch_forwards_its_arguments() {
+        let lock = lock("forward");
+        let Instance::Primary(primary) = claim(&lock, &LaunchArgs::default()).unwrap() else {
+            panic!("the first launch should own the lock");
+        };
+        let port = primary.port();
+        let mut rx = primary.serve();
+
+        let args = LaunchArgs {
+            profile: Some(ProfileArg::Name("Studio A".to_string())),
+            start: true,
+            ..Default::default()
+        };
+        assert!(matches!(claim(&lock, &args).unwrap(), Instance::Forwarded));
+        assert_eq!(rx.try_recv().unwrap(), args);
+
+        release(&lock, port);
+        assert!(!lock.exists());
+    }
+
+    #[test]
+    fn stale_lock_is_taken_over() {
+        let lock = lock("stale");
+        std::fs::create_dir_all(lock.parent().unwrap()).unwrap();
+        // A port nothing listens on: bind one, note it, close it
+        let port = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        std::fs::write(&lock, format!(r#"{{"port":{port},"token":"gone"}}"#)).unwrap();
+
+        let Instance::Primary(primary) = claim(&lock, &LaunchArgs::default()).unwrap() else {
+            panic!("a stale lock should be taken over");
+        };
+        assert_eq!(read_owner(&lock).map(|o| o.port), primary.port());
+        release(&lock, primary.port());
+    }
+}
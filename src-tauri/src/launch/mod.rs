@@ -0,0 +1,8 @@
+// Launch
+// Command-line arguments and single-instance forwarding
+
+mod args;
+mod instance;
+
+pub use args::*;
+pub use instance::*;
@@ -2,22 +2,48 @@
 // Provides configuration management, AMCP communication, and DeckLink integration
 
 mod amcp;
+mod api;
+mod commissioning;
 mod config;
+mod control;
+mod crash_report;
 mod decklink;
+mod diagnostics;
+mod events;
 mod http_server;
+mod ident;
+mod launch;
+mod library;
+mod logs;
+mod metrics;
+mod ports;
+mod resources;
+mod scanner;
+mod supervisor;
 mod system;
+mod template_dev;
+mod watchdog;
 
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
 use tauri::{Emitter, Manager};
 use tokio::sync::Mutex;
 
+use commissioning::{CheckResult, Commissioning, Inventory, ReportFormat};
 use config::{
-    generate_caspar_xml, parse_caspar_xml, CasparConfig, GlobalConfig, GuiSettings, MediaServer,
+    generate_caspar_xml, parse_caspar_xml, ApiSettings, CasparConfig, MetricsSettings,
+    TemplateDevSettings,
 };
+use control::{AutoStart, AutoStartStatus, Control, TestSession, TestSessions, TestTarget};
 use decklink::{DeckLinkDevice, DeckLinkStatus, DuplexMode};
+use diagnostics::{Diagnostic, RuleLoadSummary};
+use events::{EventBus, TopicFilter};
+use launch::{Instance, LaunchArgs, ProfileArg};
+use library::{
+    ChannelCompatibility, LibraryChanges, LibraryMedia, LibraryStatus, MediaLibrary, MediaQuery,
+};
+use logs::{LogPage, LogQuery};
+use ports::{PortPreflight, PortRole, Protocol, SystemProber};
 
 // Public re-exports for hardware-in-the-loop tests and external tooling. These
 // expose the same enumeration path the Tauri commands use, without making the
@@ -27,30 +53,109 @@ pub use decklink::{
     list_devices as enumerate_decklink_devices, set_device_label as set_decklink_device_label,
 };
 
+// Headless supervision (the caspar-supervisor binary): the same launcher,
+// restart policy and diagnostics the GUI drives, without Tauri.
+pub use config::{GlobalConfig, GuiSettings};
+pub use diagnostics::DiagnosticsEngine;
+pub use logs::{LogEntry, LogLevel, LogStore};
+pub use supervisor::{EventSink, Launcher, LauncherEvent};
+
 /// Application state shared across commands
 pub struct AppState {
     pub amcp_client: Arc<Mutex<amcp::AmcpClient>>,
     pub gui_settings: Arc<Mutex<GuiSettings>>,
     pub test_server: http_server::TestServerState,
-    /// The launched CasparCG server process, if running
-    pub caspar_process: Arc<Mutex<Option<std::process::Child>>>,
-    /// The media scanner process launched alongside the server, if running
-    pub scanner_process: Arc<Mutex<Option<std::process::Child>>>,
-    /// Desired state: true while the server should be supervised. Start sets it,
-    /// Stop/app-exit/give-up clear it. The supervisor consults it so a user Stop
-    /// is never mistaken for a crash and never triggers a restart.
-    pub server_should_run: Arc<AtomicBool>,
-}
-
-impl Default for AppState {
-    fn default() -> Self {
+    /// The supervised CasparCG server and scanner, with their logs and
+    /// diagnostics. Its events are forwarded to the GUI as Tauri events.
+    pub launcher: Arc<Launcher>,
+    /// The opt-in REST control API
+    pub api_server: api::ApiServerState,
+    /// The opt-in Prometheus metrics endpoint
+    pub metrics_server: api::HttpListenerState,
+    /// Every backend event; the webview and the event WebSocket subscribe
+    pub events: Arc<EventBus>,
+    /// The start-on-launch countdown
+    pub auto_start: Arc<AutoStart>,
+    /// Channel and DeckLink output tests that are running
+    pub tests: Arc<TestSessions>,
+    /// Media, templates and fonts listed by the running scanner
+    pub media_library: Arc<MediaLibrary>,
+    /// The template dev server, while one is running
+    pub template_dev: Mutex<Option<template_dev::TemplateDev>>,
+    /// The commissioning walk-through, from its start until closed
+    pub commissioning: Mutex<Option<Commissioning>>,
+    /// Started with `--read-only`: commands that change anything refuse
+    pub read_only: bool,
+    /// A profile file named on the command line, for the window to open
+    pub pending_open: std::sync::Mutex<Option<PathBuf>>,
+}
+
+impl AppState {
+    pub fn new(app: tauri::AppHandle, read_only: bool) -> Self {
+        // The webview is one subscriber of the bus: forward every event to it
+        // under its topic name.
+        let events = Arc::new(EventBus::new());
+        let (_, mut rx) = events.subscribe(&TopicFilter::default(), 0);
+        tauri::async_runtime::spawn(async move {
+            use tokio::sync::broadcast::error::RecvError;
+            loop {
+                match rx.recv().await {
+                    Ok(event) => {
+                        let _ = app.emit(&event.topic, event.payload);
+                    }
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+        let (logs, log_error) = LogStore::with_log_dir(GuiSettings::logs_dir());
+        let (diagnostics, rule_errors) =
+            DiagnosticsEngine::with_user_rules(GuiSettings::diagnostic_rules_path());
+        let launcher = Arc::new(Launcher::new(
+            logs,
+            diagnostics,
+            GuiSettings::crash_reports_dir(),
+            events.launcher_sink(),
+        ));
+        if let Some(e) = log_error {
+            launcher.log(LogLevel::Warning, e);
+        }
+        for e in rule_errors {
+            launcher.log(LogLevel::Warning, format!("Diagnostic rules: {e}"));
+        }
         Self {
             amcp_client: Arc::new(Mutex::new(amcp::AmcpClient::new())),
             gui_settings: Arc::new(Mutex::new(GuiSettings::load())),
             test_server: http_server::create_test_server_state(),
-            caspar_process: Arc::new(Mutex::new(None)),
-            scanner_process: Arc::new(Mutex::new(None)),
-            server_should_run: Arc::new(AtomicBool::new(false)),
+            launcher,
+            api_server: Default::default(),
+            metrics_server: Default::default(),
+            events,
+            auto_start: Default::default(),
+            tests: Default::default(),
+            media_library: Arc::new(MediaLibrary::new(GuiSettings::thumbnail_cache_dir())),
+            template_dev: Default::default(),
+            commissioning: Default::default(),
+            read_only,
+            pending_open: Default::default(),
+        }
+    }
+
+    /// Refuse a change when started with `--read-only`
+    pub fn writable(&self) -> Result<(), String> {
+        self.control().writable()
+    }
+
+    /// The shared state as the operations the commands and the API have in common
+    pub fn control(&self) -> Control {
+        Control {
+            amcp_client: self.amcp_client.clone(),
+            gui_settings: self.gui_settings.clone(),
+            launcher: self.launcher.clone(),
+            events: self.events.clone(),
+            auto_start: self.auto_start.clone(),
+            tests: self.tests.clone(),
+            read_only: self.read_only,
         }
     }
 }
@@ -70,7 +175,12 @@ async fn load_caspar_config(path: String) -> Result<CasparConfig, String> {
 
 /// Save a CasparCG XML configuration file
 #[tauri::command]
-async fn save_caspar_config(path: String, config: CasparConfig) -> Result<(), String> {
+async fn save_caspar_config(
+    path: String,
+    config: CasparConfig,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    state.writable()?;
     let xml = generate_caspar_xml(&config)
         .map_err(|e| format!("Failed to generate XML: {}", e))?;
 
@@ -86,7 +196,12 @@ async fn load_global_config(path: String) -> Result<GlobalConfig, String> {
 
 /// Save a global configuration profile (JSON)
 #[tauri::command]
-async fn save_global_config(path: String, mut config: GlobalConfig) -> Result<(), String> {
+async fn save_global_config(
+    path: String,
+    mut config: GlobalConfig,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    state.writable()?;
     config.touch();
     config
         .save_to_file(&PathBuf::from(&path))
@@ -108,35 +223,7 @@ async fn create_default_config(name: String) -> Result<GlobalConfig, String> {
 /// Get list of available profiles in the profiles directory
 #[tauri::command]
 async fn list_profiles(state: tauri::State<'_, AppState>) -> Result<Vec<String>, String> {
-    let settings = state.gui_settings.lock().await;
-    let profiles_dir = settings
-        .profiles_dir()
-        .ok_or_else(|| "CasparCG path not set".to_string())?;
-
-    if !profiles_dir.exists() {
-        return Ok(vec![]);
-    }
-
-    let entries = std::fs::read_dir(&profiles_dir)
-        .map_err(|e| format!("Failed to read profiles directory: {}", e))?;
-
-    let profiles: Vec<String> = entries
-        .filter_map(|e| e.ok())
-        .filter(|e| {
-            e.path()
-                .extension()
-                .map(|ext| ext == "json")
-                .unwrap_or(false)
-        })
-        .filter_map(|e| {
-            e.path()
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .map(|s| s.to_string())
-        })
-        .collect();
-
-    Ok(profiles)
+    state.control().list_profiles().await
 }
 
 // ============================================================================
@@ -160,7 +247,9 @@ async fn get_decklink_info(persistent_id: String) -> Result<DeckLinkDevice, Stri
 async fn set_decklink_label(
     persistent_id: String,
     label: String,
+    state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
+    state.writable()?;
     decklink::set_device_label(&persistent_id, &label).map_err(|e| e.to_string())
 }
 
@@ -169,7 +258,9 @@ async fn set_decklink_label(
 async fn set_decklink_duplex_mode(
     persistent_id: String,
     mode: String,
+    state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
+    state.writable()?;
     let duplex_mode: DuplexMode = mode.parse().map_err(|e: decklink::DeckLinkError| e.to_string())?;
     decklink::set_duplex_mode(&persistent_id, duplex_mode).map_err(|e| e.to_string())
 }
@@ -190,14 +281,23 @@ async fn get_decklink_status(index: u32) -> Result<DeckLinkStatus, String> {
 /// output directly via the DeckLink SDK, bypassing CasparCG's GPU mixer, so the
 /// physical output can be verified even where CasparCG renders black.
 #[tauri::command]
-async fn start_decklink_output_test(index: u32, mode: u32) -> Result<(), String> {
-    decklink::output_test_start(index, mode).map_err(|e| e.to_string())
+async fn start_decklink_output_test(
+    index: u32,
+    mode: u32,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    state.writable()?;
+    decklink::output_test_start(index, mode).map_err(|e| e.to_string())?;
+    let label = if mode == 1 { "SDI key" } else { "SDI fill" };
+    let control = state.control();
+    control.begin_test(TestTarget::Output { device: index }, label, "GUI", None).await;
+    Ok(())
 }
 
 /// Stop a direct SDI output test on a device (1-based index).
 #[tauri::command]
-async fn stop_decklink_output_test(index: u32) -> Result<(), String> {
-    decklink::output_test_stop(index).map_err(|e| e.to_string())
+async fn stop_decklink_output_test(index: u32, state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.control().stop_test(TestTarget::Output { device: index }).await
 }
 
 // ============================================================================
@@ -221,7 +321,7 @@ async fn amcp_connect(
     let mut settings = state.gui_settings.lock().await;
     settings.last_host = Some(host);
     settings.last_port = Some(port);
-    settings.last_server_was_running = true;
+    settings.last_amcp_connected = true;
     let _ = settings.save();
 
     Ok(())
@@ -235,7 +335,7 @@ async fn amcp_disconnect(state: tauri::State<'_, AppState>) -> Result<(), String
 
     // Update GUI settings
     let mut settings = state.gui_settings.lock().await;
-    settings.last_server_was_running = false;
+    settings.last_amcp_connected = false;
     let _ = settings.save();
 
     Ok(())
@@ -277,8 +377,8 @@ async fn amcp_send_command(
     command: String,
     state: tauri::State<'_, AppState>,
 ) -> Result<amcp::AmcpResponse, String> {
-    let client = state.amcp_client.lock().await;
-    client.send_command(&command).await.map_err(|e| e.to_string())
+    state.writable()?;
+    state.control().amcp_command(&command).await
 }
 
 // ============================================================================
@@ -333,12 +433,15 @@ async fn start_test_server(
             )
         })?;
 
-    http_server::start_server(state.test_server.clone(), port, test_dir).await
+    let port = http_server::start_server(state.test_server.clone(), port, test_dir, state.control()).await?;
+    state.launcher.ports.hold(PortRole::TestServer, Protocol::Tcp, port);
+    Ok(port)
 }
 
 /// Stop the test HTTP server
 #[tauri::command]
 async fn stop_test_server(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.launcher.ports.release(PortRole::TestServer);
     http_server::stop_server(state.test_server.clone()).await
 }
 
@@ -348,23 +451,61 @@ async fn get_test_server_url(state: tauri::State<'_, AppState>) -> Result<Option
     Ok(http_server::get_server_url(state.test_server.clone()).await)
 }
 
-/// Start a channel test by loading fill/key identifier patterns
+/// Start a channel test by playing its identification slate and tone
 #[tauri::command]
 async fn test_channel(
     channel: u32,
+    config: GlobalConfig,
     state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
-    // Get the test server URL
-    let server_url = http_server::get_server_url(state.test_server.clone())
-        .await
-        .ok_or_else(|| "Test server is not running. Start it first.".to_string())?;
+    state.writable()?;
+    let control = state.control();
+    let slates = control.channel_slates(&config).await;
+    let audio = control.channel_audio(&config).await;
+    let index = (channel as usize).checked_sub(1);
+    let slate = index.and_then(|i| slates.get(i));
+    let tone = index.and_then(|i| audio.get(i)).and_then(|a| a.as_deref());
+    {
+        let client = state.amcp_client.lock().await;
+        client
+            .start_channel_test(channel, slate, tone)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    control.begin_test(TestTarget::Channel { channel }, "identification", "GUI", None).await;
+    Ok(())
+}
 
-    // Load the test pattern
-    let client = state.amcp_client.lock().await;
-    client
-        .start_channel_test(channel, &server_url)
+/// Write the AV sync loop for the channel's video mode into the media folder
+/// and play it on the channel: a flash frame with a beep on the same frame,
+/// for measuring lip-sync offsets downstream
+#[tauri::command]
+async fn test_av_sync(
+    channel: u32,
+    config: GlobalConfig,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    state.writable()?;
+    let mode = (channel as usize)
+        .checked_sub(1)
+        .and_then(|i| config.caspar.channels.get(i))
+        .map(|c| c.video_mode.clone())
+        .ok_or_else(|| format!("Channel {} is not in the profile", channel))?;
+    let control = state.control();
+    let media = control.media_dir(&config).await?;
+    let ident = config.ident.clone();
+    let clip = tokio::task::spawn_blocking(move || ident::write_av_sync(&mode, &ident, &media))
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())??;
+    {
+        let client = state.amcp_client.lock().await;
+        client
+            .start_av_sync(channel, &clip)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    control.begin_test(TestTarget::Channel { channel }, "AV sync", "GUI", None).await;
+    Ok(())
 }
 
 /// Stop a channel test by clearing the test layers
@@ -373,31 +514,30 @@ async fn stop_channel_test(
     channel: u32,
     state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
-    let client = state.amcp_client.lock().await;
-    client
-        .stop_channel_test(channel)
-        .await
-        .map_err(|e| e.to_string())
+    state.control().stop_test(TestTarget::Channel { channel }).await
 }
 
-/// Test all configured channels
+/// Test every channel of `config`
 #[tauri::command]
 async fn test_all_channels(
-    channel_count: u32,
+    config: GlobalConfig,
     state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
-    // Get the test server URL
-    let server_url = http_server::get_server_url(state.test_server.clone())
-        .await
-        .ok_or_else(|| "Test server is not running. Start it first.".to_string())?;
-
-    // Load test patterns on all channels
-    let client = state.amcp_client.lock().await;
-    for channel in 1..=channel_count {
-        client
-            .start_channel_test(channel, &server_url)
+    state.writable()?;
+    let control = state.control();
+    let slates = control.channel_slates(&config).await;
+    let audio = control.channel_audio(&config).await;
+    for (i, _) in config.caspar.channels.iter().enumerate() {
+        let channel = i as u32 + 1;
+        let tone = audio.get(i).and_then(|a| a.as_deref());
+        state
+            .amcp_client
+            .lock()
+            .await
+            .start_channel_test(channel, slates.get(i), tone)
             .await
             .map_err(|e| format!("Failed to test channel {}: {}", channel, e))?;
+        control.begin_test(TestTarget::Channel { channel }, "identification", "GUI", None).await;
     }
 
     Ok(())
@@ -409,498 +549,310 @@ async fn stop_all_channel_tests(
     channel_count: u32,
     state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
-    let client = state.amcp_client.lock().await;
-    client
-        .stop_all_channel_tests(channel_count)
-        .await
-        .map_err(|e| e.to_string())
+    {
+        let client = state.amcp_client.lock().await;
+        client
+            .stop_all_channel_tests(channel_count)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    let control = state.control();
+    for channel in 1..=channel_count {
+        control.end_test(TestTarget::Channel { channel });
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Test Session Commands
+// ============================================================================
+
+/// Every channel and DeckLink output test that is running
+#[tauri::command]
+async fn get_test_sessions(state: tauri::State<'_, AppState>) -> Result<Vec<TestSession>, String> {
+    Ok(state.tests.list())
+}
+
+/// Stop the test on one channel or output, whoever started it
+#[tauri::command]
+async fn stop_test_session(target: TestTarget, state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.control().stop_test(target).await
+}
+
+/// Stop every test, e.g. when the GUI switches profile
+#[tauri::command]
+async fn release_test_sessions(reason: String, state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.control().release_tests(&reason).await;
+    Ok(())
 }
 
 // ============================================================================
 // CasparCG Server Process Commands
 // ============================================================================
 
-/// Terminate a process and its whole child tree (CEF/scanner subprocesses).
-fn kill_process_tree(pid: u32) {
-    #[cfg(windows)]
-    {
-        use std::os::windows::process::CommandExt;
-        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
-        let _ = std::process::Command::new("taskkill")
-            .args(["/F", "/T", "/PID", &pid.to_string()])
-            .creation_flags(CREATE_NO_WINDOW)
-            .output();
-    }
-    #[cfg(not(windows))]
-    {
-        let _ = std::process::Command::new("kill").arg(pid.to_string()).output();
-    }
+/// Write the active configuration to casparcg.config and launch casparcg.exe
+/// from the configured installation directory, supervised with the profile's
+/// restart policy. `profile` names the saved profile, remembered so the next
+/// launch can start it again.
+#[tauri::command]
+async fn start_caspar_server(
+    config: GlobalConfig,
+    profile: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    state.writable()?;
+    state.control().start_server(config, profile.as_deref()).await
 }
 
-/// Terminate any stray casparcg.exe a previous session left running, so it cannot
-/// keep holding the DeckLink card or the AMCP port.
-fn kill_stale_casparcg() {
-    #[cfg(windows)]
-    {
-        use std::os::windows::process::CommandExt;
-        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
-        let _ = std::process::Command::new("taskkill")
-            .args(["/F", "/T", "/IM", "casparcg.exe"])
-            .creation_flags(CREATE_NO_WINDOW)
-            .output();
-        let _ = std::process::Command::new("taskkill")
-            .args(["/F", "/T", "/IM", "scanner.exe"])
-            .creation_flags(CREATE_NO_WINDOW)
-            .output();
-    }
+/// Stop the launched CasparCG server process.
+#[tauri::command]
+async fn stop_caspar_server(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.writable()?;
+    state.control().stop_server().await
 }
 
-/// Terminate only casparcg.exe (and its CEF children), leaving scanner.exe alone.
-/// Used before a supervised restart: the crashed server's orphaned CEF helpers
-/// can keep holding the DeckLink card, which would make the fresh instance fail
-/// "Could not enable primary video output" — but the media scanner is healthy
-/// and must survive the server bouncing under it.
-fn kill_casparcg_only() {
-    #[cfg(windows)]
-    {
-        use std::os::windows::process::CommandExt;
-        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
-        let _ = std::process::Command::new("taskkill")
-            .args(["/F", "/T", "/IM", "casparcg.exe"])
-            .creation_flags(CREATE_NO_WINDOW)
-            .output();
-    }
+/// Whether the launched CasparCG server process is still running.
+#[tauri::command]
+async fn caspar_server_running(state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.control().server_running().await)
 }
 
-// --- Supervisor policy ---------------------------------------------------------
-// The launcher's second job (after config) is to keep the server and scanner
-// alive deterministically. These constants encode that policy in one place.
-
-/// How often the supervisor checks the server and scanner.
-const SUPERVISOR_TICK: Duration = Duration::from_secs(3);
-/// Pause before relaunching a crashed server, so a hard-failing config cannot
-/// spin the CPU between attempts.
-const SERVER_RESTART_BACKOFF: Duration = Duration::from_secs(2);
-/// CasparCG's documented "please restart me" exit code (see the stock
-/// casparcg_auto_restart.bat, which restarts on ERRORLEVEL >= 5). Codes below
-/// this are a clean or fatal shutdown we must not fight.
-const CASPAR_RESTART_EXIT_CODE: i32 = 5;
-/// Crash-loop guard: at most this many crash-restarts within `CRASH_WINDOW`
-/// before the supervisor gives up. Without it an unrenderable config (e.g. the
-/// AMD GPU mixer that black-screens and crashes) would thrash the machine.
-const MAX_SERVER_CRASHES: usize = 3;
-/// Rolling window over which `MAX_SERVER_CRASHES` is counted.
-const CRASH_WINDOW: Duration = Duration::from_secs(60);
-
-/// Spawn casparcg.exe from `dir`, streaming its console output into the GUI as
-/// `caspar-log` events (no separate console window) — the embedded live log the
-/// classic launcher is built around. The config file is expected to already be
-/// written. Returns the child so the caller can track and supervise it.
-fn spawn_caspar(dir: &std::path::Path, app: &tauri::AppHandle) -> Result<std::process::Child, String> {
-    let exe = dir.join("casparcg.exe");
-    let mut command = std::process::Command::new(&exe);
-    command
-        .current_dir(dir)
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped());
-    #[cfg(windows)]
-    {
-        use std::os::windows::process::CommandExt;
-        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
-        command.creation_flags(CREATE_NO_WINDOW);
-    }
-    let mut child = command
-        .spawn()
-        .map_err(|e| format!("Failed to launch CasparCG: {}", e))?;
-
-    // Blocking reads run on their own threads so the async runtime is never
-    // stalled; they end at EOF when the process exits.
-    for stream in [
-        child.stdout.take().map(StdStream::Out),
-        child.stderr.take().map(StdStream::Err),
-    ]
-    .into_iter()
-    .flatten()
-    {
-        let app = app.clone();
-        std::thread::spawn(move || {
-            use std::io::BufRead;
-            let lines = match stream {
-                StdStream::Out(out) => Box::new(std::io::BufReader::new(out).lines())
-                    as Box<dyn Iterator<Item = std::io::Result<String>>>,
-                StdStream::Err(err) => Box::new(std::io::BufReader::new(err).lines()),
-            };
-            for line in lines.map_while(Result::ok) {
-                let _ = app.emit("caspar-log", line);
-            }
-        });
-    }
+/// The latest step of the start-on-launch countdown, if one ran
+#[tauri::command]
+async fn get_auto_start(state: tauri::State<'_, AppState>) -> Result<Option<AutoStartStatus>, String> {
+    Ok(state.auto_start.status())
+}
 
-    let _ = app.emit("caspar-log", format!("[launcher] started {}", exe.display()));
-    Ok(child)
-}
-
-/// Return to a clean stopped state from inside the supervisor: stop wanting the
-/// server, kill the media scanner, and tell the GUI its endpoint is gone. Used
-/// when the server exits cleanly/fatally or the crash-loop guard trips, so the
-/// machine is not left with an orphaned scanner and a stale endpoint readout.
-async fn supervisor_stand_down(
-    should_run: &Arc<AtomicBool>,
-    scanner_arc: &Arc<Mutex<Option<std::process::Child>>>,
-    app: &tauri::AppHandle,
-) {
-    should_run.store(false, Ordering::Release);
-    if let Some(mut scanner) = scanner_arc.lock().await.take() {
-        kill_process_tree(scanner.id());
-        let _ = scanner.wait();
-    }
-    let _ = app.emit("scanner-endpoint", serde_json::Value::Null);
+/// Keep the server stopped: abort the start-on-launch countdown. Returns
+/// whether one was in progress.
+#[tauri::command]
+async fn cancel_auto_start(state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    state.writable()?;
+    Ok(state.control().cancel_auto_start())
 }
 
-/// Launch the CasparCG media scanner (scanner.exe) from the install directory,
-/// streaming its output into the GUI log prefixed with [scanner]. CasparCG 2.x
-/// queries the scanner over HTTP for CLS/TLS/THUMBNAIL listings and thumbnails,
-/// so a client cannot browse media without it. Returns the child if it launched.
-///
-/// `host`/`port` pin the scanner's HTTP listener. The media-scanner reads nconf
-/// keys with a "__" separator, so `http__host`/`http__port` populate its
-/// `{ http: { host, port } }` config. CasparCG must query this exact endpoint —
-/// `start_caspar_server` writes the matching `<amcp><media-server>` block.
-fn spawn_scanner(
-    dir: &std::path::Path,
-    app: &tauri::AppHandle,
-    host: &str,
-    port: u16,
-) -> Option<std::process::Child> {
-    let exe = dir.join("scanner.exe");
-    if !exe.exists() {
-        return None;
-    }
-    let mut command = std::process::Command::new(&exe);
-    command
-        .current_dir(dir)
-        .env("http__host", host)
-        .env("http__port", port.to_string())
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped());
-    #[cfg(windows)]
-    {
-        use std::os::windows::process::CommandExt;
-        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
-        command.creation_flags(CREATE_NO_WINDOW);
-    }
-    let mut child = command.spawn().ok()?;
-    for stream in [
-        child.stdout.take().map(StdStream::Out),
-        child.stderr.take().map(StdStream::Err),
-    ]
-    .into_iter()
-    .flatten()
-    {
-        let app = app.clone();
-        std::thread::spawn(move || {
-            use std::io::BufRead;
-            let lines = match stream {
-                StdStream::Out(out) => Box::new(std::io::BufReader::new(out).lines())
-                    as Box<dyn Iterator<Item = std::io::Result<String>>>,
-                StdStream::Err(err) => Box::new(std::io::BufReader::new(err).lines()),
-            };
-            for line in lines.map_while(Result::ok) {
-                let _ = app.emit("caspar-log", format!("[scanner] {}", line));
-            }
-        });
-    }
-    Some(child)
+// ============================================================================
+// Log Commands
+// ============================================================================
+
+/// Query the captured console history. Filters by source, minimum level and
+/// text; pages newest-first via `before_seq`, or tails via `after_seq`.
+#[tauri::command]
+async fn get_log_entries(
+    query: Option<LogQuery>,
+    state: tauri::State<'_, AppState>,
+) -> Result<LogPage, String> {
+    Ok(state.launcher.logs.query(&query.unwrap_or_default()))
 }
 
-/// Write the active configuration to casparcg.config and launch casparcg.exe
-/// from the configured installation directory.
+/// Directory holding the launcher's rotating log files, if file logging is on
 #[tauri::command]
-async fn start_caspar_server(
-    mut config: GlobalConfig,
+async fn get_log_dir(state: tauri::State<'_, AppState>) -> Result<Option<String>, String> {
+    Ok(state.launcher.logs.log_dir().map(|d| d.display().to_string()))
+}
+
+// ============================================================================
+// Diagnostics Commands
+// ============================================================================
+
+/// Diagnostics raised since launch (or the last clear), oldest first
+#[tauri::command]
+async fn get_diagnostics(state: tauri::State<'_, AppState>) -> Result<Vec<Diagnostic>, String> {
+    Ok(state.launcher.diagnostics.recent())
+}
+
+#[tauri::command]
+async fn clear_diagnostics(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.launcher.diagnostics.clear();
+    Ok(())
+}
+
+/// Re-read the site rules file so edits apply without restarting the launcher
+#[tauri::command]
+async fn reload_diagnostic_rules(
     state: tauri::State<'_, AppState>,
-    app: tauri::AppHandle,
-) -> Result<(), String> {
-    // Already running? (Reap a process that has since exited.)
-    {
-        let mut proc = state.caspar_process.lock().await;
-        match proc.as_mut().map(|c| c.try_wait()) {
-            Some(Ok(None)) => return Err("CasparCG server is already running".to_string()),
-            Some(_) => *proc = None,
-            None => {}
-        }
-    }
+) -> Result<RuleLoadSummary, String> {
+    Ok(state.launcher.diagnostics.reload())
+}
 
-    // Clean slate: terminate any stray casparcg.exe a previous session left
-    // behind. Otherwise it keeps the DeckLink card and AMCP port, and the new
-    // instance fails with "Could not enable primary video output".
-    kill_stale_casparcg();
-
-    // Resolve the installation directory and executable.
-    let caspar_path = {
-        let settings = state.gui_settings.lock().await;
-        settings
-            .caspar_path
-            .clone()
-            .ok_or_else(|| "CasparCG path is not set — complete setup first".to_string())?
-    };
-    let dir = PathBuf::from(&caspar_path);
-    let exe = dir.join("casparcg.exe");
-    if !exe.exists() {
-        return Err(format!("casparcg.exe not found in {}", dir.display()));
-    }
+// ============================================================================
+// Resource Commands
+// ============================================================================
 
-    // Pin the media scanner to a free loopback port and point CasparCG at it.
-    // CasparCG proxies CLS/TLS/THUMBNAIL to the scanner over HTTP; the stock port
-    // 8000 routinely clashes with another local web service on a shared box, and
-    // then the scanner cannot bind it and every listing fails with "Invalid
-    // Response". Resolve the port now so the written config and the spawned
-    // scanner always agree on the same endpoint.
-    let scanner_host = system::scanner::HOST.to_string();
-    let scanner_port = system::scanner::pick_port();
-    config.caspar.amcp.media_server = Some(MediaServer {
-        host: scanner_host.clone(),
-        port: scanner_port,
-    });
-
-    // Write the active configuration so the server starts with what is shown.
-    let xml = generate_caspar_xml(&config.caspar)
-        .map_err(|e| format!("Failed to generate config: {}", e))?;
-    std::fs::write(dir.join("casparcg.config"), xml)
-        .map_err(|e| format!("Failed to write casparcg.config: {}", e))?;
-
-    // Mark the server as wanted before launching so the supervisor (below) keeps
-    // it alive; Stop clears this, so a deliberate stop is never read as a crash.
-    state.server_should_run.store(true, Ordering::Release);
-
-    // Launch casparcg.exe with its console streamed into the embedded GUI log.
-    let child = match spawn_caspar(&dir, &app) {
-        Ok(child) => child,
-        Err(e) => {
-            state.server_should_run.store(false, Ordering::Release);
-            return Err(e);
-        }
-    };
-    *state.caspar_process.lock().await = Some(child);
-
-    // Launch the media scanner alongside the server so a connected client can
-    // list media/templates and fetch thumbnails (CLS/TLS/THUMBNAIL). Surface the
-    // resolved endpoint both as a log line and as a structured `scanner-endpoint`
-    // event so the GUI can show which port it landed on — on a busy box this is
-    // not the stock 8000, and that fact is needed to make sense of the listings.
-    if let Some(scanner) = spawn_scanner(&dir, &app, &scanner_host, scanner_port) {
-        let on_preferred = scanner_port == system::scanner::PREFERRED_PORT;
-        let msg = if on_preferred {
-            format!("[launcher] media scanner on {scanner_host}:{scanner_port}")
-        } else {
-            format!(
-                "[launcher] preferred port {} busy — media scanner on {scanner_host}:{scanner_port} instead",
-                system::scanner::PREFERRED_PORT
-            )
-        };
-        let _ = app.emit("caspar-log", msg);
-        let _ = app.emit(
-            "scanner-endpoint",
-            serde_json::json!({
-                "host": scanner_host,
-                "port": scanner_port,
-                "isDefault": on_preferred,
-            }),
-        );
-        *state.scanner_process.lock().await = Some(scanner);
-    } else {
-        let _ = app.emit(
-            "caspar-log",
-            "[launcher] scanner.exe not found — media listing will be unavailable".to_string(),
-        );
-    }
+/// CPU, memory, thread and handle samples of the server and scanner trees,
+/// taken after `since` (all of them without it)
+#[tauri::command]
+async fn get_resource_history(
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    state: tauri::State<'_, AppState>,
+) -> Result<resources::ResourceSeries, String> {
+    Ok(state.control().resources(since))
+}
 
-    // Supervisor: keep both the server and the scanner alive while the server is
-    // wanted. The scanner is safe to relaunch freely. The server is restarted on
-    // a crash or its restart-request exit code, but a crash-loop guard stops it
-    // thrashing on a config the machine cannot render, and a clean/fatal exit is
-    // left to stand. A user Stop clears `server_should_run`, so a deliberate stop
-    // is never mistaken for a crash.
-    {
-        let scanner_arc = state.scanner_process.clone();
-        let caspar_arc = state.caspar_process.clone();
-        let should_run = state.server_should_run.clone();
-        let app2 = app.clone();
-        let dir2 = dir.clone();
-        let scanner_host2 = scanner_host.clone();
-        tauri::async_runtime::spawn(async move {
-            let mut crashes: Vec<Instant> = Vec::new();
-            loop {
-                tokio::time::sleep(SUPERVISOR_TICK).await;
-                if !should_run.load(Ordering::Acquire) {
-                    break; // deliberate Stop / app exit — teardown is the caller's job
-                }
+// ============================================================================
+// Port Commands
+// ============================================================================
 
-                // --- Server: detect an exit and decide whether to restart. ---
-                let exit_code: Option<Option<i32>> = {
-                    let mut cp = caspar_arc.lock().await;
-                    match cp.as_mut().map(|c| c.try_wait()) {
-                        Some(Ok(Some(status))) => {
-                            *cp = None; // reaped — we own the restart from here
-                            Some(status.code())
-                        }
-                        Some(Ok(None)) => None, // still running
-                        Some(Err(_)) => {
-                            *cp = None;
-                            Some(None) // wait failed — treat as gone, code unknown
-                        }
-                        None => Some(None), // no handle — gone
-                    }
-                };
+/// Probe every port starting `config` would use, as Start's pre-flight does,
+/// without starting anything
+#[tauri::command]
+async fn check_ports(
+    config: GlobalConfig,
+    state: tauri::State<'_, AppState>,
+) -> Result<PortPreflight, String> {
+    Ok(state.launcher.ports.check(&config, &SystemProber))
+}
 
-                if let Some(code) = exit_code {
-                    // A Stop may have raced in while we looked — re-check intent.
-                    if !should_run.load(Ordering::Acquire) {
-                        break;
-                    }
+/// The ports the latest Start settled on, reassignments included
+#[tauri::command]
+async fn get_port_preflight(
+    state: tauri::State<'_, AppState>,
+) -> Result<Option<PortPreflight>, String> {
+    Ok(state.launcher.ports.last_preflight())
+}
 
-                    // Codes below 5 are a clean or fatal shutdown (e.g. a config
-                    // CasparCG refuses); restarting would only fail the same way.
-                    let restart = !matches!(code, Some(c) if c < CASPAR_RESTART_EXIT_CODE);
-                    if !restart {
-                        let shown = code.map(|c| c.to_string()).unwrap_or_else(|| "?".into());
-                        let _ = app2.emit(
-                            "caspar-log",
-                            format!("[launcher] CasparCG exited (code {shown}) — not restarting"),
-                        );
-                        supervisor_stand_down(&should_run, &scanner_arc, &app2).await;
-                        break;
-                    }
+// ============================================================================
+// Media Library Commands
+// ============================================================================
 
-                    // Code 5 is a deliberate restart request, not a crash; only
-                    // genuine crashes count against the loop guard.
-                    let is_crash = !matches!(code, Some(CASPAR_RESTART_EXIT_CODE));
-                    if is_crash {
-                        let now = Instant::now();
-                        crashes.retain(|t| now.duration_since(*t) < CRASH_WINDOW);
-                        crashes.push(now);
-                        if crashes.len() > MAX_SERVER_CRASHES {
-                            let _ = app2.emit(
-                                "caspar-log",
-                                format!(
-                                    "[launcher] CasparCG crashed {} times in {}s — giving up. Check the GPU/config, then press Start.",
-                                    crashes.len(),
-                                    CRASH_WINDOW.as_secs()
-                                ),
-                            );
-                            supervisor_stand_down(&should_run, &scanner_arc, &app2).await;
-                            break;
-                        }
-                    }
+/// The scanner the launcher started, or an error when none is running
+fn scanner_endpoint(state: &AppState) -> Result<scanner::ScannerEndpoint, String> {
+    state
+        .launcher
+        .scanner_endpoint()
+        .ok_or_else(|| "The media scanner is not running".to_string())
+}
 
-                    let reason = match code {
-                        Some(CASPAR_RESTART_EXIT_CODE) => "requested a restart".to_string(),
-                        Some(c) => format!("crashed (code {c})"),
-                        None => "stopped unexpectedly".to_string(),
-                    };
-                    let _ = app2.emit("caspar-log", format!("[launcher] CasparCG {reason} — restarting…"));
+/// Clips matching `query`, from the last listing of the scanner
+#[tauri::command]
+async fn list_media(
+    query: Option<MediaQuery>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<LibraryMedia>, String> {
+    Ok(state.media_library.media(&query.unwrap_or_default()))
+}
 
-                    tokio::time::sleep(SERVER_RESTART_BACKOFF).await;
-                    if !should_run.load(Ordering::Acquire) {
-                        break;
-                    }
-                    // Clear orphaned CEF children that may still hold the card —
-                    // but not the healthy scanner running under it.
-                    kill_casparcg_only();
-                    match spawn_caspar(&dir2, &app2) {
-                        Ok(mut child) => {
-                            // A Stop may have raced in during launch — if the
-                            // server is no longer wanted, do not leave an orphan
-                            // casparcg.exe holding the card.
-                            if !should_run.load(Ordering::Acquire) {
-                                kill_process_tree(child.id());
-                                let _ = child.wait();
-                                break;
-                            }
-                            *caspar_arc.lock().await = Some(child);
-                        }
-                        Err(e) => {
-                            let _ = app2.emit("caspar-log", format!("[launcher] restart failed: {e}"));
-                            supervisor_stand_down(&should_run, &scanner_arc, &app2).await;
-                            break;
-                        }
-                    }
-                    continue; // the scanner is checked on the next tick
-                }
+/// Templates whose name contains every word of `search`
+#[tauri::command]
+async fn list_templates(
+    search: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<scanner::TemplateItem>, String> {
+    Ok(state.media_library.templates(search.as_deref()))
+}
 
-                // --- Scanner: relaunch on the same port if it has died. ---
-                let mut sp = scanner_arc.lock().await;
-                let scanner_dead = !matches!(sp.as_mut().map(|c| c.try_wait()), Some(Ok(None)));
-                if scanner_dead {
-                    if let Some(child) = spawn_scanner(&dir2, &app2, &scanner_host2, scanner_port) {
-                        let _ = app2.emit(
-                            "caspar-log",
-                            format!(
-                                "[launcher] media scanner restarted on {scanner_host2}:{scanner_port}"
-                            ),
-                        );
-                        *sp = Some(child);
-                    }
-                }
-            }
-        });
+/// Fonts whose name contains every word of `search`
+#[tauri::command]
+async fn list_fonts(
+    search: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<scanner::FontItem>, String> {
+    Ok(state.media_library.fonts(search.as_deref()))
+}
+
+/// Ask the scanner for its listings now instead of at the next poll
+#[tauri::command]
+async fn refresh_media_library(state: tauri::State<'_, AppState>) -> Result<LibraryChanges, String> {
+    let endpoint = scanner_endpoint(&state)?;
+    let changes = state.media_library.refresh(&endpoint).await?;
+    if !changes.is_empty() {
+        publish_library_changes(&state.events, &changes);
     }
+    Ok(changes)
+}
 
-    Ok(())
+#[tauri::command]
+async fn get_media_library_status(state: tauri::State<'_, AppState>) -> Result<LibraryStatus, String> {
+    Ok(state.media_library.status())
 }
 
-/// Helper to thread either child stream through one spawn loop.
-enum StdStream {
-    Out(std::process::ChildStdout),
-    Err(std::process::ChildStderr),
+/// A clip's thumbnail as a PNG data URL, or None while the scanner has none
+#[tauri::command]
+async fn get_media_thumbnail(
+    name: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Option<String>, String> {
+    use base64::Engine;
+    let endpoint = scanner_endpoint(&state)?;
+    let png = state.media_library.thumbnail(&endpoint, &name).await?;
+    Ok(png.map(|png| {
+        format!(
+            "data:image/png;base64,{}",
+            base64::engine::general_purpose::STANDARD.encode(png)
+        )
+    }))
 }
 
-/// Stop the launched CasparCG server process.
+/// Media whose frame rate or raster differs from each channel of `config`
 #[tauri::command]
-async fn stop_caspar_server(
+async fn check_media_compatibility(
+    config: GlobalConfig,
     state: tauri::State<'_, AppState>,
-    app: tauri::AppHandle,
-) -> Result<(), String> {
-    // Clear the desired-state flag first so the supervisor sees a deliberate stop
-    // and never restarts the server we are about to kill.
-    state.server_should_run.store(false, Ordering::Release);
-
-    // Take the server handle so the supervisor sees it gone too.
-    let server = state.caspar_process.lock().await.take();
-
-    // Stop the media scanner alongside the server, and tell the GUI its endpoint
-    // is gone so the panel does not imply a live scanner.
-    if let Some(mut scanner) = state.scanner_process.lock().await.take() {
-        kill_process_tree(scanner.id());
-        let _ = scanner.wait();
-    }
-    let _ = app.emit("scanner-endpoint", serde_json::Value::Null);
-
-    if let Some(mut child) = server {
-        // Kill the whole tree, not just the direct child — CasparCG spawns CEF
-        // subprocesses that would otherwise survive and keep holding the card.
-        kill_process_tree(child.id());
-        let _ = child.wait();
-        Ok(())
-    } else {
-        Err("CasparCG server is not running".to_string())
-    }
+) -> Result<Vec<ChannelCompatibility>, String> {
+    let media = state.media_library.media(&MediaQuery::default());
+    Ok(library::check_compatibility(&config.caspar.channels, &media))
 }
 
-/// Whether the launched CasparCG server process is still running.
+/// Write the compatibility report for `config` as CSV into the reports
+/// directory. Returns the path of the written file.
 #[tauri::command]
-async fn caspar_server_running(state: tauri::State<'_, AppState>) -> Result<bool, String> {
-    let mut proc = state.caspar_process.lock().await;
-    match proc.as_mut().map(|c| c.try_wait()) {
-        Some(Ok(None)) => Ok(true),
-        Some(_) => {
-            *proc = None;
-            Ok(false)
-        }
-        None => Ok(false),
+async fn export_media_compatibility(
+    config: GlobalConfig,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    let media = state.media_library.media(&MediaQuery::default());
+    let report = library::check_compatibility(&config.caspar.channels, &media);
+    let dir = GuiSettings::reports_dir()
+        .ok_or_else(|| "Could not determine config directory".to_string())?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!(
+        "media-compatibility-{}.csv",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    std::fs::write(&path, library::compatibility_csv(&report))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+const MEDIA_LIBRARY_TOPIC: &str = "media-library";
+
+fn publish_library_changes(events: &EventBus, changes: &LibraryChanges) {
+    if let Ok(payload) = serde_json::to_value(changes) {
+        events.publish(MEDIA_LIBRARY_TOPIC, payload);
     }
 }
 
+// ============================================================================
+// Crash Report Commands
+// ============================================================================
+
+/// Assemble a crash report now and save it to the crash-reports directory.
+/// `config` is the profile to include — the one the GUI has loaded. Returns the
+/// path of the written zip.
+#[tauri::command]
+async fn create_crash_report(
+    config: Option<GlobalConfig>,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    let dir = state.gui_settings.lock().await.caspar_path.clone().map(PathBuf::from);
+    let report = state
+        .launcher
+        .collect_crash_report("requested from the GUI".to_string(), config, dir)
+        .await;
+    state
+        .launcher
+        .save_crash_report(report)
+        .await
+        .map(|p| p.to_string_lossy().into_owned())
+}
+
+/// Directory crash reports are written to
+#[tauri::command]
+async fn get_crash_report_dir() -> Result<Option<String>, String> {
+    Ok(GuiSettings::crash_reports_dir().map(|p| p.to_string_lossy().into_owned()))
+}
+
 // ============================================================================
 // System Info Commands
 // ============================================================================
@@ -911,10 +863,10 @@ async fn get_ndi_version() -> Result<Option<String>, String> {
     Ok(system::ndi::get_ndi_version())
 }
 
-/// Get Media Scanner version
+/// Get the version of the media scanner the launcher started
 #[tauri::command]
-async fn get_scanner_version(url: Option<String>) -> Result<Option<String>, String> {
-    Ok(system::scanner::get_scanner_version(url.as_deref()).await)
+async fn get_scanner_version(state: tauri::State<'_, AppState>) -> Result<Option<String>, String> {
+    Ok(scanner::get_scanner_version(state.launcher.scanner_endpoint()).await)
 }
 
 /// Get this host's primary IPv4 — the address a remote operator's client connects
@@ -927,17 +879,7 @@ async fn get_primary_ip() -> Result<Option<String>, String> {
 /// Get all system version information
 #[tauri::command]
 async fn get_system_versions(state: tauri::State<'_, AppState>) -> Result<system::SystemVersions, String> {
-    let mut versions = system::collect_system_info().await;
-
-    // Try to get CasparCG version if connected
-    let client = state.amcp_client.lock().await;
-    if client.is_connected() {
-        if let Ok(version) = client.version().await {
-            versions.caspar_version = Some(version);
-        }
-    }
-
-    Ok(versions)
+    Ok(state.control().system_versions().await)
 }
 
 // ============================================================================
@@ -951,14 +893,25 @@ async fn get_gui_settings(state: tauri::State<'_, AppState>) -> Result<GuiSettin
     Ok(settings.clone())
 }
 
-/// Save GUI settings
+/// Save GUI settings. The active profile, the running flags and the API,
+/// metrics and template dev settings are kept as they are: the backend owns
+/// those, and the GUI's copy may be stale.
 #[tauri::command]
 async fn save_gui_settings(
     settings: GuiSettings,
     state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
+    state.writable()?;
     let mut current = state.gui_settings.lock().await;
-    *current = settings;
+    *current = GuiSettings {
+        last_profile: current.last_profile.clone(),
+        last_server_was_running: current.last_server_was_running,
+        last_amcp_connected: current.last_amcp_connected,
+        api: current.api.clone(),
+        metrics: current.metrics.clone(),
+        template_dev: current.template_dev.clone(),
+        ..settings
+    };
     current.save().map_err(|e| format!("Failed to save settings: {}", e))
 }
 
@@ -968,6 +921,7 @@ async fn set_caspar_path(
     path: String,
     state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
+    state.writable()?;
     let mut settings = state.gui_settings.lock().await;
     settings.caspar_path = Some(path.clone());
     settings.save().map_err(|e| format!("Failed to save settings: {}", e))?;
@@ -982,6 +936,458 @@ async fn set_caspar_path(
     Ok(())
 }
 
+// ============================================================================
+// Control API Commands
+// ============================================================================
+
+async fn api_status(state: &AppState) -> api::ApiStatus {
+    api::ApiStatus {
+        settings: state.gui_settings.lock().await.api.clone(),
+        address: state.api_server.read().await.addr().map(|a| a.to_string()),
+    }
+}
+
+/// Start the control API if the settings enable it. Called at launch and
+/// whenever the API settings change.
+async fn apply_api_settings(state: &AppState) -> Result<(), String> {
+    api::stop_api(state.api_server.clone()).await;
+    state.launcher.ports.release(PortRole::ControlApi);
+    let settings = state.gui_settings.lock().await.api.clone();
+    if !settings.enabled {
+        return Ok(());
+    }
+    let addr = api::start_api(state.api_server.clone(), &settings, state.control()).await?;
+    state
+        .launcher
+        .ports
+        .hold(PortRole::ControlApi, Protocol::Tcp, addr.port());
+    state
+        .launcher
+        .log(LogLevel::Info, format!("control API listening on http://{}/api/v1", addr));
+    Ok(())
+}
+
+/// Current control API settings and listening address
+#[tauri::command]
+async fn get_api_status(state: tauri::State<'_, AppState>) -> Result<api::ApiStatus, String> {
+    Ok(api_status(&state).await)
+}
+
+/// Save the control API settings and restart it to match. Enabling without a
+/// token generates one; pass no token to regenerate it.
+#[tauri::command]
+async fn set_api_settings(
+    mut settings: ApiSettings,
+    state: tauri::State<'_, AppState>,
+) -> Result<api::ApiStatus, String> {
+    state.writable()?;
+    if settings.enabled && settings.token.as_deref().unwrap_or("").is_empty() {
+        settings.token = Some(api::generate_token()?);
+    }
+    {
+        let mut current = state.gui_settings.lock().await;
+        current.api = settings;
+        current.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+    }
+    apply_api_settings(&state).await?;
+    Ok(api_status(&state).await)
+}
+
+// ============================================================================
+// Metrics Commands
+// ============================================================================
+
+async fn metrics_status(state: &AppState) -> metrics::MetricsStatus {
+    metrics::MetricsStatus {
+        settings: state.gui_settings.lock().await.metrics.clone(),
+        address: state.metrics_server.read().await.addr().map(|a| a.to_string()),
+    }
+}
+
+/// Start the metrics endpoint if the settings enable it. Called at launch and
+/// whenever the metrics settings change.
+async fn apply_metrics_settings(state: &AppState) -> Result<(), String> {
+    metrics::stop_metrics(state.metrics_server.clone()).await;
+    state.launcher.ports.release(PortRole::Metrics);
+    let settings = state.gui_settings.lock().await.metrics.clone();
+    if !settings.enabled {
+        return Ok(());
+    }
+    let addr =
+        metrics::start_metrics(state.metrics_server.clone(), &settings, state.control()).await?;
+    state
+        .launcher
+        .ports
+        .hold(PortRole::Metrics, Protocol::Tcp, addr.port());
+    state
+        .launcher
+        .log(LogLevel::Info, format!("metrics available at http://{}/metrics", addr));
+    Ok(())
+}
+
+/// Current metrics settings and listening address
+#[tauri::command]
+async fn get_metrics_status(
+    state: tauri::State<'_, AppState>,
+) -> Result<metrics::MetricsStatus, String> {
+    Ok(metrics_status(&state).await)
+}
+
+/// Save the metrics settings and restart the endpoint to match
+#[tauri::command]
+async fn set_metrics_settings(
+    settings: MetricsSettings,
+    state: tauri::State<'_, AppState>,
+) -> Result<metrics::MetricsStatus, String> {
+    state.writable()?;
+    {
+        let mut current = state.gui_settings.lock().await;
+        current.metrics = settings;
+        current.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+    }
+    apply_metrics_settings(&state).await?;
+    Ok(metrics_status(&state).await)
+}
+
+// ============================================================================
+// Template Dev Server Commands
+// ============================================================================
+
+async fn template_dev_status(state: &AppState) -> template_dev::TemplateDevStatus {
+    let settings = state.gui_settings.lock().await.template_dev.clone();
+    let session = state.template_dev.lock().await;
+    template_dev::TemplateDevStatus {
+        settings,
+        address: session.as_ref().map(|s| s.address().to_string()),
+        url: session.as_ref().map(|s| s.url().to_string()),
+    }
+}
+
+/// Current template dev settings, and where the session is serving
+#[tauri::command]
+async fn get_template_dev_status(
+    state: tauri::State<'_, AppState>,
+) -> Result<template_dev::TemplateDevStatus, String> {
+    Ok(template_dev_status(&state).await)
+}
+
+/// Save the settings, then serve and watch the workspace, putting the
+/// template on air. Replaces a running session.
+#[tauri::command]
+async fn start_template_dev(
+    settings: TemplateDevSettings,
+    state: tauri::State<'_, AppState>,
+) -> Result<template_dev::TemplateDevStatus, String> {
+    state.writable()?;
+    {
+        let mut current = state.gui_settings.lock().await;
+        current.template_dev = settings.clone();
+        current.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+    }
+    {
+        let mut session = state.template_dev.lock().await;
+        if let Some(previous) = session.take() {
+            previous.stop().await;
+        }
+        state.launcher.ports.release(PortRole::TemplateDev);
+        let started = template_dev::TemplateDev::start(settings, state.control()).await?;
+        state
+            .launcher
+            .ports
+            .hold(PortRole::TemplateDev, Protocol::Tcp, started.address().port());
+        state
+            .launcher
+            .log(LogLevel::Info, format!("template dev server on {}", started.url()));
+        *session = Some(started);
+    }
+    Ok(template_dev_status(&state).await)
+}
+
+/// Stop serving and watching the workspace. The template stays on air.
+#[tauri::command]
+async fn stop_template_dev(
+    state: tauri::State<'_, AppState>,
+) -> Result<template_dev::TemplateDevStatus, String> {
+    if let Some(session) = state.template_dev.lock().await.take() {
+        session.stop().await;
+    }
+    state.launcher.ports.release(PortRole::TemplateDev);
+    Ok(template_dev_status(&state).await)
+}
+
+/// Save the test data and, while a session runs, send it to the template
+#[tauri::command]
+async fn set_template_dev_data(data: String, state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.writable()?;
+    {
+        let mut current = state.gui_settings.lock().await;
+        current.template_dev.data = data.clone();
+        current.save().map_err(|e| format!("Failed to save settings: {}", e))?;
+    }
+    match state.template_dev.lock().await.as_ref() {
+        Some(session) => session.set_data(data).await,
+        None => Ok(()),
+    }
+}
+
+// ============================================================================
+// Commissioning Commands
+// ============================================================================
+
+/// The commissioning session, if one is open
+#[tauri::command]
+async fn get_commissioning(state: tauri::State<'_, AppState>) -> Result<Option<Commissioning>, String> {
+    Ok(state.commissioning.lock().await.clone())
+}
+
+/// Start walking the active profile's outputs, replacing any open session
+#[tauri::command]
+async fn start_commissioning(
+    operator: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Commissioning, String> {
+    state.writable()?;
+    let control = state.control();
+    let config = control.active_config().await?;
+    commissioning::stop_played(&control).await;
+    let profile = config.name.clone();
+    let session = Commissioning::new(&profile, operator.trim(), config, chrono::Utc::now());
+    session.publish(&control);
+    *state.commissioning.lock().await = Some(session.clone());
+    Ok(session)
+}
+
+/// Play step `index`'s identification on its output
+#[tauri::command]
+async fn play_commissioning_step(
+    index: usize,
+    state: tauri::State<'_, AppState>,
+) -> Result<Commissioning, String> {
+    state.writable()?;
+    let control = state.control();
+    let mut guard = state.commissioning.lock().await;
+    let session = guard.as_mut().ok_or_else(|| "No commissioning session".to_string())?;
+    session.play(index, &control).await?;
+    session.publish(&control);
+    Ok(session.clone())
+}
+
+/// Record the operator's pass, fail or skip and notes for step `index`
+#[tauri::command]
+async fn record_commissioning_step(
+    index: usize,
+    result: CheckResult,
+    notes: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Commissioning, String> {
+    state.writable()?;
+    let mut guard = state.commissioning.lock().await;
+    let session = guard.as_mut().ok_or_else(|| "No commissioning session".to_string())?;
+    session.record(index, result, &notes, chrono::Utc::now())?;
+    session.publish(&state.control());
+    Ok(session.clone())
+}
+
+/// Stop the identification and mark the session finished. It stays open for
+/// the report.
+#[tauri::command]
+async fn finish_commissioning(state: tauri::State<'_, AppState>) -> Result<Commissioning, String> {
+    let control = state.control();
+    let mut guard = state.commissioning.lock().await;
+    let session = guard.as_mut().ok_or_else(|| "No commissioning session".to_string())?;
+    session.finish(&control, chrono::Utc::now()).await;
+    session.publish(&control);
+    Ok(session.clone())
+}
+
+/// Stop the identification and discard the session
+#[tauri::command]
+async fn close_commissioning(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let control = state.control();
+    commissioning::stop_played(&control).await;
+    *state.commissioning.lock().await = None;
+    control.events.publish(commissioning::COMMISSIONING_TOPIC, serde_json::Value::Null);
+    Ok(())
+}
+
+/// Write the session's report, with the hardware inventory and versions, into
+/// the reports directory. Returns the path of the written file.
+#[tauri::command]
+async fn export_commissioning_report(
+    format: ReportFormat,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    let session = state
+        .commissioning
+        .lock()
+        .await
+        .clone()
+        .ok_or_else(|| "No commissioning session".to_string())?;
+    let inventory = Inventory::collect(&state.control()).await;
+    let now = chrono::Utc::now();
+    let report = commissioning::render_report(&session, &inventory, format, now);
+    let dir = GuiSettings::reports_dir()
+        .ok_or_else(|| "Could not determine config directory".to_string())?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!(
+        "commissioning-{}.{}",
+        now.with_timezone(&chrono::Local).format("%Y%m%d-%H%M%S"),
+        format.extension()
+    ));
+    std::fs::write(&path, report).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+// ============================================================================
+// Launch Arguments
+// ============================================================================
+
+/// Topic a forwarded launch's profile file is published on, for the window
+const OPEN_PROFILE_FILE_TOPIC: &str = "open-profile-file";
+
+/// Topic a forwarded launch's saved profile is published on, for the window
+/// to show without making it active
+const SHOW_PROFILE_TOPIC: &str = "show-profile";
+
+/// What the window needs from the command line
+#[derive(serde::Serialize)]
+struct LaunchOptions {
+    read_only: bool,
+    /// A profile file to open unsaved. Handed out once.
+    open_file: Option<String>,
+}
+
+/// A profile named on the command line, once opened
+enum Opened {
+    /// Now the active profile; `restarted` when a running server moved to it
+    Profile { name: String, restarted: bool },
+    /// A file outside the profiles folder, shown in the window unsaved
+    File(Box<GlobalConfig>),
+}
+
+/// The read-only flag, and the profile file this launch named
+#[tauri::command]
+async fn get_launch_options(state: tauri::State<'_, AppState>) -> Result<LaunchOptions, String> {
+    let open_file = state
+        .pending_open
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take();
+    Ok(LaunchOptions {
+        read_only: state.read_only,
+        open_file: open_file.map(|p| p.display().to_string()),
+    })
+}
+
+/// Make a named profile (or a file in the profiles folder) the active one. A
+/// file from elsewhere is loaded for the window to show, and is only saved
+/// into the profiles folder if the user saves it. A forwarded launch only
+/// shows a saved profile: activating it would restart a running server.
+async fn open_launch_profile(
+    state: &AppState,
+    profile: &ProfileArg,
+    forwarded: bool,
+) -> Result<Opened, String> {
+    let control = state.control();
+    let name = match profile {
+        ProfileArg::Name(name) => control.find_profile(name).await?,
+        ProfileArg::File(path) => match control.profile_at(path).await {
+            Some(name) => name,
+            None => {
+                let config = GlobalConfig::load_from_file(path)
+                    .map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+                if forwarded {
+                    state
+                        .events
+                        .publish(OPEN_PROFILE_FILE_TOPIC, serde_json::json!(path.display().to_string()));
+                } else {
+                    *state.pending_open.lock().unwrap_or_else(|e| e.into_inner()) = Some(path.clone());
+                }
+                return Ok(Opened::File(Box::new(config)));
+            }
+        },
+    };
+    if forwarded {
+        state.events.publish(SHOW_PROFILE_TOPIC, serde_json::json!(name));
+        return Ok(Opened::Profile { name, restarted: false });
+    }
+    control
+        .writable()
+        .map_err(|e| format!("{e}: not making '{name}' the active profile"))?;
+    let restarted = control.activate_profile(&name).await?;
+    Ok(Opened::Profile { name, restarted })
+}
+
+/// `--start`: start what the launch opened, or the active profile. A running
+/// server is only restarted for a saved profile other than the one it runs.
+async fn start_launch_profile(state: &AppState, opened: Option<Opened>) {
+    let control = state.control();
+    if let Err(e) = control.writable() {
+        state.launcher.log(LogLevel::Warning, format!("--start ignored: {e}"));
+        return;
+    }
+    if matches!(opened, Some(Opened::Profile { restarted: true, .. })) {
+        return;
+    }
+    if control.server_running().await {
+        let active = control.gui_settings.lock().await.last_profile.clone();
+        match opened {
+            Some(Opened::Profile { name, .. }) if active.as_deref() != Some(name.as_str()) => {
+                if let Err(e) = control.activate_profile(&name).await {
+                    state.launcher.log(LogLevel::Error, format!("--start: {}", e));
+                }
+            }
+            _ => state
+                .launcher
+                .log(LogLevel::Info, "--start: the server is already running"),
+        }
+        return;
+    }
+    let result = match opened {
+        Some(Opened::File(config)) => control.start_server(*config, None).await,
+        Some(Opened::Profile { name, .. }) => control.start_profile(Some(&name)).await.map(drop),
+        None => control.start_profile(None).await.map(drop),
+    };
+    if let Err(e) = result {
+        state.launcher.log(LogLevel::Error, format!("--start: {}", e));
+    }
+}
+
+/// Arguments handed over by a second launch: open its profile, start it if
+/// asked, and bring this window forward.
+async fn apply_forwarded_args(app: &tauri::AppHandle, args: LaunchArgs) {
+    let state = app.state::<AppState>();
+    if args.settings.is_some() || args.read_only {
+        state.launcher.log(
+            LogLevel::Warning,
+            "already running — --settings and --read-only only apply to a fresh launch",
+        );
+    }
+    if let Some(window) = app.get_webview_window("main") {
+        if args.minimized {
+            let _ = window.minimize();
+        } else {
+            let _ = window.unminimize();
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+    }
+    let opened = match &args.profile {
+        Some(profile) => match open_launch_profile(&state, profile, true).await {
+            Ok(opened) => Some(opened),
+            Err(e) => {
+                state.launcher.log(LogLevel::Error, e);
+                return;
+            }
+        },
+        None => None,
+    };
+    if args.start {
+        start_launch_profile(&state, opened).await;
+    }
+}
+
 // ============================================================================
 // File Dialog Commands
 // ============================================================================
@@ -1040,10 +1446,102 @@ async fn pick_save_location(
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let args = match LaunchArgs::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", launch::USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("caspar-server-gui: {e}\n\n{}", launch::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if let Some(path) = &args.settings {
+        GuiSettings::use_settings_path(path.clone());
+    }
+
+    // One GUI per machine: a second launch hands its arguments over and exits,
+    // rather than starting a second supervisor fighting over the same card.
+    let lock = GuiSettings::instance_lock_path();
+    let (instance, instance_error) = match lock.as_deref().map(|lock| launch::claim(lock, &args)) {
+        Some(Ok(Instance::Forwarded)) => return,
+        Some(Ok(Instance::Primary(primary))) => (Some(primary), None),
+        Some(Err(e)) => (None, Some(e.to_string())),
+        None => (None, None),
+    };
+    let lock_port = instance.as_ref().and_then(|primary| primary.port());
+    let forwarded = instance.map(|primary| primary.serve());
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
-        .manage(AppState::default())
+        .setup(move |app| {
+            app.manage(AppState::new(app.handle().clone(), args.read_only));
+            let state = app.state::<AppState>();
+            if let Some(e) = instance_error {
+                state.launcher.log(
+                    LogLevel::Warning,
+                    format!("single-instance check failed ({e}) — another GUI may be running"),
+                );
+            }
+            if args.minimized {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.minimize();
+                }
+            }
+            // Select the profile before the window reads the settings
+            let opened = match &args.profile {
+                Some(profile) => {
+                    tauri::async_runtime::block_on(open_launch_profile(&state, profile, false))
+                        .map(Some)
+                }
+                None => Ok(None),
+            };
+            if let Some(mut rx) = forwarded {
+                let handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    while let Some(args) = rx.recv().await {
+                        apply_forwarded_args(&handle, args).await;
+                    }
+                });
+            }
+            // Keep the media library in step with whichever scanner is running
+            let (library, launcher, events) = (
+                state.media_library.clone(),
+                state.launcher.clone(),
+                state.events.clone(),
+            );
+            tauri::async_runtime::spawn(async move {
+                library
+                    .watch(
+                        || launcher.scanner_endpoint(),
+                        |changes| publish_library_changes(&events, changes),
+                    )
+                    .await
+            });
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let state = handle.state::<AppState>();
+                if let Err(e) = apply_api_settings(&state).await {
+                    state.launcher.log(LogLevel::Error, e);
+                }
+                if let Err(e) = apply_metrics_settings(&state).await {
+                    state.launcher.log(LogLevel::Error, e);
+                }
+                // Start what the command line asked for, or bring the server
+                // back after a reboot or power cut.
+                let control = state.control();
+                tauri::async_runtime::spawn(control.clone().track_stand_downs());
+                tauri::async_runtime::spawn(control.clone().expire_tests());
+                match opened {
+                    Ok(opened) if args.start => start_launch_profile(&state, opened).await,
+                    Ok(_) => control.run_auto_start().await,
+                    Err(e) => state.launcher.log(LogLevel::Error, e),
+                }
+            });
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // Config commands
             load_caspar_config,
@@ -1077,11 +1575,50 @@ pub fn run() {
             test_channel,
             stop_channel_test,
             test_all_channels,
+            test_av_sync,
             stop_all_channel_tests,
+            // Test session commands
+            get_test_sessions,
+            stop_test_session,
+            release_test_sessions,
+            // Commissioning commands
+            get_commissioning,
+            start_commissioning,
+            play_commissioning_step,
+            record_commissioning_step,
+            finish_commissioning,
+            close_commissioning,
+            export_commissioning_report,
             // CasparCG server process commands
             start_caspar_server,
             stop_caspar_server,
             caspar_server_running,
+            get_auto_start,
+            cancel_auto_start,
+            // Log commands
+            get_log_entries,
+            get_log_dir,
+            // Diagnostics commands
+            get_diagnostics,
+            clear_diagnostics,
+            reload_diagnostic_rules,
+            // Resource commands
+            get_resource_history,
+            // Port commands
+            check_ports,
+            get_port_preflight,
+            // Media library commands
+            list_media,
+            list_templates,
+            list_fonts,
+            refresh_media_library,
+            get_media_library_status,
+            get_media_thumbnail,
+            check_media_compatibility,
+            export_media_compatibility,
+            // Crash report commands
+            create_crash_report,
+            get_crash_report_dir,
             // System info commands
             get_ndi_version,
             get_scanner_version,
@@ -1091,6 +1628,18 @@ pub fn run() {
             get_gui_settings,
             save_gui_settings,
             set_caspar_path,
+            // Control API commands
+            get_api_status,
+            set_api_settings,
+            // Metrics commands
+            get_metrics_status,
+            set_metrics_settings,
+            get_template_dev_status,
+            start_template_dev,
+            stop_template_dev,
+            set_template_dev_data,
+            // Launch commands
+            get_launch_options,
             // File dialog commands
             pick_folder,
             pick_config_file,
@@ -1098,35 +1647,25 @@ pub fn run() {
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
-        .run(|app_handle, event| {
+        .run(move |app_handle, event| {
             // When the GUI exits, kill the launched server and its tree so no
             // casparcg.exe is left holding the DeckLink card.
             if let tauri::RunEvent::Exit = event {
-                // Stop any direct SDI output tests (releases the cards' outputs).
+                // Stop every test it knows of, then any direct SDI output test
+                // left over (releases the cards' outputs).
+                let control = app_handle.state::<AppState>().control();
+                tauri::async_runtime::block_on(async {
+                    let release = control.release_tests("app exit");
+                    let _ = tokio::time::timeout(std::time::Duration::from_secs(2), release).await;
+                });
                 decklink::output_test_stop_all();
 
-                let app_state = app_handle.state::<AppState>();
-                // Stop wanting the server so the supervisor cannot race a restart
-                // against shutdown.
-                app_state.server_should_run.store(false, Ordering::Release);
-                let pid = app_state
-                    .caspar_process
-                    .try_lock()
-                    .ok()
-                    .and_then(|mut guard| guard.take())
-                    .map(|child| child.id());
-                if let Some(pid) = pid {
-                    kill_process_tree(pid);
-                }
-                // Also stop the media scanner.
-                let scanner_pid = app_state
-                    .scanner_process
-                    .try_lock()
-                    .ok()
-                    .and_then(|mut guard| guard.take())
-                    .map(|child| child.id());
-                if let Some(pid) = scanner_pid {
-                    kill_process_tree(pid);
+                // Kill the server and scanner trees, and stop wanting the server
+                // so the supervisor cannot race a restart against shutdown.
+                app_handle.state::<AppState>().launcher.kill_now();
+
+                if let Some(lock) = &lock {
+                    launch::release(lock, lock_port);
                 }
             }
         });
@@ -0,0 +1,417 @@
+// Media library
+// The scanner's listings kept in memory and refreshed incrementally
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Serialize;
+
+use super::query::matches_words;
+use super::{MediaQuery, ThumbnailCache};
+use crate::scanner::{
+    FontItem, MediaInfo, MediaItem, MediaKind, ScannerClient, ScannerEndpoint, TemplateItem,
+};
+
+/// How often the watch loop asks the scanner for its listings
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Listings are bigger than a version check: a CLS of tens of thousands of
+/// clips takes the Node scanner a few seconds.
+const LISTING_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// One clip with everything known about it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LibraryMedia {
+    pub name: String,
+    /// The folder part of the name, `""` at the top of the media folder
+    pub folder: String,
+    pub kind: MediaKind,
+    pub size: u64,
+    pub modified: Option<NaiveDateTime>,
+    pub frames: u64,
+    pub duration_secs: Option<f64>,
+    pub frame_rate: Option<f64>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub codec: Option<String>,
+    pub field_order: Option<String>,
+    /// Whether the scanner has made a thumbnail of it yet
+    pub has_thumbnail: bool,
+}
+
+impl LibraryMedia {
+    pub fn new(item: &MediaItem, info: Option<&MediaInfo>) -> Self {
+        let info = info.cloned().unwrap_or_default();
+        Self {
+            name: item.name.clone(),
+            folder: item.name.rsplit_once('/').map(|(f, _)| f.to_string()).unwrap_or_default(),
+            kind: item.kind,
+            size: item.size,
+            modified: item.modified,
+            frames: item.frames,
+            duration_secs: info.duration_secs.or_else(|| item.duration_secs()),
+            frame_rate: info.frame_rate.or_else(|| item.frame_rate()),
+            width: info.width,
+            height: info.height,
+            codec: info.codec,
+            field_order: info.field_order,
+            has_thumbnail: false,
+        }
+    }
+
+    /// Whether the scanner's listing shows the file has changed
+    fn same_file(&self, item: &MediaItem) -> bool {
+        self.size == item.size && self.modified == item.modified && self.kind == item.kind
+    }
+}
+
+/// What one refresh changed, published as `media-library`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LibraryChanges {
+    pub added: Vec<String>,
+    pub changed: Vec<String>,
+    pub removed: Vec<String>,
+    pub templates_changed: bool,
+    pub fonts_changed: bool,
+}
+
+impl LibraryChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.changed.is_empty()
+            && self.removed.is_empty()
+            && !self.templates_changed
+            && !self.fonts_changed
+    }
+}
+
+/// Counts and freshness of the library, for the GUI
+#[derive(Debug, Clone, Serialize)]
+pub struct LibraryStatus {
+    pub media: usize,
+    pub templates: usize,
+    pub fonts: usize,
+    /// When the last refresh succeeded
+    pub refreshed: Option<DateTime<Utc>>,
+    /// The scanner the listings came from
+    pub endpoint: Option<ScannerEndpoint>,
+    /// Why the last refresh failed, until one succeeds
+    pub error: Option<String>,
+}
+
+/// One refresh's worth of scanner replies
+struct Listings {
+    media: Vec<MediaItem>,
+    details: HashMap<String, MediaInfo>,
+    /// Clips the scanner has a thumbnail of
+    thumbnails: HashSet<String>,
+    templates: Vec<TemplateItem>,
+    fonts: Vec<FontItem>,
+}
+
+#[derive(Default)]
+struct Catalog {
+    media: BTreeMap<String, LibraryMedia>,
+    templates: Vec<TemplateItem>,
+    fonts: Vec<FontItem>,
+    refreshed: Option<DateTime<Utc>>,
+    endpoint: Option<ScannerEndpoint>,
+    error: Option<String>,
+}
+
+/// The media, templates and fonts the running scanner knows about. The last
+/// listing is kept while the scanner is down, so the library stays browsable
+/// between runs; `LibraryStatus::refreshed` says how old it is.
+pub struct MediaLibrary {
+    catalog: Mutex<Catalog>,
+    thumbnails: ThumbnailCache,
+    /// One refresh at a time: a manual refresh waits for the watch loop's
+    refreshing: tokio::sync::Mutex<()>,
+}
+
+impl MediaLibrary {
+    /// A library caching thumbnails in `thumbnail_dir`
+    pub fn new(thumbnail_dir: Option<PathBuf>) -> Self {
+        Self {
+            catalog: Mutex::new(Catalog::default()),
+            thumbnails: ThumbnailCache::new(thumbnail_dir),
+            refreshing: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    fn client(endpoint: &ScannerEndpoint) -> ScannerClient {
+        ScannerClient::new(endpoint.clone()).with_timeout(LISTING_TIMEOUT)
+    }
+
+    /// Fetch the listings from the scanner at `endpoint` and merge them in.
+    /// Only new and changed clips get their details fetched.
+    pub async fn refresh(&self, endpoint: &ScannerEndpoint) -> Result<LibraryChanges, String> {
+        let _refreshing = self.refreshing.lock().await;
+        let result = self.fetch(endpoint).await;
+        let mut catalog = self.catalog.lock().unwrap_or_else(|e| e.into_inner());
+        catalog.endpoint = Some(endpoint.clone());
+        match result {
+            Ok(listings) => {
+                let changes = merge(&mut catalog, listings);
+                catalog.refreshed = Some(Utc::now());
+                catalog.error = None;
+                drop(catalog);
+                for name in changes.changed.iter().chain(&changes.removed) {
+                    self.thumbnails.forget(name);
+                }
+                Ok(changes)
+            }
+            Err(e) => {
+                catalog.error = Some(e.clone());
+                Err(e)
+            }
+        }
+    }
+
+    async fn fetch(&self, endpoint: &ScannerEndpoint) -> Result<Listings, String> {
+        let client = Self::client(endpoint);
+        let media = client.media().await.map_err(|e| format!("CLS: {e}"))?;
+        let templates = client.templates().await.map_err(|e| format!("TLS: {e}"))?;
+        let fonts = client.fonts().await.map_err(|e| format!("FLS: {e}"))?;
+        let thumbnails = match client.thumbnails().await {
+            Ok(list) => list.into_iter().map(|t| t.name).collect(),
+            Err(_) => HashSet::new(),
+        };
+
+        // The details come as one document per clip for the whole library, so
+        // they are only fetched when a clip is new or has changed.
+        let stale = {
+            let catalog = self.catalog.lock().unwrap_or_else(|e| e.into_inner());
+            media.iter().any(|item| {
+                !catalog.media.get(&item.name).is_some_and(|known| known.same_file(item))
+            })
+        };
+        let details = if stale {
+            // Details are a bonus: a scanner without them still lists media.
+            client.media_info().await.ok().flatten().unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        Ok(Listings {
+            media,
+            details: details.into_iter().collect(),
+            thumbnails,
+            templates,
+            fonts,
+        })
+    }
+
+    /// The clips matching `query`, in name order
+    pub fn media(&self, query: &MediaQuery) -> Vec<LibraryMedia> {
+        let catalog = self.catalog.lock().unwrap_or_else(|e| e.into_inner());
+        catalog.media.values().filter(|m| query.matches(m)).cloned().collect()
+    }
+
+    /// One clip by its CasparCG name, any case
+    pub fn clip(&self, name: &str) -> Option<LibraryMedia> {
+        let catalog = self.catalog.lock().unwrap_or_else(|e| e.into_inner());
+        catalog.media.get(&name.to_uppercase()).cloned()
+    }
+
+    /// The templates whose name contains every word of `search`
+    pub fn templates(&self, search: Option<&str>) -> Vec<TemplateItem> {
+        let catalog = self.catalog.lock().unwrap_or_else(|e| e.into_inner());
+        catalog
+            .templates
+            .iter()
+            .filter(|t| search.is_none_or(|s| matches_words(s, &[&t.name])))
+            .cloned()
+            .collect()
+    }
+
+    /// The fonts whose name or file contains every word of `search`
+    pub fn fonts(&self, search: Option<&str>) -> Vec<FontItem> {
+        let catalog = self.catalog.lock().unwrap_or_else(|e| e.into_inner());
+        catalog
+            .fonts
+            .iter()
+            .filter(|f| {
+                search.is_none_or(|s| matches_words(s, &[&f.name, f.file.as_deref().unwrap_or_default()]))
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn status(&self) -> LibraryStatus {
+        let catalog = self.catalog.lock().unwrap_or_else(|e| e.into_inner());
+        LibraryStatus {
+            media: catalog.media.len(),
+            templates: catalog.templates.len(),
+            fonts: catalog.fonts.len(),
+            refreshed: catalog.refreshed,
+            endpoint: catalog.endpoint.clone(),
+            error: catalog.error.clone(),
+        }
+    }
+
+    /// The PNG thumbnail of a clip, through the on-disk cache. `Ok(None)` when
+    /// the scanner has not made one yet.
+    pub async fn thumbnail(&self, endpoint: &ScannerEndpoint, name: &str) -> Result<Option<Vec<u8>>, String> {
+        let modified = self.clip(name).and_then(|clip| clip.modified);
+        self.thumbnails
+            .get(&Self::client(endpoint), &name.to_uppercase(), modified)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Refresh from whichever scanner `endpoint` names, every
+    /// `REFRESH_INTERVAL`, and hand each non-empty change to `on_change`.
+    /// Runs for the life of the app.
+    pub async fn watch<E, C>(&self, endpoint: E, on_change: C)
+    where
+        E: Fn() -> Option<ScannerEndpoint>,
+        C: Fn(&LibraryChanges),
+    {
+        loop {
+            if let Some(endpoint) = endpoint() {
+                if let Ok(changes) = self.refresh(&endpoint).await {
+                    if !changes.is_empty() {
+                        on_change(&changes);
+                    }
+                }
+            }
+            tokio::time::sleep(REFRESH_INTERVAL).await;
+        }
+    }
+}
+
+/// Merge fresh listings into the catalog and say what changed
+fn merge(catalog: &mut Catalog, listings: Listings) -> LibraryChanges {
+    let Listings {
+        media,
+        details,
+        thumbnails,
+        templates,
+        fonts,
+    } = listings;
+    let mut changes = LibraryChanges::default();
+    let mut old = std::mem::take(&mut catalog.media);
+    for item in media {
+        let has_thumbnail = thumbnails.contains(&item.name);
+        let mut entry = match old.remove(&item.name) {
+            // A thumbnail the scanner has just made counts as a change
+            Some(known) if known.same_file(&item) => {
+                if known.has_thumbnail != has_thumbnail {
+                    changes.changed.push(item.name.clone());
+                }
+                known
+            }
+            Some(_) => {
+                changes.changed.push(item.name.clone());
+                LibraryMedia::new(&item, details.get(&item.name))
+            }
+            None => {
+                changes.added.push(item.name.clone());
+                LibraryMedia::new(&item, details.get(&item.name))
+            }
+        };
+        entry.has_thumbnail = has_thumbnail;
+        catalog.media.insert(item.name, entry);
+    }
+    changes.removed = old.into_keys().collect();
+    changes.templates_changed = catalog.templates != templates;
+    changes.fonts_changed = catalog.fonts != fonts;
+    catalog.templates = templates;
+    catalog.fonts = fonts;
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::State;
+    use axum::routing::get;
+    use std::sync::Arc;
+
+    /// A scanner whose CLS can be changed between refreshes
+    async fn scanner(cls: Arc<Mutex<String>>) -> ScannerEndpoint {
+        let router = axum::Router::new()
+            .route(
+                "/cls",
+                get(|State(cls): State<Arc<Mutex<String>>>| async move { cls.lock().unwrap().clone() }),
+            )
+            .route("/tls", get(|| async { "200 TLS OK\r\nCG/LT\r\n" }))
+            .route("/fls", get(|| async { "200 FLS OK\r\n" }))
+            .route(
+                "/thumbnail",
+                get(|| async { "200 THUMBNAIL LIST OK\r\n\"AMB\" 20240101T120000 4\r\n" }),
+            )
+            .route(
+                "/media",
+                get(|| async {
+                    r#"[{"_id": "AMB", "mediainfo": {"streams": [{"codec_type": "video",
+                        "codec_name": "h264", "width": 1920, "height": 1080, "avg_frame_rate": "25/1"}]}}]"#
+                }),
+            )
+            .with_state(cls);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        ScannerEndpoint::new("127.0.0.1", port)
+    }
+
+    #[test]
+    fn refresh_reports_only_what_changed() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let cls = Arc::new(Mutex::new(
+                "200 CLS OK\r\n\"AMB\" MOVIE 100 20240101120000 250 1/25\r\n\"GO\" STILL 5 20240101120000 0 0/1\r\n"
+                    .to_string(),
+            ));
+            let endpoint = scanner(cls.clone()).await;
+            let library = MediaLibrary::new(None);
+
+            let first = library.refresh(&endpoint).await.unwrap();
+            assert_eq!(first.added, ["AMB", "GO"]);
+            assert!(first.templates_changed);
+            let amb = library.clip("amb").unwrap();
+            assert_eq!((amb.width, amb.codec.as_deref()), (Some(1920), Some("h264")));
+            assert_eq!(amb.duration_secs, Some(10.0));
+            assert!(amb.has_thumbnail);
+            assert!(!library.clip("GO").unwrap().has_thumbnail);
+
+            assert!(library.refresh(&endpoint).await.unwrap().is_empty());
+
+            *cls.lock().unwrap() =
+                "200 CLS OK\r\n\"AMB\" MOVIE 200 20240102120000 250 1/25\r\n\"NEW/ONE\" MOVIE 1 20240102120000 25 1/25\r\n"
+                    .to_string();
+            let next = library.refresh(&endpoint).await.unwrap();
+            assert_eq!(next.added, ["NEW/ONE"]);
+            assert_eq!(next.changed, ["AMB"]);
+            assert_eq!(next.removed, ["GO"]);
+            assert!(!next.templates_changed);
+
+            let found = library.media(&MediaQuery {
+                folder: Some("new".to_string()),
+                ..Default::default()
+            });
+            assert_eq!(found.len(), 1);
+            assert_eq!(library.templates(Some("lt")).len(), 1);
+            assert_eq!(library.status().media, 2);
+        });
+    }
+
+    #[test]
+    fn a_failed_refresh_keeps_the_last_listing() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let cls = Arc::new(Mutex::new("200 CLS OK\r\n\"AMB\" MOVIE 1 20240101120000 1 1/25\r\n".to_string()));
+            let endpoint = scanner(cls).await;
+            let library = MediaLibrary::new(None);
+            library.refresh(&endpoint).await.unwrap();
+
+            let port = crate::ports::free_port(crate::ports::Protocol::Tcp).unwrap();
+            let gone = ScannerEndpoint::new("127.0.0.1", port);
+            assert!(library.refresh(&gone).await.is_err());
+            let status = library.status();
+            assert_eq!(status.media, 1);
+            assert!(status.error.unwrap().starts_with("CLS"));
+        });
+    }
+}
@@ -0,0 +1,317 @@
+// Media compatibility
+// Which clips do not match a channel's video mode, and how badly
+
+use serde::Serialize;
+
+use super::LibraryMedia;
+use crate::config::{Channel, VideoMode};
+use crate::scanner::MediaKind;
+
+/// Rates whose ratio is this close to a whole number are in cadence
+/// (30000/1001 against 30 is 0.1% out, and is not)
+const RATE_TOLERANCE: f64 = 0.0002;
+
+/// How a clip's motion differs from what the channel outputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimingMismatch {
+    /// Frames are repeated or dropped evenly (25p on 50p, 50p on 25p, 23.98p
+    /// on 1080i59.94 with 2:3 pulldown). Plays smoothly, motion looks different.
+    CadenceCompatible,
+    /// No even cadence: frames are repeated or dropped irregularly and motion
+    /// judders (25p on 59.94) unless the clip is converted
+    NeedsConversion,
+    /// An interlaced clip on a progressive channel: the fields are shown
+    /// woven together and combing shows on motion
+    InterlaceMismatch,
+}
+
+/// How a clip's raster differs from the channel's
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RasterMismatch {
+    /// Smaller than the channel (SD or HD on UHD): scaled up and soft
+    Upscaled,
+    /// Larger than the channel: scaled down
+    Downscaled,
+    /// Same height, different width: stretched or squeezed
+    Reshaped,
+}
+
+/// One clip that does not match a channel
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MediaMismatch {
+    pub name: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub frame_rate: Option<f64>,
+    pub interlaced: bool,
+    pub timing: Option<TimingMismatch>,
+    pub raster: Option<RasterMismatch>,
+    /// The mismatch in words, e.g. `25p on 59.94p: needs frame-rate conversion`
+    pub detail: String,
+}
+
+/// The media that does not match one channel
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelCompatibility {
+    /// 1-based, as in AMCP
+    pub channel: usize,
+    pub video_mode: VideoMode,
+    /// Clips compared against the mode
+    pub checked: usize,
+    /// Clips with no resolution or frame rate known (unprobed), not compared
+    pub unknown: usize,
+    pub mismatches: Vec<MediaMismatch>,
+}
+
+/// Compare every movie and still against every channel's video mode. Audio is
+/// skipped; stills are only compared by raster.
+pub fn check_compatibility(channels: &[Channel], media: &[LibraryMedia]) -> Vec<ChannelCompatibility> {
+    channels
+        .iter()
+        .enumerate()
+        .map(|(i, channel)| {
+            let mode = &channel.video_mode;
+            let mut report = ChannelCompatibility {
+                channel: i + 1,
+                video_mode: mode.clone(),
+                checked: 0,
+                unknown: 0,
+                mismatches: Vec::new(),
+            };
+            for clip in media.iter().filter(|m| m.kind != MediaKind::Audio) {
+                let known = match clip.kind {
+                    MediaKind::Movie => clip.height.is_some() || clip.frame_rate.is_some(),
+                    _ => clip.height.is_some(),
+                };
+                if !known {
+                    report.unknown += 1;
+                    continue;
+                }
+                report.checked += 1;
+                if let Some(mismatch) = compare(clip, mode) {
+                    report.mismatches.push(mismatch);
+                }
+            }
+            report
+        })
+        .collect()
+}
+
+fn compare(clip: &LibraryMedia, mode: &VideoMode) -> Option<MediaMismatch> {
+    let interlaced = clip.field_order.as_deref().is_some_and(|o| o != "progressive");
+    let timing = match (clip.kind, clip.frame_rate) {
+        (MediaKind::Movie, Some(fps)) => timing(fps, interlaced, mode),
+        _ => None,
+    };
+    let raster = match (clip.width, clip.height) {
+        (Some(w), Some(h)) => raster((w, h), mode.resolution()),
+        _ => None,
+    };
+    if timing.is_none() && raster.is_none() {
+        return None;
+    }
+
+    let mut detail = Vec::new();
+    if let (Some(timing), Some(fps)) = (timing, clip.frame_rate) {
+        let what = match timing {
+            TimingMismatch::CadenceCompatible => "cadence-compatible",
+            TimingMismatch::NeedsConversion => "needs frame-rate conversion",
+            TimingMismatch::InterlaceMismatch => "interlace mismatch",
+        };
+        detail.push(format!(
+            "{} on {}: {what}",
+            rate_name(fps, interlaced),
+            rate_name(mode.frame_rate(), mode.interlaced())
+        ));
+    }
+    if let (Some(raster), Some(w), Some(h)) = (raster, clip.width, clip.height) {
+        let (cw, ch) = mode.resolution();
+        let what = match raster {
+            RasterMismatch::Upscaled => "scaled up",
+            RasterMismatch::Downscaled => "scaled down",
+            RasterMismatch::Reshaped => "stretched",
+        };
+        detail.push(format!("{w}x{h} on {cw}x{ch}: {what}"));
+    }
+    Some(MediaMismatch {
+        name: clip.name.clone(),
+        width: clip.width,
+        height: clip.height,
+        frame_rate: clip.frame_rate,
+        interlaced,
+        timing,
+        raster,
+        detail: detail.join("; "),
+    })
+}
+
+/// Classify a clip of `fps` frames a second against `mode`. A progressive
+/// clip plays natively at the channel's frame rate and, on an interlaced
+/// channel, at its field rate too (each frame becomes one field).
+fn timing(fps: f64, interlaced: bool, mode: &VideoMode) -> Option<TimingMismatch> {
+    let frame_rate = mode.frame_rate();
+    let same = |a: f64, b: f64| (a / b - 1.0).abs() < RATE_TOLERANCE;
+    if interlaced {
+        return if !mode.interlaced() {
+            Some(TimingMismatch::InterlaceMismatch)
+        } else if same(fps, frame_rate) {
+            None
+        } else {
+            Some(TimingMismatch::NeedsConversion)
+        };
+    }
+    if same(fps, frame_rate) || (mode.interlaced() && same(fps, frame_rate * 2.0)) {
+        return None;
+    }
+    // The rate pictures leave the channel at: fields on an interlaced one
+    let output = if mode.interlaced() { frame_rate * 2.0 } else { frame_rate };
+    let ratio = if output > fps { output / fps } else { fps / output };
+    let whole = (ratio - ratio.round()).abs() < ratio * RATE_TOLERANCE;
+    let pulldown = output > fps && (ratio - 2.5).abs() < ratio * RATE_TOLERANCE;
+    Some(if whole || pulldown {
+        TimingMismatch::CadenceCompatible
+    } else {
+        TimingMismatch::NeedsConversion
+    })
+}
+
+fn raster(clip: (u32, u32), channel: (u32, u32)) -> Option<RasterMismatch> {
+    if clip == channel {
+        None
+    } else if clip.1 < channel.1 {
+        Some(RasterMismatch::Upscaled)
+    } else if clip.1 > channel.1 {
+        Some(RasterMismatch::Downscaled)
+    } else {
+        Some(RasterMismatch::Reshaped)
+    }
+}
+
+/// `25p`, `59.94p`, `29.97i` (frames, not fields)
+fn rate_name(fps: f64, interlaced: bool) -> String {
+    let rate = format!("{:.2}", fps);
+    let rate = rate.trim_end_matches('0').trim_end_matches('.');
+    format!("{rate}{}", if interlaced { 'i' } else { 'p' })
+}
+
+/// The report as CSV, one row per mismatched clip per channel
+pub fn compatibility_csv(report: &[ChannelCompatibility]) -> String {
+    let mut out = String::from("channel,video_mode,clip,width,height,frame_rate,scan,timing,raster,detail\n");
+    for channel in report {
+        let mode = serde_json::to_value(&channel.video_mode)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        for m in &channel.mismatches {
+            let number = |n: Option<u32>| n.map(|n| n.to_string()).unwrap_or_default();
+            let fields = [
+                channel.channel.to_string(),
+                mode.clone(),
+                m.name.clone(),
+                number(m.width),
+                number(m.height),
+                m.frame_rate.map(|f| format!("{f:.3}")).unwrap_or_default(),
+                if m.interlaced { "interlaced" } else { "progressive" }.to_string(),
+                label(m.timing),
+                label(m.raster),
+                m.detail.clone(),
+            ];
+            let row: Vec<_> = fields.iter().map(|f| csv_field(f)).collect();
+            out.push_str(&row.join(","));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// An enum's serialised name, or empty
+fn label<T: Serialize>(value: Option<T>) -> String {
+    value
+        .and_then(|v| serde_json::to_value(v).ok())
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(name: &str, size: (u32, u32), fps: f64, field_order: &str) -> LibraryMedia {
+        LibraryMedia {
+            name: name.to_string(),
+            folder: String::new(),
+            kind: MediaKind::Movie,
+            size: 0,
+            modified: None,
+            frames: 0,
+            duration_secs: None,
+            frame_rate: Some(fps),
+            width: Some(size.0),
+            height: Some(size.1),
+            codec: None,
+            field_order: Some(field_order.to_string()),
+            has_thumbnail: false,
+        }
+    }
+
+    #[test]
+    fn classifies_frame_rate_and_raster_mismatches() {
+        let ntsc = 60000.0 / 1001.0;
+        let hd = (1920, 1080);
+        let cases = [
+            (VideoMode::P1080_5000, clip("A", hd, 25.0, "progressive"), Some(TimingMismatch::CadenceCompatible)),
+            (VideoMode::P1080_2500, clip("A", hd, 50.0, "progressive"), Some(TimingMismatch::CadenceCompatible)),
+            (VideoMode::P1080_5994, clip("A", hd, 25.0, "progressive"), Some(TimingMismatch::NeedsConversion)),
+            (VideoMode::P1080_2997, clip("A", hd, 30.0, "progressive"), Some(TimingMismatch::NeedsConversion)),
+            (VideoMode::I1080_5994, clip("A", hd, 24000.0 / 1001.0, "progressive"), Some(TimingMismatch::CadenceCompatible)),
+            (VideoMode::P1080_5000, clip("A", hd, 25.0, "tt"), Some(TimingMismatch::InterlaceMismatch)),
+            (VideoMode::I1080_5000, clip("A", hd, 25.0, "tt"), None),
+            (VideoMode::I1080_5000, clip("A", hd, 50.0, "progressive"), None),
+            (VideoMode::I1080_5000, clip("A", hd, 25.0, "progressive"), None),
+            (VideoMode::P1080_5994, clip("A", hd, ntsc, "progressive"), None),
+        ];
+        for (mode, clip, expected) in cases {
+            assert_eq!(
+                timing(clip.frame_rate.unwrap(), clip.field_order.as_deref() == Some("tt"), &mode),
+                expected,
+                "{} fps ({:?}) on {}",
+                clip.frame_rate.unwrap(),
+                clip.field_order,
+                mode.display_name()
+            );
+        }
+
+        let channels = [
+            Channel { video_mode: VideoMode::P2160_5994, consumers: vec![] },
+            Channel { video_mode: VideoMode::P1080_2500, consumers: vec![] },
+        ];
+        let mut unprobed = clip("UNPROBED", hd, 25.0, "progressive");
+        unprobed.width = None;
+        unprobed.height = None;
+        unprobed.frame_rate = None;
+        let media = [clip("NEWS/SD", (720, 576), 25.0, "bb"), clip("HD", hd, 25.0, "progressive"), unprobed];
+        let report = check_compatibility(&channels, &media);
+
+        assert_eq!((report[0].checked, report[0].unknown), (2, 1));
+        let sd = &report[0].mismatches[0];
+        assert_eq!(sd.timing, Some(TimingMismatch::InterlaceMismatch));
+        assert_eq!(sd.raster, Some(RasterMismatch::Upscaled));
+        assert_eq!(sd.detail, "25i on 59.94p: interlace mismatch; 720x576 on 3840x2160: scaled up");
+        assert_eq!(report[0].mismatches[1].timing, Some(TimingMismatch::NeedsConversion));
+        assert_eq!(report[1].mismatches.len(), 1);
+
+        let csv = compatibility_csv(&report);
+        assert_eq!(csv.lines().count(), 4);
+        assert!(csv.contains("\n1,2160p5994,NEWS/SD,720,576,25.000,interlaced,interlace_mismatch,upscaled,25i on 59.94p:"));
+    }
+}
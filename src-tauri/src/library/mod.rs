@@ -0,0 +1,13 @@
+// Media library
+// What the server can play: media, templates and fonts from the scanner, and
+// how the media matches each channel
+
+mod catalog;
+mod compat;
+mod query;
+mod thumbnails;
+
+pub use catalog::*;
+pub use compat::*;
+pub use query::*;
+pub use thumbnails::*;
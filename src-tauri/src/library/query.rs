@@ -0,0 +1,117 @@
+// Library search
+// Search terms and filters over the media, template and font listings
+
+use serde::Deserialize;
+
+use super::LibraryMedia;
+use crate::scanner::MediaKind;
+
+/// Frame rates within this of each other are the same rate (29.97 vs 30000/1001)
+const FRAME_RATE_TOLERANCE: f64 = 0.01;
+
+/// What `list_media` returns. Every field is optional; an empty query lists
+/// everything.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MediaQuery {
+    /// Words that must all appear in the name or codec, any case
+    pub search: Option<String>,
+    pub kind: Option<MediaKind>,
+    /// Only clips in this folder or below, e.g. `CLIPS/NEWS`
+    pub folder: Option<String>,
+    pub codec: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub frame_rate: Option<f64>,
+    pub min_duration_secs: Option<f64>,
+    pub max_duration_secs: Option<f64>,
+}
+
+impl MediaQuery {
+    pub fn matches(&self, media: &LibraryMedia) -> bool {
+        let codec = media.codec.as_deref().unwrap_or_default();
+        if let Some(search) = &self.search {
+            if !matches_words(search, &[&media.name, codec]) {
+                return false;
+            }
+        }
+        if self.kind.is_some_and(|kind| kind != media.kind) {
+            return false;
+        }
+        if let Some(folder) = &self.folder {
+            let folder = folder.trim_matches('/').to_uppercase();
+            let inside = media.folder == folder || media.folder.starts_with(&format!("{folder}/"));
+            if !folder.is_empty() && !inside {
+                return false;
+            }
+        }
+        if self.codec.as_ref().is_some_and(|c| !c.eq_ignore_ascii_case(codec)) {
+            return false;
+        }
+        if self.width.is_some() && self.width != media.width
+            || self.height.is_some() && self.height != media.height
+        {
+            return false;
+        }
+        if let Some(rate) = self.frame_rate {
+            if !media.frame_rate.is_some_and(|r| (r - rate).abs() < FRAME_RATE_TOLERANCE) {
+                return false;
+            }
+        }
+        let duration = media.duration_secs.unwrap_or(0.0);
+        !(self.min_duration_secs.is_some_and(|min| duration < min)
+            || self.max_duration_secs.is_some_and(|max| duration > max))
+    }
+}
+
+/// Whether every whitespace-separated word of `search` appears in one of `fields`
+pub fn matches_words(search: &str, fields: &[&str]) -> bool {
+    let fields: Vec<String> = fields.iter().map(|f| f.to_lowercase()).collect();
+    search
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .all(|word| fields.iter().any(|f| f.contains(&word)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{MediaInfo, MediaItem, TimeBase};
+
+    fn clip(name: &str, width: u32, fps: u32, frames: u64) -> LibraryMedia {
+        let item = MediaItem {
+            name: name.to_string(),
+            kind: MediaKind::Movie,
+            size: 1,
+            modified: None,
+            frames,
+            time_base: Some(TimeBase { num: 1, den: fps }),
+        };
+        let info = MediaInfo {
+            width: Some(width),
+            codec: Some("h264".to_string()),
+            ..Default::default()
+        };
+        LibraryMedia::new(&item, Some(&info))
+    }
+
+    #[test]
+    fn filters_by_words_folder_raster_and_duration() {
+        let news = clip("CLIPS/NEWS/OPENER HD", 1920, 25, 250);
+        let promo = clip("CLIPS/PROMO", 1280, 50, 1500);
+
+        let q = |json: &str| serde_json::from_str::<MediaQuery>(json).unwrap();
+        assert!(q("{}").matches(&news));
+        assert!(q(r#"{"search": "news h264"}"#).matches(&news));
+        assert!(!q(r#"{"search": "news prores"}"#).matches(&news));
+        assert!(q(r#"{"folder": "clips/"}"#).matches(&promo));
+        assert!(q(r#"{"folder": "CLIPS/NEWS"}"#).matches(&news));
+        assert!(!q(r#"{"folder": "CLIPS/NEWS"}"#).matches(&promo));
+        assert!(!q(r#"{"folder": "CLIPS/NEW"}"#).matches(&news));
+        assert!(q(r#"{"width": 1280, "frame_rate": 50.0}"#).matches(&promo));
+        assert!(!q(r#"{"width": 1280}"#).matches(&news));
+        assert!(q(r#"{"max_duration_secs": 10}"#).matches(&news));
+        assert!(!q(r#"{"min_duration_secs": 60}"#).matches(&news));
+        assert!(!q(r#"{"kind": "still"}"#).matches(&news));
+    }
+}
@@ -0,0 +1,127 @@
+// Thumbnail cache
+// Scanner thumbnails kept on disk, keyed by clip name and modification time
+
+use std::path::PathBuf;
+
+use chrono::NaiveDateTime;
+
+use crate::scanner::{ScannerClient, ScannerError};
+
+/// PNG thumbnails fetched from the scanner. A file is named after a hash of
+/// the clip's name and its modification time, so a replaced clip gets a new
+/// thumbnail and the old one is pruned when the new one is fetched.
+#[derive(Debug, Clone)]
+pub struct ThumbnailCache {
+    dir: Option<PathBuf>,
+}
+
+impl ThumbnailCache {
+    /// A cache in `dir`, or none at all (every thumbnail fetched) without one
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        Self { dir }
+    }
+
+    /// The thumbnail of `name` as it was at `modified`: from disk if cached,
+    /// else from the scanner. `Ok(None)` while the scanner has none for it.
+    pub async fn get(
+        &self,
+        client: &ScannerClient,
+        name: &str,
+        modified: Option<NaiveDateTime>,
+    ) -> Result<Option<Vec<u8>>, ScannerError> {
+        let path = self.path(name, modified);
+        if let Some(png) = path.as_ref().and_then(|p| std::fs::read(p).ok()) {
+            return Ok(Some(png));
+        }
+        let png = match client.thumbnail(name).await {
+            Ok(png) => png,
+            Err(ScannerError::Status(404)) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        if let Some(path) = path {
+            // A cache that cannot be written only costs a refetch next time.
+            self.forget(name);
+            if let Some(dir) = path.parent() {
+                let _ = std::fs::create_dir_all(dir);
+            }
+            let partial = path.with_extension("part");
+            if std::fs::write(&partial, &png).is_ok() {
+                let _ = std::fs::rename(&partial, &path);
+            }
+        }
+        Ok(Some(png))
+    }
+
+    /// Remove every cached thumbnail of `name`
+    pub fn forget(&self, name: &str) {
+        let Some(dir) = &self.dir else { return };
+        let prefix = format!("{:016x}-", name_hash(name));
+        let Ok(entries) = std::fs::read_dir(dir) else { return };
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+
+    fn path(&self, name: &str, modified: Option<NaiveDateTime>) -> Option<PathBuf> {
+        let stamp = modified.map_or_else(|| "0".to_string(), |m| m.format("%Y%m%d%H%M%S").to_string());
+        self.dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{:016x}-{stamp}.png", name_hash(name))))
+    }
+}
+
+/// FNV-1a of the upper-cased name: stable across builds, unlike `DefaultHasher`,
+/// so the cache survives an upgrade
+fn name_hash(name: &str) -> u64 {
+    name.to_uppercase().bytes().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::ScannerEndpoint;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn fetches_once_per_modification_time() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let fetches = Arc::new(AtomicUsize::new(0));
+            let counter = fetches.clone();
+            let router = axum::Router::new().route(
+                "/thumbnail/{*name}",
+                axum::routing::get(move || {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    // "PNG!" in base64
+                    async { "201 THUMBNAIL RETRIEVE OK\r\nUE5HIQ==\r\n" }
+                }),
+            );
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+            tokio::spawn(async move { axum::serve(listener, router).await });
+            let client = ScannerClient::new(ScannerEndpoint::new("127.0.0.1", port));
+
+            let dir = std::env::temp_dir().join(format!("caspar-thumbs-{}", std::process::id()));
+            let cache = ThumbnailCache::new(Some(dir.clone()));
+            let t1 = NaiveDateTime::parse_from_str("20240101120000", "%Y%m%d%H%M%S").ok();
+            let t2 = NaiveDateTime::parse_from_str("20240102120000", "%Y%m%d%H%M%S").ok();
+
+            assert_eq!(cache.get(&client, "AMB", t1).await.unwrap().unwrap(), b"PNG!");
+            assert_eq!(cache.get(&client, "amb", t1).await.unwrap().unwrap(), b"PNG!");
+            assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+            // The clip was replaced: fetched again, and the old file pruned
+            cache.get(&client, "AMB", t2).await.unwrap();
+            assert_eq!(fetches.load(Ordering::SeqCst), 2);
+            assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+            cache.forget("AMB");
+            assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+            let _ = std::fs::remove_dir_all(&dir);
+        });
+    }
+}
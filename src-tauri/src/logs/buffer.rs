@@ -0,0 +1,258 @@
+// Bounded in-memory log history
+// A ring buffer of recent entries with filtered, paged queries for the GUI
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::{LogEntry, LogLevel, LogSource};
+
+/// Entries returned by a query when the caller does not ask for a page size.
+const DEFAULT_PAGE_SIZE: usize = 500;
+
+/// Filter and paging parameters for a log query. Every field is optional; an
+/// empty query returns the newest page of everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogQuery {
+    /// Only these sources (all sources when absent or empty)
+    #[serde(default)]
+    pub sources: Option<Vec<LogSource>>,
+    /// Only entries at or above this level
+    #[serde(default)]
+    pub min_level: Option<LogLevel>,
+    /// Case-insensitive substring the text must contain
+    #[serde(default)]
+    pub search: Option<String>,
+    /// Only entries newer than this sequence number (tailing)
+    #[serde(default)]
+    pub after_seq: Option<u64>,
+    /// Only entries older than this sequence number (scrolling back)
+    #[serde(default)]
+    pub before_seq: Option<u64>,
+    /// Maximum entries to return; the newest matches win, or the oldest
+    /// after `after_seq` when tailing
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl LogQuery {
+    fn matches(&self, entry: &LogEntry, needle: Option<&str>) -> bool {
+        if let Some(sources) = &self.sources {
+            if !sources.is_empty() && !sources.contains(&entry.source) {
+                return false;
+            }
+        }
+        if let Some(min) = self.min_level {
+            if entry.level < min {
+                return false;
+            }
+        }
+        if self.after_seq.is_some_and(|after| entry.seq <= after) {
+            return false;
+        }
+        if self.before_seq.is_some_and(|before| entry.seq >= before) {
+            return false;
+        }
+        match needle {
+            Some(needle) => entry.text.to_lowercase().contains(needle),
+            None => true,
+        }
+    }
+}
+
+/// One page of query results, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogPage {
+    pub entries: Vec<LogEntry>,
+    /// Whether older matching entries exist before the first one returned —
+    /// pass that entry's `seq` as `before_seq` to fetch them
+    pub has_more: bool,
+    /// When tailing with `after_seq`: whether newer matching entries exist
+    /// after the last one returned — pass that entry's `seq` as `after_seq`
+    #[serde(default)]
+    pub has_newer: bool,
+    /// Sequence number of the newest entry in the buffer, matching or not
+    pub latest_seq: Option<u64>,
+}
+
+/// Fixed-capacity ring buffer: once full, each new entry evicts the oldest.
+#[derive(Debug)]
+pub struct LogBuffer {
+    entries: VecDeque<LogEntry>,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity.min(4096)),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn push(&mut self, entry: LogEntry) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// The newest `n` entries from `source`, oldest first
+    pub fn tail(&self, source: LogSource, n: usize) -> Vec<LogEntry> {
+        let mut tail: Vec<LogEntry> = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|e| e.source == source)
+            .take(n)
+            .cloned()
+            .collect();
+        tail.reverse();
+        tail
+    }
+
+    pub fn query(&self, query: &LogQuery) -> LogPage {
+        let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+        let needle = query
+            .search
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_lowercase);
+
+        let latest_seq = self.entries.back().map(|e| e.seq);
+
+        // Tailing walks oldest-first from `after_seq`, so nothing just after it
+        // is skipped when more than a page has arrived.
+        if query.after_seq.is_some() {
+            let mut matched = self
+                .entries
+                .iter()
+                .filter(|e| query.matches(e, needle.as_deref()));
+            let entries: Vec<LogEntry> = matched.by_ref().take(limit).cloned().collect();
+            let has_newer = matched.next().is_some();
+            return LogPage {
+                entries,
+                // Nothing after `after_seq` precedes the first one returned
+                has_more: false,
+                has_newer,
+                latest_seq,
+            };
+        }
+
+        // Walk newest-first so the page is the most recent matches, then flip.
+        let mut matched = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|e| query.matches(e, needle.as_deref()));
+        let mut entries: Vec<LogEntry> = matched.by_ref().take(limit).cloned().collect();
+        let has_more = matched.next().is_some();
+        entries.reverse();
+
+        LogPage {
+            entries,
+            has_more,
+            has_newer: false,
+            latest_seq,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn entry(seq: u64, source: LogSource, level: LogLevel, text: &str) -> LogEntry {
+        LogEntry {
+            seq,
+            source,
+            level,
+            timestamp: Utc::now(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn evicts_oldest_when_full() {
+        let mut buffer = LogBuffer::new(3);
+        for seq in 1..=5 {
+            buffer.push(entry(seq, LogSource::Server, LogLevel::Info, "line"));
+        }
+        let page = buffer.query(&LogQuery::default());
+        let seqs: Vec<u64> = page.entries.iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![3, 4, 5]);
+        assert_eq!(page.latest_seq, Some(5));
+    }
+
+    #[test]
+    fn filters_and_pages_newest_first() {
+        let mut buffer = LogBuffer::new(100);
+        for seq in 1..=10 {
+            let level = if seq % 2 == 0 { LogLevel::Error } else { LogLevel::Info };
+            buffer.push(entry(seq, LogSource::Server, level, &format!("message {seq}")));
+        }
+        buffer.push(entry(11, LogSource::Launcher, LogLevel::Error, "giving up"));
+
+        let query = LogQuery {
+            sources: Some(vec![LogSource::Server]),
+            min_level: Some(LogLevel::Error),
+            limit: Some(2),
+            ..LogQuery::default()
+        };
+        let page = buffer.query(&query);
+        let seqs: Vec<u64> = page.entries.iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![8, 10]);
+        assert!(page.has_more);
+
+        // The next page back starts before the oldest entry just returned.
+        let older = buffer.query(&LogQuery {
+            before_seq: Some(8),
+            ..query
+        });
+        let seqs: Vec<u64> = older.entries.iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![4, 6]);
+    }
+
+    // More than a page arrived since the last poll: the tail resumes right
+    // after `after_seq` and says there is more to fetch.
+    #[test]
+    fn tailing_returns_the_oldest_new_entries_first() {
+        let mut buffer = LogBuffer::new(100);
+        for seq in 1..=10 {
+            buffer.push(entry(seq, LogSource::Server, LogLevel::Info, "line"));
+        }
+        let query = LogQuery {
+            after_seq: Some(4),
+            limit: Some(3),
+            ..LogQuery::default()
+        };
+        let page = buffer.query(&query);
+        let seqs: Vec<u64> = page.entries.iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![5, 6, 7]);
+        assert!(page.has_newer);
+        assert!(!page.has_more);
+
+        let rest = buffer.query(&LogQuery {
+            after_seq: Some(7),
+            ..query
+        });
+        let seqs: Vec<u64> = rest.entries.iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![8, 9, 10]);
+        assert!(!rest.has_newer);
+    }
+
+    #[test]
+    fn search_is_case_insensitive() {
+        let mut buffer = LogBuffer::new(10);
+        buffer.push(entry(1, LogSource::Server, LogLevel::Info, "DeckLink consumer ready"));
+        buffer.push(entry(2, LogSource::Server, LogLevel::Info, "OpenGL device"));
+        let page = buffer.query(&LogQuery {
+            search: Some("decklink".to_string()),
+            ..LogQuery::default()
+        });
+        assert_eq!(page.entries.len(), 1);
+        assert_eq!(page.entries[0].seq, 1);
+    }
+}
@@ -0,0 +1,225 @@
+// Log entry types and line parsing
+// Turns raw console lines from each process into structured entries
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Which process produced a log line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogSource {
+    /// casparcg.exe console output
+    Server,
+    /// The media scanner's console output
+    Scanner,
+    /// The launcher's own orchestration messages (start, restart, give-up)
+    Launcher,
+}
+
+impl LogSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogSource::Server => "server",
+            LogSource::Scanner => "scanner",
+            LogSource::Launcher => "launcher",
+        }
+    }
+}
+
+/// Severity of a log entry, ordered from least to most severe. Mirrors the
+/// level tokens CasparCG writes (`[trace]` … `[fatal]`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warning,
+    Error,
+    Fatal,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warning => "warning",
+            LogLevel::Error => "error",
+            LogLevel::Fatal => "fatal",
+        }
+    }
+
+    /// Parse a CasparCG level token. Accepts the short forms spdlog/pino use.
+    pub fn from_token(token: &str) -> Option<Self> {
+        match token.trim().to_lowercase().as_str() {
+            "trace" => Some(LogLevel::Trace),
+            "debug" => Some(LogLevel::Debug),
+            "info" => Some(LogLevel::Info),
+            "warning" | "warn" => Some(LogLevel::Warning),
+            "error" | "err" => Some(LogLevel::Error),
+            "fatal" | "critical" => Some(LogLevel::Fatal),
+            _ => None,
+        }
+    }
+
+    /// Map a pino numeric level (the media scanner's JSON log format).
+    fn from_pino(level: u64) -> Self {
+        match level {
+            0..=10 => LogLevel::Trace,
+            11..=20 => LogLevel::Debug,
+            21..=30 => LogLevel::Info,
+            31..=40 => LogLevel::Warning,
+            41..=50 => LogLevel::Error,
+            _ => LogLevel::Fatal,
+        }
+    }
+}
+
+/// One structured log line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Monotonic sequence number, unique for the lifetime of the launcher
+    pub seq: u64,
+    pub source: LogSource,
+    pub level: LogLevel,
+    /// When the line was logged — the process's own timestamp when it printed
+    /// one, otherwise the time the launcher received it
+    pub timestamp: DateTime<Utc>,
+    /// The message, without the timestamp/level prefix
+    pub text: String,
+}
+
+impl LogEntry {
+    /// The entry as one line of text, as written to the launcher's log files
+    pub fn to_line(&self) -> String {
+        format!(
+            "{} [{}] [{}] {}",
+            self.timestamp.format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            self.source.as_str(),
+            self.level.as_str(),
+            self.text
+        )
+    }
+}
+
+/// A raw line broken into its parts, before it is assigned a sequence number
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedLine {
+    pub level: LogLevel,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub text: String,
+}
+
+/// Parse one console line from `source`. Lines that carry no recognisable
+/// structure are kept verbatim at info level — nothing is ever dropped.
+pub fn parse_line(source: LogSource, raw: &str) -> ParsedLine {
+    let raw = raw.trim_end_matches(['\r', '\n']);
+    let parsed = match source {
+        LogSource::Server => parse_caspar_line(raw),
+        // The scanner logs pino JSON, but falls back to plain text for anything
+        // printed before its logger is up (and for Node's own crash output).
+        LogSource::Scanner => parse_pino_line(raw).or_else(|| parse_caspar_line(raw)),
+        LogSource::Launcher => None,
+    };
+    parsed.unwrap_or_else(|| ParsedLine {
+        level: LogLevel::Info,
+        timestamp: None,
+        text: raw.to_string(),
+    })
+}
+
+/// Parse CasparCG's `[2024-05-01 12:00:00.123] [info]    message` format.
+fn parse_caspar_line(raw: &str) -> Option<ParsedLine> {
+    let rest = raw.strip_prefix('[')?;
+    let (stamp, rest) = rest.split_once(']')?;
+    let rest = rest.trim_start().strip_prefix('[')?;
+    let (level, text) = rest.split_once(']')?;
+    let level = LogLevel::from_token(level)?;
+
+    Some(ParsedLine {
+        level,
+        timestamp: parse_local_timestamp(stamp),
+        text: text.trim().to_string(),
+    })
+}
+
+/// CasparCG stamps lines in local time with millisecond precision.
+fn parse_local_timestamp(stamp: &str) -> Option<DateTime<Utc>> {
+    let naive = NaiveDateTime::parse_from_str(stamp.trim(), "%Y-%m-%d %H:%M:%S%.f").ok()?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|local| local.with_timezone(&Utc))
+}
+
+/// Parse a pino JSON line: `{"level":30,"time":1714560000000,"msg":"…"}`.
+fn parse_pino_line(raw: &str) -> Option<ParsedLine> {
+    if !raw.trim_start().starts_with('{') {
+        return None;
+    }
+    let value: serde_json::Value = serde_json::from_str(raw.trim()).ok()?;
+    let level = value.get("level")?.as_u64().map(LogLevel::from_pino)?;
+    let timestamp = value
+        .get("time")
+        .and_then(|t| t.as_i64())
+        .and_then(DateTime::<Utc>::from_timestamp_millis);
+    let text = match value.get("msg").and_then(|m| m.as_str()) {
+        Some(msg) => {
+            // pino puts error details in `err`; keep the message that matters.
+            match value.pointer("/err/message").and_then(|m| m.as_str()) {
+                Some(err) if !msg.contains(err) => format!("{msg}: {err}"),
+                _ => msg.to_string(),
+            }
+        }
+        None => raw.trim().to_string(),
+    };
+
+    Some(ParsedLine {
+        level,
+        timestamp,
+        text,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_caspar_level_and_text() {
+        let line = "[2024-05-01 12:00:00.123] [warning] Failed to enable external keyer.";
+        let parsed = parse_line(LogSource::Server, line);
+        assert_eq!(parsed.level, LogLevel::Warning);
+        assert_eq!(parsed.text, "Failed to enable external keyer.");
+        assert!(parsed.timestamp.is_some());
+    }
+
+    #[test]
+    fn keeps_unstructured_lines_verbatim() {
+        let parsed = parse_line(LogSource::Server, "Press any key to exit.\r\n");
+        assert_eq!(parsed.level, LogLevel::Info);
+        assert_eq!(parsed.text, "Press any key to exit.");
+        assert!(parsed.timestamp.is_none());
+    }
+
+    #[test]
+    fn parses_scanner_pino_json() {
+        let line = r#"{"level":50,"time":1714560000000,"msg":"listen failed","err":{"message":"EADDRINUSE"}}"#;
+        let parsed = parse_line(LogSource::Scanner, line);
+        assert_eq!(parsed.level, LogLevel::Error);
+        assert_eq!(parsed.text, "listen failed: EADDRINUSE");
+        assert_eq!(
+            parsed.timestamp.map(|t| t.timestamp_millis()),
+            Some(1714560000000)
+        );
+    }
+
+    #[test]
+    fn levels_order_by_severity() {
+        assert!(LogLevel::Trace < LogLevel::Info);
+        assert!(LogLevel::Warning < LogLevel::Error);
+        assert_eq!(LogLevel::from_token("WARN"), Some(LogLevel::Warning));
+    }
+}
@@ -0,0 +1,136 @@
+// Rotating on-disk log
+// Mirrors every entry to <dir>/<stem>.log, rolling over to numbered files by size
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::LogEntry;
+
+/// Roll the active file over once it would grow past this size.
+pub const DEFAULT_MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// Rolled-over files kept besides the active one (`<stem>.1.log` … `<stem>.N.log`).
+pub const DEFAULT_KEEP_FILES: usize = 5;
+
+/// Size-rotated plain-text log file. Write errors are swallowed: losing a line
+/// on disk must never break the live log or the supervisor feeding it.
+#[derive(Debug)]
+pub struct RotatingLogFile {
+    dir: PathBuf,
+    stem: String,
+    max_bytes: u64,
+    keep: usize,
+    file: Option<File>,
+    written: u64,
+}
+
+impl RotatingLogFile {
+    /// Open (or create) `<dir>/<stem>.log`, appending to what is already there.
+    pub fn open(
+        dir: &Path,
+        stem: &str,
+        max_bytes: u64,
+        keep: usize,
+    ) -> std::io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let mut log = Self {
+            dir: dir.to_path_buf(),
+            stem: stem.to_string(),
+            max_bytes: max_bytes.max(1024),
+            keep,
+            file: None,
+            written: 0,
+        };
+        log.reopen()?;
+        Ok(log)
+    }
+
+    /// Path of the file currently being written
+    pub fn active_path(&self) -> PathBuf {
+        self.numbered_path(0)
+    }
+
+    /// Directory holding the active and rolled-over files
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn numbered_path(&self, n: usize) -> PathBuf {
+        if n == 0 {
+            self.dir.join(format!("{}.log", self.stem))
+        } else {
+            self.dir.join(format!("{}.{}.log", self.stem, n))
+        }
+    }
+
+    fn reopen(&mut self) -> std::io::Result<()> {
+        let path = self.active_path();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        self.written = file.metadata().map(|m| m.len()).unwrap_or(0);
+        self.file = Some(file);
+        Ok(())
+    }
+
+    /// Shift `<stem>.log` → `.1.log` → `.2.log` …, dropping the oldest.
+    fn rotate(&mut self) {
+        self.file = None;
+        if self.keep == 0 {
+            let _ = std::fs::remove_file(self.active_path());
+        } else {
+            let _ = std::fs::remove_file(self.numbered_path(self.keep));
+            for n in (0..self.keep).rev() {
+                let from = self.numbered_path(n);
+                if from.exists() {
+                    let _ = std::fs::rename(&from, self.numbered_path(n + 1));
+                }
+            }
+        }
+        let _ = self.reopen();
+    }
+
+    pub fn write_entry(&mut self, entry: &LogEntry) {
+        let line = format!("{}\n", entry.to_line());
+        if self.written > 0 && self.written + line.len() as u64 > self.max_bytes {
+            self.rotate();
+        }
+        if let Some(file) = self.file.as_mut() {
+            if file.write_all(line.as_bytes()).is_ok() {
+                self.written += line.len() as u64;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logs::{LogLevel, LogSource};
+    use chrono::Utc;
+
+    #[test]
+    fn rotates_and_keeps_a_bounded_number_of_files() {
+        let dir = std::env::temp_dir().join(format!("caspar-log-rotate-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut log = RotatingLogFile::open(&dir, "launcher", 1024, 2).unwrap();
+        let entry = LogEntry {
+            seq: 1,
+            source: LogSource::Server,
+            level: LogLevel::Info,
+            timestamp: Utc::now(),
+            text: "x".repeat(200),
+        };
+        for _ in 0..40 {
+            log.write_entry(&entry);
+        }
+
+        assert!(dir.join("launcher.log").exists());
+        assert!(dir.join("launcher.1.log").exists());
+        assert!(dir.join("launcher.2.log").exists());
+        assert!(!dir.join("launcher.3.log").exists());
+        let active = std::fs::metadata(dir.join("launcher.log")).unwrap().len();
+        assert!(active <= 1024);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
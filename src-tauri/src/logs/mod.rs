@@ -0,0 +1,13 @@
+// Structured console log capture
+// Parses server/scanner/launcher output into typed entries, keeps a bounded
+// history the GUI can page through, and mirrors it to rotating files on disk
+
+mod buffer;
+mod entry;
+mod file;
+mod store;
+
+pub use buffer::*;
+pub use entry::*;
+pub use file::*;
+pub use store::*;
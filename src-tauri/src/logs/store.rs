@@ -0,0 +1,124 @@
+// Log store
+// The single entry point every process reader and the launcher log through
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::Utc;
+
+use super::{
+    parse_line, LogBuffer, LogEntry, LogLevel, LogPage, LogQuery, LogSource, RotatingLogFile,
+    DEFAULT_KEEP_FILES, DEFAULT_MAX_FILE_BYTES,
+};
+
+/// Entries kept in memory for the GUI to query. At a few hundred bytes each
+/// this bounds the history to a few megabytes however long the server runs.
+pub const DEFAULT_BUFFER_CAPACITY: usize = 10_000;
+
+/// File stem of the launcher's own rotating log.
+const LOG_FILE_STEM: &str = "caspar-server-gui";
+
+/// Thread-safe log sink: assigns sequence numbers, keeps the ring buffer and
+/// writes the rotating file. Uses a std mutex because the console readers are
+/// plain blocking threads, not async tasks.
+#[derive(Debug)]
+pub struct LogStore {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    buffer: LogBuffer,
+    file: Option<RotatingLogFile>,
+    next_seq: u64,
+}
+
+impl LogStore {
+    pub fn new(capacity: usize, file: Option<RotatingLogFile>) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                buffer: LogBuffer::new(capacity),
+                file,
+                next_seq: 1,
+            }),
+        }
+    }
+
+    /// A store writing to `dir`, or memory-only if the directory is unusable,
+    /// with why file logging is off for the caller to log.
+    pub fn with_log_dir(dir: Option<PathBuf>) -> (Self, Option<String>) {
+        let (file, error) = match dir {
+            Some(dir) => match RotatingLogFile::open(
+                &dir,
+                LOG_FILE_STEM,
+                DEFAULT_MAX_FILE_BYTES,
+                DEFAULT_KEEP_FILES,
+            ) {
+                Ok(file) => (Some(file), None),
+                Err(e) => (
+                    None,
+                    Some(format!("File logging disabled ({}): {}", dir.display(), e)),
+                ),
+            },
+            None => (None, None),
+        };
+        (Self::new(DEFAULT_BUFFER_CAPACITY, file), error)
+    }
+
+    /// Parse and record one raw console line from a process.
+    pub fn record(&self, source: LogSource, raw: &str) -> LogEntry {
+        let parsed = parse_line(source, raw);
+        self.push(source, parsed.level, parsed.timestamp, parsed.text)
+    }
+
+    /// Record a message the launcher generated itself.
+    pub fn launcher(&self, level: LogLevel, text: impl Into<String>) -> LogEntry {
+        self.push(LogSource::Launcher, level, None, text.into())
+    }
+
+    fn push(
+        &self,
+        source: LogSource,
+        level: LogLevel,
+        timestamp: Option<chrono::DateTime<Utc>>,
+        text: String,
+    ) -> LogEntry {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = LogEntry {
+            seq: inner.next_seq,
+            source,
+            level,
+            timestamp: timestamp.unwrap_or_else(Utc::now),
+            text,
+        };
+        inner.next_seq += 1;
+        if let Some(file) = inner.file.as_mut() {
+            file.write_entry(&entry);
+        }
+        inner.buffer.push(entry.clone());
+        entry
+    }
+
+    pub fn query(&self, query: &LogQuery) -> LogPage {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.buffer.query(query)
+    }
+
+    /// The newest `n` lines from one source, oldest first
+    pub fn tail(&self, source: LogSource, n: usize) -> Vec<LogEntry> {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.buffer.tail(source, n)
+    }
+
+    /// Directory the rotating file is written to, if file logging is active
+    pub fn log_dir(&self) -> Option<PathBuf> {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.file.as_ref().map(|f| f.dir().to_path_buf())
+    }
+}
+
+impl Default for LogStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_BUFFER_CAPACITY, None)
+    }
+}
@@ -0,0 +1,404 @@
+// Metrics collection
+// Samples the launcher, AMCP, DeckLink and OSC state for one scrape
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use tokio::sync::Mutex;
+
+use super::Exposition;
+use crate::config::ResourceProcess;
+use crate::control::Control;
+use crate::decklink;
+use crate::resources::ResourceSample;
+use crate::watchdog::{AmcpProbe, FrameTiming};
+
+/// Longest a scrape waits for the AMCP round trip
+const AMCP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Exit reasons that count as crashes (see `ExitRecord::is_crash`)
+const CRASH_REASONS: [&str; 3] = ["crash", "hung", "unknown"];
+
+/// Exit reasons a restart can follow (see `RestartPolicy::exit_reason`)
+const RESTART_REASONS: [&str; 6] = ["crash", "hung", "unknown", "restart_request", "clean", "fatal"];
+
+/// Everything one scrape reports, gathered before any of it is written
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSnapshot {
+    pub server_up: bool,
+    pub supervising: bool,
+    pub uptime_secs: Option<f64>,
+    pub scanner_up: bool,
+    /// Totals of every exit the supervisor has seen, by (action, reason)
+    pub exits: BTreeMap<(String, String), u64>,
+    /// None when the server is down, Some(None) when it did not answer
+    pub amcp_rtt_secs: Option<Option<f64>>,
+    pub decklink: Vec<DeckLinkLock>,
+    /// Per-channel frame timing, when the watchdog listens for OSC
+    pub frames: BTreeMap<u32, FrameTiming>,
+    /// Latest resource sample of each process that is running
+    pub resources: BTreeMap<ResourceProcess, ResourceSample>,
+}
+
+/// Lock state of one DeckLink device
+#[derive(Debug, Clone)]
+pub struct DeckLinkLock {
+    pub index: u32,
+    pub model: String,
+    pub input_locked: bool,
+    pub reference_locked: bool,
+}
+
+/// The `/metrics` collector. Keeps its own AMCP connection between scrapes
+/// (CasparCG logs every new client), reopened when the server's port changes.
+pub struct Metrics {
+    control: Control,
+    probe: Mutex<Option<(u16, AmcpProbe)>>,
+}
+
+impl Metrics {
+    pub fn new(control: Control) -> Self {
+        Self {
+            control,
+            probe: Mutex::new(None),
+        }
+    }
+
+    /// Sample everything and write it in the text exposition format
+    pub async fn scrape(&self) -> String {
+        render(&self.snapshot().await)
+    }
+
+    pub async fn snapshot(&self) -> MetricsSnapshot {
+        let launcher = &self.control.launcher;
+        let server_up = launcher.is_running().await;
+        let amcp_rtt_secs = match (server_up, launcher.amcp_port()) {
+            (true, Some(port)) => Some(self.amcp_round_trip(port).await),
+            _ => None,
+        };
+        let scanner_up = launcher.scanner_running().await;
+        let mut resources = launcher.resources.latest();
+        resources.retain(|process, _| match process {
+            ResourceProcess::Server => server_up,
+            ResourceProcess::Scanner => scanner_up,
+        });
+        MetricsSnapshot {
+            server_up,
+            supervising: launcher.is_supervising(),
+            uptime_secs: launcher.uptime().await.map(|d| d.as_secs_f64()),
+            scanner_up,
+            exits: launcher.exits.totals(),
+            amcp_rtt_secs,
+            // Asking the driver blocks; keep it off the runtime.
+            decklink: tokio::task::spawn_blocking(decklink_locks).await.unwrap_or_default(),
+            frames: launcher
+                .osc_activity()
+                .map(|osc| osc.frame_timing().into_iter().collect())
+                .unwrap_or_default(),
+            resources,
+        }
+    }
+
+    async fn amcp_round_trip(&self, port: u16) -> Option<f64> {
+        let mut probe = self.probe.lock().await;
+        if probe.as_ref().map(|(p, _)| *p) != Some(port) {
+            *probe = Some((port, AmcpProbe::new("127.0.0.1", port, AMCP_TIMEOUT)));
+        }
+        let (_, probe) = probe.as_mut()?;
+        let started = Instant::now();
+        probe.ping().await.ok()?;
+        Some(started.elapsed().as_secs_f64())
+    }
+}
+
+/// Input and reference lock of every device the driver reports. Empty when
+/// the driver is missing or the build has no DeckLink support.
+fn decklink_locks() -> Vec<DeckLinkLock> {
+    let Ok(devices) = decklink::list_devices() else {
+        return Vec::new();
+    };
+    devices
+        .into_iter()
+        .filter_map(|device| {
+            let status = decklink::get_device_status(device.index).ok()?;
+            Some(DeckLinkLock {
+                index: device.index,
+                model: device.model_name,
+                input_locked: status.input_signal_locked,
+                reference_locked: status.reference_signal_locked,
+            })
+        })
+        .collect()
+}
+
+/// Name, type, help and value of each per-channel frame timing metric
+type FrameFamily = (&'static str, &'static str, &'static str, fn(&FrameTiming) -> f64);
+
+const FRAME_FAMILIES: [FrameFamily; 4] = [
+    (
+        "caspar_channel_frames_total",
+        "counter",
+        "Frames the channel's profiler reported over OSC",
+        |t| t.frames as f64,
+    ),
+    (
+        "caspar_channel_late_frames_total",
+        "counter",
+        "Frames that took longer than their slot (dropped or repeated on output)",
+        |t| t.late as f64,
+    ),
+    (
+        "caspar_channel_frame_time_seconds",
+        "gauge",
+        "Time the channel's latest frame took",
+        |t| t.last_secs,
+    ),
+    (
+        "caspar_channel_frame_budget_seconds",
+        "gauge",
+        "Time the channel is allowed per frame",
+        |t| t.budget_secs,
+    ),
+];
+
+/// Name, help and value of each per-process resource metric
+type ResourceFamily = (&'static str, &'static str, fn(&ResourceSample) -> f64);
+
+const RESOURCE_FAMILIES: [ResourceFamily; 4] = [
+    (
+        "caspar_process_cpu_percent",
+        "CPU of the process tree over the last sample interval (100 is one core)",
+        |s| s.cpu_percent,
+    ),
+    (
+        "caspar_process_resident_bytes",
+        "Resident memory of the process tree",
+        |s| s.rss_bytes as f64,
+    ),
+    ("caspar_process_threads", "Threads in the process tree", |s| s.threads as f64),
+    (
+        "caspar_process_handles",
+        "Open handles (Windows) or file descriptors (Linux) of the process tree",
+        |s| s.handles as f64,
+    ),
+];
+
+fn flag(value: bool) -> f64 {
+    if value {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Write a snapshot. Counters with a reason label start every known reason at
+/// zero, so `increase()` sees the first crash rather than a new series.
+pub fn render(snapshot: &MetricsSnapshot) -> String {
+    let mut out = Exposition::default();
+
+    out.family("caspar_server_up", "gauge", "Whether casparcg.exe is running");
+    out.sample("caspar_server_up", &[], flag(snapshot.server_up));
+    out.family(
+        "caspar_supervisor_active",
+        "gauge",
+        "Whether the supervisor is watching the server (0 once it has stood down)",
+    );
+    out.sample("caspar_supervisor_active", &[], flag(snapshot.supervising));
+    out.family(
+        "caspar_server_uptime_seconds",
+        "gauge",
+        "Seconds since casparcg.exe was last launched (0 while down)",
+    );
+    out.sample("caspar_server_uptime_seconds", &[], snapshot.uptime_secs.unwrap_or(0.0));
+    out.family("caspar_scanner_up", "gauge", "Whether the media scanner is running");
+    out.sample("caspar_scanner_up", &[], flag(snapshot.scanner_up));
+
+    let mut restarts: BTreeMap<&str, u64> = RESTART_REASONS.iter().map(|r| (*r, 0)).collect();
+    let mut crashes: BTreeMap<&str, u64> = CRASH_REASONS.iter().map(|r| (*r, 0)).collect();
+    for ((action, reason), count) in &snapshot.exits {
+        if action == "restart" {
+            *restarts.entry(reason).or_insert(0) += count;
+        }
+        if CRASH_REASONS.contains(&reason.as_str()) {
+            *crashes.entry(reason).or_insert(0) += count;
+        }
+    }
+    out.family(
+        "caspar_server_restarts_total",
+        "counter",
+        "Server restarts by the supervisor, by the exit that caused them",
+    );
+    for (reason, count) in &restarts {
+        out.sample("caspar_server_restarts_total", &[("reason", reason)], *count as f64);
+    }
+    out.family(
+        "caspar_server_crashes_total",
+        "counter",
+        "Server crashes, hangs and unexplained exits, whether or not restarted",
+    );
+    for (reason, count) in &crashes {
+        out.sample("caspar_server_crashes_total", &[("reason", reason)], *count as f64);
+    }
+
+    out.family("caspar_amcp_up", "gauge", "Whether the server answered an AMCP VERSION");
+    out.sample("caspar_amcp_up", &[], flag(matches!(snapshot.amcp_rtt_secs, Some(Some(_)))));
+    if let Some(Some(rtt)) = snapshot.amcp_rtt_secs {
+        out.family(
+            "caspar_amcp_round_trip_seconds",
+            "gauge",
+            "AMCP VERSION round-trip time",
+        );
+        out.sample("caspar_amcp_round_trip_seconds", &[], rtt);
+    }
+
+    if !snapshot.decklink.is_empty() {
+        out.family(
+            "decklink_input_locked",
+            "gauge",
+            "Whether the DeckLink device has locked to an input signal",
+        );
+        for d in &snapshot.decklink {
+            let index = d.index.to_string();
+            let labels = [("device", index.as_str()), ("model", d.model.as_str())];
+            out.sample("decklink_input_locked", &labels, flag(d.input_locked));
+        }
+        out.family(
+            "decklink_reference_locked",
+            "gauge",
+            "Whether the DeckLink device has locked to its reference (genlock)",
+        );
+        for d in &snapshot.decklink {
+            let index = d.index.to_string();
+            let labels = [("device", index.as_str()), ("model", d.model.as_str())];
+            out.sample("decklink_reference_locked", &labels, flag(d.reference_locked));
+        }
+    }
+
+    if !snapshot.frames.is_empty() {
+        for (name, kind, help, value) in FRAME_FAMILIES {
+            out.family(name, kind, help);
+            for (channel, timing) in &snapshot.frames {
+                let channel = channel.to_string();
+                out.sample(name, &[("channel", channel.as_str())], value(timing));
+            }
+        }
+    }
+
+    if !snapshot.resources.is_empty() {
+        for (name, help, value) in RESOURCE_FAMILIES {
+            out.family(name, "gauge", help);
+            for (process, sample) in &snapshot.resources {
+                let process = match process {
+                    ResourceProcess::Server => "server",
+                    ResourceProcess::Scanner => "scanner",
+                };
+                out.sample(name, &[("process", process)], value(sample));
+            }
+        }
+    }
+
+    out.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_line<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+        text.lines().find(|l| l.starts_with(prefix))
+    }
+
+    #[test]
+    fn renders_a_running_server() {
+        let mut snapshot = MetricsSnapshot {
+            server_up: true,
+            supervising: true,
+            uptime_secs: Some(42.5),
+            scanner_up: true,
+            amcp_rtt_secs: Some(Some(0.004)),
+            ..Default::default()
+        };
+        snapshot.exits.insert(("restart".into(), "crash".into()), 2);
+        snapshot.exits.insert(("stand".into(), "hung".into()), 1);
+        snapshot.exits.insert(("stand".into(), "clean".into()), 1);
+        snapshot.decklink.push(DeckLinkLock {
+            index: 1,
+            model: "DeckLink Duo 2".into(),
+            input_locked: true,
+            reference_locked: false,
+        });
+        snapshot.frames.insert(
+            1,
+            FrameTiming {
+                frames: 100,
+                late: 3,
+                last_secs: 0.018,
+                budget_secs: 0.02,
+            },
+        );
+
+        snapshot.resources.insert(
+            ResourceProcess::Server,
+            ResourceSample {
+                timestamp: chrono::Utc::now(),
+                pid: 4242,
+                processes: 3,
+                cpu_percent: 85.5,
+                rss_bytes: 2_000_000_000,
+                threads: 120,
+                handles: 900,
+            },
+        );
+
+        let text = render(&snapshot);
+        assert_eq!(sample_line(&text, "caspar_server_up "), Some("caspar_server_up 1"));
+        assert_eq!(
+            sample_line(&text, "caspar_process_resident_bytes"),
+            Some("caspar_process_resident_bytes{process=\"server\"} 2000000000")
+        );
+        assert_eq!(
+            sample_line(&text, "caspar_server_uptime_seconds "),
+            Some("caspar_server_uptime_seconds 42.5")
+        );
+        assert_eq!(
+            sample_line(&text, "caspar_server_restarts_total{reason=\"crash\"}"),
+            Some("caspar_server_restarts_total{reason=\"crash\"} 2")
+        );
+        assert_eq!(
+            sample_line(&text, "caspar_server_crashes_total{reason=\"crash\"}"),
+            Some("caspar_server_crashes_total{reason=\"crash\"} 2")
+        );
+        assert_eq!(
+            sample_line(&text, "caspar_server_crashes_total{reason=\"hung\"}"),
+            Some("caspar_server_crashes_total{reason=\"hung\"} 1")
+        );
+        assert!(sample_line(&text, "caspar_server_crashes_total{reason=\"clean\"}").is_none());
+        assert_eq!(
+            sample_line(&text, "caspar_amcp_round_trip_seconds "),
+            Some("caspar_amcp_round_trip_seconds 0.004")
+        );
+        assert_eq!(
+            sample_line(&text, "decklink_reference_locked"),
+            Some("decklink_reference_locked{device=\"1\",model=\"DeckLink Duo 2\"} 0")
+        );
+        assert_eq!(
+            sample_line(&text, "caspar_channel_late_frames_total"),
+            Some("caspar_channel_late_frames_total{channel=\"1\"} 3")
+        );
+    }
+
+    #[test]
+    fn a_stopped_server_reports_zeroes_and_no_latency() {
+        let text = render(&MetricsSnapshot::default());
+        assert_eq!(sample_line(&text, "caspar_server_up "), Some("caspar_server_up 0"));
+        assert_eq!(sample_line(&text, "caspar_amcp_up "), Some("caspar_amcp_up 0"));
+        assert_eq!(
+            sample_line(&text, "caspar_server_restarts_total{reason=\"hung\"}"),
+            Some("caspar_server_restarts_total{reason=\"hung\"} 0")
+        );
+        assert!(!text.contains("caspar_amcp_round_trip_seconds"));
+        assert!(!text.contains("decklink_input_locked"));
+        assert!(!text.contains("caspar_channel_frames_total"));
+        assert!(!text.contains("caspar_process_cpu_percent"));
+    }
+}
@@ -0,0 +1,84 @@
+// Prometheus text format
+// Writes metric families in the text exposition format (0.0.4)
+
+use std::fmt::Write;
+
+/// Content type Prometheus expects for the text format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// A scrape being written: call `family` once per metric, then `sample` for
+/// each labelled value of it.
+#[derive(Debug, Default)]
+pub struct Exposition {
+    out: String,
+}
+
+impl Exposition {
+    /// `kind` is `gauge` or `counter`
+    pub fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.out, "# HELP {name} {}", help.replace('\\', "\\\\").replace('\n', "\\n"));
+        let _ = writeln!(self.out, "# TYPE {name} {kind}");
+    }
+
+    pub fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.out.push_str(name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(k, v)| format!("{k}=\"{}\"", escape_label(v)))
+                .collect();
+            let _ = write!(self.out, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(self.out, " {}", format_value(value));
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_families_labels_and_escapes() {
+        let mut out = Exposition::default();
+        out.family("caspar_up", "gauge", "Whether casparcg.exe is running");
+        out.sample("caspar_up", &[], 1.0);
+        out.family("caspar_decklink_input_locked", "gauge", "Input lock");
+        out.sample(
+            "caspar_decklink_input_locked",
+            &[("device", "1"), ("model", "DeckLink \"Duo\" 2")],
+            0.0,
+        );
+        out.sample("caspar_amcp_rtt_seconds", &[], 0.0125);
+        assert_eq!(
+            out.finish(),
+            "# HELP caspar_up Whether casparcg.exe is running\n\
+             # TYPE caspar_up gauge\n\
+             caspar_up 1\n\
+             # HELP caspar_decklink_input_locked Input lock\n\
+             # TYPE caspar_decklink_input_locked gauge\n\
+             caspar_decklink_input_locked{device=\"1\",model=\"DeckLink \\\"Duo\\\" 2\"} 0\n\
+             caspar_amcp_rtt_seconds 0.0125\n"
+        );
+    }
+}
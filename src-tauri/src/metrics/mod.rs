@@ -0,0 +1,10 @@
+// Prometheus metrics
+// Exposes server, supervisor, AMCP, DeckLink and channel health for scraping
+
+mod collect;
+mod exposition;
+mod server;
+
+pub use collect::*;
+pub use exposition::*;
+pub use server::*;
@@ -0,0 +1,79 @@
+// Metrics server
+// Serves /metrics on its own listener, apart from the control API
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use serde::Serialize;
+
+use super::{Metrics, CONTENT_TYPE};
+use crate::api::{start_listener, stop_listener, HttpListenerState};
+use crate::config::MetricsSettings;
+use crate::control::Control;
+
+/// The metrics settings plus where the endpoint is actually listening
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsStatus {
+    #[serde(flatten)]
+    pub settings: MetricsSettings,
+    /// Listening address while running
+    pub address: Option<String>,
+}
+
+/// `GET /metrics`. Unauthenticated, as Prometheus scrapers expect — it only
+/// reads, and binds to loopback unless the operator says otherwise.
+pub fn metrics_router(control: Control) -> Router {
+    Router::new()
+        .route("/metrics", get(scrape))
+        .with_state(Arc::new(Metrics::new(control)))
+}
+
+async fn scrape(State(metrics): State<Arc<Metrics>>) -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], metrics.scrape().await)
+}
+
+/// Start the metrics endpoint on `settings.bind`
+pub async fn start_metrics(
+    state: HttpListenerState,
+    settings: &MetricsSettings,
+    control: Control,
+) -> Result<SocketAddr, String> {
+    start_listener(state, &settings.bind, metrics_router(control), "metrics endpoint").await
+}
+
+/// Stop the metrics endpoint if it is running
+pub async fn stop_metrics(state: HttpListenerState) {
+    stop_listener(state).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GuiSettings;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    #[test]
+    fn scrapes_an_idle_launcher() {
+        let control = Control::for_tests(GuiSettings::default());
+        let request = Request::builder().uri("/metrics").body(Body::empty()).unwrap();
+        let (status, content_type, body) = tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let response = metrics_router(control).oneshot(request).await.unwrap();
+            let status = response.status();
+            let content_type = response.headers()[header::CONTENT_TYPE].to_str().unwrap().to_string();
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, content_type, String::from_utf8(bytes.to_vec()).unwrap())
+        });
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, CONTENT_TYPE);
+        assert!(body.contains("# TYPE caspar_server_up gauge\ncaspar_server_up 0\n"));
+        assert!(body.contains("caspar_scanner_up 0\n"));
+        assert!(body.contains("caspar_server_crashes_total{reason=\"crash\"} 0\n"));
+    }
+}
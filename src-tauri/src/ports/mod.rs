@@ -0,0 +1,10 @@
+// Ports
+// Registry of every port the app listens on, and the pre-flight check at launch
+
+mod preflight;
+mod probe;
+mod registry;
+
+pub use preflight::*;
+pub use probe::*;
+pub use registry::*;
@@ -0,0 +1,211 @@
+// Port pre-flight
+// Probes every port before a launch and blocks or reassigns on a clash
+
+use serde::Serialize;
+
+use super::{PortClaim, PortRole, Prober, Protocol};
+use crate::config::PortConflictAction;
+
+/// Fresh ports tried before a reassignment gives up
+const REASSIGN_ATTEMPTS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PortStatus {
+    Free,
+    /// The GUI's own listener, not probed
+    Held,
+    /// Taken, and moved to `port`
+    Reassigned,
+    /// Taken, and the policy refuses to move it
+    Blocked,
+}
+
+/// The outcome for one claim
+#[derive(Debug, Clone, Serialize)]
+pub struct PortCheck {
+    pub role: PortRole,
+    pub protocol: Protocol,
+    /// The port asked for
+    pub requested: u16,
+    /// The port to use: `requested` unless reassigned
+    pub port: u16,
+    pub status: PortStatus,
+    /// Who holds `requested`, when it was taken
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conflict: Option<String>,
+}
+
+impl PortCheck {
+    /// What went wrong with this port, for the log and Start's error
+    pub fn problem(&self) -> Option<String> {
+        let taken = format!(
+            "{} port {}/{} is in use by {}",
+            self.role.label(),
+            self.requested,
+            self.protocol,
+            self.conflict.as_deref().unwrap_or("another process")
+        );
+        match self.status {
+            PortStatus::Free | PortStatus::Held => None,
+            PortStatus::Reassigned => Some(format!("{taken} — using {} instead", self.port)),
+            PortStatus::Blocked => Some(taken),
+        }
+    }
+}
+
+/// Every port a launch uses, checked
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PortPreflight {
+    pub checks: Vec<PortCheck>,
+}
+
+impl PortPreflight {
+    pub fn blocked(&self) -> bool {
+        self.checks.iter().any(|c| c.status == PortStatus::Blocked)
+    }
+
+    /// The port settled on for `role`
+    pub fn port(&self, role: PortRole) -> Option<u16> {
+        self.checks.iter().find(|c| c.role == role).map(|c| c.port)
+    }
+
+    /// One line per clash, blocked ones first
+    pub fn problems(&self) -> Vec<String> {
+        let mut checks: Vec<&PortCheck> = self.checks.iter().collect();
+        checks.sort_by_key(|c| c.status != PortStatus::Blocked);
+        checks.into_iter().filter_map(PortCheck::problem).collect()
+    }
+}
+
+/// Check `claims` in order. Held claims are the GUI's own and are not probed,
+/// but a later claim on the same port and protocol clashes with them, as it
+/// does with any earlier claim.
+pub fn preflight(claims: &[PortClaim], prober: &dyn Prober) -> PortPreflight {
+    // (protocol, port, who) of everything settled so far
+    let mut taken: Vec<(Protocol, u16, String)> = Vec::new();
+    let in_use = |taken: &[(Protocol, u16, String)], protocol, port| {
+        taken
+            .iter()
+            .find(|(p, n, _)| *p == protocol && *n == port)
+            .map(|(_, _, who)| who.clone())
+    };
+
+    let mut checks = Vec::new();
+    for claim in claims {
+        let mut check = PortCheck {
+            role: claim.role,
+            protocol: claim.protocol,
+            requested: claim.port,
+            port: claim.port,
+            status: PortStatus::Free,
+            conflict: None,
+        };
+        if claim.held {
+            check.status = PortStatus::Held;
+        } else {
+            check.conflict = in_use(&taken, claim.protocol, claim.port)
+                .or_else(|| prober.taken(claim.protocol, claim.port));
+        }
+        if check.conflict.is_some() {
+            let fresh = match claim.on_conflict {
+                PortConflictAction::Block => None,
+                PortConflictAction::Reassign => (0..REASSIGN_ATTEMPTS)
+                    .filter_map(|_| prober.free_port(claim.protocol))
+                    .find(|port| in_use(&taken, claim.protocol, *port).is_none()),
+            };
+            match fresh {
+                Some(port) => {
+                    check.port = port;
+                    check.status = PortStatus::Reassigned;
+                }
+                None => check.status = PortStatus::Blocked,
+            }
+        }
+        let who = if claim.held {
+            format!("this app's {}", claim.role.label())
+        } else {
+            format!("the {}", claim.role.label())
+        };
+        taken.push((claim.protocol, check.port, who));
+        checks.push(check);
+    }
+    PortPreflight { checks }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    /// Ports 5250/tcp and 8010/tcp are taken; free ports count up from 40000
+    struct FakeProber {
+        next: Cell<u16>,
+    }
+
+    impl Prober for FakeProber {
+        fn taken(&self, protocol: Protocol, port: u16) -> Option<String> {
+            (protocol == Protocol::Tcp && [5250, 8010].contains(&port))
+                .then(|| "casparcg.exe (PID 1234)".to_string())
+        }
+
+        fn free_port(&self, _: Protocol) -> Option<u16> {
+            self.next.set(self.next.get() + 1);
+            Some(self.next.get())
+        }
+    }
+
+    fn claim(role: PortRole, protocol: Protocol, port: u16, on_conflict: PortConflictAction) -> PortClaim {
+        PortClaim {
+            role,
+            protocol,
+            port,
+            held: false,
+            on_conflict,
+        }
+    }
+
+    #[test]
+    fn blocks_or_reassigns_by_policy() {
+        let prober = FakeProber { next: Cell::new(40000) };
+        let claims = [
+            claim(PortRole::Amcp, Protocol::Tcp, 5250, PortConflictAction::Block),
+            claim(PortRole::Scanner, Protocol::Tcp, 8010, PortConflictAction::Reassign),
+            claim(PortRole::Osc, Protocol::Udp, 5250, PortConflictAction::Block),
+        ];
+        let result = preflight(&claims, &prober);
+
+        assert!(result.blocked());
+        assert_eq!(result.checks[0].status, PortStatus::Blocked);
+        assert_eq!(result.checks[1].status, PortStatus::Reassigned);
+        assert_eq!(result.port(PortRole::Scanner), Some(40001));
+        // Same number, other protocol: no clash
+        assert_eq!(result.checks[2].status, PortStatus::Free);
+        assert_eq!(
+            result.problems(),
+            vec![
+                "AMCP port 5250/tcp is in use by casparcg.exe (PID 1234)".to_string(),
+                "media scanner port 8010/tcp is in use by casparcg.exe (PID 1234) — using 40001 instead"
+                    .to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn clashes_with_the_apps_own_listeners() {
+        let prober = FakeProber { next: Cell::new(40000) };
+        let mut api = claim(PortRole::ControlApi, Protocol::Tcp, 9740, PortConflictAction::Block);
+        api.held = true;
+        let claims = [
+            api,
+            claim(PortRole::Amcp, Protocol::Tcp, 9740, PortConflictAction::Reassign),
+        ];
+        let result = preflight(&claims, &prober);
+
+        assert_eq!(result.checks[0].status, PortStatus::Held);
+        assert_eq!(result.checks[1].status, PortStatus::Reassigned);
+        assert_eq!(result.checks[1].conflict.as_deref(), Some("this app's control API"));
+        assert!(!result.blocked());
+    }
+}
@@ -0,0 +1,259 @@
+// Port probing
+// Wildcard-bind checks, and which process owns a taken port
+
+use std::net::{TcpListener, UdpSocket};
+
+use serde::Serialize;
+
+use super::Protocol;
+
+/// Probe address. A port counts as free only if a WILDCARD (0.0.0.0) bind
+/// succeeds. A specific-address bind (127.0.0.1) wrongly succeeds on Windows
+/// when another process already holds the same port on 0.0.0.0 (a specific
+/// address wins over a wildcard without SO_EXCLUSIVEADDRUSE), so probing the
+/// wildcard is what actually detects an in-use port. The listeners themselves
+/// may still bind loopback only; the wildcard is used purely to probe.
+const PROBE_ANY: &str = "0.0.0.0";
+
+/// Whether nothing listens on `port`
+pub fn is_free(protocol: Protocol, port: u16) -> bool {
+    match protocol {
+        Protocol::Tcp => TcpListener::bind((PROBE_ANY, port)).is_ok(),
+        Protocol::Udp => UdpSocket::bind((PROBE_ANY, port)).is_ok(),
+    }
+}
+
+/// A port the OS hands out as free on every interface
+pub fn free_port(protocol: Protocol) -> Option<u16> {
+    match protocol {
+        Protocol::Tcp => TcpListener::bind((PROBE_ANY, 0)).and_then(|l| l.local_addr()),
+        Protocol::Udp => UdpSocket::bind((PROBE_ANY, 0)).and_then(|s| s.local_addr()),
+    }
+    .ok()
+    .map(|addr| addr.port())
+}
+
+/// The process listening on a port, where the OS tells
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PortOwner {
+    pub pid: u32,
+    pub name: Option<String>,
+}
+
+impl std::fmt::Display for PortOwner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{} (PID {})", name, self.pid),
+            None => write!(f, "PID {}", self.pid),
+        }
+    }
+}
+
+/// Which process holds `port`. `None` when it is free, or the OS does not say.
+pub fn owner(protocol: Protocol, port: u16) -> Option<PortOwner> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::owner(protocol, port)
+    }
+    #[cfg(windows)]
+    {
+        windows::owner(protocol, port)
+    }
+    #[cfg(not(any(target_os = "linux", windows)))]
+    {
+        let _ = (protocol, port);
+        None
+    }
+}
+
+/// How the pre-flight check looks at ports; the system's, or a test's
+pub trait Prober {
+    /// Who holds `port` when it is taken
+    fn taken(&self, protocol: Protocol, port: u16) -> Option<String>;
+    fn free_port(&self, protocol: Protocol) -> Option<u16>;
+}
+
+/// Probes the real network stack
+pub struct SystemProber;
+
+impl Prober for SystemProber {
+    fn taken(&self, protocol: Protocol, port: u16) -> Option<String> {
+        if is_free(protocol, port) {
+            return None;
+        }
+        Some(
+            owner(protocol, port)
+                .map(|owner| owner.to_string())
+                .unwrap_or_else(|| "another process".to_string()),
+        )
+    }
+
+    fn free_port(&self, protocol: Protocol) -> Option<u16> {
+        free_port(protocol)
+    }
+}
+
+/// Inodes of the sockets on `port` in a /proc/net table: listening ones for
+/// TCP, any for UDP
+#[cfg(any(target_os = "linux", test))]
+fn socket_inodes(table: &str, protocol: Protocol, port: u16) -> Vec<u64> {
+    const TCP_LISTEN: &str = "0A";
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let local_port = fields.get(1)?.rsplit_once(':')?.1;
+            if u16::from_str_radix(local_port, 16).ok()? != port
+                || (protocol == Protocol::Tcp && *fields.get(3)? != TCP_LISTEN)
+            {
+                return None;
+            }
+            fields.get(9)?.parse().ok()
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::*;
+
+    pub fn owner(protocol: Protocol, port: u16) -> Option<PortOwner> {
+        let tables = match protocol {
+            Protocol::Tcp => ["/proc/net/tcp", "/proc/net/tcp6"],
+            Protocol::Udp => ["/proc/net/udp", "/proc/net/udp6"],
+        };
+        let inodes: Vec<String> = tables
+            .iter()
+            .filter_map(|path| std::fs::read_to_string(path).ok())
+            .flat_map(|table| socket_inodes(&table, protocol, port))
+            .map(|inode| format!("socket:[{inode}]"))
+            .collect();
+        if inodes.is_empty() {
+            return None;
+        }
+        // Other users' descriptors are unreadable; those owners stay unknown.
+        std::fs::read_dir("/proc").ok()?.flatten().find_map(|entry| {
+            let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+            let holds = std::fs::read_dir(entry.path().join("fd"))
+                .ok()?
+                .flatten()
+                .filter_map(|fd| std::fs::read_link(fd.path()).ok())
+                .any(|target| inodes.iter().any(|inode| target.as_os_str() == inode.as_str()));
+            holds.then(|| PortOwner {
+                pid,
+                name: std::fs::read_to_string(entry.path().join("comm"))
+                    .ok()
+                    .map(|name| name.trim().to_string()),
+            })
+        })
+    }
+}
+
+/// The PID `netstat -ano` lists for `port`: a listening TCP line or any UDP
+/// line. The state column is translated on non-English Windows, so a TCP
+/// socket counts as listening by its foreign port 0 instead.
+#[cfg(any(windows, test))]
+fn parse_netstat(text: &str, protocol: Protocol, port: u16) -> Option<u32> {
+    text.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let local = match (protocol, fields.as_slice()) {
+            (Protocol::Tcp, ["TCP", local, foreign, _, .., _]) if foreign.ends_with(":0") => *local,
+            (Protocol::Udp, ["UDP", local, _, _]) => *local,
+            _ => return None,
+        };
+        if local.rsplit_once(':')?.1.parse::<u16>().ok()? != port {
+            return None;
+        }
+        fields.last()?.parse().ok()
+    })
+}
+
+/// The image name in `tasklist /FO CSV /NH` output
+#[cfg(any(windows, test))]
+fn parse_tasklist(text: &str) -> Option<String> {
+    let first = text.lines().next()?.split(',').next()?;
+    let name = first.trim().trim_matches('"');
+    (!name.is_empty() && first.trim().starts_with('"')).then(|| name.to_string())
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::*;
+    use std::os::windows::process::CommandExt;
+    use std::process::Command;
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    fn run(program: &str, args: &[&str]) -> Option<String> {
+        let output = Command::new(program)
+            .args(args)
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    pub fn owner(protocol: Protocol, port: u16) -> Option<PortOwner> {
+        let pid = parse_netstat(&run("netstat", &["-ano"])?, protocol, port)?;
+        let filter = format!("PID eq {pid}");
+        let name = run("tasklist", &["/FI", &filter, "/FO", "CSV", "/NH"])
+            .as_deref()
+            .and_then(parse_tasklist);
+        Some(PortOwner { pid, name })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_listening_sockets_in_proc_net() {
+        let tcp = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n   \
+                   0: 00000000:1482 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 4242 1 0 100 0 0 10 0\n   \
+                   1: 0100007F:1482 0100007F:D431 01 00000000:00000000 00:00000000 00000000  1000        0 4343 1 0 20 4 30 10 -1\n";
+        assert_eq!(socket_inodes(tcp, Protocol::Tcp, 5250), vec![4242]);
+        assert!(socket_inodes(tcp, Protocol::Tcp, 5251).is_empty());
+        let udp = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops\n  \
+                   7: 0100007F:186A 00000000:0000 07 00000000:00000000 00:00000000 00000000  1000        0 5151 2 0 0\n";
+        assert_eq!(socket_inodes(udp, Protocol::Udp, 6250), vec![5151]);
+    }
+
+    #[test]
+    fn parses_netstat_and_tasklist() {
+        let netstat = "\r\nActive Connections\r\n\r\n  Proto  Local Address          Foreign Address        State           PID\r\n  \
+                       TCP    0.0.0.0:5250           0.0.0.0:0              LISTENING       1234\r\n  \
+                       TCP    127.0.0.1:5250         127.0.0.1:50122        ESTABLISHED     1234\r\n  \
+                       TCP    [::]:8010              [::]:0                 LISTENING       888\r\n  \
+                       UDP    127.0.0.1:6250         *:*                                    4321\r\n";
+        assert_eq!(parse_netstat(netstat, Protocol::Tcp, 5250), Some(1234));
+        assert_eq!(parse_netstat(netstat, Protocol::Tcp, 8010), Some(888));
+        assert_eq!(parse_netstat(netstat, Protocol::Udp, 6250), Some(4321));
+        assert_eq!(parse_netstat(netstat, Protocol::Tcp, 6250), None);
+
+        // German Windows
+        let netstat = "  TCP    127.0.0.1:5250         127.0.0.1:50122        HERGESTELLT     777\r\n  \
+                       TCP    0.0.0.0:5250           0.0.0.0:0              ABHÖREN         1234\r\n";
+        assert_eq!(parse_netstat(netstat, Protocol::Tcp, 5250), Some(1234));
+
+        let tasklist = "\"casparcg.exe\",\"1234\",\"Console\",\"1\",\"812,344 K\"\r\n";
+        assert_eq!(parse_tasklist(tasklist), Some("casparcg.exe".to_string()));
+        assert_eq!(parse_tasklist("INFO: No tasks are running which match the specified criteria.\r\n"), None);
+    }
+
+    #[test]
+    fn a_bound_port_is_taken_and_its_owner_found() {
+        let listener = TcpListener::bind(("0.0.0.0", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(!is_free(Protocol::Tcp, port));
+        assert!(SystemProber.taken(Protocol::Tcp, port).is_some());
+        #[cfg(target_os = "linux")]
+        assert_eq!(owner(Protocol::Tcp, port).map(|o| o.pid), Some(std::process::id()));
+        drop(listener);
+        assert!(is_free(Protocol::Tcp, port));
+    }
+}
@@ -0,0 +1,146 @@
+// Port registry
+// The ports a launch needs and the ones the GUI already holds
+
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+use super::{PortPreflight, Prober};
+use crate::config::{GlobalConfig, PortConflictAction};
+use crate::scanner;
+
+/// What a port is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PortRole {
+    /// CasparCG's AMCP controller
+    Amcp,
+    /// The media scanner's HTTP API
+    Scanner,
+    /// The watchdog's OSC receiver
+    Osc,
+    /// The preview/colour test server
+    TestServer,
+    ControlApi,
+    Metrics,
+    /// The template development server
+    TemplateDev,
+}
+
+impl PortRole {
+    pub fn label(self) -> &'static str {
+        match self {
+            PortRole::Amcp => "AMCP",
+            PortRole::Scanner => "media scanner",
+            PortRole::Osc => "watchdog OSC",
+            PortRole::TestServer => "test server",
+            PortRole::ControlApi => "control API",
+            PortRole::Metrics => "metrics",
+            PortRole::TemplateDev => "template dev server",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    Tcp,
+    Udp,
+}
+
+impl std::fmt::Display for Protocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Protocol::Tcp => "tcp",
+            Protocol::Udp => "udp",
+        })
+    }
+}
+
+/// One port something listens on
+#[derive(Debug, Clone, Serialize)]
+pub struct PortClaim {
+    pub role: PortRole,
+    pub protocol: Protocol,
+    pub port: u16,
+    /// The GUI already listens here, so it is not probed
+    pub held: bool,
+    pub on_conflict: PortConflictAction,
+}
+
+/// Every port the app uses: the GUI's own listeners, held while they run, and
+/// the ones a launch asks for. Shared by the launcher and the GUI commands.
+#[derive(Debug, Default)]
+pub struct PortRegistry {
+    held: Mutex<Vec<PortClaim>>,
+    /// What the latest launch settled on
+    last: Mutex<Option<PortPreflight>>,
+}
+
+impl PortRegistry {
+    /// Record a listener the GUI has opened, replacing any earlier one for `role`
+    pub fn hold(&self, role: PortRole, protocol: Protocol, port: u16) {
+        let mut held = self.held.lock().unwrap_or_else(|e| e.into_inner());
+        held.retain(|claim| claim.role != role);
+        held.push(PortClaim {
+            role,
+            protocol,
+            port,
+            held: true,
+            on_conflict: PortConflictAction::Block,
+        });
+    }
+
+    /// Forget the listener for `role` once it has closed
+    pub fn release(&self, role: PortRole) {
+        self.held
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|claim| claim.role != role);
+    }
+
+    /// The ports launching `config` needs, after the ones already held
+    pub fn plan(&self, config: &GlobalConfig) -> Vec<PortClaim> {
+        let mut claims = self.held.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let mut claim = |role, protocol, port, on_conflict| {
+            claims.push(PortClaim {
+                role,
+                protocol,
+                port,
+                held: false,
+                on_conflict,
+            })
+        };
+        claim(
+            PortRole::Amcp,
+            Protocol::Tcp,
+            config.caspar.controllers.tcp.port,
+            config.ports.amcp,
+        );
+        claim(
+            PortRole::Scanner,
+            Protocol::Tcp,
+            scanner::PREFERRED_PORT,
+            PortConflictAction::Reassign,
+        );
+        if let (true, Some(port)) = (config.watchdog.enabled, config.watchdog.osc_port) {
+            claim(PortRole::Osc, Protocol::Udp, port, config.ports.osc);
+        }
+        claims
+    }
+
+    /// Check the ports launching `config` needs, without recording anything
+    pub fn check(&self, config: &GlobalConfig, prober: &dyn Prober) -> PortPreflight {
+        super::preflight(&self.plan(config), prober)
+    }
+
+    /// Remember what a launch settled on, for `last_preflight`
+    pub fn record(&self, preflight: PortPreflight) {
+        *self.last.lock().unwrap_or_else(|e| e.into_inner()) = Some(preflight);
+    }
+
+    /// The ports of the latest launch
+    pub fn last_preflight(&self) -> Option<PortPreflight> {
+        self.last.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
@@ -0,0 +1,191 @@
+// Resource alerts
+// Threshold rules held for a duration, raised and cleared as events
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use super::ResourceSample;
+use crate::config::{ResourceAlertRule, ResourceMetric, ResourceProcess};
+
+/// A rule that has tripped (`active`) or has since dropped back below its
+/// threshold
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceAlert {
+    pub rule: ResourceAlertRule,
+    /// The sampled value that tripped or cleared it
+    pub value: f64,
+    pub active: bool,
+    pub message: String,
+}
+
+#[derive(Debug)]
+struct RuleState {
+    rule: ResourceAlertRule,
+    /// When the value first went above the threshold, while it stays there
+    above_since: Option<Instant>,
+    firing: bool,
+}
+
+/// Evaluates the profile's alert rules against each new sample. A rule fires
+/// once when its value has been above the threshold for `for_secs`, and
+/// clears once when it drops back.
+#[derive(Debug)]
+pub struct AlertTracker {
+    rules: Vec<RuleState>,
+}
+
+impl AlertTracker {
+    pub fn new(rules: Vec<ResourceAlertRule>) -> Self {
+        Self {
+            rules: rules
+                .into_iter()
+                .map(|rule| RuleState {
+                    rule,
+                    above_since: None,
+                    firing: false,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn observe(
+        &mut self,
+        process: ResourceProcess,
+        sample: &ResourceSample,
+        now: Instant,
+    ) -> Vec<ResourceAlert> {
+        let mut changes = Vec::new();
+        for state in self.rules.iter_mut().filter(|s| s.rule.process == process) {
+            let value = sample.value(state.rule.metric);
+            if value > state.rule.above {
+                let since = *state.above_since.get_or_insert(now);
+                let held = now.duration_since(since) >= Duration::from_secs(state.rule.for_secs);
+                if held && !state.firing {
+                    state.firing = true;
+                    changes.push(alert(&state.rule, value, true));
+                }
+            } else {
+                state.above_since = None;
+                if state.firing {
+                    state.firing = false;
+                    changes.push(alert(&state.rule, value, false));
+                }
+            }
+        }
+        changes
+    }
+}
+
+fn alert(rule: &ResourceAlertRule, value: f64, active: bool) -> ResourceAlert {
+    let process = match rule.process {
+        ResourceProcess::Server => "CasparCG",
+        ResourceProcess::Scanner => "media scanner",
+    };
+    let metric = match rule.metric {
+        ResourceMetric::CpuPercent => "CPU",
+        ResourceMetric::RssBytes => "memory",
+        ResourceMetric::Threads => "threads",
+        ResourceMetric::Handles => "handles",
+    };
+    let message = if active {
+        let held = if rule.for_secs > 0 {
+            format!(" for {}", format_duration(rule.for_secs))
+        } else {
+            String::new()
+        };
+        format!(
+            "{process} {metric} at {} — above {}{held}",
+            format_value(rule.metric, value),
+            format_value(rule.metric, rule.above)
+        )
+    } else {
+        format!(
+            "{process} {metric} back to {} (threshold {})",
+            format_value(rule.metric, value),
+            format_value(rule.metric, rule.above)
+        )
+    };
+    ResourceAlert {
+        rule: rule.clone(),
+        value,
+        active,
+        message,
+    }
+}
+
+fn format_value(metric: ResourceMetric, value: f64) -> String {
+    match metric {
+        ResourceMetric::CpuPercent => format!("{value:.0}%"),
+        ResourceMetric::RssBytes => {
+            let gb = value / (1024.0 * 1024.0 * 1024.0);
+            if gb >= 1.0 {
+                format!("{gb:.1} GB")
+            } else {
+                format!("{:.0} MB", value / (1024.0 * 1024.0))
+            }
+        }
+        ResourceMetric::Threads | ResourceMetric::Handles => format!("{value:.0}"),
+    }
+}
+
+fn format_duration(secs: u64) -> String {
+    if secs >= 60 && secs.is_multiple_of(60) {
+        format!("{} min", secs / 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+    fn rss(bytes: f64) -> ResourceSample {
+        ResourceSample {
+            timestamp: Utc::now(),
+            pid: 1,
+            processes: 1,
+            cpu_percent: 0.0,
+            rss_bytes: bytes as u64,
+            threads: 1,
+            handles: 1,
+        }
+    }
+
+    #[test]
+    fn fires_once_after_the_hold_and_clears_once() {
+        let mut tracker = AlertTracker::new(vec![ResourceAlertRule {
+            process: ResourceProcess::Server,
+            metric: ResourceMetric::RssBytes,
+            above: 6.0 * GB,
+            for_secs: 300,
+        }]);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let server = ResourceProcess::Server;
+
+        assert!(tracker.observe(server, &rss(7.0 * GB), at(0)).is_empty());
+        assert!(tracker.observe(server, &rss(7.0 * GB), at(299)).is_empty());
+        // The scanner's samples never trip a server rule
+        assert!(tracker.observe(ResourceProcess::Scanner, &rss(9.0 * GB), at(300)).is_empty());
+
+        let raised = tracker.observe(server, &rss(7.2 * GB), at(300));
+        assert_eq!(raised.len(), 1);
+        assert!(raised[0].active);
+        assert_eq!(raised[0].message, "CasparCG memory at 7.2 GB — above 6.0 GB for 5 min");
+        assert!(tracker.observe(server, &rss(7.5 * GB), at(600)).is_empty());
+
+        let cleared = tracker.observe(server, &rss(2.0 * GB), at(605));
+        assert_eq!(cleared.len(), 1);
+        assert!(!cleared[0].active);
+
+        // A dip resets the hold
+        tracker.observe(server, &rss(7.0 * GB), at(700));
+        tracker.observe(server, &rss(1.0 * GB), at(800));
+        assert!(tracker.observe(server, &rss(7.0 * GB), at(1000)).is_empty());
+    }
+}
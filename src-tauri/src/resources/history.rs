@@ -0,0 +1,104 @@
+// Resource history
+// Bounded per-process series of samples for the GUI, API and metrics
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::ResourceSample;
+use crate::config::ResourceProcess;
+
+/// Samples kept per process: two hours at the default 5 s interval
+pub const RESOURCE_HISTORY_CAPACITY: usize = 1440;
+
+/// Samples of both launched processes, oldest first
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ResourceSeries {
+    pub server: Vec<ResourceSample>,
+    pub scanner: Vec<ResourceSample>,
+}
+
+#[derive(Debug, Default)]
+pub struct ResourceHistory {
+    samples: Mutex<BTreeMap<ResourceProcess, VecDeque<ResourceSample>>>,
+}
+
+impl ResourceHistory {
+    pub fn record(&self, process: ResourceProcess, sample: ResourceSample) {
+        let mut samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        let series = samples.entry(process).or_default();
+        if series.len() == RESOURCE_HISTORY_CAPACITY {
+            series.pop_front();
+        }
+        series.push_back(sample);
+    }
+
+    /// Samples taken after `since`, or all of them
+    pub fn series(&self, since: Option<DateTime<Utc>>) -> ResourceSeries {
+        let samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        let of = |process| -> Vec<ResourceSample> {
+            samples
+                .get(&process)
+                .map(|series| {
+                    series
+                        .iter()
+                        .filter(|s| since.is_none_or(|since| s.timestamp > since))
+                        .cloned()
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        ResourceSeries {
+            server: of(ResourceProcess::Server),
+            scanner: of(ResourceProcess::Scanner),
+        }
+    }
+
+    /// The newest sample of each process
+    pub fn latest(&self) -> BTreeMap<ResourceProcess, ResourceSample> {
+        let samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        samples
+            .iter()
+            .filter_map(|(process, series)| Some((*process, series.back()?.clone())))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_a_bounded_series_and_filters_by_time() {
+        let history = ResourceHistory::default();
+        let start = Utc::now();
+        for i in 0..RESOURCE_HISTORY_CAPACITY + 10 {
+            history.record(
+                ResourceProcess::Server,
+                ResourceSample {
+                    timestamp: start + chrono::Duration::seconds(i as i64),
+                    pid: 1,
+                    processes: 1,
+                    cpu_percent: 0.0,
+                    rss_bytes: i as u64,
+                    threads: 1,
+                    handles: 1,
+                },
+            );
+        }
+        let all = history.series(None);
+        assert_eq!(all.server.len(), RESOURCE_HISTORY_CAPACITY);
+        assert_eq!(all.server[0].rss_bytes, 10);
+        assert!(all.scanner.is_empty());
+
+        let since = start + chrono::Duration::seconds(RESOURCE_HISTORY_CAPACITY as i64 + 7);
+        let recent = history.series(Some(since));
+        assert_eq!(recent.server.len(), 2);
+        assert_eq!(
+            history.latest()[&ResourceProcess::Server].rss_bytes,
+            RESOURCE_HISTORY_CAPACITY as u64 + 9
+        );
+    }
+}
@@ -0,0 +1,12 @@
+// Process resource monitoring
+// CPU, memory, thread and handle samples of the server and scanner trees
+
+mod alerts;
+mod history;
+mod sampler;
+mod table;
+
+pub use alerts::*;
+pub use history::*;
+pub use sampler::*;
+pub use table::*;
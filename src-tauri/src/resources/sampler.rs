@@ -0,0 +1,120 @@
+// Resource sampler
+// Turns successive readings of a process tree into samples with CPU usage
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::ProcessInfo;
+use crate::config::ResourceMetric;
+
+/// One reading of a launched process and its descendants, summed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResourceSample {
+    pub timestamp: DateTime<Utc>,
+    /// PID of the launched process; changes when the supervisor relaunches it
+    pub pid: u32,
+    /// Processes in the tree, the launched one included
+    pub processes: u32,
+    /// CPU since the previous sample; 100 is one full core
+    pub cpu_percent: f64,
+    pub rss_bytes: u64,
+    pub threads: u64,
+    /// Open handles on Windows, file descriptors on Linux
+    pub handles: u64,
+}
+
+impl ResourceSample {
+    pub fn value(&self, metric: ResourceMetric) -> f64 {
+        match metric {
+            ResourceMetric::CpuPercent => self.cpu_percent,
+            ResourceMetric::RssBytes => self.rss_bytes as f64,
+            ResourceMetric::Threads => self.threads as f64,
+            ResourceMetric::Handles => self.handles as f64,
+        }
+    }
+}
+
+/// Samples one tree over time. CPU usage is the CPU time each process used
+/// since the previous reading, so a process seen for the first time (a new
+/// CEF child, or a relaunched server) counts from its second reading.
+#[derive(Debug, Default)]
+pub struct TreeSampler {
+    cpu_secs: HashMap<u32, f64>,
+    at: Option<Instant>,
+}
+
+impl TreeSampler {
+    pub fn sample(
+        &mut self,
+        root: u32,
+        tree: &[ProcessInfo],
+        now: Instant,
+        timestamp: DateTime<Utc>,
+    ) -> ResourceSample {
+        let elapsed = self
+            .at
+            .map(|at| now.duration_since(at).as_secs_f64())
+            .filter(|secs| *secs > 0.0);
+        let cpu_percent = elapsed.map_or(0.0, |elapsed| {
+            let used: f64 = tree
+                .iter()
+                .filter_map(|p| Some((p.cpu_secs - self.cpu_secs.get(&p.pid)?).max(0.0)))
+                .sum();
+            used / elapsed * 100.0
+        });
+        self.cpu_secs = tree.iter().map(|p| (p.pid, p.cpu_secs)).collect();
+        self.at = Some(now);
+
+        ResourceSample {
+            timestamp,
+            pid: root,
+            processes: tree.len() as u32,
+            cpu_percent,
+            rss_bytes: tree.iter().map(|p| p.rss_bytes).sum(),
+            threads: tree.iter().map(|p| p.threads).sum(),
+            handles: tree.iter().map(|p| p.handles).sum(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn process(pid: u32, cpu_secs: f64) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            ppid: 1,
+            cpu_secs,
+            rss_bytes: 1000,
+            threads: 10,
+            handles: 50,
+        }
+    }
+
+    #[test]
+    fn cpu_is_the_time_used_since_the_last_reading() {
+        let mut sampler = TreeSampler::default();
+        let start = Instant::now();
+        let first = sampler.sample(7, &[process(7, 10.0)], start, Utc::now());
+        assert_eq!(first.cpu_percent, 0.0);
+        assert_eq!(first.rss_bytes, 1000);
+
+        // Two seconds later the server used 3 s of CPU and a new child 5 s;
+        // the child is new, so only the server's 3 s count: 150 %.
+        let second = sampler.sample(
+            7,
+            &[process(7, 13.0), process(8, 5.0)],
+            start + Duration::from_secs(2),
+            Utc::now(),
+        );
+        assert_eq!(second.cpu_percent, 150.0);
+        assert_eq!(second.processes, 2);
+        assert_eq!(second.threads, 20);
+        assert_eq!(second.value(ResourceMetric::Handles), 100.0);
+    }
+}
@@ -0,0 +1,334 @@
+// Process table
+// Reads CPU time, memory, threads and handles of process trees from the OS
+
+use std::collections::HashMap;
+
+/// One process as the OS reports it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub ppid: u32,
+    /// CPU time used so far, user and kernel
+    pub cpu_secs: f64,
+    pub rss_bytes: u64,
+    pub threads: u64,
+    /// Open handles on Windows, file descriptors on Linux
+    pub handles: u64,
+}
+
+/// `root` and every process descended from it, given (pid, ppid) pairs
+pub fn tree_members(parents: &[(u32, u32)], root: u32) -> Vec<u32> {
+    let mut members = vec![root];
+    let mut next = 0;
+    while next < members.len() {
+        let parent = members[next];
+        let children: Vec<u32> = parents
+            .iter()
+            .filter(|(pid, ppid)| *ppid == parent && !members.contains(pid))
+            .map(|(pid, _)| *pid)
+            .collect();
+        members.extend(children);
+        next += 1;
+    }
+    members
+}
+
+/// The processes of the tree under each of `roots`, keyed by root. A root that
+/// is no longer running is left out. Blocking: call it off the async runtime.
+pub fn read_trees(roots: &[u32]) -> Result<HashMap<u32, Vec<ProcessInfo>>, String> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::read_trees(roots)
+    }
+    #[cfg(windows)]
+    {
+        let mut trees = select_trees(&windows::read_all()?, roots);
+        // Only the trees' own processes are opened for their times and counters.
+        for process in trees.values_mut().flatten() {
+            windows::read_usage(process);
+        }
+        Ok(trees)
+    }
+    #[cfg(not(any(target_os = "linux", windows)))]
+    {
+        let _ = roots;
+        Err("resource sampling is not supported on this platform".to_string())
+    }
+}
+
+/// Split a whole-system table into the trees under `roots`
+#[cfg(any(windows, test))]
+fn select_trees(all: &[ProcessInfo], roots: &[u32]) -> HashMap<u32, Vec<ProcessInfo>> {
+    let parents: Vec<(u32, u32)> = all.iter().map(|p| (p.pid, p.ppid)).collect();
+    let by_pid: HashMap<u32, &ProcessInfo> = all.iter().map(|p| (p.pid, p)).collect();
+    roots
+        .iter()
+        .filter(|root| by_pid.contains_key(root))
+        .map(|&root| {
+            let tree = tree_members(&parents, root)
+                .into_iter()
+                .filter_map(|pid| by_pid.get(&pid).map(|p| (*p).clone()))
+                .collect();
+            (root, tree)
+        })
+        .collect()
+}
+
+/// The fields of `/proc/<pid>/stat` the sampler needs
+#[cfg(any(target_os = "linux", test))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ProcStat {
+    ppid: u32,
+    /// utime + stime, in clock ticks
+    cpu_ticks: u64,
+    threads: u64,
+}
+
+/// Parse `/proc/<pid>/stat`. The command name is in parentheses and may
+/// itself contain spaces or parentheses, so fields are counted from the last `)`.
+#[cfg(any(target_os = "linux", test))]
+fn parse_stat(contents: &str) -> Option<ProcStat> {
+    let rest = &contents[contents.rfind(')')? + 1..];
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    // fields[0] is field 3 (state) in proc(5)'s numbering
+    let field = |n: usize| fields.get(n - 3)?.parse::<u64>().ok();
+    Some(ProcStat {
+        ppid: field(4)? as u32,
+        cpu_ticks: field(14)? + field(15)?,
+        threads: field(20)?,
+    })
+}
+
+/// Resident memory from the `VmRSS:` line of `/proc/<pid>/status`
+#[cfg(any(target_os = "linux", test))]
+fn parse_vm_rss(status: &str) -> Option<u64> {
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::*;
+
+    /// USER_HZ, which the kernel fixes at 100 on every architecture Linux
+    /// exposes it for
+    const CLOCK_TICKS_PER_SEC: f64 = 100.0;
+
+    pub fn read_trees(roots: &[u32]) -> Result<HashMap<u32, Vec<ProcessInfo>>, String> {
+        let entries = std::fs::read_dir("/proc").map_err(|e| format!("cannot read /proc: {e}"))?;
+        let stats: HashMap<u32, ProcStat> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+                let stat = std::fs::read_to_string(entry.path().join("stat")).ok()?;
+                Some((pid, parse_stat(&stat)?))
+            })
+            .collect();
+        let parents: Vec<(u32, u32)> = stats.iter().map(|(pid, s)| (*pid, s.ppid)).collect();
+
+        // Only the trees' own processes have their status and fd table read.
+        Ok(roots
+            .iter()
+            .filter(|root| stats.contains_key(root))
+            .map(|&root| {
+                let tree = tree_members(&parents, root)
+                    .into_iter()
+                    .filter_map(|pid| {
+                        let stat = stats.get(&pid)?;
+                        let dir = std::path::PathBuf::from(format!("/proc/{pid}"));
+                        Some(ProcessInfo {
+                            pid,
+                            ppid: stat.ppid,
+                            cpu_secs: stat.cpu_ticks as f64 / CLOCK_TICKS_PER_SEC,
+                            rss_bytes: std::fs::read_to_string(dir.join("status"))
+                                .ok()
+                                .and_then(|s| parse_vm_rss(&s))
+                                .unwrap_or(0),
+                            threads: stat.threads,
+                            handles: std::fs::read_dir(dir.join("fd"))
+                                .map(|fds| fds.count() as u64)
+                                .unwrap_or(0),
+                        })
+                    })
+                    .collect();
+                (root, tree)
+            })
+            .collect())
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::*;
+
+    type Handle = isize;
+
+    const INVALID_HANDLE_VALUE: Handle = -1;
+    const TH32CS_SNAPPROCESS: u32 = 0x0000_0002;
+    const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+
+    /// PROCESSENTRY32W
+    #[repr(C)]
+    struct ProcessEntry {
+        size: u32,
+        _usage: u32,
+        process_id: u32,
+        _default_heap_id: usize,
+        _module_id: u32,
+        threads: u32,
+        parent_process_id: u32,
+        _priority: i32,
+        _flags: u32,
+        _exe_file: [u16; 260],
+    }
+
+    /// FILETIME, in 100 ns units
+    #[repr(C)]
+    #[derive(Default)]
+    struct FileTime {
+        low: u32,
+        high: u32,
+    }
+
+    impl FileTime {
+        fn secs(&self) -> f64 {
+            ((u64::from(self.high) << 32) | u64::from(self.low)) as f64 / 1e7
+        }
+    }
+
+    /// PROCESS_MEMORY_COUNTERS
+    #[repr(C)]
+    #[derive(Default)]
+    struct MemoryCounters {
+        size: u32,
+        _page_faults: u32,
+        _peak_working_set: usize,
+        working_set: usize,
+        _pools_and_pagefile: [usize; 6],
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn CreateToolhelp32Snapshot(flags: u32, process_id: u32) -> Handle;
+        fn Process32FirstW(snapshot: Handle, entry: *mut ProcessEntry) -> i32;
+        fn Process32NextW(snapshot: Handle, entry: *mut ProcessEntry) -> i32;
+        fn OpenProcess(access: u32, inherit: i32, process_id: u32) -> Handle;
+        fn CloseHandle(handle: Handle) -> i32;
+        fn GetProcessTimes(
+            process: Handle,
+            creation: *mut FileTime,
+            exit: *mut FileTime,
+            kernel: *mut FileTime,
+            user: *mut FileTime,
+        ) -> i32;
+        fn GetProcessHandleCount(process: Handle, count: *mut u32) -> i32;
+        fn K32GetProcessMemoryInfo(process: Handle, counters: *mut MemoryCounters, size: u32) -> i32;
+    }
+
+    /// Every process with its parent and thread count, from one Toolhelp
+    /// snapshot, so the CEF children are found by parent PID without starting
+    /// anything.
+    pub fn read_all() -> Result<Vec<ProcessInfo>, String> {
+        let mut processes = Vec::new();
+        unsafe {
+            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+            if snapshot == INVALID_HANDLE_VALUE {
+                return Err(format!("cannot list processes: {}", std::io::Error::last_os_error()));
+            }
+            let mut entry: ProcessEntry = std::mem::zeroed();
+            entry.size = std::mem::size_of::<ProcessEntry>() as u32;
+            let mut more = Process32FirstW(snapshot, &mut entry) != 0;
+            while more {
+                processes.push(ProcessInfo {
+                    pid: entry.process_id,
+                    ppid: entry.parent_process_id,
+                    threads: u64::from(entry.threads),
+                    ..Default::default()
+                });
+                more = Process32NextW(snapshot, &mut entry) != 0;
+            }
+            CloseHandle(snapshot);
+        }
+        Ok(processes)
+    }
+
+    /// Fill in CPU time, working set and handle count. Left at zero for a
+    /// process that has exited or may not be opened.
+    pub fn read_usage(process: &mut ProcessInfo) {
+        unsafe {
+            let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, process.pid);
+            if handle == 0 {
+                return;
+            }
+            let (mut creation, mut exit) = (FileTime::default(), FileTime::default());
+            let (mut kernel, mut user) = (FileTime::default(), FileTime::default());
+            if GetProcessTimes(handle, &mut creation, &mut exit, &mut kernel, &mut user) != 0 {
+                process.cpu_secs = kernel.secs() + user.secs();
+            }
+            let mut counters = MemoryCounters {
+                size: std::mem::size_of::<MemoryCounters>() as u32,
+                ..Default::default()
+            };
+            if K32GetProcessMemoryInfo(handle, &mut counters, counters.size) != 0 {
+                process.rss_bytes = counters.working_set as u64;
+            }
+            let mut handles = 0u32;
+            if GetProcessHandleCount(handle, &mut handles) != 0 {
+                process.handles = u64::from(handles);
+            }
+            CloseHandle(handle);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trees_follow_parent_pids() {
+        let parents = [(10, 1), (11, 10), (12, 11), (13, 10), (20, 1), (21, 20)];
+        let mut tree = tree_members(&parents, 10);
+        tree.sort();
+        assert_eq!(tree, vec![10, 11, 12, 13]);
+        assert_eq!(tree_members(&parents, 21), vec![21]);
+    }
+
+    #[test]
+    fn parses_proc_stat_around_awkward_names() {
+        let stat = "4242 (casparcg (main)) S 4200 4242 4242 0 -1 4194560 1000 0 0 0 \
+                    350 150 0 0 20 0 37 0 123456 2000000000 150000 18446744073709551615";
+        assert_eq!(
+            parse_stat(stat),
+            Some(ProcStat {
+                ppid: 4200,
+                cpu_ticks: 500,
+                threads: 37,
+            })
+        );
+        assert_eq!(parse_vm_rss("Name:\tcasparcg\nVmRSS:\t  2048 kB\n"), Some(2048 * 1024));
+    }
+
+    #[test]
+    fn selects_trees_from_the_process_table() {
+        let process = |pid, ppid, rss_bytes| ProcessInfo {
+            pid,
+            ppid,
+            rss_bytes,
+            ..Default::default()
+        };
+        let all = [
+            process(4, 0, 0),
+            process(100, 4, 1073741824),
+            process(101, 100, 536870912),
+            process(200, 4, 104857600),
+        ];
+        let trees = select_trees(&all, &[100, 999]);
+        assert_eq!(trees.len(), 1);
+        let server = &trees[&100];
+        assert_eq!(server.len(), 2);
+        assert_eq!(server[0].pid, 100);
+        assert_eq!(server.iter().map(|p| p.rss_bytes).sum::<u64>(), 1610612736);
+    }
+}
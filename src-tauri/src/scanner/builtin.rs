@@ -0,0 +1,292 @@
+// Built-in media scanner
+// Serves the scanner HTTP API from an in-process index of the CasparCG folders
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use base64::Engine;
+
+use super::ffmpeg::Ffmpeg;
+use super::index::{self, ScanIndex, ScanRoots};
+use crate::api::{start_listener, stop_listener, HttpListenerState};
+
+/// How often the folders are walked for changes. A walk only stats files;
+/// ffprobe runs for new and changed ones.
+pub const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+type SharedIndex = Arc<RwLock<ScanIndex>>;
+
+/// The scanner CasparCG queries when a profile picks the built-in one: the
+/// same routes as the Node scanner, on the port the pre-flight settled on.
+pub struct BuiltinScanner {
+    listener: HttpListenerState,
+    stop: Arc<AtomicBool>,
+}
+
+impl BuiltinScanner {
+    /// Serve on `host:port` and start walking `roots` in the background. The
+    /// listings fill in as the first walk progresses.
+    pub async fn start(roots: ScanRoots, ffmpeg: Option<Ffmpeg>, host: &str, port: u16) -> Result<Self, String> {
+        let index = SharedIndex::default();
+        let listener = HttpListenerState::default();
+        start_listener(
+            listener.clone(),
+            &format!("{host}:{port}"),
+            router(index.clone()),
+            "built-in media scanner",
+        )
+        .await?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let (watch_index, watch_stop) = (index.clone(), stop.clone());
+        tokio::spawn(async move {
+            while !watch_stop.load(Ordering::Acquire) {
+                let (index, roots, ffmpeg, stop) =
+                    (watch_index.clone(), roots.clone(), ffmpeg.clone(), watch_stop.clone());
+                let _ = tokio::task::spawn_blocking(move || rescan(&index, &roots, ffmpeg.as_ref(), &stop)).await;
+                tokio::time::sleep(WATCH_INTERVAL).await;
+            }
+        });
+        Ok(Self { listener, stop })
+    }
+
+    /// Stop serving and watching
+    pub async fn stop(&self) {
+        self.stop.store(true, Ordering::Release);
+        stop_listener(self.listener.clone()).await;
+    }
+}
+
+/// Walk the folders once and bring the index up to date. New and changed
+/// media are probed one at a time outside the lock, so listings keep being
+/// served during a long first walk. Returns whether anything changed.
+pub fn rescan(index: &RwLock<ScanIndex>, roots: &ScanRoots, ffmpeg: Option<&Ffmpeg>, stop: &AtomicBool) -> bool {
+    let found = index::walk(&roots.media, &index::media_extensions());
+    let mut changed = false;
+    {
+        let mut index = index.write().unwrap_or_else(|e| e.into_inner());
+        let before = index.media.len();
+        index.media.retain(|id, _| found.binary_search_by(|f| f.id.as_str().cmp(id)).is_ok());
+        changed |= index.media.len() != before;
+    }
+    for file in &found {
+        if stop.load(Ordering::Acquire) {
+            return changed;
+        }
+        let known = {
+            let index = index.read().unwrap_or_else(|e| e.into_inner());
+            index.media.get(&file.id).is_some_and(|m| {
+                m.item.size == file.size && m.item.modified == file.modified
+            })
+        };
+        if known {
+            continue;
+        }
+        let entry = index::index_media(file, ffmpeg);
+        let mut index = index.write().unwrap_or_else(|e| e.into_inner());
+        match entry {
+            Some(entry) => {
+                index.media.insert(file.id.clone(), entry);
+            }
+            None => {
+                index.media.remove(&file.id);
+            }
+        }
+        changed = true;
+    }
+
+    let templates = index::walk(&roots.template, index::template_extensions())
+        .iter()
+        .map(|f| (f.id.clone(), index::template_item(f)))
+        .collect();
+    let fonts = match &roots.font {
+        Some(root) => index::walk(root, index::font_extensions())
+            .iter()
+            .map(|f| (f.id.clone(), index::font_item(f, root)))
+            .collect(),
+        None => Default::default(),
+    };
+    let mut index = index.write().unwrap_or_else(|e| e.into_inner());
+    changed |= index.templates != templates || index.fonts != fonts;
+    index.templates = templates;
+    index.fonts = fonts;
+    changed
+}
+
+fn router(index: SharedIndex) -> Router {
+    Router::new()
+        .route("/version", get(version))
+        .route("/cls", get(cls))
+        .route("/tls", get(tls))
+        .route("/fls", get(fls))
+        .route("/cinf/{*id}", get(cinf))
+        .route("/media", get(media))
+        .route("/thumbnail", get(thumbnail_list))
+        .route("/thumbnail/generate", get(thumbnail_generate))
+        .route("/thumbnail/generate/{*id}", get(thumbnail_generate))
+        .route("/thumbnail/{*id}", get(thumbnail))
+        .with_state(index)
+}
+
+/// A listing in the scanner's reply format: status line, one line per entry,
+/// blank line
+fn listing(status: &str, lines: impl Iterator<Item = String>) -> String {
+    let mut out = format!("{status}\r\n");
+    for line in lines {
+        out.push_str(&line);
+        out.push_str("\r\n");
+    }
+    out.push_str("\r\n");
+    out
+}
+
+fn read(index: &SharedIndex) -> std::sync::RwLockReadGuard<'_, ScanIndex> {
+    index.read().unwrap_or_else(|e| e.into_inner())
+}
+
+async fn version() -> String {
+    format!("built-in {}", env!("CARGO_PKG_VERSION"))
+}
+
+async fn cls(State(index): State<SharedIndex>) -> String {
+    let index = read(&index);
+    listing("200 CLS OK", index.media.values().map(|m| index::cls_line(&m.item)))
+}
+
+async fn tls(State(index): State<SharedIndex>) -> String {
+    let index = read(&index);
+    listing("200 TLS OK", index.templates.values().map(index::tls_line))
+}
+
+async fn fls(State(index): State<SharedIndex>) -> String {
+    let index = read(&index);
+    listing("200 FLS OK", index.fonts.values().map(index::fls_line))
+}
+
+async fn cinf(State(index): State<SharedIndex>, Path(id): Path<String>) -> Response {
+    let index = read(&index);
+    match index.media.get(&id.to_uppercase()) {
+        Some(m) => format!("201 CINF OK\r\n{}\r\n", index::cls_line(&m.item)).into_response(),
+        None => (StatusCode::NOT_FOUND, "404 CINF ERROR\r\n").into_response(),
+    }
+}
+
+/// The ffprobe result of every probed clip, in the Node scanner's document shape
+async fn media(State(index): State<SharedIndex>) -> Response {
+    let index = read(&index);
+    let docs: Vec<_> = index
+        .media
+        .iter()
+        .filter_map(|(id, m)| {
+            let info = m.probe.as_ref()?;
+            Some(serde_json::json!({ "_id": id, "mediaPath": m.path, "mediainfo": info }))
+        })
+        .collect();
+    axum::Json(docs).into_response()
+}
+
+async fn thumbnail_list(State(index): State<SharedIndex>) -> String {
+    let index = read(&index);
+    let lines = index.media.iter().filter_map(|(id, m)| {
+        let (made, png) = m.thumbnail.as_ref()?;
+        Some(format!("\"{id}\" {} {}", made.format("%Y%m%dT%H%M%S"), png.len()))
+    });
+    listing("200 THUMBNAIL LIST OK", lines)
+}
+
+async fn thumbnail(State(index): State<SharedIndex>, Path(id): Path<String>) -> Response {
+    let index = read(&index);
+    match index.media.get(&id.to_uppercase()).and_then(|m| m.thumbnail.as_ref()) {
+        Some((_, png)) => format!(
+            "201 THUMBNAIL RETRIEVE OK\r\n{}\r\n",
+            base64::engine::general_purpose::STANDARD.encode(png)
+        )
+        .into_response(),
+        None => (StatusCode::NOT_FOUND, "404 THUMBNAIL RETRIEVE ERROR\r\n").into_response(),
+    }
+}
+
+/// Thumbnails are made as clips are indexed, so there is nothing to start
+async fn thumbnail_generate() -> &'static str {
+    "202 THUMBNAIL GENERATE OK\r\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use crate::ports::{free_port, Protocol};
+    use crate::scanner::{MediaKind, ScannerClient, ScannerEndpoint, ScannerError};
+
+    fn folders(name: &str) -> (PathBuf, ScanRoots) {
+        let root = std::env::temp_dir().join(format!("{name}-{}", std::process::id()));
+        for dir in ["media/clips", "template/cg", "font"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::write(root.join("media/clips/amb.mov"), b"movie").unwrap();
+        std::fs::write(root.join("template/cg/lower.html"), b"<html>").unwrap();
+        std::fs::write(root.join("font/Sans.ttf"), b"font").unwrap();
+        let roots = ScanRoots {
+            media: root.join("media"),
+            template: root.join("template"),
+            font: Some(root.join("font")),
+        };
+        (root, roots)
+    }
+
+    #[test]
+    fn serves_the_scanner_api() {
+        let (root, roots) = folders("caspar-builtin-serve");
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let port = free_port(Protocol::Tcp).unwrap();
+            let scanner = BuiltinScanner::start(roots, None, "127.0.0.1", port).await.unwrap();
+            let client = ScannerClient::new(ScannerEndpoint::new("127.0.0.1", port));
+            let mut media = Vec::new();
+            for _ in 0..50 {
+                media = client.media().await.unwrap();
+                if !media.is_empty() {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+
+            assert!(client.version().await.unwrap().unwrap().starts_with("built-in"));
+            assert_eq!(media.len(), 1);
+            assert_eq!(media[0].name, "CLIPS/AMB");
+            assert_eq!(media[0].kind, MediaKind::Movie);
+            assert_eq!(client.templates().await.unwrap()[0].kind.as_deref(), Some("HTML"));
+            assert_eq!(client.fonts().await.unwrap()[0].file.as_deref(), Some("Sans.ttf"));
+            assert!(client.thumbnails().await.unwrap().is_empty());
+            assert!(matches!(client.thumbnail("CLIPS/AMB").await, Err(ScannerError::Status(404))));
+
+            scanner.stop().await;
+            assert!(client.ping().await.is_err());
+        });
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn rescan_picks_up_new_and_removed_files() {
+        let (root, roots) = folders("caspar-builtin-rescan");
+        let index = RwLock::default();
+        let stop = AtomicBool::new(false);
+        assert!(rescan(&index, &roots, None, &stop));
+        assert!(!rescan(&index, &roots, None, &stop));
+
+        std::fs::write(root.join("media/logo.png"), b"png").unwrap();
+        assert!(rescan(&index, &roots, None, &stop));
+        let names: Vec<_> = index.read().unwrap().media.keys().cloned().collect();
+        assert_eq!(names, ["CLIPS/AMB", "LOGO"]);
+
+        std::fs::remove_file(root.join("media/clips/amb.mov")).unwrap();
+        assert!(rescan(&index, &roots, None, &stop));
+        assert_eq!(index.read().unwrap().media.len(), 1);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
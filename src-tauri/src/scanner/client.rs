@@ -0,0 +1,283 @@
+// Media scanner client
+// HTTP/1 client for the scanner's API: version, listings and thumbnails
+
+use std::time::Duration;
+
+use base64::Engine;
+use http_body_util::{BodyExt, Empty, Limited};
+use hyper::body::Bytes;
+use hyper::{header, Request, StatusCode};
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpStream;
+
+use super::{listing, mediainfo};
+use super::{FontItem, MediaInfo, MediaItem, ScannerEndpoint, TemplateItem, ThumbnailInfo};
+
+/// How long one request may take, connect to last byte. The scanner is on
+/// loopback: anything slower is a scanner in trouble.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Largest reply accepted. A CLS of a big media library runs to megabytes.
+const MAX_BODY: usize = 64 * 1024 * 1024;
+
+/// Talks to one scanner's HTTP API. Cheap to create: each request is its own
+/// connection, as the scanner closes idle ones anyway.
+#[derive(Debug, Clone)]
+pub struct ScannerClient {
+    endpoint: ScannerEndpoint,
+    timeout: Duration,
+}
+
+impl ScannerClient {
+    pub fn new(endpoint: ScannerEndpoint) -> Self {
+        Self {
+            endpoint,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The scanner's version. `Ok(None)` when it answers but has no version
+    /// route, as older scanners do.
+    pub async fn version(&self) -> Result<Option<String>, ScannerError> {
+        let (status, body) = self.get("/version").await?;
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let body = text(status, body)?;
+        // Either plain text or an AMCP-style `200 VERSION OK` then the version
+        let version = listing::listing_lines(&body)?.next().map(str::to_string);
+        Ok(version.filter(|v| !v.is_empty()))
+    }
+
+    /// Whether the scanner answers HTTP at all, whatever the route says
+    pub async fn ping(&self) -> Result<(), ScannerError> {
+        self.get("/version").await.map(|_| ())
+    }
+
+    /// `GET /cls`: every playable clip
+    pub async fn media(&self) -> Result<Vec<MediaItem>, ScannerError> {
+        listing::parse_cls(&self.get_text("/cls").await?)
+    }
+
+    /// `GET /media`: ffprobe details of every clip, by CLS name. `Ok(None)`
+    /// when the scanner does not serve its documents.
+    pub async fn media_info(&self) -> Result<Option<Vec<(String, MediaInfo)>>, ScannerError> {
+        let (status, body) = self.get("/media").await?;
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        mediainfo::parse_media_docs(&text(status, body)?).map(Some)
+    }
+
+    /// `GET /tls`: every template
+    pub async fn templates(&self) -> Result<Vec<TemplateItem>, ScannerError> {
+        listing::parse_tls(&self.get_text("/tls").await?)
+    }
+
+    /// `GET /fls`: every font
+    pub async fn fonts(&self) -> Result<Vec<FontItem>, ScannerError> {
+        listing::parse_fls(&self.get_text("/fls").await?)
+    }
+
+    /// `GET /thumbnail`: the clips with a thumbnail, and when it was made
+    pub async fn thumbnails(&self) -> Result<Vec<ThumbnailInfo>, ScannerError> {
+        listing::parse_thumbnail_list(&self.get_text("/thumbnail").await?)
+    }
+
+    /// `GET /thumbnail/<name>`: the PNG thumbnail of one clip
+    pub async fn thumbnail(&self, name: &str) -> Result<Vec<u8>, ScannerError> {
+        let body = self.get_text(&format!("/thumbnail/{}", encode_path(name))).await?;
+        let data = listing::listing_lines(&body)?
+            .next()
+            .ok_or_else(|| ScannerError::InvalidResponse("empty thumbnail".to_string()))?;
+        base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_err(|e| ScannerError::InvalidResponse(format!("thumbnail is not base64: {e}")))
+    }
+
+    async fn get_text(&self, path: &str) -> Result<String, ScannerError> {
+        let (status, body) = self.get(path).await?;
+        text(status, body)
+    }
+
+    /// One GET within the timeout
+    async fn get(&self, path: &str) -> Result<(StatusCode, Bytes), ScannerError> {
+        tokio::time::timeout(self.timeout, self.exchange(path))
+            .await
+            .map_err(|_| ScannerError::Timeout(self.timeout))?
+    }
+
+    async fn exchange(&self, path: &str) -> Result<(StatusCode, Bytes), ScannerError> {
+        let ScannerEndpoint { host, port, .. } = &self.endpoint;
+        let stream = TcpStream::connect((host.as_str(), *port))
+            .await
+            .map_err(|e| ScannerError::ConnectionFailed(format!("{host}:{port}: {e}")))?;
+        let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+            .await
+            .map_err(|e| ScannerError::ConnectionFailed(e.to_string()))?;
+        tokio::spawn(connection);
+
+        let request = Request::get(path)
+            .header(header::HOST, format!("{host}:{port}"))
+            .body(Empty::<Bytes>::new())
+            .map_err(|e| ScannerError::InvalidResponse(e.to_string()))?;
+        let response = sender
+            .send_request(request)
+            .await
+            .map_err(|e| ScannerError::ConnectionFailed(e.to_string()))?;
+        let status = response.status();
+        let body = Limited::new(response.into_body(), MAX_BODY)
+            .collect()
+            .await
+            .map_err(|e| ScannerError::InvalidResponse(format!("reading reply: {e}")))?
+            .to_bytes();
+        Ok((status, body))
+    }
+}
+
+/// The version of the scanner at `endpoint`, for display. `None` when there is
+/// no scanner or it does not answer.
+pub async fn get_scanner_version(endpoint: Option<ScannerEndpoint>) -> Option<String> {
+    match ScannerClient::new(endpoint?).version().await {
+        Ok(Some(version)) => Some(version),
+        Ok(None) => Some("running (version not reported)".to_string()),
+        Err(_) => None,
+    }
+}
+
+/// A successful reply as text
+fn text(status: StatusCode, body: Bytes) -> Result<String, ScannerError> {
+    if !status.is_success() {
+        return Err(ScannerError::Status(status.as_u16()));
+    }
+    String::from_utf8(body.to_vec())
+        .map_err(|_| ScannerError::InvalidResponse("reply is not UTF-8".to_string()))
+}
+
+/// Percent-encode a clip name for a path segment, keeping its `/` separators
+fn encode_path(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for b in name.bytes() {
+        if b.is_ascii_alphanumeric() || b"-_.~/".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ScannerError {
+    #[error("Connection failed: {0}")]
+    ConnectionFailed(String),
+    #[error("No reply within {}s", .0.as_secs_f32())]
+    Timeout(Duration),
+    #[error("Scanner replied HTTP {0}")]
+    Status(u16),
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::Path;
+    use axum::response::IntoResponse;
+    use axum::routing::get;
+    use axum::Router;
+
+    /// A stand-in for the Node scanner: the same routes and reply formats
+    fn mock_scanner() -> Router {
+        Router::new()
+            .route("/version", get(|| async { "1.3.4" }))
+            .route(
+                "/cls",
+                get(|| async {
+                    "200 CLS OK\r\n\"AMB\" MOVIE 6445960 20170413102920 268 1/25\r\n\"LOGO\" STILL 1024 20240101120000 0 0/1\r\n\r\n"
+                }),
+            )
+            .route("/tls", get(|| async { "200 TLS OK\r\nCG/LOWER-THIRD\r\n\r\n" }))
+            .route("/fls", get(|| async { "200 FLS OK\r\n\"LIBERATION-SANS\"\r\n\r\n" }))
+            .route(
+                "/thumbnail",
+                get(|| async { "200 THUMBNAIL LIST OK\r\n\"AMB\" 20170413T102920 4\r\n\r\n" }),
+            )
+            .route(
+                "/thumbnail/{*name}",
+                get(|Path(name): Path<String>| async move {
+                    if name == "CLIPS/MY CLIP" {
+                        // "PNG!" in base64
+                        "201 THUMBNAIL RETRIEVE OK\r\nUE5HIQ==\r\n".into_response()
+                    } else {
+                        (StatusCode::NOT_FOUND, "404 THUMBNAIL RETRIEVE ERROR\r\n").into_response()
+                    }
+                }),
+            )
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    ""
+                }),
+            )
+    }
+
+    /// Serve `router` on a free loopback port
+    async fn serve(router: Router) -> ScannerEndpoint {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        ScannerEndpoint::new("127.0.0.1", port)
+    }
+
+    #[test]
+    fn reads_version_and_listings_from_a_scanner() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let client = ScannerClient::new(serve(mock_scanner()).await);
+            assert_eq!(client.version().await.unwrap().as_deref(), Some("1.3.4"));
+
+            let media = client.media().await.unwrap();
+            assert_eq!(media.len(), 2);
+            assert_eq!(media[0].name, "AMB");
+            assert_eq!(client.templates().await.unwrap()[0].name, "CG/LOWER-THIRD");
+            assert_eq!(client.fonts().await.unwrap()[0].name, "LIBERATION-SANS");
+            assert_eq!(client.thumbnails().await.unwrap()[0].size, 4);
+            assert_eq!(client.thumbnail("CLIPS/MY CLIP").await.unwrap(), b"PNG!");
+            assert!(matches!(
+                client.thumbnail("NOPE").await,
+                Err(ScannerError::Status(404))
+            ));
+        });
+    }
+
+    #[test]
+    fn a_scanner_without_a_version_route_is_still_up() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let router = Router::new().route("/cls", get(|| async { "200 CLS OK\r\n" }));
+            let client = ScannerClient::new(serve(router).await);
+            assert_eq!(client.version().await.unwrap(), None);
+            assert!(client.ping().await.is_ok());
+        });
+    }
+
+    #[test]
+    fn dead_and_stuck_scanners_fail_within_the_timeout() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let port = crate::ports::free_port(crate::ports::Protocol::Tcp).unwrap();
+            let dead = ScannerClient::new(ScannerEndpoint::new("127.0.0.1", port));
+            assert!(matches!(dead.version().await, Err(ScannerError::ConnectionFailed(_))));
+
+            let stuck = ScannerClient::new(serve(mock_scanner()).await)
+                .with_timeout(Duration::from_millis(200));
+            let started = std::time::Instant::now();
+            assert!(matches!(stuck.get("/slow").await, Err(ScannerError::Timeout(_))));
+            assert!(started.elapsed() < Duration::from_secs(2));
+        });
+    }
+}
@@ -0,0 +1,49 @@
+// Media scanner endpoint
+// Where the scanner listens, and the port it prefers
+
+use serde::Serialize;
+
+/// The loopback host the media scanner binds to and CasparCG queries. Loopback
+/// only — the scanner never needs to be reachable off-box, and binding all
+/// interfaces invites clashes with co-hosted services on a shared machine.
+pub const HOST: &str = "127.0.0.1";
+
+/// Preferred media-scanner port. Deliberately NOT 8000. That is CasparCG's stock
+/// port, but using it here is dangerous: on Windows a loopback bind to
+/// 127.0.0.1:8000 SUCCEEDS even while another process already holds 0.0.0.0:8000
+/// (a specific address wins over a wildcard without SO_EXCLUSIVEADDRUSE), so the
+/// scanner would silently hijack localhost:8000 — which on a shared box can be a
+/// live service behind a reverse proxy or tunnel. We never use 8000. The
+/// launch's port pre-flight moves it to a free port when it is taken.
+pub const PREFERRED_PORT: u16 = 8010;
+
+/// Where the media scanner is listening, for the GUI's endpoint readout and
+/// the scanner client
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScannerEndpoint {
+    pub host: String,
+    pub port: u16,
+    /// Whether it got the preferred port rather than a fallback
+    pub is_default: bool,
+}
+
+impl ScannerEndpoint {
+    pub fn new(host: impl Into<String>, port: u16) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            is_default: port == PREFERRED_PORT,
+        }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}:{}", self.host, self.port)
+    }
+}
+
+impl Default for ScannerEndpoint {
+    fn default() -> Self {
+        Self::new(HOST, PREFERRED_PORT)
+    }
+}
@@ -0,0 +1,167 @@
+// ffprobe and ffmpeg
+// Media probing and thumbnails for the built-in scanner, when the tools exist
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde_json::Value;
+
+use super::{MediaKind, TimeBase};
+
+/// Thumbnail width; the Node scanner's default
+const THUMBNAIL_WIDTH: u32 = 256;
+
+/// The ffprobe (and, for thumbnails, ffmpeg) the built-in scanner runs
+#[derive(Debug, Clone)]
+pub struct Ffmpeg {
+    ffprobe: PathBuf,
+    ffmpeg: Option<PathBuf>,
+}
+
+impl Ffmpeg {
+    /// ffprobe in `dir` (the CasparCG folder) or on the PATH, and ffmpeg
+    /// beside it when there is one
+    pub fn find(dir: &Path) -> Option<Self> {
+        let ffprobe = find_tool(dir, "ffprobe")?;
+        let ffmpeg = ffprobe
+            .parent()
+            .map(|d| d.join(format!("ffmpeg{}", std::env::consts::EXE_SUFFIX)))
+            .filter(|p| p.is_file())
+            .or_else(|| find_tool(dir, "ffmpeg"));
+        Some(Self { ffprobe, ffmpeg })
+    }
+
+    /// ffprobe's `-show_format -show_streams` JSON for `path`, or None if it
+    /// cannot read the file
+    pub fn probe(&self, path: &Path) -> Option<Value> {
+        let out = quiet(&self.ffprobe)
+            .args(["-v", "error", "-print_format", "json", "-show_format", "-show_streams"])
+            .arg(path)
+            .output()
+            .ok()?;
+        if !out.status.success() {
+            return None;
+        }
+        serde_json::from_slice(&out.stdout).ok()
+    }
+
+    /// A PNG thumbnail of `path`: a frame a second in for a movie, the image
+    /// itself for a still. None without ffmpeg.
+    pub fn thumbnail(&self, path: &Path, kind: MediaKind) -> Option<Vec<u8>> {
+        let ffmpeg = self.ffmpeg.as_ref()?;
+        let mut cmd = quiet(ffmpeg);
+        cmd.args(["-v", "error"]);
+        if kind == MediaKind::Movie {
+            cmd.args(["-ss", "1"]);
+        }
+        let out = cmd
+            .arg("-i")
+            .arg(path)
+            .args(["-frames:v", "1", "-vf"])
+            .arg(format!("scale={THUMBNAIL_WIDTH}:-1"))
+            .args(["-f", "image2pipe", "-vcodec", "png", "-"])
+            .output()
+            .ok()?;
+        (out.status.success() && !out.stdout.is_empty()).then_some(out.stdout)
+    }
+}
+
+/// A command with no console window and no stdin
+fn quiet(program: &Path) -> Command {
+    let mut cmd = Command::new(program);
+    cmd.stdin(Stdio::null());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    cmd
+}
+
+fn find_tool(dir: &Path, name: &str) -> Option<PathBuf> {
+    let file = format!("{name}{}", std::env::consts::EXE_SUFFIX);
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    std::iter::once(dir.to_path_buf())
+        .chain(std::env::split_paths(&path))
+        .map(|d| d.join(&file))
+        .find(|p| p.is_file())
+}
+
+/// What a CLS line says about a probed file: its kind, length in frames and
+/// time base. None when ffprobe found nothing CasparCG can play.
+pub fn cls_fields(info: &Value) -> Option<(MediaKind, u64, Option<TimeBase>)> {
+    let streams = info["streams"].as_array()?;
+    let video = streams.iter().find(|s| s["codec_type"] == "video");
+    let audio = streams.iter().any(|s| s["codec_type"] == "audio");
+    let format = info["format"]["format_name"].as_str().unwrap_or_default();
+    let duration: Option<f64> = info["format"]["duration"]
+        .as_str()
+        .and_then(|d| d.parse().ok())
+        .or_else(|| info["format"]["duration"].as_f64());
+
+    match video {
+        // Image demuxers (`image2`, `png_pipe`, ...) and single-frame video are stills
+        Some(v) if format.starts_with("image2") || format.ends_with("_pipe") || v["nb_frames"] == "1" => {
+            Some((MediaKind::Still, 0, None))
+        }
+        Some(v) => {
+            // CLS gives the time base as seconds per frame: 25/1 fps is 1/25
+            let (num, den) = v["r_frame_rate"].as_str()?.split_once('/')?;
+            let (num, den): (u32, u32) = (num.parse().ok()?, den.parse().ok()?);
+            if num == 0 || den == 0 {
+                return Some((MediaKind::Movie, 0, None));
+            }
+            let fps = f64::from(num) / f64::from(den);
+            let frames = v["nb_frames"]
+                .as_str()
+                .and_then(|n| n.parse().ok())
+                .or_else(|| duration.map(|d| (d * fps).round() as u64))
+                .unwrap_or(0);
+            Some((MediaKind::Movie, frames, Some(TimeBase { num: den, den: num })))
+        }
+        None if audio => {
+            let rate: u32 = streams
+                .iter()
+                .find(|s| s["codec_type"] == "audio")
+                .and_then(|s| s["sample_rate"].as_str()?.parse().ok())
+                .unwrap_or(48_000);
+            let frames = duration.map(|d| (d * f64::from(rate)).round() as u64).unwrap_or(0);
+            Some((MediaKind::Audio, frames, Some(TimeBase { num: 1, den: rate })))
+        }
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cls_fields_from_ffprobe() {
+        let movie = serde_json::json!({
+            "streams": [{"codec_type": "video", "r_frame_rate": "30000/1001", "nb_frames": "300"}],
+            "format": {"format_name": "mov,mp4,m4a,3gp,3g2,mj2", "duration": "10.010000"}
+        });
+        let (kind, frames, tb) = cls_fields(&movie).unwrap();
+        assert_eq!(kind, MediaKind::Movie);
+        assert_eq!(frames, 300);
+        assert_eq!(tb, Some(TimeBase { num: 1001, den: 30000 }));
+
+        let still = serde_json::json!({
+            "streams": [{"codec_type": "video", "r_frame_rate": "25/1"}],
+            "format": {"format_name": "png_pipe"}
+        });
+        assert_eq!(cls_fields(&still).unwrap().0, MediaKind::Still);
+
+        let audio = serde_json::json!({
+            "streams": [{"codec_type": "audio", "sample_rate": "48000"}],
+            "format": {"format_name": "wav", "duration": "2.0"}
+        });
+        assert_eq!(
+            cls_fields(&audio).unwrap(),
+            (MediaKind::Audio, 96_000, Some(TimeBase { num: 1, den: 48_000 }))
+        );
+        assert_eq!(cls_fields(&serde_json::json!({"streams": []})), None);
+    }
+}
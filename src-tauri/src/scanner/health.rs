@@ -0,0 +1,114 @@
+// Scanner health check
+// Decides when a running but unresponsive scanner should be relaunched
+
+use std::time::{Duration, Instant};
+
+use super::{ScannerClient, ScannerEndpoint};
+
+/// Time a fresh scanner gets to open its HTTP listener. The Node scanner
+/// indexes the media folder on start and can take a while on a big library.
+const STARTUP_GRACE: Duration = Duration::from_secs(30);
+
+/// How often a running scanner is asked for its version
+const PROBE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Unanswered probes in a row before the scanner is relaunched
+const MAX_FAILURES: u32 = 3;
+
+/// Probes the scanner's HTTP API on the supervisor's tick. A scanner process
+/// can be alive yet not serving (stuck indexing, lost its listener); CasparCG's
+/// CLS/TLS then fail just as if it had died.
+pub struct ScannerHealth {
+    client: ScannerClient,
+    started: Instant,
+    last_probe: Option<Instant>,
+    failures: u32,
+}
+
+impl ScannerHealth {
+    pub fn new(endpoint: ScannerEndpoint, now: Instant) -> Self {
+        Self {
+            client: ScannerClient::new(endpoint),
+            started: now,
+            last_probe: None,
+            failures: 0,
+        }
+    }
+
+    /// Probe when one is due. Returns the reason once the scanner has missed
+    /// enough probes in a row to be relaunched.
+    pub async fn check(&mut self, now: Instant) -> Option<String> {
+        if now.duration_since(self.started) < STARTUP_GRACE {
+            return None;
+        }
+        if self.last_probe.is_some_and(|t| now.duration_since(t) < PROBE_INTERVAL) {
+            return None;
+        }
+        self.last_probe = Some(now);
+        match self.client.ping().await {
+            Ok(()) => {
+                self.failures = 0;
+                None
+            }
+            Err(e) => {
+                self.failures += 1;
+                (self.failures >= MAX_FAILURES)
+                    .then(|| format!("{} probes unanswered ({e})", self.failures))
+            }
+        }
+    }
+
+    /// A new scanner process was launched: start its grace period over
+    pub fn restarted(&mut self, now: Instant) {
+        self.started = now;
+        self.last_probe = None;
+        self.failures = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ports::{free_port, Protocol};
+
+    #[test]
+    fn relaunches_after_repeated_misses_past_the_grace_period() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let port = free_port(Protocol::Tcp).unwrap();
+            let start = Instant::now();
+            let mut health = ScannerHealth::new(ScannerEndpoint::new("127.0.0.1", port), start);
+
+            // Still starting up: not probed
+            assert_eq!(health.check(start + Duration::from_secs(10)).await, None);
+
+            let mut now = start + STARTUP_GRACE;
+            assert_eq!(health.check(now).await, None);
+            // Too soon for the next probe
+            assert_eq!(health.check(now + Duration::from_secs(1)).await, None);
+            now += PROBE_INTERVAL;
+            assert_eq!(health.check(now).await, None);
+            now += PROBE_INTERVAL;
+            assert!(health.check(now).await.unwrap().contains("3 probes"));
+
+            health.restarted(now);
+            assert_eq!(health.check(now + PROBE_INTERVAL).await, None);
+        });
+    }
+
+    #[test]
+    fn an_answering_scanner_is_healthy() {
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+            // No /version route: a 404 still proves it is serving
+            tokio::spawn(async move { axum::serve(listener, axum::Router::new()).await });
+
+            let start = Instant::now();
+            let mut health = ScannerHealth::new(ScannerEndpoint::new("127.0.0.1", port), start);
+            for i in 0..5 {
+                let now = start + STARTUP_GRACE + PROBE_INTERVAL * i;
+                assert_eq!(health.check(now).await, None);
+            }
+        });
+    }
+}
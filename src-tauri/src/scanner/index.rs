@@ -0,0 +1,294 @@
+// Built-in scanner index
+// Walks the media, template and font folders and keeps what it found
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local, NaiveDateTime, Timelike};
+use serde_json::Value;
+
+use super::ffmpeg::{cls_fields, Ffmpeg};
+use super::{FontItem, MediaItem, MediaKind, TemplateItem};
+
+const MOVIE_EXTENSIONS: &[&str] = &[
+    "mov", "mp4", "m4v", "mxf", "avi", "mkv", "webm", "mpg", "mpeg", "m2ts", "ts", "wmv", "flv",
+    "dv", "gxf", "y4m",
+];
+const STILL_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "tga", "bmp", "tif", "tiff", "gif", "webp", "exr", "dpx",
+];
+const AUDIO_EXTENSIONS: &[&str] = &["wav", "mp3", "aac", "flac", "ogg", "m4a", "aif", "aiff", "opus", "wma"];
+const TEMPLATE_EXTENSIONS: &[&str] = &["html", "htm", "ft", "wt", "ct"];
+const FONT_EXTENSIONS: &[&str] = &["ttf", "otf", "ttc"];
+
+/// The folders the built-in scanner serves, resolved against the CasparCG folder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanRoots {
+    pub media: PathBuf,
+    pub template: PathBuf,
+    pub font: Option<PathBuf>,
+}
+
+impl ScanRoots {
+    /// The profile's `paths`, relative ones taken from `dir` as CasparCG does
+    pub fn resolve(paths: &crate::config::Paths, dir: &Path) -> Self {
+        let resolve = |p: &str| dir.join(p);
+        Self {
+            media: resolve(&paths.media),
+            template: resolve(&paths.template),
+            font: paths.font.as_deref().filter(|f| !f.is_empty()).map(resolve),
+        }
+    }
+}
+
+/// One playable file
+#[derive(Debug, Clone)]
+pub struct IndexedMedia {
+    pub item: MediaItem,
+    pub path: PathBuf,
+    /// ffprobe's JSON, when ffprobe ran
+    pub probe: Option<Value>,
+    /// When the thumbnail was made, and the PNG
+    pub thumbnail: Option<(NaiveDateTime, Vec<u8>)>,
+}
+
+/// Everything the built-in scanner has found, by CasparCG name
+#[derive(Debug, Default)]
+pub struct ScanIndex {
+    pub media: BTreeMap<String, IndexedMedia>,
+    pub templates: BTreeMap<String, TemplateItem>,
+    pub fonts: BTreeMap<String, FontItem>,
+}
+
+/// A file found by a walk: its CasparCG name, path, size and time
+#[derive(Debug, Clone)]
+pub struct FoundFile {
+    pub id: String,
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: Option<NaiveDateTime>,
+}
+
+/// Every file under `root` with one of `extensions`, named the way CasparCG
+/// names it: upper case, relative, `/`-separated, without the extension
+pub fn walk(root: &Path, extensions: &[&str]) -> Vec<FoundFile> {
+    let mut found = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(meta) = entry.metadata() else { continue };
+            if meta.is_dir() {
+                dirs.push(path);
+            } else if extension_in(&path, extensions) {
+                if let Some(id) = file_id(root, &path) {
+                    found.push(FoundFile {
+                        id,
+                        size: meta.len(),
+                        modified: meta.modified().ok().map(local_time),
+                        path,
+                    });
+                }
+            }
+        }
+    }
+    found.sort_by(|a, b| a.id.cmp(&b.id));
+    found
+}
+
+fn extension_in(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| extensions.iter().any(|x| x.eq_ignore_ascii_case(e)))
+}
+
+fn file_id(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?.with_extension("");
+    let parts: Vec<_> = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+    Some(parts.join("/").to_uppercase())
+}
+
+/// File times in local time to the second, as the Node scanner reports them
+fn local_time(time: SystemTime) -> NaiveDateTime {
+    let time = DateTime::<Local>::from(time).naive_local();
+    time.with_nanosecond(0).unwrap_or(time)
+}
+
+/// What the extension alone says a media file is
+pub fn kind_from_extension(path: &Path) -> Option<MediaKind> {
+    if extension_in(path, MOVIE_EXTENSIONS) {
+        Some(MediaKind::Movie)
+    } else if extension_in(path, STILL_EXTENSIONS) {
+        Some(MediaKind::Still)
+    } else if extension_in(path, AUDIO_EXTENSIONS) {
+        Some(MediaKind::Audio)
+    } else {
+        None
+    }
+}
+
+/// All the extensions the media folder is walked for
+pub fn media_extensions() -> Vec<&'static str> {
+    [MOVIE_EXTENSIONS, STILL_EXTENSIONS, AUDIO_EXTENSIONS].concat()
+}
+
+/// Classify a found media file: by ffprobe when there is one, else (or when
+/// it cannot read the file) by extension
+pub fn index_media(file: &FoundFile, ffmpeg: Option<&Ffmpeg>) -> Option<IndexedMedia> {
+    let probe = ffmpeg.and_then(|f| f.probe(&file.path));
+    let (kind, frames, time_base) = match probe.as_ref().and_then(cls_fields) {
+        Some(fields) => fields,
+        None => (kind_from_extension(&file.path)?, 0, None),
+    };
+    let thumbnail = match (ffmpeg, kind) {
+        (Some(f), MediaKind::Movie | MediaKind::Still) => f
+            .thumbnail(&file.path, kind)
+            .map(|png| (local_time(SystemTime::now()), png)),
+        _ => None,
+    };
+    Some(IndexedMedia {
+        item: MediaItem {
+            name: file.id.clone(),
+            kind,
+            size: file.size,
+            modified: file.modified,
+            frames,
+            time_base,
+        },
+        path: file.path.clone(),
+        probe,
+        thumbnail,
+    })
+}
+
+pub fn template_extensions() -> &'static [&'static str] {
+    TEMPLATE_EXTENSIONS
+}
+
+pub fn font_extensions() -> &'static [&'static str] {
+    FONT_EXTENSIONS
+}
+
+/// A template's TLS entry; HTML for `.html`/`.htm`, else Flash
+pub fn template_item(file: &FoundFile) -> TemplateItem {
+    let html = extension_in(&file.path, &["html", "htm"]);
+    TemplateItem {
+        name: file.id.clone(),
+        size: Some(file.size),
+        modified: file.modified,
+        kind: Some(if html { "HTML" } else { "FLASH" }.to_string()),
+    }
+}
+
+/// A font's FLS entry: its name and file relative to the font folder
+pub fn font_item(file: &FoundFile, root: &Path) -> FontItem {
+    FontItem {
+        name: file.id.clone(),
+        file: file
+            .path
+            .strip_prefix(root)
+            .ok()
+            .map(|p| p.to_string_lossy().replace('\\', "/")),
+    }
+}
+
+const TIMESTAMP: &str = "%Y%m%d%H%M%S";
+
+/// `"AMB" MOVIE 6445960 20170413102920 268 1/25`
+pub fn cls_line(item: &MediaItem) -> String {
+    let kind = match item.kind {
+        MediaKind::Movie => "MOVIE",
+        MediaKind::Still => "STILL",
+        MediaKind::Audio => "AUDIO",
+    };
+    let time_base = item
+        .time_base
+        .map_or_else(|| "0/1".to_string(), |tb| format!("{}/{}", tb.num, tb.den));
+    format!(
+        "\"{}\" {} {} {} {} {}",
+        item.name,
+        kind,
+        item.size,
+        stamp(item.modified),
+        item.frames,
+        time_base
+    )
+}
+
+/// `"CG/LOWER-THIRD" 2049 20240101120000 HTML`
+pub fn tls_line(item: &TemplateItem) -> String {
+    format!(
+        "\"{}\" {} {} {}",
+        item.name,
+        item.size.unwrap_or(0),
+        stamp(item.modified),
+        item.kind.as_deref().unwrap_or("HTML")
+    )
+}
+
+/// `"LIBERATION-SANS" LiberationSans-Regular.ttf`
+pub fn fls_line(item: &FontItem) -> String {
+    match &item.file {
+        Some(file) => format!("\"{}\" {}", item.name, file),
+        None => format!("\"{}\"", item.name),
+    }
+}
+
+fn stamp(time: Option<NaiveDateTime>) -> String {
+    time.map_or_else(|| "0".repeat(14), |t| t.format(TIMESTAMP).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{parse_cls, parse_fls, parse_tls};
+
+    #[test]
+    fn walks_and_names_files_the_way_casparcg_does() {
+        let root = std::env::temp_dir().join(format!("caspar-scan-walk-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("news/opener")).unwrap();
+        std::fs::write(root.join("news/opener/Intro Take2.mov"), b"x").unwrap();
+        std::fs::write(root.join("logo.PNG"), b"xy").unwrap();
+        std::fs::write(root.join("notes.txt"), b"no").unwrap();
+
+        let found = walk(&root, &media_extensions());
+        let ids: Vec<_> = found.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(ids, ["LOGO", "NEWS/OPENER/INTRO TAKE2"]);
+
+        let logo = index_media(&found[0], None).unwrap();
+        assert_eq!(logo.item.kind, MediaKind::Still);
+        assert_eq!(logo.item.size, 2);
+        let line = cls_line(&index_media(&found[1], None).unwrap().item);
+        let parsed = parse_cls(&format!("200 CLS OK\r\n{line}\r\n")).unwrap();
+        assert_eq!(parsed[0].name, "NEWS/OPENER/INTRO TAKE2");
+        assert_eq!(parsed[0].kind, MediaKind::Movie);
+        assert_eq!(parsed[0].modified, found[1].modified);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn template_and_font_lines_round_trip() {
+        let file = FoundFile {
+            id: "CG/LOWER THIRD".to_string(),
+            path: PathBuf::from("/t/cg/lower third.html"),
+            size: 2049,
+            modified: NaiveDateTime::parse_from_str("20240101120000", TIMESTAMP).ok(),
+        };
+        let tls = tls_line(&template_item(&file));
+        assert_eq!(tls, "\"CG/LOWER THIRD\" 2049 20240101120000 HTML");
+        assert_eq!(parse_tls(&tls).unwrap()[0], template_item(&file));
+
+        let font = font_item(
+            &FoundFile {
+                id: "SANS".to_string(),
+                path: PathBuf::from("/f/Sans.ttf"),
+                size: 1,
+                modified: None,
+            },
+            Path::new("/f"),
+        );
+        assert_eq!(parse_fls(&fls_line(&font)).unwrap()[0], font);
+    }
+}
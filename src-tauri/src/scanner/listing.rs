@@ -0,0 +1,248 @@
+// Scanner listings
+// Typed CLS/TLS/FLS/thumbnail listings parsed from the scanner's text replies
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use super::ScannerError;
+
+/// What CLS says a clip is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaKind {
+    Movie,
+    Still,
+    Audio,
+}
+
+/// CLS time base: `num/den` seconds per frame (`1/25`, `1001/30000`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TimeBase {
+    pub num: u32,
+    pub den: u32,
+}
+
+impl TimeBase {
+    /// Frames per second, when the time base is not zero
+    pub fn fps(self) -> Option<f64> {
+        (self.num > 0 && self.den > 0).then(|| f64::from(self.den) / f64::from(self.num))
+    }
+}
+
+/// One clip from `GET /cls`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MediaItem {
+    /// CasparCG's name for it: upper case, relative to the media folder, no extension
+    pub name: String,
+    pub kind: MediaKind,
+    /// File size in bytes
+    pub size: u64,
+    pub modified: Option<NaiveDateTime>,
+    /// Duration in frames (0 for stills)
+    pub frames: u64,
+    pub time_base: Option<TimeBase>,
+}
+
+impl MediaItem {
+    pub fn frame_rate(&self) -> Option<f64> {
+        self.time_base.and_then(TimeBase::fps)
+    }
+
+    pub fn duration_secs(&self) -> Option<f64> {
+        self.frame_rate().map(|fps| self.frames as f64 / fps)
+    }
+}
+
+/// One template from `GET /tls`. Older scanners list the name only.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TemplateItem {
+    pub name: String,
+    pub size: Option<u64>,
+    pub modified: Option<NaiveDateTime>,
+    /// `HTML` or `FLASH`, when reported
+    pub kind: Option<String>,
+}
+
+/// One font from `GET /fls`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FontItem {
+    pub name: String,
+    /// The font file, when reported
+    pub file: Option<String>,
+}
+
+/// One entry of `GET /thumbnail`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ThumbnailInfo {
+    pub name: String,
+    pub modified: Option<NaiveDateTime>,
+    /// PNG size in bytes
+    pub size: u64,
+}
+
+/// The lines of a listing after its AMCP-style status line (`200 CLS OK`).
+/// A 4xx/5xx status line is an error, even when HTTP said 200.
+pub(super) fn listing_lines(body: &str) -> Result<impl Iterator<Item = &str>, ScannerError> {
+    let mut lines = body.lines().map(str::trim).filter(|l| !l.is_empty()).peekable();
+    if let Some(first) = lines.peek() {
+        if let Some(code) = status_code(first) {
+            if code >= 400 {
+                return Err(ScannerError::InvalidResponse(first.to_string()));
+            }
+            lines.next();
+        }
+    }
+    Ok(lines)
+}
+
+/// The code of an AMCP-style status line such as `200 CLS OK`
+pub(super) fn status_code(line: &str) -> Option<u16> {
+    let (code, rest) = line.split_once(' ')?;
+    if code.len() != 3 || rest.is_empty() || rest.starts_with('"') {
+        return None;
+    }
+    code.parse().ok()
+}
+
+/// Split a listing line into its fields: the first may be quoted and contain
+/// spaces, the rest are separated by whitespace.
+fn fields(line: &str) -> Vec<&str> {
+    let line = line.trim();
+    let (first, rest) = match line.strip_prefix('"') {
+        Some(quoted) => match quoted.split_once('"') {
+            Some((name, rest)) => (name, rest),
+            None => (quoted, ""),
+        },
+        None => line.split_once(char::is_whitespace).unwrap_or((line, "")),
+    };
+    std::iter::once(first).chain(rest.split_whitespace()).collect()
+}
+
+/// `20170413102920` (CLS) or `20170413T102920` (thumbnail list)
+fn timestamp(field: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(field, "%Y%m%d%H%M%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(field, "%Y%m%dT%H%M%S"))
+        .ok()
+}
+
+fn time_base(field: &str) -> Option<TimeBase> {
+    let (num, den) = field.split_once('/')?;
+    Some(TimeBase {
+        num: num.parse().ok()?,
+        den: den.parse().ok()?,
+    })
+}
+
+/// `"AMB" MOVIE 6445960 20170413102920 268 1/25`
+pub fn parse_cls(body: &str) -> Result<Vec<MediaItem>, ScannerError> {
+    listing_lines(body)?
+        .map(|line| {
+            let f = fields(line);
+            let kind = match f.get(1).copied() {
+                Some("MOVIE") => MediaKind::Movie,
+                Some("STILL") => MediaKind::Still,
+                Some("AUDIO") => MediaKind::Audio,
+                _ => return Err(ScannerError::InvalidResponse(format!("CLS line: {line}"))),
+            };
+            Ok(MediaItem {
+                name: f[0].to_string(),
+                kind,
+                size: f.get(2).and_then(|s| s.parse().ok()).unwrap_or(0),
+                modified: f.get(3).and_then(|s| timestamp(s)),
+                frames: f.get(4).and_then(|s| s.parse().ok()).unwrap_or(0),
+                time_base: f.get(5).and_then(|s| time_base(s)),
+            })
+        })
+        .collect()
+}
+
+/// `"CG/LOWER-THIRD"`, or `"CG/LOWER-THIRD" 2049 20240101120000 HTML`
+pub fn parse_tls(body: &str) -> Result<Vec<TemplateItem>, ScannerError> {
+    Ok(listing_lines(body)?
+        .map(|line| {
+            let f = fields(line);
+            TemplateItem {
+                name: f[0].to_string(),
+                size: f.get(1).and_then(|s| s.parse().ok()),
+                modified: f.get(2).and_then(|s| timestamp(s)),
+                kind: f.get(3).map(|s| s.to_string()),
+            }
+        })
+        .collect())
+}
+
+/// `"LIBERATION-SANS"`, optionally followed by the file
+pub fn parse_fls(body: &str) -> Result<Vec<FontItem>, ScannerError> {
+    Ok(listing_lines(body)?
+        .map(|line| {
+            let f = fields(line);
+            FontItem {
+                name: f[0].to_string(),
+                file: (f.len() > 1).then(|| f[1..].join(" ")),
+            }
+        })
+        .collect())
+}
+
+/// `"AMB" 20170413T102920 2318`
+pub fn parse_thumbnail_list(body: &str) -> Result<Vec<ThumbnailInfo>, ScannerError> {
+    Ok(listing_lines(body)?
+        .map(|line| {
+            let f = fields(line);
+            ThumbnailInfo {
+                name: f[0].to_string(),
+                modified: f.get(1).and_then(|s| timestamp(s)),
+                size: f.get(2).and_then(|s| s.parse().ok()).unwrap_or(0),
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_cls_with_quoted_names() {
+        let body = "200 CLS OK\r\n\
+            \"AMB\" MOVIE 6445960 20170413102920 268 1/25\r\n\
+            \"STILLS/LOGO BIG\" STILL 1024 20240101120000 0 0/1\r\n\
+            \"AUDIO/TONE\" AUDIO 88200 20240101120000 480 1001/24000\r\n\r\n";
+        let media = parse_cls(body).unwrap();
+        assert_eq!(media.len(), 3);
+        assert_eq!(media[0].name, "AMB");
+        assert_eq!(media[0].kind, MediaKind::Movie);
+        assert_eq!(media[0].size, 6_445_960);
+        assert_eq!(media[0].frames, 268);
+        assert_eq!(media[0].frame_rate(), Some(25.0));
+        assert_eq!(media[0].duration_secs(), Some(10.72));
+        assert_eq!(
+            media[0].modified.unwrap().to_string(),
+            "2017-04-13 10:29:20"
+        );
+        assert_eq!(media[1].name, "STILLS/LOGO BIG");
+        assert_eq!(media[1].frame_rate(), None);
+        assert!((media[2].frame_rate().unwrap() - 23.976).abs() < 0.001);
+    }
+
+    #[test]
+    fn parses_old_and_new_template_listings() {
+        let old = parse_tls("200 TLS OK\r\nCG/LOWER-THIRD\r\n\"CG/FULL SCREEN\"\r\n").unwrap();
+        assert_eq!(old[0].name, "CG/LOWER-THIRD");
+        assert_eq!(old[1].name, "CG/FULL SCREEN");
+        assert_eq!(old[1].kind, None);
+        let new = parse_tls("200 TLS OK\r\n\"CG/LT\" 2049 20240101120000 HTML\r\n").unwrap();
+        assert_eq!(new[0].size, Some(2049));
+        assert_eq!(new[0].kind.as_deref(), Some("HTML"));
+    }
+
+    #[test]
+    fn error_status_line_is_an_error() {
+        assert!(parse_cls("500 FAILED\r\n").is_err());
+        assert!(parse_cls("200 CLS OK\r\n\"X\" VIDEO 1\r\n").is_err());
+        assert!(parse_fls("").unwrap().is_empty());
+        let thumbs = parse_thumbnail_list("200 THUMBNAIL LIST OK\r\n\"AMB\" 20170413T102920 2318\r\n").unwrap();
+        assert_eq!(thumbs[0].size, 2318);
+        assert!(thumbs[0].modified.is_some());
+    }
+}
@@ -0,0 +1,125 @@
+// Media details
+// Resolution, frame rate, codec and duration from ffprobe-style JSON
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::ScannerError;
+
+/// What ffprobe says about a clip beyond the CLS line
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MediaInfo {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub frame_rate: Option<f64>,
+    /// Video codec, else audio codec, e.g. `h264` or `prores`
+    pub codec: Option<String>,
+    pub duration_secs: Option<f64>,
+    /// ffprobe's field order: `progressive`, `tt`, `bb`, `tb` or `bt`
+    pub field_order: Option<String>,
+}
+
+impl MediaInfo {
+    /// Read ffprobe's `-show_streams -show_format` JSON, or the scanner's copy
+    /// of it (which nests `codec_type`/`codec_name` under `codec`)
+    pub fn from_ffprobe(info: &Value) -> Self {
+        let streams = info["streams"].as_array().map(Vec::as_slice).unwrap_or_default();
+        let of_type = |kind: &str| {
+            streams.iter().find(|s| {
+                s["codec_type"].as_str().or(s["codec"]["type"].as_str()) == Some(kind)
+            })
+        };
+        let video = of_type("video");
+        let codec_of = |s: &Value| {
+            s["codec_name"]
+                .as_str()
+                .or(s["codec"]["name"].as_str())
+                .or(s["codec"]["long_name"].as_str())
+                .map(str::to_string)
+        };
+        let number = |v: &Value| v.as_f64().or_else(|| v.as_str()?.parse().ok());
+        Self {
+            width: video.and_then(|v| v["width"].as_u64()).map(|w| w as u32),
+            height: video.and_then(|v| v["height"].as_u64()).map(|h| h as u32),
+            frame_rate: video.and_then(|v| {
+                ratio(&v["avg_frame_rate"]).or_else(|| ratio(&v["r_frame_rate"]))
+            }),
+            codec: video.or_else(|| of_type("audio")).and_then(codec_of),
+            duration_secs: number(&info["format"]["duration"])
+                .or_else(|| video.and_then(|v| number(&v["duration"]))),
+            field_order: info["field_order"]
+                .as_str()
+                .or_else(|| video.and_then(|v| v["field_order"].as_str()))
+                .filter(|o| *o != "unknown")
+                .map(str::to_string),
+        }
+    }
+
+    /// Whether the clip is interlaced, when the field order is known
+    pub fn interlaced(&self) -> Option<bool> {
+        self.field_order.as_deref().map(|o| o != "progressive")
+    }
+}
+
+/// `"25/1"` or `"30000/1001"` as a number; `0/0` (unknown) as `None`
+fn ratio(v: &Value) -> Option<f64> {
+    let (num, den) = v.as_str()?.split_once('/')?;
+    let (num, den): (f64, f64) = (num.parse().ok()?, den.parse().ok()?);
+    (num > 0.0 && den > 0.0).then(|| num / den)
+}
+
+/// The scanner's `GET /media`: one document per clip, its `_id` the CLS name
+/// and its `mediainfo` the ffprobe result
+pub fn parse_media_docs(body: &str) -> Result<Vec<(String, MediaInfo)>, ScannerError> {
+    let docs: Value = serde_json::from_str(body)
+        .map_err(|e| ScannerError::InvalidResponse(format!("media documents: {e}")))?;
+    let docs = docs
+        .as_array()
+        .ok_or_else(|| ScannerError::InvalidResponse("media documents: not a list".to_string()))?;
+    Ok(docs
+        .iter()
+        .filter_map(|doc| {
+            let id = doc["_id"].as_str().or(doc["id"].as_str())?;
+            let info = doc.get("mediainfo")?;
+            Some((id.to_uppercase(), MediaInfo::from_ffprobe(info)))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_ffprobe_and_scanner_documents() {
+        let ffprobe = serde_json::json!({
+            "streams": [
+                {"codec_type": "audio", "codec_name": "pcm_s24le"},
+                {"codec_type": "video", "codec_name": "prores", "width": 1920, "height": 1080,
+                 "r_frame_rate": "25/1", "avg_frame_rate": "25/1", "field_order": "tt"}
+            ],
+            "format": {"duration": "12.480000"}
+        });
+        let info = MediaInfo::from_ffprobe(&ffprobe);
+        assert_eq!((info.width, info.height), (Some(1920), Some(1080)));
+        assert_eq!(info.frame_rate, Some(25.0));
+        assert_eq!(info.codec.as_deref(), Some("prores"));
+        assert_eq!(info.duration_secs, Some(12.48));
+        assert_eq!(info.interlaced(), Some(true));
+
+        let docs = r#"[
+            {"_id": "AMB", "mediainfo": {"field_order": "progressive", "streams": [
+                {"codec": {"type": "video", "long_name": "H.264"}, "width": 1280, "height": 720,
+                 "avg_frame_rate": "0/0", "r_frame_rate": "60000/1001"}],
+              "format": {"duration": 10.72}}},
+            {"_id": "NO-INFO"}
+        ]"#;
+        let parsed = parse_media_docs(docs).unwrap();
+        assert_eq!(parsed.len(), 1);
+        let (name, info) = &parsed[0];
+        assert_eq!(name, "AMB");
+        assert_eq!(info.codec.as_deref(), Some("H.264"));
+        assert!((info.frame_rate.unwrap() - 59.94).abs() < 0.01);
+        assert_eq!(info.interlaced(), Some(false));
+    }
+}
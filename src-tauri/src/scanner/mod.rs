@@ -0,0 +1,21 @@
+// Media scanner
+// Endpoint, HTTP client and health check for CasparCG's media scanner, and
+// a built-in scanner serving the same API
+
+mod builtin;
+mod client;
+mod endpoint;
+mod ffmpeg;
+mod health;
+mod index;
+mod listing;
+mod mediainfo;
+
+pub use builtin::*;
+pub use client::*;
+pub use endpoint::*;
+pub use ffmpeg::*;
+pub use health::*;
+pub use index::*;
+pub use listing::*;
+pub use mediainfo::*;
@@ -0,0 +1,69 @@
+// Server exit history
+// Remembers recent exits and what the supervisor did about each
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Exits kept for crash reports; older ones are dropped.
+const MAX_EXITS: usize = 50;
+
+/// One server exit as the supervisor saw it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExitRecord {
+    pub timestamp: DateTime<Utc>,
+    /// Exit code, when the process reported one
+    pub code: Option<i32>,
+    /// The watchdog killed the server for not responding
+    pub hung: bool,
+    /// How long that run lasted
+    pub uptime_secs: f64,
+    /// What the supervisor did: "restart", "stand" or "give_up"
+    pub action: String,
+    /// Why it exited, from `RestartPolicy::exit_reason`
+    #[serde(default)]
+    pub reason: String,
+}
+
+impl ExitRecord {
+    /// A crash rather than a clean, fatal or requested exit
+    pub fn is_crash(&self) -> bool {
+        matches!(self.reason.as_str(), "crash" | "hung" | "unknown")
+    }
+}
+
+/// Recent exits across supervisor runs, oldest first, plus running totals
+#[derive(Debug, Default)]
+pub struct ExitHistory {
+    exits: Mutex<VecDeque<ExitRecord>>,
+    /// Exits since launch by (action, reason); never trimmed
+    totals: Mutex<BTreeMap<(String, String), u64>>,
+}
+
+impl ExitHistory {
+    pub fn record(&self, record: ExitRecord) {
+        *self
+            .totals
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry((record.action.clone(), record.reason.clone()))
+            .or_insert(0) += 1;
+        let mut exits = self.exits.lock().unwrap_or_else(|e| e.into_inner());
+        if exits.len() == MAX_EXITS {
+            exits.pop_front();
+        }
+        exits.push_back(record);
+    }
+
+    pub fn recent(&self) -> Vec<ExitRecord> {
+        let exits = self.exits.lock().unwrap_or_else(|e| e.into_inner());
+        exits.iter().cloned().collect()
+    }
+
+    /// Exits since launch, counted by (action, reason)
+    pub fn totals(&self) -> BTreeMap<(String, String), u64> {
+        self.totals.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
@@ -0,0 +1,718 @@
+// Launcher
+// Starts, supervises and stops CasparCG and its scanner, without any GUI
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::Mutex;
+
+use super::process::{self, kill_process_tree};
+use super::{ExitHistory, ExitRecord, RestartPolicy};
+use crate::config::{
+    generate_caspar_xml, parse_caspar_xml, GlobalConfig, MediaServer, OscClient, ScannerMode,
+};
+use crate::crash_report::{self, CrashReport};
+use crate::diagnostics::{Diagnostic, DiagnosticsEngine};
+use crate::logs::{LogEntry, LogLevel, LogSource, LogStore};
+use crate::ports::{PortPreflight, PortRegistry, PortRole, PortStatus, SystemProber};
+use crate::resources::{ResourceAlert, ResourceHistory};
+use crate::scanner::{self, BuiltinScanner, Ffmpeg, ScanRoots, ScannerEndpoint};
+use crate::system;
+use crate::watchdog::OscActivity;
+
+/// Console lines per process included in a crash report.
+const CRASH_REPORT_LINES: usize = 500;
+
+/// Something the launcher reports to whoever is watching it: the GUI forwards
+/// these as Tauri events, the headless supervisor prints them.
+#[derive(Debug, Clone)]
+pub enum LauncherEvent {
+    Log(LogEntry),
+    Diagnostic(Diagnostic),
+    /// The scanner came up (`Some`) or went away (`None`)
+    ScannerEndpoint(Option<ScannerEndpoint>),
+    /// A crash report was written to this path
+    CrashReport(PathBuf),
+    /// A profile was made active from outside the GUI (the REST API)
+    ProfileActivated(String),
+    /// casparcg.exe was launched: by Start, or relaunched by the supervisor
+    ServerStarted { profile: String, restart: bool },
+    /// casparcg.exe exited on its own, and what the supervisor did about it
+    ServerExit(ExitRecord),
+    /// A Stop ended the server
+    ServerStopped,
+    /// The supervisor stopped keeping the server up, and why
+    StoodDown(String),
+    /// A resource alert rule tripped or cleared
+    ResourceAlert(ResourceAlert),
+    /// The ports a Start checked, and what it did about clashes
+    PortPreflight(PortPreflight),
+}
+
+impl LauncherEvent {
+    /// The event name the GUI listens for
+    pub fn name(&self) -> &'static str {
+        match self {
+            LauncherEvent::Log(_) => "caspar-log",
+            LauncherEvent::Diagnostic(_) => "diagnostic",
+            LauncherEvent::ScannerEndpoint(_) => "scanner-endpoint",
+            LauncherEvent::CrashReport(_) => "crash-report",
+            LauncherEvent::ProfileActivated(_) => "profile-activated",
+            LauncherEvent::ServerStarted { .. } => "server-started",
+            LauncherEvent::ServerExit(_) => "server-exit",
+            LauncherEvent::ServerStopped => "server-stopped",
+            LauncherEvent::StoodDown(_) => "server-stood-down",
+            LauncherEvent::ResourceAlert(_) => "resource-alert",
+            LauncherEvent::PortPreflight(_) => "port-preflight",
+        }
+    }
+
+    pub fn payload(&self) -> serde_json::Value {
+        let value = match self {
+            LauncherEvent::Log(entry) => serde_json::to_value(entry),
+            LauncherEvent::Diagnostic(diagnostic) => serde_json::to_value(diagnostic),
+            LauncherEvent::ScannerEndpoint(endpoint) => serde_json::to_value(endpoint),
+            LauncherEvent::CrashReport(path) => serde_json::to_value(path.to_string_lossy()),
+            LauncherEvent::ProfileActivated(name) => serde_json::to_value(name),
+            LauncherEvent::ServerStarted { profile, restart } => {
+                Ok(serde_json::json!({ "profile": profile, "restart": restart }))
+            }
+            LauncherEvent::ServerExit(record) => serde_json::to_value(record),
+            LauncherEvent::ServerStopped => Ok(serde_json::Value::Null),
+            LauncherEvent::StoodDown(reason) => serde_json::to_value(reason),
+            LauncherEvent::ResourceAlert(alert) => serde_json::to_value(alert),
+            LauncherEvent::PortPreflight(preflight) => serde_json::to_value(preflight),
+        };
+        value.unwrap_or(serde_json::Value::Null)
+    }
+}
+
+/// Receives every `LauncherEvent`. Called from console reader threads as well as
+/// the supervisor task, so it must be quick and thread-safe.
+pub type EventSink = Arc<dyn Fn(LauncherEvent) + Send + Sync>;
+
+/// Owns the launched server and scanner and everything recorded about them.
+/// Shared as `Arc<Launcher>` between the GUI commands (or the headless
+/// supervisor) and the supervisor task it spawns.
+pub struct Launcher {
+    /// Structured console history of the server, scanner and launcher
+    pub logs: LogStore,
+    /// Known-failure matcher run over every log line and server exit
+    pub diagnostics: DiagnosticsEngine,
+    /// Recent server exits and the supervisor's response, for crash reports
+    pub exits: ExitHistory,
+    /// CPU, memory, thread and handle samples of the server and scanner trees
+    pub resources: ResourceHistory,
+    /// Every port the launch and the GUI listen on
+    pub ports: PortRegistry,
+    /// The launched CasparCG server process, if running
+    pub(super) caspar_process: Mutex<Option<std::process::Child>>,
+    /// The media scanner process launched alongside the server, if running
+    pub(super) scanner_process: Mutex<Option<std::process::Child>>,
+    /// The in-process scanner, when the profile runs the built-in one
+    builtin_scanner: Mutex<Option<BuiltinScanner>>,
+    /// Desired state: true while the server should be supervised. Start sets it,
+    /// Stop/app-exit/give-up clear it. The supervisor consults it so a user Stop
+    /// is never mistaken for a crash and never triggers a restart.
+    should_run: AtomicBool,
+    /// Bumped by every Start. A supervisor task only acts while its own Start is
+    /// the latest, so a quick Stop→Start never leaves two supervising one server.
+    generation: AtomicU64,
+    /// True from Start until the latest supervisor task has finished, including
+    /// any crash report it writes on the way out
+    supervising: AtomicBool,
+    /// Name of the profile the latest Start launched
+    profile: std::sync::Mutex<Option<String>>,
+    /// AMCP port of the latest Start's profile
+    amcp_port: std::sync::Mutex<Option<u16>>,
+    /// When the current casparcg.exe was launched
+    launched_at: std::sync::Mutex<Option<Instant>>,
+    /// Where the scanner the latest Start launched is listening
+    scanner_endpoint: std::sync::Mutex<Option<ScannerEndpoint>>,
+    /// The watchdog's OSC counters for the current Start, when it listens
+    pub(super) osc: std::sync::Mutex<Option<Arc<OscActivity>>>,
+    crash_reports_dir: Option<PathBuf>,
+    sink: EventSink,
+}
+
+impl Launcher {
+    pub fn new(
+        logs: LogStore,
+        diagnostics: DiagnosticsEngine,
+        crash_reports_dir: Option<PathBuf>,
+        sink: EventSink,
+    ) -> Self {
+        Self {
+            logs,
+            diagnostics,
+            exits: ExitHistory::default(),
+            resources: ResourceHistory::default(),
+            ports: PortRegistry::default(),
+            caspar_process: Mutex::new(None),
+            scanner_process: Mutex::new(None),
+            builtin_scanner: Mutex::new(None),
+            should_run: AtomicBool::new(false),
+            generation: AtomicU64::new(0),
+            supervising: AtomicBool::new(false),
+            profile: std::sync::Mutex::new(None),
+            amcp_port: std::sync::Mutex::new(None),
+            launched_at: std::sync::Mutex::new(None),
+            scanner_endpoint: std::sync::Mutex::new(None),
+            osc: std::sync::Mutex::new(None),
+            crash_reports_dir,
+            sink,
+        }
+    }
+
+    pub fn emit(&self, event: LauncherEvent) {
+        (self.sink)(event);
+    }
+
+    /// Record a console line from `source`, checking it against the diagnostics
+    /// catalogue.
+    pub fn record(&self, source: LogSource, raw: &str) {
+        let entry = self.logs.record(source, raw);
+        let diagnostic = self.diagnostics.check_line(&entry);
+        self.emit(LauncherEvent::Log(entry));
+        if let Some(diagnostic) = diagnostic {
+            self.emit(LauncherEvent::Diagnostic(diagnostic));
+        }
+    }
+
+    /// Record a message from the launcher itself.
+    pub fn log(&self, level: LogLevel, text: impl Into<String>) {
+        let entry = self.logs.launcher(level, text);
+        self.emit(LauncherEvent::Log(entry));
+    }
+
+    /// Explain a launcher event (e.g. a watchdog hang) against the catalogue,
+    /// attaching the server's last console lines.
+    pub fn diagnose_event(&self, event: &str, reason: &str) {
+        let last_output = self
+            .logs
+            .tail(LogSource::Server, 20)
+            .into_iter()
+            .map(|e| e.text)
+            .collect();
+        for diagnostic in self.diagnostics.check_event(event, reason, last_output) {
+            self.emit(LauncherEvent::Diagnostic(diagnostic));
+        }
+    }
+
+    /// Explain a server exit code against the diagnostics catalogue.
+    pub fn diagnose_exit(&self, code: i32) {
+        for diagnostic in self.diagnostics.check_exit(code) {
+            self.emit(LauncherEvent::Diagnostic(diagnostic));
+        }
+    }
+
+    /// Gather a crash report from what has been recorded and the install at
+    /// `dir`. CasparCG's own log files are found through the profile's
+    /// `paths.log`, or the written casparcg.config when no profile is given.
+    pub async fn collect_crash_report(
+        &self,
+        reason: String,
+        profile: Option<GlobalConfig>,
+        dir: Option<PathBuf>,
+    ) -> CrashReport {
+        let mut report = CrashReport::new(reason);
+        report.logs = [LogSource::Server, LogSource::Scanner, LogSource::Launcher]
+            .into_iter()
+            .map(|source| (source, self.logs.tail(source, CRASH_REPORT_LINES)))
+            .collect();
+        // Log files of up to 4 MB and the DeckLink driver: keep them off the runtime.
+        let profile_log = profile.as_ref().map(|p| p.caspar.paths.log.clone());
+        let (casparcg_config, caspar_logs, decklink) = tokio::task::spawn_blocking(move || {
+            let Some(dir) = dir else {
+                return (None, Vec::new(), crash_report::decklink_snapshot());
+            };
+            let config = std::fs::read_to_string(dir.join("casparcg.config")).ok();
+            let log_path = profile_log
+                .or_else(|| {
+                    let xml = config.as_deref()?;
+                    parse_caspar_xml(xml).ok().map(|c| c.paths.log)
+                })
+                .filter(|p| !p.is_empty());
+            // CasparCG resolves relative paths against its own directory.
+            let logs = log_path
+                .map(|log_path| crash_report::caspar_log_files(&dir.join(log_path)))
+                .unwrap_or_default();
+            (config, logs, crash_report::decklink_snapshot())
+        })
+        .await
+        .unwrap_or_default();
+        report.casparcg_config = casparcg_config;
+        report.caspar_logs = caspar_logs;
+        report.decklink = decklink;
+        report.profile = profile;
+        report.system = Some(system::collect_system_info(self.scanner_endpoint()).await);
+        report.exits = self.exits.recent();
+        report.diagnostics = self.diagnostics.recent();
+        report
+    }
+
+    /// Write a crash report into the crash-reports directory and log where it went.
+    pub async fn save_crash_report(&self, report: CrashReport) -> Result<PathBuf, String> {
+        let dir = self
+            .crash_reports_dir
+            .clone()
+            .ok_or_else(|| "Could not determine config directory".to_string())?;
+        let path = tokio::task::spawn_blocking(move || {
+            std::fs::create_dir_all(&dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+            report
+                .write_zip_in(&dir)
+                .map_err(|e| format!("Failed to write crash report: {}", e))
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+        self.log(
+            LogLevel::Info,
+            format!("crash report saved to {}", path.display()),
+        );
+        self.emit(LauncherEvent::CrashReport(path.clone()));
+        Ok(path)
+    }
+
+    /// After the supervisor stands down on a crash, leave a report behind for
+    /// engineering. Failure to write one is logged, never fatal.
+    pub(super) async fn report_stand_down(&self, reason: String, profile: &GlobalConfig, dir: &Path) {
+        let report = self
+            .collect_crash_report(reason, Some(profile.clone()), Some(dir.to_path_buf()))
+            .await;
+        if let Err(e) = self.save_crash_report(report).await {
+            self.log(LogLevel::Warning, e);
+        }
+    }
+
+    /// Spawn casparcg.exe from `dir`, streaming its console into the log — the
+    /// embedded live log the classic launcher is built around.
+    pub(super) fn spawn_server(self: &Arc<Self>, dir: &Path) -> Result<std::process::Child, String> {
+        let mut child = process::spawn_caspar(dir)?;
+        let launcher = self.clone();
+        process::pipe_console(&mut child, move |line| launcher.record(LogSource::Server, line));
+        *self.launched_at.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+        self.log(
+            LogLevel::Info,
+            format!("started {}", dir.join("casparcg.exe").display()),
+        );
+        Ok(child)
+    }
+
+    /// Spawn scanner.exe, streaming its console into the log as the scanner
+    /// source. CasparCG 2.x queries the scanner over HTTP for CLS/TLS/THUMBNAIL
+    /// listings and thumbnails, so a client cannot browse media without it.
+    pub(super) fn spawn_scanner(
+        self: &Arc<Self>,
+        dir: &Path,
+        host: &str,
+        port: u16,
+    ) -> Option<std::process::Child> {
+        let mut child = process::spawn_scanner(dir, host, port)?;
+        let launcher = self.clone();
+        process::pipe_console(&mut child, move |line| launcher.record(LogSource::Scanner, line));
+        Some(child)
+    }
+
+    /// Whether the server should currently be running (and is supervised)
+    pub fn should_run(&self) -> bool {
+        self.should_run.load(Ordering::Acquire)
+    }
+
+    /// Whether the supervisor task of Start number `generation` should carry on
+    pub(super) fn wanted(&self, generation: u64) -> bool {
+        self.should_run() && self.generation.load(Ordering::Acquire) == generation
+    }
+
+    /// Called by a supervisor task as it ends
+    pub(super) fn supervision_ended(&self, generation: u64) {
+        if self.generation.load(Ordering::Acquire) == generation {
+            self.supervising.store(false, Ordering::Release);
+        }
+    }
+
+    /// Whether a supervisor is still running: false once it has stood down (and
+    /// written its crash report) or noticed a Stop
+    pub fn is_supervising(&self) -> bool {
+        self.supervising.load(Ordering::Acquire)
+    }
+
+    /// Name of the profile the latest Start launched, running or not
+    pub fn profile_name(&self) -> Option<String> {
+        self.profile.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// AMCP port of the profile the latest Start launched
+    pub fn amcp_port(&self) -> Option<u16> {
+        *self.amcp_port.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// How long the current casparcg.exe has been up, while it is running
+    pub async fn uptime(&self) -> Option<Duration> {
+        if !self.is_running().await {
+            return None;
+        }
+        let launched_at = *self.launched_at.lock().unwrap_or_else(|e| e.into_inner());
+        launched_at.map(|t| t.elapsed())
+    }
+
+    /// Whether the media scanner launched with the server is running
+    pub async fn scanner_running(&self) -> bool {
+        if self.builtin_scanner.lock().await.is_some() {
+            return true;
+        }
+        let mut proc = self.scanner_process.lock().await;
+        matches!(proc.as_mut().map(|c| c.try_wait()), Some(Ok(None)))
+    }
+
+    /// Where the media scanner launched with the server listens, while it runs
+    pub fn scanner_endpoint(&self) -> Option<ScannerEndpoint> {
+        self.scanner_endpoint.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Per-channel OSC activity and frame timing, when the watchdog listens
+    /// for OSC on the current Start
+    pub fn osc_activity(&self) -> Option<Arc<OscActivity>> {
+        self.osc.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Write `config` as casparcg.config in the install directory `dir`, launch
+    /// casparcg.exe and the media scanner from there, and supervise both with the
+    /// profile's restart policy until `stop` or a stand-down.
+    pub async fn start(self: &Arc<Self>, mut config: GlobalConfig, dir: PathBuf) -> Result<(), String> {
+        // Already running? (Reap a process that has since exited.)
+        {
+            let mut proc = self.caspar_process.lock().await;
+            match proc.as_mut().map(|c| c.try_wait()) {
+                Some(Ok(None)) => return Err("CasparCG server is already running".to_string()),
+                Some(_) => *proc = None,
+                None => {}
+            }
+        }
+
+        // Clean slate: terminate any stray casparcg.exe a previous session left
+        // behind. Otherwise it keeps the DeckLink card and AMCP port, and the new
+        // instance fails with "Could not enable primary video output".
+        process::kill_stale_casparcg();
+
+        let exe = dir.join("casparcg.exe");
+        if !exe.exists() {
+            return Err(format!("casparcg.exe not found in {}", dir.display()));
+        }
+
+        // Check every port the launch needs before anything binds one. A taken
+        // AMCP port otherwise only shows as CasparCG failing to start, and the
+        // GUI's own listeners are easy to collide with on a shared box.
+        let preflight = self.ports.check(&config, &SystemProber);
+        for check in &preflight.checks {
+            if let Some(problem) = check.problem() {
+                let level = if check.status == PortStatus::Blocked {
+                    LogLevel::Error
+                } else {
+                    LogLevel::Warning
+                };
+                self.log(level, problem);
+            }
+        }
+        self.emit(LauncherEvent::PortPreflight(preflight.clone()));
+        if preflight.blocked() {
+            return Err(format!("Port conflict: {}", preflight.problems().join("; ")));
+        }
+        if let Some(port) = preflight.port(PortRole::Amcp) {
+            config.caspar.controllers.tcp.port = port;
+        }
+        if let Some(port) = preflight.port(PortRole::Osc) {
+            config.watchdog.osc_port = Some(port);
+        }
+
+        // Pin the media scanner to the port the pre-flight settled on and point
+        // CasparCG at it. CasparCG proxies CLS/TLS/THUMBNAIL to the scanner over
+        // HTTP; the stock port 8000 routinely clashes with another local web
+        // service on a shared box, and then the scanner cannot bind it and every
+        // listing fails with "Invalid Response". Resolve the port now so the
+        // written config and the spawned scanner always agree on the same endpoint.
+        let scanner_host = scanner::HOST.to_string();
+        let scanner_port = preflight
+            .port(PortRole::Scanner)
+            .unwrap_or(scanner::PREFERRED_PORT);
+        self.ports.record(preflight);
+        config.caspar.amcp.media_server = Some(MediaServer {
+            host: scanner_host.clone(),
+            port: scanner_port,
+        });
+
+        // The watchdog watches per-channel OSC when given a port: have CasparCG
+        // send its per-frame OSC to the launcher as a predefined client.
+        if let (true, Some(port)) = (config.watchdog.enabled, config.watchdog.osc_port) {
+            let client = OscClient {
+                address: "127.0.0.1".to_string(),
+                port,
+            };
+            let osc = config.caspar.osc.get_or_insert_with(Default::default);
+            if !osc.predefined_clients.contains(&client) {
+                osc.predefined_clients.push(client);
+            }
+        }
+
+        // Write the active configuration so the server starts with what is shown.
+        let xml = generate_caspar_xml(&config.caspar)
+            .map_err(|e| format!("Failed to generate config: {}", e))?;
+        std::fs::write(dir.join("casparcg.config"), xml)
+            .map_err(|e| format!("Failed to write casparcg.config: {}", e))?;
+
+        // Mark the server as wanted before launching so the supervisor keeps it
+        // alive; Stop clears this, so a deliberate stop is never read as a crash.
+        let generation = self.generation.fetch_add(1, Ordering::AcqRel) + 1;
+        self.should_run.store(true, Ordering::Release);
+
+        // Launch casparcg.exe with its console streamed into the log.
+        let child = match self.spawn_server(&dir) {
+            Ok(child) => child,
+            Err(e) => {
+                self.should_run.store(false, Ordering::Release);
+                return Err(e);
+            }
+        };
+        *self.caspar_process.lock().await = Some(child);
+        *self.profile.lock().unwrap_or_else(|e| e.into_inner()) = Some(config.name.clone());
+        *self.amcp_port.lock().unwrap_or_else(|e| e.into_inner()) =
+            Some(config.caspar.controllers.tcp.port);
+        self.emit(LauncherEvent::ServerStarted {
+            profile: config.name.clone(),
+            restart: false,
+        });
+
+        // Launch the media scanner alongside the server so a connected client can
+        // list media/templates and fetch thumbnails (CLS/TLS/THUMBNAIL). Surface
+        // the resolved endpoint both as a log line and as a structured event so
+        // the GUI can show which port it landed on — on a busy box this is not the
+        // stock 8000, and that fact is needed to make sense of the listings.
+        let builtin_scanner = config.scanner.mode == ScannerMode::BuiltIn;
+        let scanner_up = if builtin_scanner {
+            self.start_builtin_scanner(&config, &dir, &scanner_host, scanner_port).await
+        } else if let Some(scanner) = self.spawn_scanner(&dir, &scanner_host, scanner_port) {
+            self.log(
+                LogLevel::Info,
+                format!("media scanner on {scanner_host}:{scanner_port}"),
+            );
+            *self.scanner_process.lock().await = Some(scanner);
+            true
+        } else {
+            self.log(
+                LogLevel::Warning,
+                "scanner.exe not found — media listing will be unavailable",
+            );
+            false
+        };
+        if scanner_up {
+            let endpoint = ScannerEndpoint::new(scanner_host.clone(), scanner_port);
+            *self.scanner_endpoint.lock().unwrap_or_else(|e| e.into_inner()) = Some(endpoint.clone());
+            self.emit(LauncherEvent::ScannerEndpoint(Some(endpoint)));
+        }
+
+        if config.resources.enabled {
+            tokio::spawn(self.clone().sample_resources(generation, config.resources.clone()));
+        }
+
+        // The restart policy is read once per Start: editing the profile while
+        // the server runs takes effect on the next Start.
+        let run = super::run::Run {
+            generation,
+            amcp_port: config.caspar.controllers.tcp.port,
+            policy: RestartPolicy::new(config.supervisor.clone()),
+            watchdog: config.watchdog.clone(),
+            profile: config,
+            dir,
+            scanner_host,
+            scanner_port,
+            builtin_scanner,
+        };
+        self.supervising.store(true, Ordering::Release);
+        tokio::spawn(self.clone().supervise(run));
+        Ok(())
+    }
+
+    /// Stop the server and scanner. Errors if the server was not running.
+    pub async fn stop(&self) -> Result<(), String> {
+        // Clear the desired-state flag first so the supervisor sees a deliberate
+        // stop and never restarts the server we are about to kill.
+        self.should_run.store(false, Ordering::Release);
+
+        // Take the server handle so the supervisor sees it gone too.
+        let server = self.caspar_process.lock().await.take();
+
+        // Stop the media scanner alongside the server, and report its endpoint
+        // gone so the GUI does not imply a live scanner.
+        self.stop_scanner().await;
+
+        if let Some(mut child) = server {
+            // Kill the whole tree, not just the direct child — CasparCG spawns
+            // CEF subprocesses that would otherwise survive and keep holding the
+            // card.
+            kill_process_tree(child.id());
+            let _ = child.wait();
+            self.emit(LauncherEvent::ServerStopped);
+            Ok(())
+        } else {
+            Err("CasparCG server is not running".to_string())
+        }
+    }
+
+    /// Serve the scanner API from the launcher itself, over the profile's
+    /// media, template and font folders. ffprobe (from the CasparCG folder or
+    /// the PATH) is used when present; without it media is classified by
+    /// extension and has no thumbnails.
+    async fn start_builtin_scanner(&self, config: &GlobalConfig, dir: &Path, host: &str, port: u16) -> bool {
+        let roots = ScanRoots::resolve(&config.caspar.paths, dir);
+        let ffmpeg = Ffmpeg::find(dir);
+        if ffmpeg.is_none() {
+            self.log(
+                LogLevel::Warning,
+                "ffprobe not found — the built-in scanner classifies media by extension only",
+            );
+        }
+        match BuiltinScanner::start(roots, ffmpeg, host, port).await {
+            Ok(scanner) => {
+                self.log(LogLevel::Info, format!("built-in media scanner on {host}:{port}"));
+                *self.builtin_scanner.lock().await = Some(scanner);
+                true
+            }
+            Err(e) => {
+                self.log(
+                    LogLevel::Warning,
+                    format!("{e} — media listing will be unavailable"),
+                );
+                false
+            }
+        }
+    }
+
+    async fn stop_scanner(&self) {
+        if let Some(mut scanner) = self.scanner_process.lock().await.take() {
+            kill_process_tree(scanner.id());
+            let _ = scanner.wait();
+        }
+        if let Some(scanner) = self.builtin_scanner.lock().await.take() {
+            scanner.stop().await;
+        }
+        *self.scanner_endpoint.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        self.emit(LauncherEvent::ScannerEndpoint(None));
+    }
+
+    /// Return to a clean stopped state from inside the supervisor: stop wanting
+    /// the server, kill the media scanner, and report its endpoint gone. Used
+    /// when the server exits cleanly/fatally or the crash-loop guard trips, so
+    /// the machine is not left with an orphaned scanner and a stale endpoint.
+    pub(super) async fn stand_down(&self, reason: &str) {
+        self.should_run.store(false, Ordering::Release);
+        self.stop_scanner().await;
+        self.emit(LauncherEvent::StoodDown(reason.to_string()));
+    }
+
+    /// Whether the launched CasparCG server process is still running.
+    pub async fn is_running(&self) -> bool {
+        let mut proc = self.caspar_process.lock().await;
+        match proc.as_mut().map(|c| c.try_wait()) {
+            Some(Ok(None)) => true,
+            Some(_) => {
+                *proc = None;
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Kill the server and scanner trees without waiting, for process exit
+    /// where the async runtime may already be gone.
+    pub fn kill_now(&self) {
+        // Stop wanting the server so the supervisor cannot race a restart
+        // against shutdown.
+        self.should_run.store(false, Ordering::Release);
+        for slot in [&self.caspar_process, &self.scanner_process] {
+            let pid = slot
+                .try_lock()
+                .ok()
+                .and_then(|mut guard| guard.take())
+                .map(|child| child.id());
+            if let Some(pid) = pid {
+                kill_process_tree(pid);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::config::RestartMode;
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::Duration;
+
+    /// An install directory whose casparcg.exe is a script that logs a line and
+    /// crashes with code 7.
+    fn crashing_install(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let exe = dir.join("casparcg.exe");
+        std::fs::write(
+            &exe,
+            "#!/bin/sh\necho '[2025-01-01 12:00:00.000] [error] Could not enable primary video output.'\nexit 7\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&exe, std::fs::Permissions::from_mode(0o755)).unwrap();
+        dir
+    }
+
+    #[test]
+    fn supervises_to_a_give_up_and_writes_a_crash_report() {
+        let dir = crashing_install("launcher-give-up");
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = events.clone();
+        let sink: EventSink = Arc::new(move |e: LauncherEvent| seen.lock().unwrap().push(e));
+        let launcher = Arc::new(Launcher::new(
+            LogStore::default(),
+            DiagnosticsEngine::default(),
+            Some(dir.join("crash-reports")),
+            sink,
+        ));
+
+        let mut config = GlobalConfig::new("Crashing");
+        config.supervisor.restart = RestartMode::Always;
+        config.supervisor.tick_secs = 1;
+        config.supervisor.backoff_secs = 0.0;
+        config.supervisor.max_crashes = 1;
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            launcher.start(config, dir.clone()).await.unwrap();
+            for _ in 0..100 {
+                if !launcher.is_supervising() {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        });
+
+        assert!(!launcher.is_supervising() && !launcher.should_run());
+        let actions: Vec<_> = launcher.exits.recent().into_iter().map(|e| (e.code, e.action)).collect();
+        assert_eq!(
+            actions,
+            [(Some(7), "restart".to_string()), (Some(7), "give_up".to_string())]
+        );
+        assert!(std::fs::read_to_string(dir.join("casparcg.config")).is_ok());
+
+        let events = events.lock().unwrap();
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, LauncherEvent::Diagnostic(d) if d.rule_id == "decklink-output-in-use")));
+        let report = events.iter().find_map(|e| match e {
+            LauncherEvent::CrashReport(path) => Some(path.clone()),
+            _ => None,
+        });
+        assert!(report.is_some_and(|p| p.exists()));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,14 @@
+// Server supervision module
+// Launches CasparCG and its media scanner and keeps them running
+
+mod history;
+mod launcher;
+mod policy;
+mod process;
+mod run;
+mod sampling;
+
+pub use history::*;
+pub use launcher::*;
+pub use policy::*;
+pub use process::kill_process_tree;
@@ -0,0 +1,263 @@
+// Restart policy
+// Applies a profile's SupervisorConfig to each server exit
+
+use std::time::{Duration, Instant};
+
+use crate::config::{RestartMode, SupervisorConfig};
+
+/// What the supervisor should do about a server exit
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExitDecision {
+    /// Relaunch after `delay`; `reason` describes the exit ("crashed (code 3)")
+    Restart { reason: String, delay: Duration },
+    /// Leave the server stopped; the exit was clean, fatal, or restarts are off
+    Stand { reason: String },
+    /// The crash-loop guard tripped
+    GiveUp { crashes: usize, window: Duration },
+}
+
+impl ExitDecision {
+    /// Short name for the exit history
+    pub fn action(&self) -> &'static str {
+        match self {
+            ExitDecision::Restart { .. } => "restart",
+            ExitDecision::Stand { .. } => "stand",
+            ExitDecision::GiveUp { .. } => "give_up",
+        }
+    }
+}
+
+/// One supervisor run's restart state: the crash history for the loop guard and
+/// the streak of back-to-back crashes that drives the exponential backoff.
+#[derive(Debug)]
+pub struct RestartPolicy {
+    config: SupervisorConfig,
+    crashes: Vec<Instant>,
+    streak: u32,
+}
+
+impl RestartPolicy {
+    pub fn new(config: SupervisorConfig) -> Self {
+        Self {
+            config,
+            crashes: Vec::new(),
+            streak: 0,
+        }
+    }
+
+    /// How often the supervisor polls the processes
+    pub fn tick(&self) -> Duration {
+        Duration::from_secs(self.config.tick_secs.max(1))
+    }
+
+    fn crash_window(&self) -> Duration {
+        Duration::from_secs(self.config.crash_window_secs)
+    }
+
+    /// Pause before relaunching after the `streak`-th crash in a row:
+    /// `backoff_secs × multiplier^(streak-1)`, capped at `backoff_max_secs`.
+    pub fn backoff(&self, streak: u32) -> Duration {
+        let c = &self.config;
+        let exponent = streak.saturating_sub(1).min(64) as i32;
+        let secs = c.backoff_secs.max(0.0) * c.backoff_multiplier.max(1.0).powi(exponent);
+        Duration::from_secs_f64(secs.min(c.backoff_max_secs.max(c.backoff_secs)).max(0.0))
+    }
+
+    /// Classify an exit for counting: `hung`, `restart_request`, `clean` (0),
+    /// `fatal` (below `fatal_below`), `crash`, or `unknown` when there is no code.
+    pub fn exit_reason(&self, code: Option<i32>, hung: bool) -> &'static str {
+        match code {
+            _ if hung => "hung",
+            None => "unknown",
+            Some(c) if self.config.restart_request_codes.contains(&c) => "restart_request",
+            Some(0) => "clean",
+            Some(c) if (0..self.config.fatal_below).contains(&c) => "fatal",
+            Some(_) => "crash",
+        }
+    }
+
+    /// Decide on an exit. `code` is the exit code when known, `hung` is set when
+    /// the watchdog killed the server, and `uptime` is how long that run lasted.
+    pub fn on_exit(
+        &mut self,
+        code: Option<i32>,
+        hung: bool,
+        uptime: Duration,
+        now: Instant,
+    ) -> ExitDecision {
+        let describe = || match code {
+            _ if hung => "hung (killed by the watchdog)".to_string(),
+            Some(c) if self.config.restart_request_codes.contains(&c) => {
+                "requested a restart".to_string()
+            }
+            Some(c) => format!("crashed (code {c})"),
+            None => "stopped unexpectedly".to_string(),
+        };
+        let shown = code.map(|c| c.to_string()).unwrap_or_else(|| "?".into());
+
+        if self.config.restart == RestartMode::Never {
+            let reason = if hung {
+                "hung (killed by the watchdog) — not restarting (restart policy: never)".to_string()
+            } else {
+                format!("exited (code {shown}) — not restarting (restart policy: never)")
+            };
+            return ExitDecision::Stand { reason };
+        }
+
+        // A restart request is not a crash: the base pause, no growth.
+        if !hung && code.is_some_and(|c| self.config.restart_request_codes.contains(&c)) {
+            return ExitDecision::Restart {
+                reason: describe(),
+                delay: self.backoff(1),
+            };
+        }
+
+        // Codes 0..`fatal_below` are a clean or fatal shutdown; restarting would
+        // only fail the same way unless the policy says always. Windows crash
+        // codes (NTSTATUS, e.g. 0xC0000005) arrive negative and are crashes.
+        let clean = !hung && code.is_some_and(|c| (0..self.config.fatal_below).contains(&c));
+        if clean && self.config.restart == RestartMode::OnFailure {
+            return ExitDecision::Stand {
+                reason: format!("exited (code {shown}) — not restarting"),
+            };
+        }
+
+        let window = self.crash_window();
+        self.crashes.retain(|t| now.duration_since(*t) < window);
+        self.crashes.push(now);
+        if self.config.max_crashes > 0 && self.crashes.len() > self.config.max_crashes as usize {
+            return ExitDecision::GiveUp {
+                crashes: self.crashes.len(),
+                window,
+            };
+        }
+
+        // A run that outlived the window was healthy: start the backoff over.
+        if uptime >= window {
+            self.streak = 0;
+        }
+        self.streak = self.streak.saturating_add(1);
+        let reason = if clean {
+            format!("exited (code {shown})")
+        } else {
+            describe()
+        };
+        ExitDecision::Restart {
+            reason,
+            delay: self.backoff(self.streak),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHORT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn default_policy_matches_the_stock_restart_script() {
+        let mut policy = RestartPolicy::new(SupervisorConfig::default());
+        let now = Instant::now();
+
+        assert!(matches!(
+            policy.on_exit(Some(0), false, SHORT, now),
+            ExitDecision::Stand { .. }
+        ));
+        assert_eq!(
+            policy.on_exit(Some(5), false, SHORT, now),
+            ExitDecision::Restart {
+                reason: "requested a restart".into(),
+                delay: Duration::from_secs(2)
+            }
+        );
+
+        // Three crashes back off 2, 4, 8s; the fourth inside the window gives up.
+        for expected in [2, 4, 8] {
+            match policy.on_exit(Some(-1073741819), false, SHORT, now) {
+                ExitDecision::Restart { delay, .. } => {
+                    assert_eq!(delay, Duration::from_secs(expected))
+                }
+                other => panic!("expected a restart, got {other:?}"),
+            }
+        }
+        assert_eq!(
+            policy.on_exit(None, false, SHORT, now),
+            ExitDecision::GiveUp {
+                crashes: 4,
+                window: Duration::from_secs(60)
+            }
+        );
+    }
+
+    #[test]
+    fn never_mode_leaves_every_exit_to_stand() {
+        let mut policy = RestartPolicy::new(SupervisorConfig {
+            restart: RestartMode::Never,
+            ..SupervisorConfig::default()
+        });
+        let now = Instant::now();
+        for code in [Some(0), Some(5), Some(-1), None] {
+            assert!(matches!(
+                policy.on_exit(code, false, SHORT, now),
+                ExitDecision::Stand { .. }
+            ));
+        }
+        assert!(matches!(
+            policy.on_exit(Some(1), true, SHORT, now),
+            ExitDecision::Stand { .. }
+        ));
+    }
+
+    #[test]
+    fn always_mode_retries_indefinitely_with_capped_backoff() {
+        let mut policy = RestartPolicy::new(SupervisorConfig {
+            restart: RestartMode::Always,
+            max_crashes: 0,
+            backoff_secs: 1.0,
+            backoff_max_secs: 30.0,
+            ..SupervisorConfig::default()
+        });
+        let start = Instant::now();
+        let mut last = Duration::ZERO;
+        for i in 0..20 {
+            match policy.on_exit(Some(0), false, SHORT, start + Duration::from_secs(i)) {
+                ExitDecision::Restart { reason, delay } => {
+                    assert_eq!(reason, "exited (code 0)");
+                    last = delay;
+                }
+                other => panic!("expected a restart, got {other:?}"),
+            }
+        }
+        assert_eq!(last, Duration::from_secs(30));
+
+        // A run that stayed up for the whole window resets the backoff.
+        match policy.on_exit(Some(3), false, Duration::from_secs(600), start) {
+            ExitDecision::Restart { delay, .. } => assert_eq!(delay, Duration::from_secs(1)),
+            other => panic!("expected a restart, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn hung_server_restarts_as_a_crash_despite_its_exit_code() {
+        let mut policy = RestartPolicy::new(SupervisorConfig::default());
+        assert_eq!(
+            policy.on_exit(Some(1), true, SHORT, Instant::now()),
+            ExitDecision::Restart {
+                reason: "hung (killed by the watchdog)".into(),
+                delay: Duration::from_secs(2)
+            }
+        );
+    }
+
+    #[test]
+    fn exit_reasons_label_each_kind_of_exit() {
+        let policy = RestartPolicy::new(SupervisorConfig::default());
+        assert_eq!(policy.exit_reason(Some(1), true), "hung");
+        assert_eq!(policy.exit_reason(None, false), "unknown");
+        assert_eq!(policy.exit_reason(Some(5), false), "restart_request");
+        assert_eq!(policy.exit_reason(Some(0), false), "clean");
+        assert_eq!(policy.exit_reason(Some(2), false), "fatal");
+        assert_eq!(policy.exit_reason(Some(-1073741819), false), "crash");
+    }
+}
@@ -0,0 +1,134 @@
+// Server and scanner processes
+// Spawning casparcg.exe / scanner.exe with captured consoles, and killing them
+
+use std::process::{Child, Command, Stdio};
+
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+/// Terminate a process and its whole child tree (CEF/scanner subprocesses).
+pub fn kill_process_tree(pid: u32) {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        let _ = Command::new("taskkill")
+            .args(["/F", "/T", "/PID", &pid.to_string()])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output();
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = Command::new("kill").arg(pid.to_string()).output();
+    }
+}
+
+/// Terminate any stray casparcg.exe a previous session left running, so it cannot
+/// keep holding the DeckLink card or the AMCP port.
+pub fn kill_stale_casparcg() {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        let _ = Command::new("taskkill")
+            .args(["/F", "/T", "/IM", "casparcg.exe"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output();
+        let _ = Command::new("taskkill")
+            .args(["/F", "/T", "/IM", "scanner.exe"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output();
+    }
+}
+
+/// Terminate only casparcg.exe (and its CEF children), leaving scanner.exe alone.
+/// Used before a supervised restart: the crashed server's orphaned CEF helpers
+/// can keep holding the DeckLink card, which would make the fresh instance fail
+/// "Could not enable primary video output" — but the media scanner is healthy
+/// and must survive the server bouncing under it.
+pub fn kill_casparcg_only() {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        let _ = Command::new("taskkill")
+            .args(["/F", "/T", "/IM", "casparcg.exe"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output();
+    }
+}
+
+/// A command for an executable in the install directory, run from there with
+/// its console captured and no window of its own.
+fn captured(exe: &std::path::Path, dir: &std::path::Path) -> Command {
+    let mut command = Command::new(exe);
+    command
+        .current_dir(dir)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command
+}
+
+/// Spawn casparcg.exe from `dir`. The config file is expected to already be
+/// written; the console is left piped for `pipe_console`.
+pub fn spawn_caspar(dir: &std::path::Path) -> Result<Child, String> {
+    captured(&dir.join("casparcg.exe"), dir)
+        .spawn()
+        .map_err(|e| format!("Failed to launch CasparCG: {}", e))
+}
+
+/// Spawn the CasparCG media scanner (scanner.exe) from the install directory, or
+/// `None` if it is not installed or will not start.
+///
+/// `host`/`port` pin the scanner's HTTP listener. The media-scanner reads nconf
+/// keys with a "__" separator, so `http__host`/`http__port` populate its
+/// `{ http: { host, port } }` config. CasparCG must query this exact endpoint —
+/// `Launcher::start` writes the matching `<amcp><media-server>` block.
+pub fn spawn_scanner(dir: &std::path::Path, host: &str, port: u16) -> Option<Child> {
+    let exe = dir.join("scanner.exe");
+    if !exe.exists() {
+        return None;
+    }
+    captured(&exe, dir)
+        .env("http__host", host)
+        .env("http__port", port.to_string())
+        .spawn()
+        .ok()
+}
+
+/// Feed each line of a child's stdout and stderr to `on_line`. Blocking reads
+/// run on their own threads so the async runtime is never stalled; they end at
+/// EOF when the process exits. Lines are decoded lossily: a stray non-UTF-8 byte
+/// from a Windows console must not end the reader, or the pipe fills and the
+/// process stalls on its next write.
+pub fn pipe_console<F>(child: &mut Child, on_line: F)
+where
+    F: Fn(&str) + Clone + Send + 'static,
+{
+    let streams: [Option<Box<dyn std::io::Read + Send>>; 2] = [
+        child.stdout.take().map(|s| Box::new(s) as Box<dyn std::io::Read + Send>),
+        child.stderr.take().map(|s| Box::new(s) as Box<dyn std::io::Read + Send>),
+    ];
+    for stream in streams.into_iter().flatten() {
+        let on_line = on_line.clone();
+        std::thread::spawn(move || {
+            use std::io::BufRead;
+            let mut reader = std::io::BufReader::new(stream);
+            let mut buf = Vec::new();
+            loop {
+                buf.clear();
+                match reader.read_until(b'\n', &mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {
+                        let line = String::from_utf8_lossy(&buf);
+                        if !line.trim().is_empty() {
+                            on_line(&line);
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
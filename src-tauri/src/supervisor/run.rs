@@ -0,0 +1,283 @@
+// Supervisor loop
+// Watches one started server until Stop or a stand-down
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+use super::launcher::{Launcher, LauncherEvent};
+use super::process::{self, kill_process_tree};
+use super::{ExitDecision, ExitRecord, RestartPolicy};
+use crate::config::{GlobalConfig, WatchdogConfig};
+use crate::logs::LogLevel;
+use crate::scanner::{ScannerEndpoint, ScannerHealth};
+use crate::watchdog::{Watchdog, WatchdogVerdict};
+
+/// What one Start hands its supervisor task
+pub(super) struct Run {
+    /// Which Start this is (see `Launcher::wanted`)
+    pub generation: u64,
+    /// The profile as written to casparcg.config, for crash reports
+    pub profile: GlobalConfig,
+    /// CasparCG installation directory
+    pub dir: PathBuf,
+    pub scanner_host: String,
+    pub scanner_port: u16,
+    /// The scanner is the launcher's own, served in-process: nothing to relaunch
+    pub builtin_scanner: bool,
+    pub amcp_port: u16,
+    pub policy: RestartPolicy,
+    pub watchdog: WatchdogConfig,
+}
+
+impl Launcher {
+    /// Keep both the server and the scanner alive while the server is wanted.
+    /// The scanner is safe to relaunch freely. Whether the server is relaunched
+    /// is the profile's restart policy: by default on a crash or its
+    /// restart-request exit code, with a growing backoff and a crash-loop guard
+    /// so it cannot thrash on a config the machine cannot render, while a
+    /// clean/fatal exit is left to stand. A user Stop clears `should_run`, so a
+    /// deliberate stop is never mistaken for a crash. The optional watchdog
+    /// covers the case an exit never comes: a hung server is killed and handled
+    /// like a crash.
+    pub(super) async fn supervise(self: Arc<Self>, run: Run) {
+        let Run {
+            generation,
+            profile,
+            dir,
+            scanner_host,
+            scanner_port,
+            builtin_scanner,
+            amcp_port,
+            mut policy,
+            watchdog: watchdog_config,
+        } = run;
+        let mut launched_at = Instant::now();
+        let mut scanner_health = ScannerHealth::new(
+            ScannerEndpoint::new(scanner_host.clone(), scanner_port),
+            Instant::now(),
+        );
+        let mut watchdog = if watchdog_config.enabled {
+            let mut wd = Watchdog::new(
+                &watchdog_config,
+                "127.0.0.1",
+                amcp_port,
+                Instant::now(),
+            );
+            if let Some(port) = watchdog_config.osc_port {
+                if let Err(e) = wd.listen_osc(port).await {
+                    self.log(
+                        LogLevel::Warning,
+                        format!("watchdog cannot receive OSC on port {port} ({e}) — AMCP ping only"),
+                    );
+                }
+            }
+            Some(wd)
+        } else {
+            None
+        };
+        *self.osc.lock().unwrap_or_else(|e| e.into_inner()) =
+            watchdog.as_ref().and_then(|wd| wd.osc_activity());
+        // Set when the watchdog kills a hung server, so the resulting exit
+        // (taskkill's code 1, otherwise read as a fatal stop) is restarted.
+        let mut hung: Option<String> = None;
+        loop {
+            tokio::time::sleep(policy.tick()).await;
+            if !self.wanted(generation) {
+                break; // deliberate Stop / app exit — teardown is the caller's job
+            }
+
+            // --- Server: detect an exit and decide whether to restart. ---
+            let exit_code: Option<Option<i32>> = {
+                let mut cp = self.caspar_process.lock().await;
+                match cp.as_mut().map(|c| c.try_wait()) {
+                    Some(Ok(Some(status))) => {
+                        *cp = None; // reaped — we own the restart from here
+                        Some(status.code())
+                    }
+                    Some(Ok(None)) => None, // still running
+                    Some(Err(_)) => {
+                        *cp = None;
+                        Some(None) // wait failed — treat as gone, code unknown
+                    }
+                    None => Some(None), // no handle — gone
+                }
+            };
+
+            if let Some(code) = exit_code {
+                // A Stop may have raced in while we looked — re-check intent.
+                if !self.wanted(generation) {
+                    break;
+                }
+                let hung_reason = hung.take();
+                if let (Some(c), None) = (code, &hung_reason) {
+                    self.diagnose_exit(c);
+                }
+
+                let now = Instant::now();
+                let decision = policy.on_exit(
+                    code,
+                    hung_reason.is_some(),
+                    now.duration_since(launched_at),
+                    now,
+                );
+                let record = ExitRecord {
+                    timestamp: chrono::Utc::now(),
+                    code,
+                    hung: hung_reason.is_some(),
+                    uptime_secs: now.duration_since(launched_at).as_secs_f64(),
+                    action: decision.action().to_string(),
+                    reason: policy.exit_reason(code, hung_reason.is_some()).to_string(),
+                };
+                self.exits.record(record.clone());
+                self.emit(LauncherEvent::ServerExit(record));
+                let delay = match decision {
+                    ExitDecision::Restart { reason, delay } => {
+                        let when = if delay.as_secs_f64() >= 1.0 {
+                            format!("restarting in {:.0}s…", delay.as_secs_f64())
+                        } else {
+                            "restarting…".to_string()
+                        };
+                        self.log(LogLevel::Warning, format!("CasparCG {reason} — {when}"));
+                        delay
+                    }
+                    ExitDecision::Stand { reason } => {
+                        let reason = format!("CasparCG {reason}");
+                        self.log(LogLevel::Warning, reason.clone());
+                        self.stand_down(&reason).await;
+                        // A clean exit needs no report; anything else does.
+                        if code != Some(0) {
+                            self.report_stand_down(reason, &profile, &dir).await;
+                        }
+                        break;
+                    }
+                    ExitDecision::GiveUp { crashes, window } => {
+                        self.log(
+                            LogLevel::Error,
+                            format!(
+                                "CasparCG crashed {crashes} times in {}s — giving up. Check the GPU/config, then press Start.",
+                                window.as_secs()
+                            ),
+                        );
+                        let reason = format!(
+                            "crashed {crashes} times in {}s — supervisor gave up",
+                            window.as_secs()
+                        );
+                        self.stand_down(&reason).await;
+                        self.report_stand_down(reason, &profile, &dir).await;
+                        break;
+                    }
+                };
+
+                tokio::time::sleep(delay).await;
+                if !self.wanted(generation) {
+                    break;
+                }
+                // Clear orphaned CEF children that may still hold the card —
+                // but not the healthy scanner running under it.
+                process::kill_casparcg_only();
+                match self.spawn_server(&dir) {
+                    Ok(mut child) => {
+                        // A Stop may have raced in during launch — if the
+                        // server is no longer wanted, do not leave an orphan
+                        // casparcg.exe holding the card.
+                        if !self.wanted(generation) {
+                            kill_process_tree(child.id());
+                            let _ = child.wait();
+                            break;
+                        }
+                        *self.caspar_process.lock().await = Some(child);
+                        self.emit(LauncherEvent::ServerStarted {
+                            profile: profile.name.clone(),
+                            restart: true,
+                        });
+                        launched_at = Instant::now();
+                        if let Some(wd) = watchdog.as_mut() {
+                            wd.restart(Instant::now());
+                        }
+                    }
+                    Err(e) => {
+                        let reason = format!("restart failed: {e}");
+                        self.log(LogLevel::Error, reason.clone());
+                        self.stand_down(&reason).await;
+                        self.report_stand_down(reason, &profile, &dir).await;
+                        break;
+                    }
+                }
+                continue; // the scanner is checked on the next tick
+            }
+
+            // --- Watchdog: the server is running, but is it responding? ---
+            if let Some(wd) = watchdog.as_mut() {
+                match wd.tick(Instant::now()).await {
+                    Some(WatchdogVerdict::Missed { count, threshold, reason }) => {
+                        self.log(
+                            LogLevel::Warning,
+                            format!("watchdog: missed heartbeat {count}/{threshold} — {reason}"),
+                        );
+                    }
+                    Some(WatchdogVerdict::Hung { reason }) => {
+                        if !self.wanted(generation) {
+                            break;
+                        }
+                        self.log(
+                            LogLevel::Error,
+                            format!("CasparCG is not responding ({reason}) — killing it for a restart"),
+                        );
+                        self.diagnose_event("watchdog-hung", &reason);
+                        if let Some(child) = self.caspar_process.lock().await.as_mut() {
+                            kill_process_tree(child.id());
+                            let _ = child.kill();
+                        }
+                        hung = Some(reason);
+                        continue; // the exit is picked up on the next tick
+                    }
+                    _ => {}
+                }
+            }
+
+            // --- Scanner: relaunch on the same port if it has died, or is
+            // alive but no longer answers its HTTP API. ---
+            if builtin_scanner {
+                continue;
+            }
+            let alive = matches!(
+                self.scanner_process.lock().await.as_mut().map(|c| c.try_wait()),
+                Some(Ok(None))
+            );
+            // The health check is an HTTP request: make it without holding the
+            // process lock, which `stop` and `kill_now` wait on.
+            let unresponsive = if alive {
+                scanner_health.check(Instant::now()).await
+            } else {
+                None
+            };
+            if alive && unresponsive.is_none() {
+                continue;
+            }
+            let mut sp = self.scanner_process.lock().await;
+            if !self.wanted(generation) {
+                break;
+            }
+            if let Some(reason) = unresponsive {
+                self.log(
+                    LogLevel::Warning,
+                    format!("media scanner is not responding ({reason}) — restarting it"),
+                );
+                if let Some(mut child) = sp.take() {
+                    kill_process_tree(child.id());
+                    let _ = child.wait();
+                }
+            }
+            if let Some(child) = self.spawn_scanner(&dir, &scanner_host, scanner_port) {
+                self.log(
+                    LogLevel::Warning,
+                    format!("media scanner restarted on {scanner_host}:{scanner_port}"),
+                );
+                *sp = Some(child);
+                scanner_health.restarted(Instant::now());
+            }
+        }
+        self.supervision_ended(generation);
+    }
+}
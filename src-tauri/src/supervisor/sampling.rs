@@ -0,0 +1,64 @@
+// Resource sampling loop
+// Samples the server and scanner trees while one Start is wanted
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::launcher::{Launcher, LauncherEvent};
+use crate::config::{ResourceConfig, ResourceProcess};
+use crate::logs::LogLevel;
+use crate::resources::{read_trees, AlertTracker, TreeSampler};
+
+impl Launcher {
+    /// Sample CPU, memory, threads and handles of both process trees every
+    /// `interval_secs` into `self.resources`, raising the profile's alerts.
+    /// Runs beside the supervisor and ends with the same Start.
+    pub(super) async fn sample_resources(self: Arc<Self>, generation: u64, config: ResourceConfig) {
+        let interval = Duration::from_secs(config.interval_secs.max(1));
+        let mut samplers: BTreeMap<ResourceProcess, TreeSampler> = BTreeMap::new();
+        let mut alerts = AlertTracker::new(config.alerts);
+        let mut warned = false;
+        loop {
+            tokio::time::sleep(interval).await;
+            if !self.wanted(generation) {
+                break;
+            }
+            let roots = [
+                (ResourceProcess::Server, self.caspar_process.lock().await.as_ref().map(|c| c.id())),
+                (ResourceProcess::Scanner, self.scanner_process.lock().await.as_ref().map(|c| c.id())),
+            ];
+            let pids: Vec<u32> = roots.iter().filter_map(|(_, pid)| *pid).collect();
+            if pids.is_empty() {
+                continue;
+            }
+            // Reading the process table blocks: keep it off the runtime.
+            let trees = match tokio::task::spawn_blocking(move || read_trees(&pids)).await {
+                Ok(Ok(trees)) => trees,
+                Ok(Err(e)) => {
+                    if !warned {
+                        self.log(LogLevel::Warning, format!("resource sampling unavailable: {e}"));
+                        warned = true;
+                    }
+                    continue;
+                }
+                Err(_) => continue,
+            };
+
+            let now = Instant::now();
+            let timestamp = chrono::Utc::now();
+            for (process, pid) in roots {
+                let Some(tree) = pid.and_then(|pid| trees.get(&pid).map(|t| (pid, t))) else {
+                    continue;
+                };
+                let sample = samplers.entry(process).or_default().sample(tree.0, tree.1, now, timestamp);
+                for alert in alerts.observe(process, &sample, now) {
+                    let level = if alert.active { LogLevel::Warning } else { LogLevel::Info };
+                    self.log(level, alert.message.clone());
+                    self.emit(LauncherEvent::ResourceAlert(alert));
+                }
+                self.resources.record(process, sample);
+            }
+        }
+    }
+}
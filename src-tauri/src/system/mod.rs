@@ -3,13 +3,13 @@
 
 pub mod ndi;
 pub mod network;
-pub mod scanner;
 
 pub use ndi::get_ndi_version;
-pub use scanner::get_scanner_version;
 
 use serde::{Deserialize, Serialize};
 
+use crate::scanner::{get_scanner_version, ScannerEndpoint};
+
 /// Combined system information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemVersions {
@@ -34,12 +34,13 @@ impl Default for SystemVersions {
     }
 }
 
-/// Collect all system version information
-pub async fn collect_system_info() -> SystemVersions {
+/// Collect all system version information, asking the scanner at `scanner`
+/// (the one the launcher started) for its version
+pub async fn collect_system_info(scanner: Option<ScannerEndpoint>) -> SystemVersions {
     SystemVersions {
         caspar_version: None, // Set by AMCP connection
         decklink_version: crate::decklink::get_driver_version().ok().flatten(),
         ndi_version: get_ndi_version(),
-        scanner_version: get_scanner_version(None).await,
+        scanner_version: get_scanner_version(scanner).await,
     }
 }
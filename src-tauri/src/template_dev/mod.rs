@@ -0,0 +1,7 @@
+// Template development
+// Serves a template workspace and reloads it in CasparCG as it is edited
+
+mod session;
+mod watch;
+
+pub use session::*;
@@ -0,0 +1,412 @@
+// Template development session
+// Serves a workspace, rebuilds it on save and reloads the template in CasparCG
+
+use std::io::{BufRead, BufReader, Read};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::http::header::{HeaderValue, CACHE_CONTROL};
+use axum::response::Response;
+use axum::Router;
+use serde::Serialize;
+use tower_http::services::ServeDir;
+
+use super::watch::Snapshot;
+use crate::api::{start_listener, stop_listener, HttpListenerState};
+use crate::config::{TemplateDevSettings, TemplateReload};
+use crate::control::Control;
+use crate::logs::LogLevel;
+#[cfg(windows)]
+use crate::supervisor::kill_process_tree;
+
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+/// How often the workspace is walked for changes. A change is acted on once
+/// a second walk sees the same files, so an editor or build writing several
+/// files reloads once.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A build still running after this long is killed and nothing is reloaded
+pub const BUILD_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// The settings plus where a session is serving, for the GUI
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateDevStatus {
+    #[serde(flatten)]
+    pub settings: TemplateDevSettings,
+    /// Listening address while running
+    pub address: Option<String>,
+    /// The URL CasparCG is given
+    pub url: Option<String>,
+}
+
+/// What the serving side and the watch loop share
+struct Session {
+    settings: TemplateDevSettings,
+    workspace: PathBuf,
+    url: String,
+    /// The last test data, sent with every ADD and UPDATE
+    data: Mutex<String>,
+    control: Control,
+    /// The build in progress, for `stop` to kill
+    build: Arc<Mutex<Option<Child>>>,
+}
+
+/// A running template development session
+pub struct TemplateDev {
+    listener: HttpListenerState,
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    session: Arc<Session>,
+}
+
+impl TemplateDev {
+    /// Serve the workspace, build it and put the template on air, then keep
+    /// watching it until stopped
+    pub async fn start(settings: TemplateDevSettings, control: Control) -> Result<Self, String> {
+        let workspace = settings
+            .workspace
+            .as_deref()
+            .filter(|w| !w.is_empty())
+            .map(PathBuf::from)
+            .ok_or_else(|| "No template workspace set".to_string())?;
+        if !workspace.is_dir() {
+            return Err(format!("Template workspace not found: {}", workspace.display()));
+        }
+        let served = served_dir(&workspace, &settings);
+
+        let listener = HttpListenerState::default();
+        let addr = start_listener(listener.clone(), &settings.bind, router(&served), "template dev server").await?;
+        let session = Arc::new(Session {
+            url: template_url(addr, &settings.template),
+            data: Mutex::new(settings.data.clone()),
+            workspace: workspace.clone(),
+            settings,
+            control,
+            build: Arc::default(),
+        });
+        session.log(LogLevel::Info, format!("serving {} at http://{}", served.display(), addr));
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let (watch_session, watch_stop) = (session.clone(), stop.clone());
+        // A build must not trigger itself: its output folder is not walked,
+        // and whatever changes while it runs is taken as its own writing.
+        let builds = session.settings.build_command.as_deref().is_some_and(|c| !c.is_empty());
+        let exclude = (builds && served != workspace).then_some(served);
+        tokio::spawn(async move {
+            let snapshot = || {
+                let (root, exclude) = (workspace.clone(), exclude.clone());
+                async move {
+                    tokio::task::spawn_blocking(move || Snapshot::take(&root, exclude.as_deref()))
+                        .await
+                        .unwrap_or_default()
+                }
+            };
+            let mut seen = snapshot().await;
+            watch_session.refresh(&[]).await;
+            if builds {
+                seen = snapshot().await;
+            }
+            while !watch_stop.load(Ordering::Acquire) {
+                tokio::time::sleep(POLL_INTERVAL).await;
+                if snapshot().await.changes_since(&seen).is_empty() {
+                    continue;
+                }
+                let mut settled = snapshot().await;
+                loop {
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                    let next = snapshot().await;
+                    if next == settled {
+                        break;
+                    }
+                    settled = next;
+                }
+                let changed = settled.changes_since(&seen);
+                seen = settled;
+                if !watch_stop.load(Ordering::Acquire) {
+                    watch_session.refresh(&changed).await;
+                    if builds {
+                        seen = snapshot().await;
+                    }
+                }
+            }
+        });
+        Ok(Self {
+            listener,
+            addr,
+            stop,
+            session,
+        })
+    }
+
+    /// Stop serving and watching, killing a build in progress. The template
+    /// stays on air.
+    pub async fn stop(&self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(mut child) = self.session.build.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            kill_build(&mut child);
+            self.session.log(LogLevel::Warning, "build killed".to_string());
+        }
+        stop_listener(self.listener.clone()).await;
+        self.session.log(LogLevel::Info, "stopped".to_string());
+    }
+
+    /// Where the workspace is served
+    pub fn address(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The URL CasparCG is given
+    pub fn url(&self) -> &str {
+        &self.session.url
+    }
+
+    /// Keep `data` as the test data and send it to the template now
+    pub async fn set_data(&self, data: String) -> Result<(), String> {
+        *self.session.data.lock().unwrap_or_else(|e| e.into_inner()) = data.clone();
+        let (channel, layer) = (self.session.settings.channel, self.session.settings.layer);
+        self.session.control.ensure_amcp().await?;
+        let client = self.session.control.amcp_client.lock().await;
+        client.cg_update(channel, layer, &data).await.map_err(|e| e.to_string())
+    }
+}
+
+impl Session {
+    fn log(&self, level: LogLevel, text: String) {
+        self.control.launcher.log(level, format!("template: {text}"));
+    }
+
+    /// Build if there is a build command, then reload. `changed` empty is the
+    /// first load.
+    async fn refresh(&self, changed: &[String]) {
+        let why = describe_changes(changed);
+        if let Some(command) = self.settings.build_command.as_deref().filter(|c| !c.is_empty()) {
+            self.log(LogLevel::Info, format!("building ({why}): {command}"));
+            let (command, workspace, control, running) =
+                (command.to_string(), self.workspace.clone(), self.control.clone(), self.build.clone());
+            let built = tokio::task::spawn_blocking(move || build(&command, &workspace, &control, &running, BUILD_TIMEOUT))
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r);
+            if let Err(e) = built {
+                self.log(LogLevel::Error, format!("build failed ({e}), not reloaded"));
+                return;
+            }
+        }
+        let target = format!("{}-{}", self.settings.channel, self.settings.layer);
+        match self.reload(changed.is_empty()).await {
+            Ok(()) => self.log(LogLevel::Info, format!("reloaded {} on {} ({why})", self.url, target)),
+            Err(e) => self.log(LogLevel::Warning, format!("reload on {target} failed: {e}")),
+        }
+    }
+
+    /// Put the template on air again with the last test data. The first load
+    /// always adds it, whatever the reload mode.
+    async fn reload(&self, first: bool) -> Result<(), String> {
+        let (channel, layer) = (self.settings.channel, self.settings.layer);
+        let data = self.data.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        self.control.ensure_amcp().await?;
+        let client = self.control.amcp_client.lock().await;
+        let result = match self.settings.reload {
+            TemplateReload::Update if !first => client.cg_update(channel, layer, &data).await,
+            _ => match client.cg_clear(channel, layer).await {
+                Ok(()) => client.cg_add(channel, layer, &self.url, &data).await,
+                Err(e) => Err(e),
+            },
+        };
+        result.map_err(|e| e.to_string())
+    }
+}
+
+/// The folder served: `serve_dir` under the workspace, else the workspace
+fn served_dir(workspace: &Path, settings: &TemplateDevSettings) -> PathBuf {
+    match settings.serve_dir.as_deref().filter(|d| !d.is_empty()) {
+        Some(dir) => workspace.join(dir),
+        None => workspace.to_path_buf(),
+    }
+}
+
+/// The workspace as static files, never cached: CasparCG's browser would
+/// otherwise keep showing the previous save
+fn router(served: &Path) -> Router {
+    Router::new()
+        .fallback_service(ServeDir::new(served))
+        .layer(axum::middleware::map_response(|mut response: Response| async move {
+            response.headers_mut().insert(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+            response
+        }))
+}
+
+/// The template's URL for CasparCG, on loopback when serving on every interface
+fn template_url(addr: SocketAddr, template: &str) -> String {
+    let host = if addr.ip().is_unspecified() {
+        "127.0.0.1".to_string()
+    } else {
+        addr.ip().to_string()
+    };
+    let path = template.replace('\\', "/").replace(' ', "%20");
+    format!("http://{}:{}/{}", host, addr.port(), path.trim_start_matches('/'))
+}
+
+/// `a.html`, or `a.html and 2 more`, for the log
+fn describe_changes(changed: &[String]) -> String {
+    match changed {
+        [] => "first load".to_string(),
+        [one] => one.clone(),
+        [first, rest @ ..] => format!("{} and {} more", first, rest.len()),
+    }
+}
+
+/// Run `command` through the shell in `workspace`, logging its output line by
+/// line as it comes. The child is kept in `running` while it runs; taking it
+/// from there stops the build, and it is killed after `timeout`.
+fn build(
+    command: &str,
+    workspace: &Path,
+    control: &Control,
+    running: &Mutex<Option<Child>>,
+    timeout: Duration,
+) -> Result<(), String> {
+    #[cfg(windows)]
+    let mut shell = {
+        use std::os::windows::process::CommandExt;
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]).creation_flags(CREATE_NO_WINDOW);
+        shell
+    };
+    #[cfg(not(windows))]
+    let mut shell = {
+        use std::os::unix::process::CommandExt;
+        let mut shell = Command::new("sh");
+        // Its own process group, so a kill reaches what the command starts
+        shell.args(["-c", command]).process_group(0);
+        shell
+    };
+    let mut child = shell
+        .current_dir(workspace)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("could not run it: {e}"))?;
+
+    let stream = |pipe: Option<Box<dyn Read + Send>>, level: LogLevel| {
+        let launcher = control.launcher.clone();
+        std::thread::spawn(move || {
+            let Some(pipe) = pipe else { return };
+            for line in BufReader::new(pipe).lines().map_while(Result::ok) {
+                if !line.trim().is_empty() {
+                    launcher.log(level, format!("template build: {line}"));
+                }
+            }
+        })
+    };
+    let stdout = stream(child.stdout.take().map(|p| Box::new(p) as _), LogLevel::Info);
+    let stderr = stream(child.stderr.take().map(|p| Box::new(p) as _), LogLevel::Warning);
+    *running.lock().unwrap_or_else(|e| e.into_inner()) = Some(child);
+
+    // The output threads are only joined after a normal exit: a killed shell
+    // may leave children holding the pipes open.
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        let mut slot = running.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(child) = slot.as_mut() else {
+            return Err("stopped".to_string());
+        };
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            slot.take();
+            break status;
+        }
+        if Instant::now() >= deadline {
+            kill_build(child);
+            slot.take();
+            return Err(format!("timed out after {:?}", timeout));
+        }
+        drop(slot);
+        std::thread::sleep(Duration::from_millis(100));
+    };
+    let _ = stdout.join();
+    let _ = stderr.join();
+    match status.code() {
+        _ if status.success() => Ok(()),
+        Some(code) => Err(format!("exit code {code}")),
+        None => Err("killed".to_string()),
+    }
+}
+
+/// Kill a build and everything it started — `npm run build` leaves `node`
+/// writing into the workspace if only the shell goes: the process tree on
+/// Windows, the process group elsewhere
+fn kill_build(child: &mut Child) {
+    #[cfg(windows)]
+    kill_process_tree(child.id());
+    #[cfg(not(windows))]
+    {
+        let group = format!("-{}", child.id());
+        let _ = Command::new("kill").args(["-KILL", "--", &group]).output();
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_template_urls_for_casparcg() {
+        let any: SocketAddr = "0.0.0.0:8760".parse().unwrap();
+        assert_eq!(template_url(any, "lower third/index.html"), "http://127.0.0.1:8760/lower%20third/index.html");
+        let local: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+        assert_eq!(template_url(local, "\\cg\\strap.html"), "http://127.0.0.1:9000/cg/strap.html");
+
+        assert_eq!(describe_changes(&[]), "first load");
+        let changed = ["a.html".to_string(), "b.css".to_string(), "c.js".to_string()];
+        assert_eq!(describe_changes(&changed), "a.html and 2 more");
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn build_output_goes_to_the_log_and_failures_report_the_exit_code() {
+        use crate::config::GuiSettings;
+        use crate::logs::LogSource;
+
+        let control = Control::for_tests(GuiSettings::default());
+        let dir = std::env::temp_dir();
+        let running = Mutex::new(None);
+        let minute = Duration::from_secs(60);
+        build("echo compiled; echo oops >&2", &dir, &control, &running, minute).unwrap();
+        assert_eq!(build("exit 3", &dir, &control, &running, minute), Err("exit code 3".to_string()));
+        // What the command started goes with it
+        let pid_file = dir.join(format!("template-build-{}.pid", std::process::id()));
+        let command = format!("sleep 30 & echo $! > '{}'; wait", pid_file.display());
+        assert_eq!(
+            build(&command, &dir, &control, &running, Duration::from_millis(300)),
+            Err("timed out after 300ms".to_string())
+        );
+        assert!(running.lock().unwrap().is_none());
+        let sleeper = std::fs::read_to_string(&pid_file).unwrap();
+        std::fs::remove_file(&pid_file).ok();
+        let alive = || Command::new("kill").args(["-0", sleeper.trim()]).status().unwrap().success();
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while alive() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert!(!alive(), "the build's child outlived it");
+
+        let lines: Vec<_> = control
+            .launcher
+            .logs
+            .tail(LogSource::Launcher, 10)
+            .into_iter()
+            .map(|e| (e.level, e.text))
+            .collect();
+        assert!(lines.contains(&(LogLevel::Info, "template build: compiled".to_string())));
+        assert!(lines.contains(&(LogLevel::Warning, "template build: oops".to_string())));
+    }
+}
@@ -0,0 +1,89 @@
+// Workspace watcher
+// Polls a template workspace for added, changed and removed files
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Folders never watched: dependencies and version control churn on their own
+const IGNORED_DIRS: &[&str] = &["node_modules", ".git"];
+
+/// Size and modification time of every file under a root, by relative path
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot(BTreeMap<String, (u64, Option<SystemTime>)>);
+
+impl Snapshot {
+    /// Walk `root`, skipping `exclude` (a build's output folder, which would
+    /// otherwise trigger a rebuild of itself)
+    pub fn take(root: &Path, exclude: Option<&Path>) -> Self {
+        let mut files = BTreeMap::new();
+        let mut dirs = vec![root.to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let Ok(meta) = entry.metadata() else { continue };
+                if meta.is_dir() {
+                    let ignored = entry
+                        .file_name()
+                        .to_str()
+                        .is_some_and(|name| IGNORED_DIRS.contains(&name));
+                    if !ignored && exclude != Some(path.as_path()) {
+                        dirs.push(path);
+                    }
+                } else if let Some(relative) = relative(root, &path) {
+                    files.insert(relative, (meta.len(), meta.modified().ok()));
+                }
+            }
+        }
+        Self(files)
+    }
+
+    /// Paths added, changed or removed since `earlier`
+    pub fn changes_since(&self, earlier: &Snapshot) -> Vec<String> {
+        let changed = self
+            .0
+            .iter()
+            .filter(|(path, stamp)| earlier.0.get(*path) != Some(stamp))
+            .map(|(path, _)| path.clone());
+        let removed = earlier.0.keys().filter(|path| !self.0.contains_key(*path)).cloned();
+        let mut all: Vec<_> = changed.chain(removed).collect();
+        all.sort();
+        all
+    }
+}
+
+fn relative(root: &Path, path: &Path) -> Option<String> {
+    let relative: PathBuf = path.strip_prefix(root).ok()?.to_path_buf();
+    let parts: Vec<_> = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+    Some(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_added_changed_and_removed_files() {
+        let root = std::env::temp_dir().join(format!("caspar-template-watch-{}", std::process::id()));
+        for dir in ["src", "dist", "node_modules/lib"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::write(root.join("src/index.html"), b"<html>").unwrap();
+        std::fs::write(root.join("src/style.css"), b"body {}").unwrap();
+        std::fs::write(root.join("node_modules/lib/x.js"), b"x").unwrap();
+        std::fs::write(root.join("dist/index.html"), b"<html>").unwrap();
+
+        let before = Snapshot::take(&root, Some(&root.join("dist")));
+        assert!(Snapshot::take(&root, Some(&root.join("dist"))).changes_since(&before).is_empty());
+
+        std::fs::write(root.join("src/index.html"), b"<html><body>").unwrap();
+        std::fs::remove_file(root.join("src/style.css")).unwrap();
+        std::fs::write(root.join("src/main.js"), b"go()").unwrap();
+        std::fs::write(root.join("node_modules/lib/y.js"), b"y").unwrap();
+        std::fs::write(root.join("dist/index.html"), b"<html><body>").unwrap();
+        let after = Snapshot::take(&root, Some(&root.join("dist")));
+        assert_eq!(after.changes_since(&before), ["src/index.html", "src/main.js", "src/style.css"]);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
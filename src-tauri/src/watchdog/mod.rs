@@ -0,0 +1,12 @@
+// Hung-server watchdog
+// Detects a CasparCG that is still running but has stopped answering or rendering
+
+mod monitor;
+mod osc;
+mod probe;
+mod runner;
+
+pub use monitor::*;
+pub use osc::*;
+pub use probe::*;
+pub use runner::*;
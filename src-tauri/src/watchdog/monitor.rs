@@ -0,0 +1,186 @@
+// Hang detection state machine
+// Turns heartbeat results into a verdict, with a start-up grace period
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// What one heartbeat concluded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum WatchdogVerdict {
+    /// Answered, and every channel is still rendering
+    Healthy,
+    /// Not answering yet, but still inside the start-up grace period
+    Starting,
+    /// A heartbeat was missed; not yet enough to act on
+    Missed {
+        count: u32,
+        threshold: u32,
+        reason: String,
+    },
+    /// Missed `threshold` heartbeats in a row — the server is hung
+    Hung { reason: String },
+}
+
+/// Counts consecutive missed heartbeats for one server run. Pure: the caller
+/// supplies the ping result, the OSC counters and the clock.
+#[derive(Debug, Clone)]
+pub struct HangMonitor {
+    threshold: u32,
+    grace: Duration,
+    started: Instant,
+    answered_once: bool,
+    missed: u32,
+    last_osc: Option<HashMap<u32, u64>>,
+}
+
+impl HangMonitor {
+    pub fn new(threshold: u32, grace: Duration, now: Instant) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            grace,
+            started: now,
+            answered_once: false,
+            missed: 0,
+            last_osc: None,
+        }
+    }
+
+    /// Start over for a freshly launched server.
+    pub fn restart(&mut self, now: Instant) {
+        *self = Self::new(self.threshold, self.grace, now);
+    }
+
+    /// Feed one heartbeat. `ping` is the AMCP probe's result; `osc` the current
+    /// per-channel OSC message counts, when OSC is being watched.
+    pub fn observe(
+        &mut self,
+        ping: Result<(), String>,
+        osc: Option<HashMap<u32, u64>>,
+        now: Instant,
+    ) -> WatchdogVerdict {
+        let stalled = osc.as_ref().and_then(|current| self.stalled_channels(current));
+        if osc.is_some() {
+            self.last_osc = osc;
+        }
+
+        let reason = match (ping, stalled) {
+            (Ok(()), None) => {
+                self.answered_once = true;
+                self.missed = 0;
+                return WatchdogVerdict::Healthy;
+            }
+            (Err(e), _) => format!("AMCP not answering: {e}"),
+            (Ok(()), Some(channels)) => format!("no frames from channel {channels}"),
+        };
+
+        if !self.answered_once && now.duration_since(self.started) < self.grace {
+            return WatchdogVerdict::Starting;
+        }
+
+        self.missed += 1;
+        if self.missed >= self.threshold {
+            WatchdogVerdict::Hung { reason }
+        } else {
+            WatchdogVerdict::Missed {
+                count: self.missed,
+                threshold: self.threshold,
+                reason,
+            }
+        }
+    }
+
+    /// Channels that were sending OSC at the last heartbeat but have sent
+    /// nothing since, as a display list ("1, 3").
+    fn stalled_channels(&self, current: &HashMap<u32, u64>) -> Option<String> {
+        let last = self.last_osc.as_ref()?;
+        let mut stalled: Vec<u32> = last
+            .iter()
+            .filter(|(channel, count)| current.get(channel).copied().unwrap_or(0) <= **count)
+            .map(|(channel, _)| *channel)
+            .collect();
+        if stalled.is_empty() {
+            return None;
+        }
+        stalled.sort_unstable();
+        Some(
+            stalled
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(n: u64) -> Duration {
+        Duration::from_secs(n)
+    }
+
+    #[test]
+    fn grace_period_then_threshold() {
+        let t0 = Instant::now();
+        let mut monitor = HangMonitor::new(3, secs(30), t0);
+
+        // Still booting: silence is expected.
+        assert_eq!(monitor.observe(Err("refused".into()), None, t0 + secs(5)), WatchdogVerdict::Starting);
+
+        // Past the grace period misses count up to the threshold.
+        assert!(matches!(
+            monitor.observe(Err("refused".into()), None, t0 + secs(31)),
+            WatchdogVerdict::Missed { count: 1, .. }
+        ));
+        assert!(matches!(
+            monitor.observe(Err("timed out".into()), None, t0 + secs(36)),
+            WatchdogVerdict::Missed { count: 2, .. }
+        ));
+        assert!(matches!(
+            monitor.observe(Err("timed out".into()), None, t0 + secs(41)),
+            WatchdogVerdict::Hung { .. }
+        ));
+    }
+
+    #[test]
+    fn an_answer_resets_the_count_and_ends_the_grace_period() {
+        let t0 = Instant::now();
+        let mut monitor = HangMonitor::new(2, secs(60), t0);
+        assert_eq!(monitor.observe(Ok(()), None, t0 + secs(5)), WatchdogVerdict::Healthy);
+
+        // Having answered once, a miss counts even inside the grace window.
+        assert!(matches!(
+            monitor.observe(Err("timed out".into()), None, t0 + secs(10)),
+            WatchdogVerdict::Missed { count: 1, .. }
+        ));
+        assert_eq!(monitor.observe(Ok(()), None, t0 + secs(15)), WatchdogVerdict::Healthy);
+        assert!(matches!(
+            monitor.observe(Err("timed out".into()), None, t0 + secs(20)),
+            WatchdogVerdict::Missed { count: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn frozen_osc_counts_as_a_miss_while_amcp_answers() {
+        let t0 = Instant::now();
+        let mut monitor = HangMonitor::new(2, secs(0), t0);
+        let counts = |a: u64, b: u64| Some(HashMap::from([(1, a), (2, b)]));
+
+        assert_eq!(monitor.observe(Ok(()), counts(100, 100), t0), WatchdogVerdict::Healthy);
+        assert_eq!(monitor.observe(Ok(()), counts(350, 350), t0 + secs(5)), WatchdogVerdict::Healthy);
+
+        // Channel 2 stops rendering; AMCP still answers.
+        match monitor.observe(Ok(()), counts(600, 350), t0 + secs(10)) {
+            WatchdogVerdict::Missed { reason, .. } => assert!(reason.contains("channel 2")),
+            other => panic!("unexpected {other:?}"),
+        }
+        assert!(matches!(
+            monitor.observe(Ok(()), counts(850, 350), t0 + secs(15)),
+            WatchdogVerdict::Hung { .. }
+        ));
+    }
+}
@@ -0,0 +1,304 @@
+// OSC activity listener
+// Counts the per-frame OSC messages CasparCG sends for each channel
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::net::UdpSocket;
+use tokio::task::JoinHandle;
+
+/// Largest datagram CasparCG sends; a full channel state bundle fits easily.
+const MAX_DATAGRAM: usize = 65_536;
+
+/// Call `f` with each message in an OSC packet, descending into bundles.
+/// Malformed trailing data is ignored rather than rejecting the messages
+/// already read.
+fn for_each_message(packet: &[u8], f: &mut impl FnMut(&[u8])) {
+    if let Some(mut rest) = packet.strip_prefix(b"#bundle\0") {
+        // 8-byte time tag, then size-prefixed elements.
+        if rest.len() < 8 {
+            return;
+        }
+        rest = &rest[8..];
+        while rest.len() >= 4 {
+            let size = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+            rest = &rest[4..];
+            if size > rest.len() {
+                return;
+            }
+            for_each_message(&rest[..size], f);
+            rest = &rest[size..];
+        }
+    } else if packet.first() == Some(&b'/') {
+        f(packet);
+    }
+}
+
+/// An OSC string at the start of `data`, and the padded length it occupies
+fn osc_string(data: &[u8]) -> Option<(&str, usize)> {
+    let end = data.iter().position(|&b| b == 0)?;
+    let text = std::str::from_utf8(&data[..end]).ok()?;
+    Some((text, (end + 4) & !3))
+}
+
+fn message_address(message: &[u8]) -> Option<&str> {
+    osc_string(message).map(|(address, _)| address)
+}
+
+/// The numeric arguments of a message (`i`, `h`, `f`, `d`) in order. Parsing
+/// stops at a type it does not know or at truncated data.
+pub fn message_numbers(message: &[u8]) -> Vec<f64> {
+    let mut numbers = Vec::new();
+    let Some((_, skip)) = osc_string(message) else {
+        return numbers;
+    };
+    let Some((tags, tags_len)) = message.get(skip..).and_then(osc_string) else {
+        return numbers;
+    };
+    let mut args = message.get(skip + tags_len..).unwrap_or_default();
+    for tag in tags.strip_prefix(',').unwrap_or_default().bytes() {
+        let size = match tag {
+            b'i' | b'f' => 4,
+            b'h' | b'd' => 8,
+            b's' => match osc_string(args) {
+                Some((_, len)) => len,
+                None => break,
+            },
+            b'T' | b'F' | b'N' | b'I' => 0,
+            _ => break,
+        };
+        let Some(bytes) = args.get(..size) else { break };
+        match tag {
+            b'i' => numbers.push(i32::from_be_bytes(bytes.try_into().unwrap_or_default()) as f64),
+            b'f' => numbers.push(f32::from_be_bytes(bytes.try_into().unwrap_or_default()) as f64),
+            b'h' => numbers.push(i64::from_be_bytes(bytes.try_into().unwrap_or_default()) as f64),
+            b'd' => numbers.push(f64::from_be_bytes(bytes.try_into().unwrap_or_default())),
+            _ => {}
+        }
+        args = &args[size..];
+    }
+    numbers
+}
+
+/// The channel an OSC address belongs to: `/channel/2/stage/...` → 2.
+pub fn channel_of(address: &str) -> Option<u32> {
+    let mut parts = address.trim_start_matches('/').split('/');
+    match (parts.next(), parts.next()) {
+        (Some("channel"), Some(n)) => n.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Per-channel frame timing from CasparCG's `/channel/N/profiler/time`
+/// messages: `[time taken, time allowed]` for each frame, in seconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameTiming {
+    pub frames: u64,
+    /// Frames that took longer than their slot — each one a dropped or
+    /// repeated frame on the output
+    pub late: u64,
+    pub last_secs: f64,
+    pub budget_secs: f64,
+}
+
+/// Running count of OSC messages received per channel
+#[derive(Debug, Default)]
+pub struct OscActivity {
+    counts: Mutex<HashMap<u32, u64>>,
+    timing: Mutex<HashMap<u32, FrameTiming>>,
+}
+
+impl OscActivity {
+    pub fn record_packet(&self, packet: &[u8]) {
+        let mut counts = self.counts.lock().unwrap_or_else(|e| e.into_inner());
+        let mut timing = self.timing.lock().unwrap_or_else(|e| e.into_inner());
+        for_each_message(packet, &mut |message| {
+            let Some(address) = message_address(message) else { return };
+            let Some(channel) = channel_of(address) else { return };
+            *counts.entry(channel).or_insert(0) += 1;
+            if address.ends_with("/profiler/time") {
+                if let [taken, allowed, ..] = message_numbers(message)[..] {
+                    let t = timing.entry(channel).or_default();
+                    t.frames += 1;
+                    if taken > allowed {
+                        t.late += 1;
+                    }
+                    t.last_secs = taken;
+                    t.budget_secs = allowed;
+                }
+            }
+        });
+    }
+
+    /// Message counts so far, keyed by channel number
+    pub fn snapshot(&self) -> HashMap<u32, u64> {
+        self.counts.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Frame timing so far, for channels that report it
+    pub fn frame_timing(&self) -> HashMap<u32, FrameTiming> {
+        self.timing.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// A UDP listener feeding an `OscActivity`. Stops when dropped.
+#[derive(Debug)]
+pub struct OscListener {
+    activity: Arc<OscActivity>,
+    port: u16,
+    task: JoinHandle<()>,
+}
+
+impl OscListener {
+    /// Listen on 127.0.0.1:`port` (0 picks a free port). Must be called from
+    /// within the async runtime.
+    pub async fn bind(port: u16) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind(("127.0.0.1", port)).await?;
+        let port = socket.local_addr()?.port();
+        let activity = Arc::new(OscActivity::default());
+        let sink = activity.clone();
+        let task = tokio::spawn(async move {
+            let mut buf = vec![0u8; MAX_DATAGRAM];
+            while let Ok((len, _)) = socket.recv_from(&mut buf).await {
+                sink.record_packet(&buf[..len]);
+            }
+        });
+        Ok(Self {
+            activity,
+            port,
+            task,
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn activity(&self) -> &Arc<OscActivity> {
+        &self.activity
+    }
+}
+
+impl Drop for OscListener {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The address of every message in a packet, in order.
+    fn packet_addresses(packet: &[u8], out: &mut Vec<String>) {
+        for_each_message(packet, &mut |message| {
+            if let Some(address) = message_address(message) {
+                out.push(address.to_string());
+            }
+        });
+    }
+
+    /// Encode a bare OSC message with no arguments.
+    fn message(address: &str) -> Vec<u8> {
+        let mut out = address.as_bytes().to_vec();
+        out.push(0);
+        while !out.len().is_multiple_of(4) {
+            out.push(0);
+        }
+        out.extend_from_slice(b",\0\0\0");
+        out
+    }
+
+    fn bundle(elements: &[Vec<u8>]) -> Vec<u8> {
+        let mut out = b"#bundle\0".to_vec();
+        out.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]);
+        for element in elements {
+            out.extend_from_slice(&(element.len() as u32).to_be_bytes());
+            out.extend_from_slice(element);
+        }
+        out
+    }
+
+    #[test]
+    fn counts_messages_per_channel_through_nested_bundles() {
+        let activity = OscActivity::default();
+        let inner = bundle(&[message("/channel/2/mixer/audio/volume")]);
+        activity.record_packet(&bundle(&[
+            message("/channel/1/stage/layer/10/foreground/file/time"),
+            message("/channel/1/framerate"),
+            inner,
+            message("/diag/mixer"),
+        ]));
+
+        let counts = activity.snapshot();
+        assert_eq!(counts.get(&1), Some(&2));
+        assert_eq!(counts.get(&2), Some(&1));
+        assert_eq!(counts.len(), 2);
+    }
+
+    /// Encode a message with float arguments.
+    fn floats(address: &str, args: &[f32]) -> Vec<u8> {
+        let mut out = message(address);
+        out.truncate(out.len() - 4);
+        let mut tags = format!(",{}", "f".repeat(args.len())).into_bytes();
+        tags.push(0);
+        while !tags.len().is_multiple_of(4) {
+            tags.push(0);
+        }
+        out.extend_from_slice(&tags);
+        for arg in args {
+            out.extend_from_slice(&arg.to_be_bytes());
+        }
+        out
+    }
+
+    #[test]
+    fn counts_late_frames_from_the_profiler() {
+        let activity = OscActivity::default();
+        activity.record_packet(&bundle(&[
+            floats("/channel/1/profiler/time", &[0.010, 0.020]),
+            floats("/channel/1/profiler/time", &[0.030, 0.020]),
+            floats("/channel/2/profiler/time", &[0.015, 0.040]),
+            floats("/channel/2/framerate", &[50.0]),
+        ]));
+        let timing = activity.frame_timing();
+        assert_eq!(timing[&1].frames, 2);
+        assert_eq!(timing[&1].late, 1);
+        assert!((timing[&1].last_secs - 0.030).abs() < 1e-6);
+        assert_eq!(timing[&2].late, 0);
+        assert_eq!(activity.snapshot()[&2], 2);
+        assert_eq!(message_numbers(&message("/channel/1/framerate")), Vec::<f64>::new());
+    }
+
+    #[test]
+    fn tolerates_truncated_bundles() {
+        let mut packet = bundle(&[message("/channel/1/framerate")]);
+        packet.extend_from_slice(&[0, 0, 0, 99, b'/']);
+        let mut addresses = Vec::new();
+        packet_addresses(&packet, &mut addresses);
+        assert_eq!(addresses, vec!["/channel/1/framerate".to_string()]);
+        assert_eq!(channel_of("/channel/x/framerate"), None);
+    }
+
+    #[test]
+    fn listener_counts_datagrams() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let listener = OscListener::bind(0).await.unwrap();
+            let sender = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+            for _ in 0..3 {
+                sender
+                    .send_to(&message("/channel/1/framerate"), ("127.0.0.1", listener.port()))
+                    .await
+                    .unwrap();
+            }
+            for _ in 0..50 {
+                if listener.activity().snapshot().get(&1) == Some(&3) {
+                    return;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+            panic!("expected 3 messages, got {:?}", listener.activity().snapshot());
+        });
+    }
+}
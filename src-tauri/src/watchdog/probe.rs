@@ -0,0 +1,112 @@
+// AMCP liveness probe
+// Pings the server over its own AMCP connection, with a hard timeout
+
+use std::time::Duration;
+
+use crate::amcp::AmcpClient;
+
+/// Heartbeat over a dedicated AMCP connection — never the GUI's shared client,
+/// whose lock may be held by the very command that is stuck. The connection is
+/// kept open between pings (CasparCG logs every new client) and dropped after
+/// any failure so the next ping starts clean.
+pub struct AmcpProbe {
+    host: String,
+    port: u16,
+    timeout: Duration,
+    client: Option<AmcpClient>,
+}
+
+impl AmcpProbe {
+    pub fn new(host: impl Into<String>, port: u16, timeout: Duration) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            timeout,
+            client: None,
+        }
+    }
+
+    /// One heartbeat: any well-formed AMCP reply within the timeout counts.
+    /// VERSION is answered by the AMCP thread without touching a channel, so a
+    /// frozen channel is caught by the OSC check rather than here.
+    pub async fn ping(&mut self) -> Result<(), String> {
+        let result = tokio::time::timeout(self.timeout, self.exchange()).await;
+        match result {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => {
+                self.client = None;
+                Err(e)
+            }
+            Err(_) => {
+                self.client = None;
+                Err(format!("no reply within {}s", self.timeout.as_secs_f32()))
+            }
+        }
+    }
+
+    async fn exchange(&mut self) -> Result<(), String> {
+        if self.client.is_none() {
+            let mut client = AmcpClient::new();
+            client
+                .connect(&self.host, self.port)
+                .await
+                .map_err(|e| e.to_string())?;
+            self.client = Some(client);
+        }
+        let client = self.client.as_ref().ok_or("not connected")?;
+        client
+            .send_command("VERSION")
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn answers_then_times_out_when_the_server_stops_replying() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+
+            // A fake server that answers the first VERSION, then goes silent
+            // with the connection still open — a deadlocked CasparCG.
+            tokio::spawn(async move {
+                let (socket, _) = listener.accept().await.unwrap();
+                let (read, mut write) = socket.into_split();
+                let mut lines = BufReader::new(read).lines();
+                let mut answered = false;
+                while let Ok(Some(_)) = lines.next_line().await {
+                    if !answered {
+                        write.write_all(b"201 VERSION OK\r\n2.4.0 Stable\r\n").await.unwrap();
+                        answered = true;
+                    }
+                }
+            });
+
+            let mut probe = AmcpProbe::new("127.0.0.1", port, Duration::from_millis(200));
+            assert_eq!(probe.ping().await, Ok(()));
+            assert!(probe.ping().await.is_err());
+        });
+    }
+
+    #[test]
+    fn refused_connection_is_a_miss() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            // Bind then drop to get a port nothing listens on.
+            let port = {
+                let l = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+                l.local_addr().unwrap().port()
+            };
+            let mut probe = AmcpProbe::new("127.0.0.1", port, Duration::from_millis(500));
+            assert!(probe.ping().await.is_err());
+        });
+    }
+}
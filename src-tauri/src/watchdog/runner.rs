@@ -0,0 +1,69 @@
+// Watchdog runner
+// Paces heartbeats for the supervisor and combines the AMCP and OSC checks
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::{AmcpProbe, HangMonitor, OscActivity, OscListener, WatchdogVerdict};
+use crate::config::WatchdogConfig;
+
+/// One server run's watchdog, driven from the supervisor's loop. The supervisor
+/// ticks faster than heartbeats are due, so `tick` is a no-op until the next
+/// interval has elapsed.
+pub struct Watchdog {
+    interval: Duration,
+    probe: AmcpProbe,
+    osc: Option<OscListener>,
+    monitor: HangMonitor,
+    next_beat: Instant,
+}
+
+impl Watchdog {
+    /// A watchdog for the server answering AMCP on `host:port`.
+    pub fn new(config: &WatchdogConfig, host: &str, port: u16, now: Instant) -> Self {
+        let interval = Duration::from_secs(config.interval_secs.max(1));
+        Self {
+            interval,
+            probe: AmcpProbe::new(host, port, Duration::from_secs(config.timeout_secs.max(1))),
+            osc: None,
+            monitor: HangMonitor::new(
+                config.missed_threshold,
+                Duration::from_secs(config.startup_grace_secs),
+                now,
+            ),
+            next_beat: now + interval,
+        }
+    }
+
+    /// Also watch per-channel OSC activity on `port`. CasparCG must be told to
+    /// send there (a predefined OSC client in casparcg.config). Returns the
+    /// port actually bound.
+    pub async fn listen_osc(&mut self, port: u16) -> std::io::Result<u16> {
+        let listener = OscListener::bind(port).await?;
+        let bound = listener.port();
+        self.osc = Some(listener);
+        Ok(bound)
+    }
+
+    /// What the OSC listener has seen, when there is one
+    pub fn osc_activity(&self) -> Option<Arc<OscActivity>> {
+        self.osc.as_ref().map(|l| l.activity().clone())
+    }
+
+    /// Run a heartbeat if one is due. `None` when it is not yet time.
+    pub async fn tick(&mut self, now: Instant) -> Option<WatchdogVerdict> {
+        if now < self.next_beat {
+            return None;
+        }
+        self.next_beat = now + self.interval;
+        let ping = self.probe.ping().await;
+        let osc = self.osc.as_ref().map(|l| l.activity().snapshot());
+        Some(self.monitor.observe(ping, osc, Instant::now()))
+    }
+
+    /// Reset for a freshly launched server: a new grace period, no misses.
+    pub fn restart(&mut self, now: Instant) {
+        self.monitor.restart(now);
+        self.next_beat = now + self.interval;
+    }
+}
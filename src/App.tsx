@@ -1,7 +1,8 @@
 import { useEffect, useState } from 'react';
 import { listen } from '@tauri-apps/api/event';
 import { useAppStore } from './lib/store';
-import type { ScannerEndpoint } from './lib/types';
+import type { Diagnostic, LogEntry, ScannerEndpoint } from './lib/types';
+import * as tauri from './lib/tauri';
 import { ProfileSidebar } from './components/ProfileSidebar';
 import { TabBar } from './components/TabBar';
 import { PathsPanel } from './components/PathsPanel';
@@ -12,9 +13,11 @@ import { StatusBar } from './components/StatusBar';
 import { SetupWizard } from './components/SetupWizard';
 import { ServerPanel } from './components/ServerPanel';
 import { PreviewPanel } from './components/PreviewPanel';
+import { TemplatesPanel } from './components/TemplatesPanel';
+import { CommissioningPanel } from './components/CommissioningPanel';
 
 function App() {
-  const { activeTab, settings, initialise } = useAppStore();
+  const { activeTab, settings, readOnly, initialise } = useAppStore();
   const [isLoading, setIsLoading] = useState(true);
 
   useEffect(() => {
@@ -22,9 +25,14 @@ function App() {
   }, [initialise]);
 
   // Capture the server log at the app level so it persists across tab switches
-  // and survives a crash — ServerPanel only reads it.
+  // and survives a crash — ServerPanel only reads it. The backend keeps the
+  // history, so a window reload starts from it rather than from an empty log.
   useEffect(() => {
-    const unlisten = listen<string>('caspar-log', (event) => {
+    tauri
+      .getLogEntries({ limit: 2000 })
+      .then((page) => useAppStore.getState().setServerLog(page.entries))
+      .catch(() => {});
+    const unlisten = listen<LogEntry>('caspar-log', (event) => {
       useAppStore.getState().appendServerLog(event.payload);
     });
     return () => {
@@ -32,6 +40,20 @@ function App() {
     };
   }, []);
 
+  // Known failures diagnosed by the backend, captured app-level like the log.
+  useEffect(() => {
+    tauri
+      .getDiagnostics()
+      .then((list) => useAppStore.getState().setDiagnostics(list))
+      .catch(() => {});
+    const unlisten = listen<Diagnostic>('diagnostic', (event) => {
+      useAppStore.getState().appendDiagnostic(event.payload);
+    });
+    return () => {
+      unlisten.then((u) => u());
+    };
+  }, []);
+
   // Record the media scanner endpoint the launcher resolved at start, so the
   // Server panel can show which port it landed on (the stock 8000 is not always
   // free). Captured app-level for the same reason as the log.
@@ -44,6 +66,43 @@ function App() {
     };
   }, []);
 
+  // A profile activated through the control API: show it here too.
+  useEffect(() => {
+    const unlisten = listen<string>('profile-activated', async (event) => {
+      const { loadProfiles, loadSettings, selectProfile } = useAppStore.getState();
+      await loadSettings();
+      await loadProfiles();
+      await selectProfile(event.payload);
+    });
+    return () => {
+      unlisten.then((u) => u());
+    };
+  }, []);
+
+  // A saved profile named by a second launch: show it, it is not made active
+  useEffect(() => {
+    const unlisten = listen<string>('show-profile', async (event) => {
+      const { loadProfiles, selectProfile } = useAppStore.getState();
+      await loadProfiles();
+      await selectProfile(event.payload);
+    });
+    return () => {
+      unlisten.then((u) => u());
+    };
+  }, []);
+
+  // A profile file handed over by a second launch (e.g. a double-clicked .json)
+  useEffect(() => {
+    const unlisten = listen<string>('open-profile-file', async (event) => {
+      const { loadProfiles, openProfileFile } = useAppStore.getState();
+      await loadProfiles();
+      await openProfileFile(event.payload);
+    });
+    return () => {
+      unlisten.then((u) => u());
+    };
+  }, []);
+
   if (isLoading) {
     return (
       <div className="flex items-center justify-center h-screen bg-[var(--color-bg-primary)]">
@@ -62,6 +121,12 @@ function App() {
 
   return (
     <div className="flex flex-col h-screen bg-[var(--color-bg-primary)]">
+      {readOnly && (
+        <div className="px-4 py-1 text-xs text-center bg-amber-600/20 text-amber-300 border-b border-amber-600/40">
+          Read-only — started with --read-only, so changes, starts and stops are disabled
+        </div>
+      )}
+
       {/* Main content area */}
       <div className="flex flex-1 overflow-hidden">
         {/* Profile sidebar */}
@@ -78,7 +143,9 @@ function App() {
             {activeTab === 'paths' && <PathsPanel />}
             {activeTab === 'channels' && <ChannelsPanel />}
             {activeTab === 'preview' && <PreviewPanel />}
+            {activeTab === 'templates' && <TemplatesPanel />}
             {activeTab === 'decklink' && <DeckLinkPanel />}
+            {activeTab === 'commissioning' && <CommissioningPanel />}
             {activeTab === 'system' && <SystemInfoPanel />}
           </div>
         </div>
@@ -6,6 +6,8 @@
 import { useState } from 'react';
 import { useAppStore } from '../lib/store';
 import type {
+  AudioIdentMode,
+  AudioTracks,
   Channel,
   Consumer,
   GlobalConfig,
@@ -18,6 +20,7 @@ import type {
 import {
   VIDEO_MODES,
   DEFAULT_CHANNEL,
+  DEFAULT_IDENT_CONFIG,
   createDefaultDeckLinkConsumer,
   createDefaultNdiConsumer,
   createDefaultScreenConsumer,
@@ -61,6 +64,7 @@ export function ChannelsPanel() {
     testAllChannels,
     stopAllTests,
     testChannel,
+    testAvSync,
     stopChannelTest,
   } = useAppStore();
 
@@ -88,6 +92,11 @@ export function ChannelsPanel() {
     }
   };
 
+  const ident = currentConfig.ident ?? DEFAULT_IDENT_CONFIG;
+  const updateIdent = (changes: Partial<typeof ident>) => {
+    updateConfig({ ...currentConfig, ident: { ...ident, ...changes } });
+  };
+
   const updateChannels = (newChannels: Channel[]) => {
     const newConfig: GlobalConfig = {
       ...currentConfig,
@@ -147,6 +156,43 @@ export function ChannelsPanel() {
         </div>
       </div>
 
+      <div className="flex flex-wrap items-center gap-3 mb-4 text-sm">
+        <span className="text-[var(--color-text-secondary)]">Test audio</span>
+        <select
+          value={ident.audio}
+          onChange={(e) => updateIdent({ audio: e.target.value as AudioIdentMode })}
+          className="px-2 py-1 bg-[var(--color-bg-primary)] border border-[var(--color-border)] rounded"
+          title="Left/right ident: each pair breaks once per pair number, then left once, right twice"
+        >
+          <option value="ident">Track ident (GLITS-style)</option>
+          <option value="lineup">1 kHz line-up</option>
+          <option value="off">Off</option>
+        </select>
+        <select
+          value={ident.tracks}
+          onChange={(e) => updateIdent({ tracks: e.target.value as AudioTracks })}
+          disabled={ident.audio === 'off'}
+          className="px-2 py-1 bg-[var(--color-bg-primary)] border border-[var(--color-border)] rounded"
+        >
+          <option value="stereo">Stereo</option>
+          <option value="8ch">8 tracks</option>
+          <option value="16ch">16 tracks</option>
+        </select>
+        <label className="flex items-center gap-1">
+          <input
+            type="number"
+            min={-60}
+            max={0}
+            step={1}
+            value={ident.lineup_dbfs}
+            onChange={(e) => updateIdent({ lineup_dbfs: Number(e.target.value) })}
+            disabled={ident.audio === 'off'}
+            className="w-16 px-2 py-1 bg-[var(--color-bg-primary)] border border-[var(--color-border)] rounded"
+          />
+          <span className="text-[var(--color-text-muted)]">dBFS</span>
+        </label>
+      </div>
+
       <div className="space-y-4">
         {channels.map((channel, index) => (
           <ChannelCard
@@ -172,6 +218,13 @@ export function ChannelsPanel() {
                 console.error('Toggle channel test failed:', error);
               }
             }}
+            onAvSync={async () => {
+              try {
+                await testAvSync(index + 1);
+              } catch (error) {
+                console.error('AV sync test failed:', error);
+              }
+            }}
           />
         ))}
       </div>
@@ -191,6 +244,7 @@ interface ChannelCardProps {
   isTesting: boolean;
   canTest: boolean;
   onToggleTest: () => void;
+  onAvSync: () => void;
 }
 
 function ChannelCard({
@@ -205,6 +259,7 @@ function ChannelCard({
   isTesting,
   canTest,
   onToggleTest,
+  onAvSync,
 }: ChannelCardProps) {
   const [isExpanded, setIsExpanded] = useState(true);
   const channelIssues = issuesForChannel(issues, index);
@@ -301,6 +356,24 @@ function ChannelCard({
           >
             {isTesting ? '■ Stop' : '▶ Test'}
           </button>
+          {!isTesting && (
+            <button
+              onClick={onAvSync}
+              disabled={!canTest}
+              className={`px-2 py-1 text-xs rounded transition-colors ${
+                !canTest
+                  ? 'bg-[var(--color-bg-tertiary)] text-[var(--color-text-muted)] cursor-not-allowed'
+                  : 'bg-[var(--color-bg-tertiary)] text-[var(--color-text-secondary)] hover:bg-[var(--color-border)]'
+              }`}
+              title={
+                !canTest
+                  ? 'Connect to CasparCG server first'
+                  : 'Loop a flash frame with a coincident beep, for measuring lip-sync downstream'
+              }
+            >
+              ▶ AV Sync
+            </button>
+          )}
 
           {canRemove && (
             <button
@@ -0,0 +1,261 @@
+// Commissioning panel
+// Walks every channel and output of the active profile, playing its
+// identification, and records the operator's checks for the report
+
+import { useEffect, useState } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { useAppStore } from '../lib/store';
+import * as tauri from '../lib/tauri';
+import type { CheckResult, Commissioning } from '../lib/types';
+
+const BUTTON =
+  'px-3 py-1 bg-[var(--color-bg-tertiary)] rounded hover:bg-[var(--color-border)] transition-colors disabled:opacity-50';
+
+const RESULT_STYLE: Record<CheckResult, string> = {
+  pass: 'bg-green-500/15 text-green-400',
+  fail: 'bg-red-500/15 text-red-400',
+  skipped: 'bg-[var(--color-bg-tertiary)] text-[var(--color-text-muted)]',
+};
+
+export function CommissioningPanel() {
+  const { currentConfig, activeProfile, readOnly } = useAppStore();
+  const [session, setSession] = useState<Commissioning | null>(null);
+  const [operator, setOperator] = useState('');
+  const [selected, setSelected] = useState(0);
+  const [notes, setNotes] = useState('');
+  // Play each step's identification as soon as the walk reaches it
+  const [autoPlay, setAutoPlay] = useState(true);
+  const [exported, setExported] = useState<string | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [busy, setBusy] = useState(false);
+
+  useEffect(() => {
+    tauri
+      .getCommissioning()
+      .then((s) => {
+        setSession(s);
+        setSelected(s?.current ?? 0);
+        setNotes(s?.steps[s?.current ?? 0]?.notes ?? '');
+      })
+      .catch((e) => setError(String(e)));
+    const unlisten = listen<Commissioning | null>('commissioning', (event) => setSession(event.payload));
+    return () => {
+      unlisten.then((u) => u());
+    };
+  }, []);
+
+  const run = async (action: () => Promise<unknown>) => {
+    setError(null);
+    setBusy(true);
+    try {
+      await action();
+    } catch (e) {
+      setError(String(e));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const select = (index: number) => {
+    setSelected(index);
+    setNotes(session?.steps[index]?.notes ?? '');
+  };
+
+  const play = (index: number) => run(() => tauri.playCommissioningStep(index).then(setSession));
+
+  const record = (result: CheckResult) =>
+    run(async () => {
+      const updated = await tauri.recordCommissioningStep(selected, result, notes);
+      setSession(updated);
+      const next = selected + 1;
+      if (next < updated.steps.length) {
+        select(next);
+        setNotes(updated.steps[next].notes);
+        if (autoPlay) {
+          setSession(await tauri.playCommissioningStep(next));
+        }
+      }
+    });
+
+  const exportReport = (format: 'markdown' | 'html') =>
+    run(async () => setExported(await tauri.exportCommissioningReport(format)));
+
+  const header = (
+    <div className="mb-4">
+      <h2 className="text-lg font-semibold">Commissioning</h2>
+      <p className="text-sm text-[var(--color-text-secondary)]">
+        Walk every channel and output of the profile and record what was seen and heard
+      </p>
+    </div>
+  );
+
+  if (!session) {
+    return (
+      <div>
+        {header}
+        <div className="panel mb-6">
+          <div className="panel-header">New Session</div>
+          <div className="panel-content text-sm">
+            <p className="text-[var(--color-text-muted)] mb-4">
+              Steps through each channel of <span className="font-mono">{activeProfile ?? 'the active profile'}</span>:
+              its identification, every output's fill and key, each embedded audio pair, NDI,
+              screen and system audio. Each step plays the channel test while the server is
+              running; with it stopped, DeckLink fill and key are driven by the direct output test.
+            </p>
+            <div className="flex items-center gap-2">
+              <span className="w-28 text-[var(--color-text-secondary)]">Operator</span>
+              <input
+                value={operator}
+                placeholder="Name for the report"
+                onChange={(e) => setOperator(e.target.value)}
+                className="flex-1 px-2 py-1 bg-[var(--color-bg-primary)] border border-[var(--color-border)] rounded"
+              />
+              <button
+                onClick={() =>
+                  run(async () => {
+                    const started = await tauri.startCommissioning(operator);
+                    setSession(started);
+                    select(0);
+                    if (autoPlay && started.steps.length > 0) {
+                      setSession(await tauri.playCommissioningStep(0));
+                    }
+                  })
+                }
+                disabled={busy || readOnly || !currentConfig}
+                className="px-3 py-1 bg-[var(--color-accent)] text-white rounded hover:bg-[var(--color-accent-hover)] transition-colors disabled:opacity-50"
+              >
+                Start
+              </button>
+            </div>
+            {error && <div className="mt-2 text-[var(--color-error)]">{error}</div>}
+          </div>
+        </div>
+      </div>
+    );
+  }
+
+  const step = session.steps[selected];
+  const tally = (result?: CheckResult) => session.steps.filter((s) => s.result === result).length;
+
+  return (
+    <div>
+      {header}
+      <div className="panel mb-6">
+        <div className="panel-header">
+          {session.profile} — {session.operator || 'no operator'}, started{' '}
+          {new Date(session.started).toLocaleString()}
+          {session.finished && `, finished ${new Date(session.finished).toLocaleString()}`}
+        </div>
+        <div className="panel-content text-sm">
+          <div className="flex flex-wrap items-center gap-2 mb-4">
+            <span className="mr-auto text-[var(--color-text-secondary)]">
+              {tally('pass')} passed, {tally('fail')} failed, {tally('skipped')} skipped,{' '}
+              {tally(undefined)} to check
+            </span>
+            <label className="flex items-center gap-2">
+              <input type="checkbox" checked={autoPlay} onChange={(e) => setAutoPlay(e.target.checked)} />
+              Play each step as it comes up
+            </label>
+            <button onClick={() => run(() => tauri.finishCommissioning().then(setSession))} disabled={busy} className={BUTTON}>
+              Finish
+            </button>
+            <button onClick={() => exportReport('html')} disabled={busy} className={BUTTON}>
+              Export HTML
+            </button>
+            <button onClick={() => exportReport('markdown')} disabled={busy} className={BUTTON}>
+              Export Markdown
+            </button>
+            <button
+              onClick={() =>
+                run(async () => {
+                  await tauri.closeCommissioning();
+                  setSession(null);
+                  setExported(null);
+                })
+              }
+              disabled={busy}
+              className={BUTTON}
+            >
+              Close
+            </button>
+          </div>
+          {exported && (
+            <div className="mb-3 p-2 rounded bg-[var(--color-bg-tertiary)]">
+              Report saved to <span className="font-mono">{exported}</span>
+            </div>
+          )}
+          {error && <div className="mb-3 text-[var(--color-error)]">{error}</div>}
+
+          <div className="flex gap-4">
+            <ol className="w-80 max-h-[520px] overflow-auto border border-[var(--color-border)] rounded">
+              {session.steps.map((s, i) => (
+                <li key={i}>
+                  <button
+                    onClick={() => select(i)}
+                    className={`w-full flex items-center justify-between gap-2 px-2 py-1 text-left hover:bg-[var(--color-bg-tertiary)] ${
+                      i === selected ? 'bg-[var(--color-bg-tertiary)]' : ''
+                    }`}
+                  >
+                    <span className="truncate">{s.title}</span>
+                    {s.result && (
+                      <span className={`px-1.5 rounded text-xs ${RESULT_STYLE[s.result]}`}>{s.result}</span>
+                    )}
+                  </button>
+                </li>
+              ))}
+            </ol>
+
+            {step && (
+              <div className="flex-1">
+                <div className="font-medium mb-1">
+                  Step {selected + 1} of {session.steps.length}: {step.title}
+                </div>
+                <p className="mb-3 text-[var(--color-text-secondary)]">{step.instructions}</p>
+                <div className="flex items-center gap-2 mb-3">
+                  <button onClick={() => play(selected)} disabled={busy || readOnly} className={BUTTON}>
+                    Play identification
+                  </button>
+                  <span className="text-[var(--color-text-muted)]">
+                    {step.played === 'amcp' && 'Played with the channel test'}
+                    {step.played === 'output_test' && 'Played with the direct output test'}
+                  </span>
+                </div>
+                <textarea
+                  value={notes}
+                  rows={3}
+                  placeholder="Notes: router destination, monitor, what was wrong…"
+                  onChange={(e) => setNotes(e.target.value)}
+                  className="w-full mb-3 px-2 py-1 bg-[var(--color-bg-primary)] border border-[var(--color-border)] rounded"
+                />
+                <div className="flex items-center gap-2">
+                  <button
+                    onClick={() => record('pass')}
+                    disabled={busy || readOnly}
+                    className="px-3 py-1 rounded bg-green-600 text-white hover:bg-green-500 disabled:opacity-50"
+                  >
+                    Pass
+                  </button>
+                  <button
+                    onClick={() => record('fail')}
+                    disabled={busy || readOnly}
+                    className="px-3 py-1 rounded bg-red-600 text-white hover:bg-red-500 disabled:opacity-50"
+                  >
+                    Fail
+                  </button>
+                  <button onClick={() => record('skipped')} disabled={busy || readOnly} className={BUTTON}>
+                    Skip
+                  </button>
+                  {step.checked && (
+                    <span className="text-[var(--color-text-muted)]">
+                      Recorded {step.result} at {new Date(step.checked).toLocaleTimeString()}
+                    </span>
+                  )}
+                </div>
+              </div>
+            )}
+          </div>
+        </div>
+      </div>
+    </div>
+  );
+}
@@ -12,13 +12,14 @@ export function ProfileSidebar() {
     selectProfile,
     saveProfile,
     createProfile,
+    readOnly,
   } = useAppStore();
   const [isCreating, setIsCreating] = useState(false);
   const [newProfileName, setNewProfileName] = useState('');
   const [error, setError] = useState<string | null>(null);
 
   const handleSelectProfile = async (name: string) => {
-    if (configDirty) {
+    if (configDirty && !readOnly) {
       // TODO: Show confirmation dialog
       const confirm = window.confirm(
         'You have unsaved changes. Do you want to save before switching profiles?'
@@ -122,7 +123,7 @@ export function ProfileSidebar() {
               </button>
             </div>
           </form>
-        ) : (
+        ) : readOnly ? null : (
           <div className="flex flex-col gap-2">
             <button
               onClick={() => setIsCreating(true)}
@@ -4,9 +4,22 @@
 // — the embedded-log model of the classic CasparCG launcher.
 
 import { useEffect, useRef, useState } from 'react';
+import { listen } from '@tauri-apps/api/event';
 import { useAppStore } from '../lib/store';
 import * as tauri from '../lib/tauri';
 import { validateConfig, errorsOnly } from '../lib/validation';
+import type {
+  AutoStartStatus,
+  Diagnostic,
+  LogEntry,
+  PortCheck,
+  PortPreflight,
+  ResourceAlert,
+  ResourceSample,
+  TestSession,
+  TestTarget,
+} from '../lib/types';
+import { PORT_ROLE_LABELS } from '../lib/types';
 
 // CasparCG logs "Failed to enable external/internal keyer" at error level on any
 // DeckLink card that has no keyer hardware (e.g. the SDI Micro). It is benign:
@@ -15,51 +28,121 @@ import { validateConfig, errorsOnly } from '../lib/validation';
 // raising a false alarm.
 const BENIGN_KEYER_LOG = /failed to enable (external|internal) keyer/i;
 
-// Deterministic log colouring. Rather than substring-matching "error" anywhere
-// in a line — which mis-colours an info line that merely mentions an error — we
-// key off the structured level token each source actually emits: CasparCG's
-// "[timestamp] [level] …", the scanner's pino "level": <number>, and our own
-// "[launcher]" prefix. Same line in, same colour out, every time.
-function classifyLogLine(line: string): { cls: string; keyerNote?: boolean } {
-  // The launcher's own orchestration lines — distinct from server output.
-  if (line.startsWith('[launcher]')) {
-    const lower = line.toLowerCase();
-    const bad =
-      lower.includes('giving up') || lower.includes('failed') || lower.includes('crashed');
-    return { cls: bad ? 'text-red-400' : 'text-cyan-400' };
-  }
+// Deterministic log colouring, keyed off the level the backend parsed from each
+// source's own format (CasparCG's "[level]" token, the scanner's pino level) —
+// never off words in the message body. Same entry in, same colour out.
+function classifyLogEntry(entry: LogEntry): { cls: string; keyerNote?: boolean } {
   // The no-keyer "error" CasparCG logs on cards without keyer hardware is benign.
-  if (BENIGN_KEYER_LOG.test(line)) {
+  if (entry.source === 'server' && BENIGN_KEYER_LOG.test(entry.text)) {
     return { cls: 'text-amber-400/80', keyerNote: true };
   }
-  // Scanner lines are pino JSON: { "level": <number> }. 50+ = error/fatal,
-  // 40 = warning, below = info/debug.
-  if (line.startsWith('[scanner]')) {
-    const m = line.match(/"level":\s*(\d+)/);
-    if (m) {
-      const level = parseInt(m[1], 10);
-      if (level >= 50) return { cls: 'text-red-400' };
-      if (level >= 40) return { cls: 'text-amber-400' };
-    }
-    return { cls: 'text-[var(--color-text-muted)]' };
-  }
-  // CasparCG server lines — colour by the level token, not the message body.
-  const m = line.match(/\]\s*\[(trace|debug|info|warning|error|fatal)\]/i);
-  if (m) {
-    switch (m[1].toLowerCase()) {
-      case 'fatal':
-      case 'error':
-        return { cls: 'text-red-400' };
-      case 'warning':
-        return { cls: 'text-amber-400' };
-      case 'trace':
-      case 'debug':
-        return { cls: 'text-[var(--color-text-muted)]' };
-      default:
-        return { cls: 'text-[var(--color-text-secondary)]' };
-    }
+  switch (entry.level) {
+    case 'fatal':
+    case 'error':
+      return { cls: 'text-red-400' };
+    case 'warning':
+      return { cls: 'text-amber-400' };
+    case 'trace':
+    case 'debug':
+      return { cls: 'text-[var(--color-text-muted)]' };
+    default:
+      // The launcher's own orchestration lines — distinct from server output.
+      if (entry.source === 'launcher') return { cls: 'text-cyan-400' };
+      if (entry.source === 'scanner') return { cls: 'text-[var(--color-text-muted)]' };
+      return { cls: 'text-[var(--color-text-secondary)]' };
   }
-  return { cls: 'text-[var(--color-text-secondary)]' };
+}
+
+const DIAGNOSTIC_STYLE: Record<Diagnostic['severity'], string> = {
+  critical: 'bg-red-500/15 border-red-500/40 text-red-300',
+  error: 'bg-red-500/10 border-red-500/30 text-red-300',
+  warning: 'bg-amber-500/10 border-amber-500/30 text-amber-300',
+  info: 'bg-cyan-500/10 border-cyan-500/30 text-cyan-300',
+};
+
+/** Local wall-clock time of an entry, to the millisecond. */
+function formatLogTime(timestamp: string): string {
+  const d = new Date(timestamp);
+  if (Number.isNaN(d.getTime())) return '';
+  const pad = (n: number, w = 2) => String(n).padStart(w, '0');
+  return `${pad(d.getHours())}:${pad(d.getMinutes())}:${pad(d.getSeconds())}.${pad(d.getMilliseconds(), 3)}`;
+}
+
+function formatBytes(bytes: number): string {
+  const gb = bytes / 1024 ** 3;
+  return gb >= 1 ? `${gb.toFixed(2)} GB` : `${(bytes / 1024 ** 2).toFixed(0)} MB`;
+}
+
+/** Memory trend of the recent samples as a small inline chart. */
+function Sparkline({ samples }: { samples: ResourceSample[] }) {
+  if (samples.length < 2) return null;
+  const values = samples.map((s) => s.rss_bytes);
+  const max = Math.max(...values);
+  const min = Math.min(...values);
+  const span = max - min || 1;
+  const points = values
+    .map((v, i) => `${(i / (values.length - 1)) * 100},${18 - ((v - min) / span) * 16}`)
+    .join(' ');
+  return (
+    <svg viewBox="0 0 100 20" preserveAspectRatio="none" className="w-24 h-4">
+      <polyline points={points} fill="none" stroke="currentColor" strokeWidth="1.5" vectorEffect="non-scaling-stroke" />
+    </svg>
+  );
+}
+
+// Samples kept for the readout's trend: ten minutes at the default interval.
+const RESOURCE_TREND = 120;
+
+/** Live CPU / memory / threads / handles of the server and scanner trees. */
+function ResourceReadout({ running }: { running: boolean }) {
+  const [server, setServer] = useState<ResourceSample[]>([]);
+  const [scanner, setScanner] = useState<ResourceSample | null>(null);
+
+  useEffect(() => {
+    if (!running) return;
+    let cancelled = false;
+    let since: string | undefined;
+    const poll = async () => {
+      try {
+        const series = await tauri.getResourceHistory(since);
+        if (cancelled) return;
+        const last = series.server[series.server.length - 1] ?? series.scanner[series.scanner.length - 1];
+        if (last) since = last.timestamp;
+        if (series.server.length > 0) {
+          setServer((prev) => [...prev, ...series.server].slice(-RESOURCE_TREND));
+        }
+        if (series.scanner.length > 0) {
+          setScanner(series.scanner[series.scanner.length - 1]);
+        }
+      } catch {
+        /* ignore */
+      }
+    };
+    poll();
+    const id = setInterval(poll, 5000);
+    return () => {
+      cancelled = true;
+      clearInterval(id);
+    };
+  }, [running]);
+
+  const latest = server[server.length - 1];
+  if (!running || !latest) return null;
+  const describe = (s: ResourceSample) =>
+    `CPU ${s.cpu_percent.toFixed(0)}% · ${formatBytes(s.rss_bytes)} · ${s.threads} threads · ${s.handles} handles`;
+  return (
+    <div className="flex flex-wrap items-center gap-x-6 gap-y-1 mb-3 text-xs text-[var(--color-text-secondary)]">
+      <span className="flex items-center gap-2" title={`${latest.processes} processes (PID ${latest.pid} and children)`}>
+        CasparCG <span className="font-mono text-[var(--color-text-primary)]">{describe(latest)}</span>
+        <Sparkline samples={server.filter((s) => s.pid === latest.pid)} />
+      </span>
+      {scanner && (
+        <span>
+          Scanner <span className="font-mono">{describe(scanner)}</span>
+        </span>
+      )}
+    </div>
+  );
 }
 
 export function ServerPanel() {
@@ -71,9 +154,23 @@ export function ServerPanel() {
     serverLog,
     clearServerLog,
     scannerEndpoint,
+    diagnostics,
+    clearDiagnostics,
+    readOnly,
   } = useAppStore();
   const [running, setRunning] = useState(false);
   const [error, setError] = useState<string | null>(null);
+  // Path of the last crash report written, by the button or by the supervisor
+  // standing down after a crash.
+  const [reportPath, setReportPath] = useState<string | null>(null);
+  // The start-on-launch countdown, while it runs
+  const [autoStart, setAutoStart] = useState<AutoStartStatus | null>(null);
+  // Resource alerts currently raised, one per rule
+  const [resourceAlerts, setResourceAlerts] = useState<ResourceAlert[]>([]);
+  // Ports the latest Start found taken: moved, or blocking the start
+  const [portClashes, setPortClashes] = useState<PortCheck[]>([]);
+  // Channel and DeckLink output tests running, whoever started them
+  const [tests, setTests] = useState<TestSession[]>([]);
   // This host's primary IPv4 — what the operator points a remote client at.
   const [primaryIp, setPrimaryIp] = useState<string | null>(null);
   const logRef = useRef<HTMLDivElement>(null);
@@ -89,6 +186,53 @@ export function ServerPanel() {
     }
   }, [serverLog]);
 
+  useEffect(() => {
+    const unlisten = listen<string>('crash-report', (event) => setReportPath(event.payload));
+    // Raised alerts stay up until the backend reports the same rule cleared.
+    const unlistenAlerts = listen<ResourceAlert>('resource-alert', (event) => {
+      const alert = event.payload;
+      const sameRule = (a: ResourceAlert) => JSON.stringify(a.rule) === JSON.stringify(alert.rule);
+      setResourceAlerts((prev) => [...prev.filter((a) => !sameRule(a)), ...(alert.active ? [alert] : [])]);
+    });
+    // After a reboot the backend counts down before starting the last profile;
+    // the panel may mount mid-countdown, so ask for the current step too.
+    const showAutoStart = (status: AutoStartStatus | null) =>
+      setAutoStart(status?.phase === 'countdown' || status?.phase === 'failed' ? status : null);
+    tauri.getAutoStart().then(showAutoStart).catch(() => {});
+    const unlistenAutoStart = listen<AutoStartStatus>('auto-start', (event) => showAutoStart(event.payload));
+    const showPorts = (preflight: PortPreflight | null) =>
+      setPortClashes(preflight?.checks.filter((c) => c.status === 'reassigned' || c.status === 'blocked') ?? []);
+    tauri.getPortPreflight().then(showPorts).catch(() => {});
+    const unlistenPorts = listen<PortPreflight>('port-preflight', (event) => showPorts(event.payload));
+    tauri.getTestSessions().then(setTests).catch(() => {});
+    const unlistenTests = listen<TestSession[]>('test-sessions', (event) => setTests(event.payload));
+    return () => {
+      unlisten.then((u) => u());
+      unlistenAlerts.then((u) => u());
+      unlistenAutoStart.then((u) => u());
+      unlistenPorts.then((u) => u());
+      unlistenTests.then((u) => u());
+    };
+  }, []);
+
+  const stopTest = async (target: TestTarget) => {
+    setError(null);
+    try {
+      await tauri.stopTestSession(target);
+    } catch (e) {
+      setError(String(e));
+    }
+  };
+
+  const crashReport = async () => {
+    setError(null);
+    try {
+      setReportPath(await tauri.createCrashReport(useAppStore.getState().currentConfig));
+    } catch (e) {
+      setError(String(e));
+    }
+  };
+
   // Resolve the host's primary IP once, so the panel can show the operator the
   // exact endpoint to connect a remote client to.
   useEffect(() => {
@@ -133,8 +277,12 @@ export function ServerPanel() {
   // Poll AMCP until the freshly launched server answers, then connect — on the
   // port the active profile actually configures, not a hard-coded default.
   const autoConnect = async () => {
+    // The pre-flight may have moved AMCP off the profile's port
+    const preflight = await tauri.getPortPreflight().catch(() => null);
     const port =
-      useAppStore.getState().currentConfig?.caspar.controllers.tcp.port ?? 5250;
+      preflight?.checks.find((c) => c.role === 'amcp')?.port ??
+      useAppStore.getState().currentConfig?.caspar.controllers.tcp.port ??
+      5250;
     for (let i = 0; i < 20; i++) {
       try {
         await connect('localhost', port);
@@ -148,7 +296,7 @@ export function ServerPanel() {
   // The whole profile is validated up front; launch is refused while any hard
   // error remains, so CasparCG is never handed a config it would reject.
   const configErrors = currentConfig ? errorsOnly(validateConfig(currentConfig, deckLinkDevices)) : [];
-  const canStart = !!currentConfig && configErrors.length === 0;
+  const canStart = !!currentConfig && configErrors.length === 0 && !readOnly;
 
   const start = async () => {
     setError(null);
@@ -165,8 +313,9 @@ export function ServerPanel() {
       return;
     }
     clearServerLog();
+    clearDiagnostics();
     try {
-      await tauri.startCasparServer(cfg);
+      await tauri.startCasparServer(cfg, useAppStore.getState().activeProfile);
       setRunning(true);
       // We drive the connect here, so mark this rising edge as handled — the
       // poll's reconnect detector should only fire for a supervised restart
@@ -203,17 +352,26 @@ export function ServerPanel() {
           </p>
         </div>
         <div className="flex items-center gap-2">
+          <button
+            onClick={crashReport}
+            title="Save a zip of the logs, config, versions and DeckLink state for engineering"
+            className="px-3 py-1.5 rounded bg-[var(--color-bg-tertiary)] hover:bg-[var(--color-border)]"
+          >
+            Crash report
+          </button>
           {running ? (
             <>
               <button
                 onClick={restart}
-                className="px-3 py-1.5 rounded bg-[var(--color-bg-tertiary)] hover:bg-[var(--color-border)]"
+                disabled={readOnly}
+                className="px-3 py-1.5 rounded bg-[var(--color-bg-tertiary)] hover:bg-[var(--color-border)] disabled:opacity-50 disabled:cursor-not-allowed"
               >
                 ↻ Restart
               </button>
               <button
                 onClick={stop}
-                className="px-3 py-1.5 rounded bg-amber-600 hover:bg-amber-700 text-white"
+                disabled={readOnly}
+                className="px-3 py-1.5 rounded bg-amber-600 hover:bg-amber-700 text-white disabled:opacity-50 disabled:cursor-not-allowed"
               >
                 ■ Stop
               </button>
@@ -223,7 +381,9 @@ export function ServerPanel() {
               onClick={start}
               disabled={!canStart}
               title={
-                !currentConfig
+                readOnly
+                  ? 'Started read-only'
+                  : !currentConfig
                   ? 'Select a profile first'
                   : configErrors.length > 0
                     ? 'Resolve configuration errors before starting'
@@ -286,10 +446,101 @@ export function ServerPanel() {
         )}
       </div>
 
+      <ResourceReadout running={running} />
+
+      {autoStart?.phase === 'countdown' && (
+        <div className="mb-3 p-2 rounded bg-cyan-500/10 border border-cyan-500/30 text-cyan-300 text-sm flex items-center justify-between gap-2">
+          <span>
+            Starting profile <span className="font-mono">{autoStart.profile}</span> automatically in{' '}
+            {autoStart.remaining_secs}s
+          </span>
+          <button
+            onClick={() => tauri.cancelAutoStart().catch(() => {})}
+            disabled={readOnly}
+            className="px-3 py-1 rounded bg-[var(--color-bg-tertiary)] hover:bg-[var(--color-border)] text-[var(--color-text-primary)]"
+          >
+            Cancel
+          </button>
+        </div>
+      )}
+      {autoStart?.phase === 'failed' && (
+        <div className="mb-3 p-2 rounded bg-red-500/15 text-red-400 text-sm">
+          Automatic start of {autoStart.profile} failed: {autoStart.error}
+        </div>
+      )}
+
+      {portClashes.map((check) => (
+        <div
+          key={check.role}
+          className={`mb-3 p-2 rounded border text-sm ${
+            check.status === 'blocked'
+              ? 'bg-red-500/15 border-red-500/30 text-red-400'
+              : 'bg-amber-500/10 border-amber-500/30 text-amber-300'
+          }`}
+        >
+          {PORT_ROLE_LABELS[check.role]} port{' '}
+          {check.requested}/{check.protocol} is in use by {check.conflict ?? 'another process'}
+          {check.status === 'reassigned' ? ` — using ${check.port} instead` : ' — start blocked'}
+        </div>
+      ))}
+
+      {tests.length > 0 && (
+        <div className="mb-3 p-2 rounded border bg-cyan-500/10 border-cyan-500/30 text-sm">
+          <div className="text-cyan-300 mb-1">Active tests</div>
+          {tests.map((test) => (
+            <div key={test.id} className="flex items-center justify-between gap-2 py-0.5">
+              <span>
+                {test.target.kind === 'channel'
+                  ? `Channel ${test.target.channel}`
+                  : `DeckLink ${test.target.device} output`}{' '}
+                — {test.label}, started by {test.owner} at{' '}
+                {new Date(test.started).toLocaleTimeString()}
+                {test.deadline && (
+                  <span className="text-[var(--color-text-muted)]">
+                    {' '}
+                    (stops at {new Date(test.deadline).toLocaleTimeString()})
+                  </span>
+                )}
+              </span>
+              <button
+                onClick={() => stopTest(test.target)}
+                disabled={readOnly}
+                className="px-3 py-0.5 rounded bg-[var(--color-bg-tertiary)] hover:bg-[var(--color-border)] text-[var(--color-text-primary)]"
+              >
+                Stop
+              </button>
+            </div>
+          ))}
+        </div>
+      )}
+
+      {resourceAlerts.map((alert) => (
+        <div
+          key={JSON.stringify(alert.rule)}
+          className="mb-3 p-2 rounded border bg-amber-500/10 border-amber-500/30 text-amber-300 text-sm"
+        >
+          {alert.message}
+        </div>
+      ))}
+
       {error && (
         <div className="mb-3 p-2 rounded bg-red-500/15 text-red-400 text-sm">{error}</div>
       )}
 
+      {reportPath && (
+        <div className="mb-3 p-2 rounded bg-[var(--color-bg-tertiary)] text-sm flex items-center justify-between gap-2">
+          <span>
+            Crash report saved to <span className="font-mono">{reportPath}</span>
+          </span>
+          <button
+            onClick={() => setReportPath(null)}
+            className="text-[var(--color-text-muted)] hover:text-[var(--color-text-primary)]"
+          >
+            ✕
+          </button>
+        </div>
+      )}
+
       {!running && configErrors.length > 0 && (
         <div className="mb-3 p-3 rounded bg-red-500/10 border border-red-500/30 text-sm">
           <div className="font-medium text-red-400 mb-1">
@@ -309,6 +560,33 @@ export function ServerPanel() {
         </div>
       )}
 
+      {/* Known failures recognised in the log or exit code — newest first */}
+      {diagnostics.length > 0 && (
+        <div className="mb-3 space-y-2 max-h-56 overflow-auto">
+          {[...diagnostics].reverse().map((d) => (
+            <div
+              key={`${d.rule_id}-${d.timestamp}`}
+              className={`p-2 rounded border text-sm ${DIAGNOSTIC_STYLE[d.severity]}`}
+            >
+              <div className="font-medium">{d.title}</div>
+              <div className="text-[var(--color-text-secondary)]">{d.explanation}</div>
+              {d.likely_cause && (
+                <div className="text-[var(--color-text-secondary)]">
+                  <span className="text-[var(--color-text-muted)]">Likely cause: </span>
+                  {d.likely_cause}
+                </div>
+              )}
+              {d.suggested_action && (
+                <div className="text-[var(--color-text-secondary)]">
+                  <span className="text-[var(--color-text-muted)]">Try: </span>
+                  {d.suggested_action}
+                </div>
+              )}
+            </div>
+          ))}
+        </div>
+      )}
+
       {/* Embedded live server log */}
       <div
         ref={logRef}
@@ -321,11 +599,17 @@ export function ServerPanel() {
               : 'No output yet — press Start Server. The CasparCG console log appears here.'}
           </div>
         ) : (
-          serverLog.map((line, i) => {
-            const { cls, keyerNote } = classifyLogLine(line);
+          serverLog.map((entry) => {
+            const { cls, keyerNote } = classifyLogEntry(entry);
             return (
-              <div key={i} className={cls}>
-                {line}
+              <div key={entry.seq} className={cls}>
+                <span className="text-[var(--color-text-muted)]">
+                  {formatLogTime(entry.timestamp)}{' '}
+                </span>
+                {entry.source !== 'server' && (
+                  <span className="opacity-70">[{entry.source}] </span>
+                )}
+                {entry.text}
                 {keyerNote && (
                   <span className="text-[var(--color-text-muted)]">
                     {'  '}— expected on a card with no hardware keyer; fill output is unaffected
@@ -13,6 +13,7 @@ export function StatusBar() {
     systemVersions,
     deckLinkDevices,
     currentConfig,
+    readOnly,
   } = useAppStore();
   const [showConnectDialog, setShowConnectDialog] = useState(false);
   const [host, setHost] = useState('localhost');
@@ -48,7 +49,7 @@ export function StatusBar() {
       return;
     }
     try {
-      await tauri.startCasparServer(currentConfig);
+      await tauri.startCasparServer(currentConfig, useAppStore.getState().activeProfile);
       setServerRunning(true);
     } catch (err) {
       setServerError(String(err));
@@ -97,16 +98,19 @@ export function StatusBar() {
           {serverRunning ? (
             <button
               onClick={handleStopServer}
-              className="px-2 py-0.5 rounded bg-amber-600 hover:bg-amber-700 text-white"
+              disabled={readOnly}
+              className="px-2 py-0.5 rounded bg-amber-600 hover:bg-amber-700 text-white disabled:opacity-50"
             >
               ■ Stop Server
             </button>
           ) : (
             <button
               onClick={handleStartServer}
-              disabled={!currentConfig}
+              disabled={!currentConfig || readOnly}
               title={
-                !currentConfig
+                readOnly
+                  ? 'Started read-only'
+                  : !currentConfig
                   ? 'Select a profile first'
                   : 'Write config and launch casparcg.exe'
               }
@@ -1,9 +1,22 @@
 // System information panel
 // Shows version info for CasparCG, DeckLink, NDI, and Scanner
 
-import { useState } from 'react';
+import { useEffect, useState } from 'react';
 import { useAppStore } from '../lib/store';
 import * as tauri from '../lib/tauri';
+import type {
+  ApiSettings,
+  ApiStatus,
+  MetricsSettings,
+  MetricsStatus,
+  PortConflictAction,
+  PortPolicy,
+  PortPreflight,
+  ScannerMode,
+} from '../lib/types';
+import { PORT_ROLE_LABELS } from '../lib/types';
+
+const DEFAULT_PORT_POLICY: PortPolicy = { amcp: 'block', osc: 'reassign' };
 
 export function SystemInfoPanel() {
   const {
@@ -11,9 +24,62 @@ export function SystemInfoPanel() {
     loadSystemVersions,
     connection,
     deckLinkDevices,
+    settings,
+    saveSettings,
+    currentConfig,
+    updateConfig,
   } = useAppStore();
   const [isRefreshing, setIsRefreshing] = useState(false);
   const [xmlPreview, setXmlPreview] = useState<string | null>(null);
+  const [api, setApi] = useState<ApiStatus | null>(null);
+  const [apiBind, setApiBind] = useState('');
+  const [apiError, setApiError] = useState<string | null>(null);
+  const [metrics, setMetrics] = useState<MetricsStatus | null>(null);
+  const [metricsBind, setMetricsBind] = useState('');
+  const [metricsError, setMetricsError] = useState<string | null>(null);
+  const [ports, setPorts] = useState<PortPreflight | null>(null);
+  const [portsError, setPortsError] = useState<string | null>(null);
+
+  useEffect(() => {
+    tauri
+      .getApiStatus()
+      .then((status) => {
+        setApi(status);
+        setApiBind(status.bind);
+      })
+      .catch(() => {});
+    tauri
+      .getMetricsStatus()
+      .then((status) => {
+        setMetrics(status);
+        setMetricsBind(status.bind);
+      })
+      .catch(() => {});
+  }, []);
+
+  const applyApi = async (settings: ApiSettings) => {
+    setApiError(null);
+    try {
+      const status = await tauri.setApiSettings(settings);
+      setApi(status);
+      setApiBind(status.bind);
+    } catch (error) {
+      setApiError(String(error));
+      tauri.getApiStatus().then(setApi).catch(() => {});
+    }
+  };
+
+  const applyMetrics = async (settings: MetricsSettings) => {
+    setMetricsError(null);
+    try {
+      const status = await tauri.setMetricsSettings(settings);
+      setMetrics(status);
+      setMetricsBind(status.bind);
+    } catch (error) {
+      setMetricsError(String(error));
+      tauri.getMetricsStatus().then(setMetrics).catch(() => {});
+    }
+  };
 
   const handleRefresh = async () => {
     setIsRefreshing(true);
@@ -170,6 +236,282 @@ export function SystemInfoPanel() {
         </div>
       </div>
 
+      {/* Start on launch */}
+      {settings && (
+        <div className="panel mb-6">
+          <div className="panel-header">Start on Launch</div>
+          <div className="panel-content text-sm">
+            <p className="text-[var(--color-text-muted)] mb-4">
+              If the server was running when this PC shut down or lost power, the active profile
+              is started again when the app opens, after a countdown that can be cancelled on the
+              Server tab.
+            </p>
+            <label className="flex items-center gap-2 mb-3">
+              <input
+                type="checkbox"
+                checked={settings.auto_start ?? false}
+                onChange={(e) => saveSettings({ auto_start: e.target.checked }).catch(() => {})}
+              />
+              Always start the server when the app opens, even if it was stopped
+            </label>
+            <div className="flex items-center gap-2">
+              <span className="text-[var(--color-text-secondary)]">Countdown</span>
+              <input
+                type="number"
+                min={0}
+                value={settings.auto_start_delay_secs ?? 10}
+                onChange={(e) =>
+                  saveSettings({ auto_start_delay_secs: Math.max(0, Number(e.target.value) || 0) }).catch(() => {})
+                }
+                className="w-20 px-2 py-1 bg-[var(--color-bg-primary)] border border-[var(--color-border)] rounded font-mono"
+              />
+              <span className="text-[var(--color-text-muted)]">seconds</span>
+            </div>
+          </div>
+        </div>
+      )}
+
+      {/* Test timeout */}
+      {settings && (
+        <div className="panel mb-6">
+          <div className="panel-header">Tests</div>
+          <div className="panel-content text-sm">
+            <p className="text-[var(--color-text-muted)] mb-4">
+              Channel identification, AV sync and direct DeckLink output tests can be stopped
+              automatically, so one left running does not stay on air. Running tests are listed
+              on the Server tab.
+            </p>
+            <div className="flex items-center gap-2">
+              <span className="text-[var(--color-text-secondary)]">Stop tests after</span>
+              <select
+                value={settings.test_timeout_secs ?? 0}
+                onChange={(e) => {
+                  const secs = Number(e.target.value);
+                  saveSettings({ test_timeout_secs: secs > 0 ? secs : undefined }).catch(() => {});
+                }}
+                className="px-2 py-1 bg-[var(--color-bg-primary)] border border-[var(--color-border)] rounded"
+              >
+                <option value={0}>Never</option>
+                <option value={60}>1 minute</option>
+                <option value={300}>5 minutes</option>
+                <option value={900}>15 minutes</option>
+                <option value={3600}>1 hour</option>
+              </select>
+            </div>
+          </div>
+        </div>
+      )}
+
+      {/* Control API */}
+      {api && (
+        <div className="panel mb-6">
+          <div className="panel-header">Remote Control API</div>
+          <div className="panel-content text-sm">
+            <p className="text-[var(--color-text-muted)] mb-4">
+              HTTP API for automation, a Stream Deck or curl: start/stop, profiles,
+              DeckLink status and AMCP. Every request needs the bearer token.
+            </p>
+            <label className="flex items-center gap-2 mb-3">
+              <input
+                type="checkbox"
+                checked={api.enabled}
+                onChange={(e) => applyApi({ ...api, bind: apiBind, enabled: e.target.checked })}
+              />
+              Enabled
+            </label>
+            <div className="flex items-center gap-2 mb-3">
+              <span className="w-16 text-[var(--color-text-secondary)]">Listen</span>
+              <input
+                value={apiBind}
+                onChange={(e) => setApiBind(e.target.value)}
+                className="flex-1 px-2 py-1 bg-[var(--color-bg-primary)] border border-[var(--color-border)] rounded font-mono"
+              />
+              <button
+                onClick={() => applyApi({ ...api, bind: apiBind })}
+                disabled={apiBind === api.bind}
+                className="px-3 py-1 bg-[var(--color-bg-tertiary)] rounded hover:bg-[var(--color-border)] transition-colors disabled:opacity-50"
+              >
+                Apply
+              </button>
+            </div>
+            {api.token && (
+              <div className="flex items-center gap-2 mb-3">
+                <span className="w-16 text-[var(--color-text-secondary)]">Token</span>
+                <code className="flex-1 px-2 py-1 bg-[var(--color-bg-primary)] rounded select-all">
+                  {api.token}
+                </code>
+                <button
+                  onClick={() => applyApi({ ...api, bind: apiBind, token: undefined })}
+                  className="px-3 py-1 bg-[var(--color-bg-tertiary)] rounded hover:bg-[var(--color-border)] transition-colors"
+                >
+                  Regenerate
+                </button>
+              </div>
+            )}
+            <div className="text-[var(--color-text-muted)]">
+              {api.address
+                ? `Listening on http://${api.address}/api/v1 — description at /api/v1/openapi.json`
+                : 'Not running'}
+            </div>
+            {apiError && <div className="mt-2 text-[var(--color-error)]">{apiError}</div>}
+          </div>
+        </div>
+      )}
+
+      {/* Metrics */}
+      {metrics && (
+        <div className="panel mb-6">
+          <div className="panel-header">Prometheus Metrics</div>
+          <div className="panel-content text-sm">
+            <p className="text-[var(--color-text-muted)] mb-4">
+              Server up/down, restarts and crashes, uptime, scanner, AMCP latency, DeckLink
+              input/reference lock and per-channel late frames, for Prometheus to scrape.
+            </p>
+            <label className="flex items-center gap-2 mb-3">
+              <input
+                type="checkbox"
+                checked={metrics.enabled}
+                onChange={(e) =>
+                  applyMetrics({ ...metrics, bind: metricsBind, enabled: e.target.checked })
+                }
+              />
+              Enabled
+            </label>
+            <div className="flex items-center gap-2 mb-3">
+              <span className="w-16 text-[var(--color-text-secondary)]">Listen</span>
+              <input
+                value={metricsBind}
+                onChange={(e) => setMetricsBind(e.target.value)}
+                className="flex-1 px-2 py-1 bg-[var(--color-bg-primary)] border border-[var(--color-border)] rounded font-mono"
+              />
+              <button
+                onClick={() => applyMetrics({ ...metrics, bind: metricsBind })}
+                disabled={metricsBind === metrics.bind}
+                className="px-3 py-1 bg-[var(--color-bg-tertiary)] rounded hover:bg-[var(--color-border)] transition-colors disabled:opacity-50"
+              >
+                Apply
+              </button>
+            </div>
+            <div className="text-[var(--color-text-muted)]">
+              {metrics.address ? `Scrape http://${metrics.address}/metrics` : 'Not running'}
+            </div>
+            {metricsError && <div className="mt-2 text-[var(--color-error)]">{metricsError}</div>}
+          </div>
+        </div>
+      )}
+
+      {/* Media scanner */}
+      {currentConfig && (
+        <div className="panel mb-6">
+          <div className="panel-header">Media Scanner</div>
+          <div className="panel-content text-sm">
+            <p className="text-[var(--color-text-muted)] mb-4">
+              The scanner answers CasparCG's CLS, TLS, FLS, CINF and THUMBNAIL. The built-in one
+              walks the profile's media, template and font folders itself, probing with ffprobe
+              when it is in the CasparCG folder or on the PATH. Takes effect on the next Start.
+            </p>
+            <div className="flex items-center gap-2">
+              <span className="w-32 text-[var(--color-text-secondary)]">Scanner</span>
+              <select
+                value={currentConfig.scanner?.mode ?? 'external'}
+                onChange={(e) =>
+                  updateConfig({
+                    ...currentConfig,
+                    scanner: { mode: e.target.value as ScannerMode },
+                  })
+                }
+                className="px-2 py-1 bg-[var(--color-bg-primary)] border border-[var(--color-border)] rounded"
+              >
+                <option value="external">scanner.exe from the CasparCG folder</option>
+                <option value="built_in">Built-in</option>
+              </select>
+            </div>
+          </div>
+        </div>
+      )}
+
+      {/* Ports */}
+      {currentConfig && (
+        <div className="panel mb-6">
+          <div className="panel-header">Ports</div>
+          <div className="panel-content text-sm">
+            <p className="text-[var(--color-text-muted)] mb-4">
+              Start checks every port first. The media scanner always moves to a free port
+              when its own is taken; for the profile's ports, choose what happens. While the
+              server runs, its own ports show as taken.
+            </p>
+            {(['amcp', 'osc'] as const).map((role) => (
+              <div key={role} className="flex items-center gap-2 mb-3">
+                <span className="w-32 text-[var(--color-text-secondary)]">
+                  {PORT_ROLE_LABELS[role]} taken
+                </span>
+                <select
+                  value={(currentConfig.ports ?? DEFAULT_PORT_POLICY)[role]}
+                  onChange={(e) =>
+                    updateConfig({
+                      ...currentConfig,
+                      ports: {
+                        ...(currentConfig.ports ?? DEFAULT_PORT_POLICY),
+                        [role]: e.target.value as PortConflictAction,
+                      },
+                    })
+                  }
+                  className="px-2 py-1 bg-[var(--color-bg-primary)] border border-[var(--color-border)] rounded"
+                >
+                  <option value="block">Block the start</option>
+                  <option value="reassign">Move to a free port</option>
+                </select>
+              </div>
+            ))}
+            <button
+              onClick={() => {
+                setPortsError(null);
+                tauri
+                  .checkPorts(currentConfig)
+                  .then(setPorts)
+                  .catch((e) => setPortsError(String(e)));
+              }}
+              className="px-3 py-1 mb-3 bg-[var(--color-bg-tertiary)] rounded hover:bg-[var(--color-border)] transition-colors"
+            >
+              Check now
+            </button>
+            {ports && (
+              <table className="w-full">
+                <tbody>
+                  {ports.checks.map((check) => (
+                    <tr key={check.role} className="border-t border-[var(--color-border)]">
+                      <td className="py-1 text-[var(--color-text-secondary)]">
+                        {PORT_ROLE_LABELS[check.role]}
+                      </td>
+                      <td className="py-1 font-mono">
+                        {check.requested}/{check.protocol}
+                      </td>
+                      <td
+                        className={`py-1 ${
+                          check.status === 'blocked'
+                            ? 'text-[var(--color-error)]'
+                            : check.status === 'reassigned'
+                              ? 'text-amber-300'
+                              : 'text-[var(--color-text-muted)]'
+                        }`}
+                      >
+                        {check.status === 'free' && 'free'}
+                        {check.status === 'held' && 'in use by this app'}
+                        {check.status === 'reassigned' &&
+                          `in use by ${check.conflict} — would move to ${check.port}`}
+                        {check.status === 'blocked' &&
+                          `in use by ${check.conflict} — blocks Start`}
+                      </td>
+                    </tr>
+                  ))}
+                </tbody>
+              </table>
+            )}
+            {portsError && <div className="mt-2 text-[var(--color-error)]">{portsError}</div>}
+          </div>
+        </div>
+      )}
+
       {/* Export section */}
       <div className="panel">
         <div className="panel-header">Export Configuration</div>
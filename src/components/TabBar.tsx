@@ -9,7 +9,9 @@ const TABS: { id: TabId; label: string }[] = [
   { id: 'paths', label: 'Paths' },
   { id: 'channels', label: 'Channels' },
   { id: 'preview', label: 'Preview' },
+  { id: 'templates', label: 'Templates' },
   { id: 'decklink', label: 'DeckLink' },
+  { id: 'commissioning', label: 'Commissioning' },
   { id: 'system', label: 'System Info' },
 ];
 
@@ -27,7 +29,8 @@ export function TabBar() {
             !currentConfig &&
             tab.id !== 'system' &&
             tab.id !== 'server' &&
-            tab.id !== 'preview'
+            tab.id !== 'preview' &&
+            tab.id !== 'templates'
           }
         >
           {tab.label}
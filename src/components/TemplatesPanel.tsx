@@ -0,0 +1,256 @@
+// Templates panel
+// Previews the profile's HTML templates with test data, and serves a template
+// workspace that reloads in CasparCG on every save
+
+import { useEffect, useState } from 'react';
+import { useAppStore } from '../lib/store';
+import * as tauri from '../lib/tauri';
+import type { TemplateDevSettings, TemplateDevStatus, TemplateReload } from '../lib/types';
+
+const INPUT =
+  'flex-1 px-2 py-1 bg-[var(--color-bg-primary)] border border-[var(--color-border)] rounded font-mono';
+
+export function TemplatesPanel() {
+  const { currentConfig, readOnly, testServerUrl, testServerRunning, startTestServer } =
+    useAppStore();
+  const [templates, setTemplates] = useState<string[] | null>(null);
+  const [previewed, setPreviewed] = useState('');
+  const [previewError, setPreviewError] = useState<string | null>(null);
+  const [status, setStatus] = useState<TemplateDevStatus | null>(null);
+  const [draft, setDraft] = useState<TemplateDevSettings | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [busy, setBusy] = useState(false);
+
+  useEffect(() => {
+    tauri
+      .getTemplateDevStatus()
+      .then((s) => {
+        setStatus(s);
+        setDraft(s);
+      })
+      .catch((e) => setError(String(e)));
+  }, []);
+
+  // The preview harness is served by the local test server, not CasparCG
+  useEffect(() => {
+    if (!testServerRunning) {
+      startTestServer().catch((e) => setPreviewError(String(e)));
+    }
+  }, [testServerRunning, startTestServer]);
+
+  const loadTemplates = () => {
+    if (!testServerUrl) return;
+    setPreviewError(null);
+    fetch(`${testServerUrl}/templates`)
+      .then(async (r) => {
+        if (!r.ok) throw new Error(await r.text());
+        return r.json() as Promise<string[]>;
+      })
+      .then((list) => {
+        setTemplates(list);
+        setPreviewed((current) => (list.includes(current) ? current : list[0] ?? ''));
+      })
+      .catch((e) => setPreviewError(e instanceof Error ? e.message : String(e)));
+  };
+
+  // eslint-disable-next-line react-hooks/exhaustive-deps
+  useEffect(loadTemplates, [testServerUrl, currentConfig?.name]);
+
+  if (!draft) {
+    return error ? <div className="text-sm text-[var(--color-error)]">{error}</div> : null;
+  }
+
+  const channelCount = currentConfig?.caspar.channels.length ?? Math.max(draft.channel, 1);
+  const running = status?.address != null;
+  const set = (patch: Partial<TemplateDevSettings>) => setDraft({ ...draft, ...patch });
+  const optional = (value: string) => (value.trim() === '' ? undefined : value);
+
+  const run = async (action: () => Promise<TemplateDevStatus>) => {
+    setError(null);
+    setBusy(true);
+    try {
+      setStatus(await action());
+    } catch (e) {
+      setError(String(e));
+      tauri.getTemplateDevStatus().then(setStatus).catch(() => {});
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const sendData = async () => {
+    setError(null);
+    try {
+      await tauri.setTemplateDevData(draft.data);
+      setStatus((s) => (s ? { ...s, data: draft.data } : s));
+    } catch (e) {
+      setError(String(e));
+    }
+  };
+
+  return (
+    <div>
+      <div className="mb-4">
+        <h2 className="text-lg font-semibold">Templates</h2>
+        <p className="text-sm text-[var(--color-text-secondary)]">
+          Preview HTML templates, and develop them against the running server
+        </p>
+      </div>
+
+      <div className="panel mb-6">
+        <div className="panel-header">Preview</div>
+        <div className="panel-content text-sm">
+          <p className="text-[var(--color-text-muted)] mb-4">
+            Runs a template from the profile's template folder here, calling{' '}
+            <code>update()</code>, <code>play()</code>, <code>next()</code> and{' '}
+            <code>stop()</code> as CasparCG does. Data can be sent as written (JSON) or as
+            legacy <code>templateData</code> XML, and saved per template.
+          </p>
+          <div className="flex items-center gap-2 mb-3">
+            <span className="w-28 text-[var(--color-text-secondary)]">Template</span>
+            <select
+              value={previewed}
+              onChange={(e) => setPreviewed(e.target.value)}
+              disabled={!templates?.length}
+              className="flex-1 px-2 py-1 bg-[var(--color-bg-primary)] border border-[var(--color-border)] rounded font-mono"
+            >
+              {templates?.length === 0 && <option value="">No HTML templates found</option>}
+              {templates?.map((t) => (
+                <option key={t} value={t}>
+                  {t}
+                </option>
+              ))}
+            </select>
+            <button
+              onClick={loadTemplates}
+              className="px-3 py-1 bg-[var(--color-bg-tertiary)] rounded hover:bg-[var(--color-border)] transition-colors"
+            >
+              Refresh
+            </button>
+          </div>
+          {previewError && <div className="mb-3 text-[var(--color-error)]">{previewError}</div>}
+          {testServerUrl && previewed && (
+            <div className="h-[560px] rounded border border-[var(--color-border)] overflow-hidden">
+              <iframe
+                key={previewed}
+                src={`${testServerUrl}/templates/preview/${previewed
+                  .split('/')
+                  .map(encodeURIComponent)
+                  .join('/')}`}
+                title="Template preview"
+                className="w-full h-full border-0"
+              />
+            </div>
+          )}
+        </div>
+      </div>
+
+      <div className="panel mb-6">
+        <div className="panel-header">Template Dev Server</div>
+        <div className="panel-content text-sm">
+          <p className="text-[var(--color-text-muted)] mb-4">
+            Serves the workspace over HTTP and watches it. On every save the build command runs,
+            then the template is added again (or sent <code>CG UPDATE</code>) on the chosen
+            channel and layer with the test data. Build output and reloads appear in the log.
+          </p>
+          {[
+            ['Workspace', 'workspace', 'C:\\templates\\lower-third'],
+            ['Serve folder', 'serve_dir', 'dist (blank: the workspace)'],
+            ['Build', 'build_command', 'npm run build (optional)'],
+          ].map(([label, key, placeholder]) => (
+            <div key={key} className="flex items-center gap-2 mb-3">
+              <span className="w-28 text-[var(--color-text-secondary)]">{label}</span>
+              <input
+                value={(draft[key as keyof TemplateDevSettings] as string | undefined) ?? ''}
+                placeholder={placeholder}
+                onChange={(e) => set({ [key]: optional(e.target.value) })}
+                className={INPUT}
+              />
+            </div>
+          ))}
+          <div className="flex items-center gap-2 mb-3">
+            <span className="w-28 text-[var(--color-text-secondary)]">Entry page</span>
+            <input
+              value={draft.template}
+              onChange={(e) => set({ template: e.target.value })}
+              className={INPUT}
+            />
+          </div>
+          <div className="flex items-center gap-2 mb-3">
+            <span className="w-28 text-[var(--color-text-secondary)]">Play on</span>
+            <select
+              value={draft.channel}
+              onChange={(e) => set({ channel: parseInt(e.target.value, 10) })}
+              className="px-2 py-1 bg-[var(--color-bg-primary)] border border-[var(--color-border)] rounded"
+            >
+              {Array.from({ length: channelCount }, (_, i) => i + 1).map((c) => (
+                <option key={c} value={c}>
+                  Channel {c}
+                </option>
+              ))}
+            </select>
+            <span className="text-[var(--color-text-secondary)]">layer</span>
+            <input
+              type="number"
+              min={0}
+              value={draft.layer}
+              onChange={(e) => set({ layer: Math.max(0, Number(e.target.value) || 0) })}
+              className="w-20 px-2 py-1 bg-[var(--color-bg-primary)] border border-[var(--color-border)] rounded font-mono"
+            />
+            <select
+              value={draft.reload}
+              onChange={(e) => set({ reload: e.target.value as TemplateReload })}
+              className="px-2 py-1 bg-[var(--color-bg-primary)] border border-[var(--color-border)] rounded"
+            >
+              <option value="readd">Clear and add again on save</option>
+              <option value="update">CG UPDATE on save</option>
+            </select>
+          </div>
+          <div className="flex items-center gap-2 mb-3">
+            <span className="w-28 text-[var(--color-text-secondary)]">Listen</span>
+            <input value={draft.bind} onChange={(e) => set({ bind: e.target.value })} className={INPUT} />
+          </div>
+          <div className="flex items-start gap-2 mb-3">
+            <span className="w-28 pt-1 text-[var(--color-text-secondary)]">Test data</span>
+            <textarea
+              value={draft.data}
+              rows={5}
+              placeholder='{"f0": "Jane Smith", "f1": "Presenter"}'
+              onChange={(e) => set({ data: e.target.value })}
+              className={INPUT}
+            />
+          </div>
+          <div className="flex items-center gap-2 mb-3">
+            <button
+              onClick={() => run(() => tauri.startTemplateDev(draft))}
+              disabled={busy || readOnly || !draft.workspace}
+              className="px-3 py-1 bg-[var(--color-accent)] text-white rounded hover:bg-[var(--color-accent-hover)] transition-colors disabled:opacity-50"
+            >
+              {running ? 'Restart' : 'Start'}
+            </button>
+            <button
+              onClick={() => run(tauri.stopTemplateDev)}
+              disabled={busy || !running}
+              className="px-3 py-1 bg-[var(--color-bg-tertiary)] rounded hover:bg-[var(--color-border)] transition-colors disabled:opacity-50"
+            >
+              Stop
+            </button>
+            <button
+              onClick={sendData}
+              disabled={readOnly || !running || draft.data === status?.data}
+              className="px-3 py-1 bg-[var(--color-bg-tertiary)] rounded hover:bg-[var(--color-border)] transition-colors disabled:opacity-50"
+            >
+              Send data
+            </button>
+          </div>
+          <div className="text-[var(--color-text-muted)]">
+            {running && status?.url
+              ? `Serving on http://${status.address} — CasparCG plays ${status.url}`
+              : 'Not running'}
+          </div>
+          {error && <div className="mt-2 text-[var(--color-error)]">{error}</div>}
+        </div>
+      </div>
+    </div>
+  );
+}
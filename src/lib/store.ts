@@ -4,8 +4,10 @@ import { create } from 'zustand';
 import type {
   ConnectionStatus,
   DeckLinkDevice,
+  Diagnostic,
   GlobalConfig,
   GuiSettings,
+  LogEntry,
   ScannerEndpoint,
   SystemVersions,
   TabId,
@@ -29,6 +31,12 @@ interface AppState {
   createProfile: (name: string) => Promise<void>;
   deleteProfile: (name: string) => Promise<void>;
   updateConfig: (config: GlobalConfig) => void;
+  // Open a profile JSON from outside the profiles folder, unsaved — Save
+  // copies it in under its file name
+  openProfileFile: (path: string) => Promise<void>;
+
+  // Started with --read-only: hide the controls the backend would refuse
+  readOnly: boolean;
 
   // Connection State
   connection: ConnectionStatus;
@@ -58,12 +66,15 @@ interface AppState {
   testAllChannels: () => Promise<void>;
   stopAllTests: () => Promise<void>;
   testChannel: (channel: number) => Promise<void>;
+  testAvSync: (channel: number) => Promise<void>;
   stopChannelTest: (channel: number) => Promise<void>;
 
   // Server log — kept in the store (not in ServerPanel) so it survives tab
-  // switches and a crash; an app-level listener appends to it.
-  serverLog: string[];
-  appendServerLog: (line: string) => void;
+  // switches and a crash; an app-level listener appends to it. The backend
+  // keeps the full history, so a reload hydrates it with setServerLog.
+  serverLog: LogEntry[];
+  appendServerLog: (entry: LogEntry) => void;
+  setServerLog: (entries: LogEntry[]) => void;
   clearServerLog: () => void;
 
   // Where the media scanner ended up listening, reported by the launcher at
@@ -72,6 +83,13 @@ interface AppState {
   scannerEndpoint: ScannerEndpoint | null;
   setScannerEndpoint: (endpoint: ScannerEndpoint | null) => void;
 
+  // Known failures the backend recognised in the log or an exit code. Captured
+  // app-level like the log; the backend keeps them too, so a reload hydrates.
+  diagnostics: Diagnostic[];
+  appendDiagnostic: (diagnostic: Diagnostic) => void;
+  setDiagnostics: (diagnostics: Diagnostic[]) => void;
+  clearDiagnostics: () => Promise<void>;
+
   // Initialisation
   initialise: () => Promise<void>;
 }
@@ -98,10 +116,14 @@ export const useAppStore = create<AppState>((set, get) => ({
   },
 
   selectProfile: async (name) => {
-    const { settings } = get();
+    const { settings, activeProfile } = get();
     if (!settings?.caspar_path) return;
 
     try {
+      // Tests belong to the profile they were started under
+      if (activeProfile && activeProfile !== name) {
+        await tauri.releaseTestSessions('profile switch').catch(() => {});
+      }
       const path = `${settings.caspar_path}/caspar-gui-profiles/${name}.json`;
       const config = await tauri.loadGlobalConfig(path);
       set({ activeProfile: name, currentConfig: ensureChannelIds(config), configDirty: false });
@@ -152,6 +174,21 @@ export const useAppStore = create<AppState>((set, get) => ({
     set({ currentConfig: config, configDirty: true });
   },
 
+  openProfileFile: async (path) => {
+    const { profiles } = get();
+    const stem = path.split(/[\\/]/).pop()!.replace(/\.json$/i, '') || 'Imported';
+    let name = stem;
+    for (let n = 2; profiles.includes(name); n++) name = `${stem}-${n}`;
+    try {
+      const config = await tauri.loadGlobalConfig(path);
+      set({ activeProfile: name, currentConfig: ensureChannelIds(config), configDirty: true });
+    } catch (error) {
+      console.error('Failed to open profile file:', error);
+    }
+  },
+
+  readOnly: false,
+
   // Connection State
   connection: { connected: false },
 
@@ -275,17 +312,12 @@ export const useAppStore = create<AppState>((set, get) => ({
   },
 
   testAllChannels: async () => {
-    const { currentConfig, testServerRunning, startTestServer } = get();
+    const { currentConfig } = get();
     if (!currentConfig) return;
 
-    // Ensure test server is running
-    if (!testServerRunning) {
-      await startTestServer();
-    }
-
     const channelCount = currentConfig.caspar.channels.length;
     try {
-      await tauri.testAllChannels(channelCount);
+      await tauri.testAllChannels(currentConfig);
       // Mark all channels as testing
       const testingChannels = new Set<number>();
       for (let i = 1; i <= channelCount; i++) {
@@ -313,20 +345,31 @@ export const useAppStore = create<AppState>((set, get) => ({
   },
 
   testChannel: async (channel: number) => {
-    const { testServerRunning, startTestServer, channelsTesting } = get();
+    const { currentConfig, channelsTesting } = get();
+    if (!currentConfig) return;
 
-    // Ensure test server is running
-    if (!testServerRunning) {
-      await startTestServer();
+    try {
+      await tauri.testChannel(channel, currentConfig);
+      const newTesting = new Set(channelsTesting);
+      newTesting.add(channel);
+      set({ channelsTesting: newTesting });
+    } catch (error) {
+      console.error(`Failed to test channel ${channel}:`, error);
+      throw error;
     }
+  },
+
+  testAvSync: async (channel: number) => {
+    const { currentConfig, channelsTesting } = get();
+    if (!currentConfig) return;
 
     try {
-      await tauri.testChannel(channel);
+      await tauri.testAvSync(channel, currentConfig);
       const newTesting = new Set(channelsTesting);
       newTesting.add(channel);
       set({ channelsTesting: newTesting });
     } catch (error) {
-      console.error(`Failed to test channel ${channel}:`, error);
+      console.error(`Failed to start AV sync on channel ${channel}:`, error);
       throw error;
     }
   },
@@ -347,17 +390,35 @@ export const useAppStore = create<AppState>((set, get) => ({
 
   // Server log
   serverLog: [],
-  appendServerLog: (line) =>
+  appendServerLog: (entry) =>
     set((s) => {
-      const next = [...s.serverLog, line];
+      // History hydration and the live listener can overlap at start-up.
+      const last = s.serverLog[s.serverLog.length - 1];
+      if (last && entry.seq <= last.seq) return s;
+      const next = [...s.serverLog, entry];
       return { serverLog: next.length > 2000 ? next.slice(-2000) : next };
     }),
+  setServerLog: (entries) => set({ serverLog: entries.slice(-2000) }),
   clearServerLog: () => set({ serverLog: [] }),
 
   // Scanner endpoint
   scannerEndpoint: null,
   setScannerEndpoint: (endpoint) => set({ scannerEndpoint: endpoint }),
 
+  // Diagnostics
+  diagnostics: [],
+  appendDiagnostic: (diagnostic) =>
+    set((s) => ({ diagnostics: [...s.diagnostics, diagnostic].slice(-200) })),
+  setDiagnostics: (diagnostics) => set({ diagnostics }),
+  clearDiagnostics: async () => {
+    set({ diagnostics: [] });
+    try {
+      await tauri.clearDiagnostics();
+    } catch (error) {
+      console.error('Failed to clear diagnostics:', error);
+    }
+  },
+
   // Initialisation
   initialise: async () => {
     const { loadSettings, loadProfiles, loadDeckLinkDevices, loadSystemVersions } = get();
@@ -376,9 +437,9 @@ export const useAppStore = create<AppState>((set, get) => ({
         await selectProfile(currentSettings.last_profile);
       }
 
-      // Try auto-connect if server was running
+      // Try auto-connect if AMCP was connected when the app last closed
       if (
-        currentSettings.last_server_was_running &&
+        currentSettings.last_amcp_connected &&
         currentSettings.last_host &&
         currentSettings.last_port
       ) {
@@ -391,6 +452,17 @@ export const useAppStore = create<AppState>((set, get) => ({
       }
     }
 
+    // Command-line options; a profile file named there replaces the last one
+    try {
+      const launch = await tauri.getLaunchOptions();
+      set({ readOnly: launch.read_only });
+      if (launch.open_file && currentSettings?.caspar_path) {
+        await get().openProfileFile(launch.open_file);
+      }
+    } catch (error) {
+      console.error('Failed to read launch options:', error);
+    }
+
     // Load DeckLink devices and system versions
     await loadDeckLinkDevices();
     await loadSystemVersions();
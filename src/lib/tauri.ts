@@ -4,12 +4,37 @@
 import { invoke } from '@tauri-apps/api/core';
 import type {
   AmcpResponse,
+  ApiSettings,
+  ApiStatus,
+  AutoStartStatus,
   CasparConfig,
+  ChannelCompatibility,
+  CheckResult,
+  Commissioning,
   DeckLinkDevice,
   DeckLinkStatus,
+  Diagnostic,
   GlobalConfig,
+  FontItem,
   GuiSettings,
+  LaunchOptions,
+  LibraryChanges,
+  LibraryMedia,
+  LibraryStatus,
+  LogPage,
+  LogQuery,
+  MetricsSettings,
+  MediaQuery,
+  MetricsStatus,
+  PortPreflight,
+  ResourceSeries,
+  RuleLoadSummary,
   SystemVersions,
+  TemplateDevSettings,
+  TemplateDevStatus,
+  TemplateItem,
+  TestSession,
+  TestTarget,
 } from './types';
 
 // ============================================================================
@@ -145,16 +170,21 @@ export async function getTestServerUrl(): Promise<string | null> {
   return invoke('get_test_server_url');
 }
 
-export async function testChannel(channel: number): Promise<void> {
-  return invoke('test_channel', { channel });
+/** Write the channel's identification slate into the media folder and play it. */
+export async function testChannel(channel: number, config: GlobalConfig): Promise<void> {
+  return invoke('test_channel', { channel, config });
+}
+
+export async function testAvSync(channel: number, config: GlobalConfig): Promise<void> {
+  return invoke('test_av_sync', { channel, config });
 }
 
 export async function stopChannelTest(channel: number): Promise<void> {
   return invoke('stop_channel_test', { channel });
 }
 
-export async function testAllChannels(channelCount: number): Promise<void> {
-  return invoke('test_all_channels', { channelCount });
+export async function testAllChannels(config: GlobalConfig): Promise<void> {
+  return invoke('test_all_channels', { config });
 }
 
 export async function stopAllChannelTests(channelCount: number): Promise<void> {
@@ -169,10 +199,8 @@ export async function getNdiVersion(): Promise<string | null> {
   return invoke('get_ndi_version');
 }
 
-export async function getScannerVersion(
-  url?: string
-): Promise<string | null> {
-  return invoke('get_scanner_version', { url });
+export async function getScannerVersion(): Promise<string | null> {
+  return invoke('get_scanner_version');
 }
 
 export async function getSystemVersions(): Promise<SystemVersions> {
@@ -187,8 +215,9 @@ export async function getPrimaryIp(): Promise<string | null> {
 // CasparCG Server Process Commands
 // ============================================================================
 
-export async function startCasparServer(config: GlobalConfig): Promise<void> {
-  return invoke('start_caspar_server', { config });
+/** `profile` is the saved profile the config came from; it is started again on the next launch. */
+export async function startCasparServer(config: GlobalConfig, profile?: string | null): Promise<void> {
+  return invoke('start_caspar_server', { config, profile: profile ?? null });
 }
 
 export async function stopCasparServer(): Promise<void> {
@@ -199,6 +228,227 @@ export async function casparServerRunning(): Promise<boolean> {
   return invoke('caspar_server_running');
 }
 
+/** The latest step of the start-on-launch countdown, if one ran. */
+export async function getAutoStart(): Promise<AutoStartStatus | null> {
+  return invoke('get_auto_start');
+}
+
+/** Keep the server stopped; returns whether a countdown was running. */
+export async function cancelAutoStart(): Promise<boolean> {
+  return invoke('cancel_auto_start');
+}
+
+// ============================================================================
+// Test Session Commands
+// ============================================================================
+
+export async function getTestSessions(): Promise<TestSession[]> {
+  return invoke('get_test_sessions');
+}
+
+/** Stop the test on a channel or output, whoever started it. */
+export async function stopTestSession(target: TestTarget): Promise<void> {
+  return invoke('stop_test_session', { target });
+}
+
+/** Stop every running test, e.g. before switching profile. */
+export async function releaseTestSessions(reason: string): Promise<void> {
+  return invoke('release_test_sessions', { reason });
+}
+
+// ============================================================================
+// Commissioning Commands
+// ============================================================================
+
+export async function getCommissioning(): Promise<Commissioning | null> {
+  return invoke('get_commissioning');
+}
+
+/** Start walking the active profile's outputs, replacing any open session. */
+export async function startCommissioning(operator: string): Promise<Commissioning> {
+  return invoke('start_commissioning', { operator });
+}
+
+/** Play a step's identification: the channel test, or the direct output test with the server stopped. */
+export async function playCommissioningStep(index: number): Promise<Commissioning> {
+  return invoke('play_commissioning_step', { index });
+}
+
+export async function recordCommissioningStep(
+  index: number,
+  result: CheckResult,
+  notes: string,
+): Promise<Commissioning> {
+  return invoke('record_commissioning_step', { index, result, notes });
+}
+
+export async function finishCommissioning(): Promise<Commissioning> {
+  return invoke('finish_commissioning');
+}
+
+export async function closeCommissioning(): Promise<void> {
+  return invoke('close_commissioning');
+}
+
+/** Write the report to the reports directory; returns its path. */
+export async function exportCommissioningReport(format: 'markdown' | 'html'): Promise<string> {
+  return invoke('export_commissioning_report', { format });
+}
+
+// ============================================================================
+// Log Commands
+// ============================================================================
+
+export async function getLogEntries(query?: LogQuery): Promise<LogPage> {
+  return invoke('get_log_entries', { query });
+}
+
+export async function getLogDir(): Promise<string | null> {
+  return invoke('get_log_dir');
+}
+
+// ============================================================================
+// Diagnostics Commands
+// ============================================================================
+
+export async function getDiagnostics(): Promise<Diagnostic[]> {
+  return invoke('get_diagnostics');
+}
+
+export async function clearDiagnostics(): Promise<void> {
+  return invoke('clear_diagnostics');
+}
+
+export async function reloadDiagnosticRules(): Promise<RuleLoadSummary> {
+  return invoke('reload_diagnostic_rules');
+}
+
+// ============================================================================
+// Resource Commands
+// ============================================================================
+
+/** Server and scanner samples taken after `since` (an ISO timestamp), or all. */
+export async function getResourceHistory(since?: string): Promise<ResourceSeries> {
+  return invoke('get_resource_history', { since: since ?? null });
+}
+
+// ============================================================================
+// Port Commands
+// ============================================================================
+
+/** Probe every port starting `config` would use, without starting it. */
+export async function checkPorts(config: GlobalConfig): Promise<PortPreflight> {
+  return invoke('check_ports', { config });
+}
+
+/** The ports the latest Start settled on, or null before the first Start. */
+export async function getPortPreflight(): Promise<PortPreflight | null> {
+  return invoke('get_port_preflight');
+}
+
+// ============================================================================
+// Media Library Commands
+// ============================================================================
+
+/** Clips from the scanner's last listing that match `query`. */
+export async function listMedia(query?: MediaQuery): Promise<LibraryMedia[]> {
+  return invoke('list_media', { query: query ?? null });
+}
+
+export async function listTemplates(search?: string): Promise<TemplateItem[]> {
+  return invoke('list_templates', { search: search ?? null });
+}
+
+export async function listFonts(search?: string): Promise<FontItem[]> {
+  return invoke('list_fonts', { search: search ?? null });
+}
+
+/** Re-read the scanner's listings now; fails while no scanner is running. */
+export async function refreshMediaLibrary(): Promise<LibraryChanges> {
+  return invoke('refresh_media_library');
+}
+
+export async function getMediaLibraryStatus(): Promise<LibraryStatus> {
+  return invoke('get_media_library_status');
+}
+
+/** A clip's thumbnail as a PNG data URL, or null while the scanner has none. */
+export async function getMediaThumbnail(name: string): Promise<string | null> {
+  return invoke('get_media_thumbnail', { name });
+}
+
+/** Clips whose frame rate or raster differs from each channel of `config`. */
+export async function checkMediaCompatibility(
+  config: GlobalConfig
+): Promise<ChannelCompatibility[]> {
+  return invoke('check_media_compatibility', { config });
+}
+
+/** Write the compatibility report as CSV to the reports directory; returns its path. */
+export async function exportMediaCompatibility(config: GlobalConfig): Promise<string> {
+  return invoke('export_media_compatibility', { config });
+}
+
+// ============================================================================
+// Crash Report Commands
+// ============================================================================
+
+/** Bundle logs, config, versions and DeckLink state into a zip; returns its path. */
+export async function createCrashReport(config: GlobalConfig | null): Promise<string> {
+  return invoke('create_crash_report', { config });
+}
+
+export async function getCrashReportDir(): Promise<string | null> {
+  return invoke('get_crash_report_dir');
+}
+
+// ============================================================================
+// Control API Commands
+// ============================================================================
+
+export async function getApiStatus(): Promise<ApiStatus> {
+  return invoke('get_api_status');
+}
+
+/** Save and apply; enabling without a token generates one. */
+export async function setApiSettings(settings: ApiSettings): Promise<ApiStatus> {
+  return invoke('set_api_settings', { settings });
+}
+
+// ============================================================================
+// Metrics Commands
+// ============================================================================
+
+export async function getMetricsStatus(): Promise<MetricsStatus> {
+  return invoke('get_metrics_status');
+}
+
+export async function setMetricsSettings(settings: MetricsSettings): Promise<MetricsStatus> {
+  return invoke('set_metrics_settings', { settings });
+}
+
+// ============================================================================
+// Template Dev Server Commands
+// ============================================================================
+
+export async function getTemplateDevStatus(): Promise<TemplateDevStatus> {
+  return invoke('get_template_dev_status');
+}
+
+/** Save the settings, serve and watch the workspace, and put the template on air. */
+export async function startTemplateDev(settings: TemplateDevSettings): Promise<TemplateDevStatus> {
+  return invoke('start_template_dev', { settings });
+}
+
+export async function stopTemplateDev(): Promise<TemplateDevStatus> {
+  return invoke('stop_template_dev');
+}
+
+/** Save the test data and send it to the running template. */
+export async function setTemplateDevData(data: string): Promise<void> {
+  return invoke('set_template_dev_data', { data });
+}
+
 // ============================================================================
 // GUI Settings Commands
 // ============================================================================
@@ -215,6 +465,14 @@ export async function setCasparPath(path: string): Promise<void> {
   return invoke('set_caspar_path', { path });
 }
 
+// ============================================================================
+// Launch Commands
+// ============================================================================
+
+export async function getLaunchOptions(): Promise<LaunchOptions> {
+  return invoke('get_launch_options');
+}
+
 // ============================================================================
 // File Dialog Commands
 // ============================================================================
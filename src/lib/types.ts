@@ -161,11 +161,23 @@ export interface AmcpConfig {
   media_server?: MediaServer;
 }
 
+export interface OscClient {
+  address: string;
+  port: number;
+}
+
+export interface OscConfig {
+  default_port?: number;
+  disable_send_to_amcp_clients?: boolean;
+  predefined_clients?: OscClient[];
+}
+
 export interface CasparConfig {
   paths: Paths;
   channels: Channel[];
   controllers: Controllers;
   amcp: AmcpConfig;
+  osc?: OscConfig;
   log_level?: string;
   log_categories?: string;
   force_deinterlace?: boolean;
@@ -193,6 +205,82 @@ export interface DeckLinkConfig {
   devices: DeckLinkDeviceConfig[];
 }
 
+// Hung-server watchdog: pings AMCP (and, with osc_port set, watches each
+// channel's per-frame OSC) and kills a server that stops answering so the
+// supervisor restarts it.
+export interface WatchdogConfig {
+  enabled: boolean;
+  interval_secs: number;
+  timeout_secs: number;
+  missed_threshold: number;
+  startup_grace_secs: number;
+  osc_port?: number;
+}
+
+// When the supervisor relaunches an exited server
+export type RestartMode = 'never' | 'on_failure' | 'always';
+
+export interface SupervisorConfig {
+  restart: RestartMode;
+  tick_secs: number;
+  backoff_secs: number;
+  backoff_multiplier: number;
+  backoff_max_secs: number;
+  max_crashes: number; // 0 retries indefinitely
+  crash_window_secs: number;
+  fatal_below: number;
+  restart_request_codes: number[];
+}
+
+// Periodic CPU / memory / thread / handle sampling of the server and scanner
+// process trees, with alerts such as "RSS above 6 GB for 5 minutes".
+export type ResourceProcess = 'server' | 'scanner';
+export type ResourceMetric = 'cpu_percent' | 'rss_bytes' | 'threads' | 'handles';
+
+export interface ResourceAlertRule {
+  process: ResourceProcess;
+  metric: ResourceMetric;
+  above: number;
+  for_secs: number;
+}
+
+export interface ResourceConfig {
+  enabled: boolean;
+  interval_secs: number;
+  alerts: ResourceAlertRule[];
+}
+
+// What Start's port pre-flight does when a port the profile names is taken
+export type PortConflictAction = 'block' | 'reassign';
+
+export interface PortPolicy {
+  amcp: PortConflictAction;
+  osc: PortConflictAction;
+}
+
+// Which media scanner serves CasparCG's <media-server>
+export type ScannerMode = 'external' | 'built_in';
+
+export interface ScannerConfig {
+  mode: ScannerMode;
+}
+
+// Audio played alongside the channel test's identification slates
+export type AudioIdentMode = 'ident' | 'lineup' | 'off';
+export type AudioTracks = 'stereo' | '8ch' | '16ch';
+
+export interface IdentConfig {
+  audio: AudioIdentMode;
+  tracks: AudioTracks;
+  lineup_dbfs: number;
+}
+
+export const DEFAULT_IDENT_CONFIG: IdentConfig = {
+  audio: 'ident',
+  tracks: 'stereo',
+  lineup_dbfs: -18,
+};
+
 export interface GlobalConfig {
   version: string;
   name: string;
@@ -200,6 +288,12 @@ export interface GlobalConfig {
   modified: string;
   caspar: CasparConfig;
   decklink: DeckLinkConfig;
+  watchdog?: WatchdogConfig;
+  supervisor?: SupervisorConfig;
+  resources?: ResourceConfig;
+  ports?: PortPolicy;
+  scanner?: ScannerConfig;
+  ident?: IdentConfig;
 }
 
 // ============================================================================
@@ -211,9 +305,125 @@ export interface GuiSettings {
   last_profile?: string;
   last_host?: string;
   last_port?: number;
-  last_server_was_running: boolean;
+  last_server_was_running: boolean; // written by the backend on start/stop/stand-down
+  last_amcp_connected?: boolean; // written by the backend on AMCP connect/disconnect
+  auto_start?: boolean; // start last_profile on launch even if it was stopped
+  auto_start_delay_secs?: number;
+  test_timeout_secs?: number; // stop channel and output tests after this long
   window_width?: number;
   window_height?: number;
+  api?: ApiSettings;
+  metrics?: MetricsSettings;
+  template_dev?: TemplateDevSettings;
+}
+
+// Payload of the 'auto-start' event: the countdown before the last profile is
+// started on launch, and how it ended
+export interface AutoStartStatus {
+  profile: string;
+  phase: 'countdown' | 'started' | 'cancelled' | 'failed';
+  remaining_secs: number;
+  error?: string;
+}
+
+/** What a test occupies: a channel's AMCP test layers, or a DeckLink output driven directly */
+export type TestTarget = { kind: 'channel'; channel: number } | { kind: 'output'; device: number };
+
+// A running test, as published on the 'test-sessions' event
+export interface TestSession {
+  id: number;
+  target: TestTarget;
+  label: string; // "identification", "AV sync", "SDI fill", …
+  owner: string; // "GUI", "commissioning", …
+  started: string;
+  deadline?: string;
+}
+
+/** What a commissioning step checks */
+export type CommissioningCheck =
+  | { kind: 'identify' }
+  | { kind: 'fill'; device: number }
+  | { kind: 'key'; device: number }
+  | { kind: 'audio_pair'; device: number; pair: number }
+  | { kind: 'ndi'; name: string }
+  | { kind: 'screen'; device: number }
+  | { kind: 'system_audio' };
+
+export type CheckResult = 'pass' | 'fail' | 'skipped';
+
+export interface CommissioningStep {
+  channel: number;
+  check: CommissioningCheck;
+  title: string;
+  instructions: string; // what the operator should see or hear
+  played?: 'amcp' | 'output_test';
+  result?: CheckResult;
+  notes: string;
+  checked?: string;
+}
+
+// A walk through every output of the active profile, as published on the
+// 'commissioning' event (null once closed)
+export interface Commissioning {
+  profile: string;
+  operator: string;
+  started: string;
+  finished?: string;
+  current?: number;
+  steps: CommissioningStep[];
+}
+
+/** The opt-in REST control API (see GET /api/v1/openapi.json) */
+export interface ApiSettings {
+  enabled: boolean;
+  /** host:port, e.g. 127.0.0.1:8750 or 0.0.0.0:8750 */
+  bind: string;
+  token?: string;
+}
+
+export interface ApiStatus extends ApiSettings {
+  /** Listening address while running */
+  address: string | null;
+}
+
+/** The opt-in Prometheus endpoint, served at /metrics */
+export interface MetricsSettings {
+  enabled: boolean;
+  /** host:port, e.g. 127.0.0.1:9750 or 0.0.0.0:9750 */
+  bind: string;
+}
+
+export interface MetricsStatus extends MetricsSettings {
+  /** Listening address while running */
+  address: string | null;
+}
+
+/** How a saved template is brought back on air: CG CLEAR + ADD, or CG UPDATE */
+export type TemplateReload = 'readd' | 'update';
+
+/** Serves a template workspace and reloads it in CasparCG on every save */
+export interface TemplateDevSettings {
+  workspace?: string;
+  /** Folder under the workspace to serve, e.g. dist */
+  serve_dir?: string;
+  /** Run in the workspace before each reload, e.g. npm run build */
+  build_command?: string;
+  /** Entry page, relative to the served folder */
+  template: string;
+  channel: number;
+  layer: number;
+  reload: TemplateReload;
+  /** Test data sent with every ADD and UPDATE (JSON or XML) */
+  data: string;
+  /** host:port, e.g. 127.0.0.1:8760 */
+  bind: string;
+}
+
+export interface TemplateDevStatus extends TemplateDevSettings {
+  /** Listening address while running */
+  address: string | null;
+  /** The URL CasparCG is given */
+  url: string | null;
 }
 
 // ============================================================================
@@ -270,7 +480,15 @@ export interface AmcpResponse {
 // UI State
 // ============================================================================
 
-export type TabId = 'server' | 'paths' | 'channels' | 'preview' | 'decklink' | 'system';
+export type TabId =
+  | 'server'
+  | 'paths'
+  | 'channels'
+  | 'preview'
+  | 'templates'
+  | 'decklink'
+  | 'commissioning'
+  | 'system';
 
 export interface ConnectionStatus {
   connected: boolean;
@@ -289,6 +507,237 @@ export interface ScannerEndpoint {
   isDefault: boolean;
 }
 
+// ============================================================================
+// Media Library
+// ============================================================================
+
+// The scanner's listings as the backend keeps them. Names are CasparCG's: upper
+// case, relative to the media/template folder, without an extension.
+export type MediaKind = 'movie' | 'still' | 'audio';
+
+export interface LibraryMedia {
+  name: string;
+  folder: string;
+  kind: MediaKind;
+  size: number;
+  modified: string | null;
+  frames: number;
+  duration_secs: number | null;
+  frame_rate: number | null;
+  width: number | null;
+  height: number | null;
+  codec: string | null;
+  field_order: string | null;
+  has_thumbnail: boolean;
+}
+
+export interface MediaQuery {
+  search?: string;
+  kind?: MediaKind;
+  folder?: string;
+  codec?: string;
+  width?: number;
+  height?: number;
+  frame_rate?: number;
+  min_duration_secs?: number;
+  max_duration_secs?: number;
+}
+
+export interface TemplateItem {
+  name: string;
+  size: number | null;
+  modified: string | null;
+  kind: string | null;
+}
+
+export interface FontItem {
+  name: string;
+  file: string | null;
+}
+
+// Payload of the `media-library` event
+export interface LibraryChanges {
+  added: string[];
+  changed: string[];
+  removed: string[];
+  templates_changed: boolean;
+  fonts_changed: boolean;
+}
+
+export interface LibraryStatus {
+  media: number;
+  templates: number;
+  fonts: number;
+  refreshed: string | null;
+  endpoint: ScannerEndpoint | null;
+  error: string | null;
+}
+
+// How a clip's motion differs from its channel: evenly repeated/dropped
+// frames, irregular (judder without conversion), or interlaced on progressive
+export type TimingMismatch = 'cadence_compatible' | 'needs_conversion' | 'interlace_mismatch';
+
+export type RasterMismatch = 'upscaled' | 'downscaled' | 'reshaped';
+
+export interface MediaMismatch {
+  name: string;
+  width: number | null;
+  height: number | null;
+  frame_rate: number | null;
+  interlaced: boolean;
+  timing: TimingMismatch | null;
+  raster: RasterMismatch | null;
+  detail: string;
+}
+
+export interface ChannelCompatibility {
+  channel: number;
+  video_mode: VideoMode;
+  checked: number;
+  unknown: number;
+  mismatches: MediaMismatch[];
+}
+
+// ============================================================================
+// Server Log
+// ============================================================================
+
+// Console output is parsed by the backend into structured entries, one per
+// line, tagged with the process that wrote it. Launcher entries are the
+// supervisor's own messages (start, restart, give-up).
+export type LogSource = 'server' | 'scanner' | 'launcher';
+
+export type LogLevel = 'trace' | 'debug' | 'info' | 'warning' | 'error' | 'fatal';
+
+export interface LogEntry {
+  seq: number;
+  source: LogSource;
+  level: LogLevel;
+  timestamp: string;
+  text: string;
+}
+
+export interface LogQuery {
+  sources?: LogSource[];
+  min_level?: LogLevel;
+  search?: string;
+  after_seq?: number;
+  before_seq?: number;
+  limit?: number;
+}
+
+export interface LogPage {
+  entries: LogEntry[];
+  has_more: boolean;
+  // Tailing with after_seq: newer matches remain after the last entry
+  has_newer: boolean;
+  latest_seq: number | null;
+}
+
+// ============================================================================
+// Diagnostics
+// ============================================================================
+
+// A known failure recognised in the log or in the server's exit code, matched
+// against the backend's rule catalogue (built-in plus diagnostic-rules.json).
+export type DiagnosticSeverity = 'info' | 'warning' | 'error' | 'critical';
+
+export type DiagnosticTrigger =
+  | { kind: 'log_line'; source: LogSource; seq: number; text: string }
+  | { kind: 'exit_code'; code: number }
+  | { kind: 'event'; event: string; reason: string; last_output: string[] };
+
+export interface Diagnostic {
+  rule_id: string;
+  severity: DiagnosticSeverity;
+  title: string;
+  explanation: string;
+  likely_cause: string;
+  suggested_action: string;
+  trigger: DiagnosticTrigger;
+  timestamp: string;
+}
+
+export interface RuleLoadSummary {
+  rule_count: number;
+  user_rules_path: string | null;
+  errors: string[];
+}
+
+// ============================================================================
+// Resources
+// ============================================================================
+
+// One sample of a launched process and its descendants (CEF helpers), summed
+export interface ResourceSample {
+  timestamp: string;
+  pid: number;
+  processes: number;
+  cpu_percent: number; // 100 is one full core
+  rss_bytes: number;
+  threads: number;
+  handles: number; // file descriptors on Linux
+}
+
+export interface ResourceSeries {
+  server: ResourceSample[];
+  scanner: ResourceSample[];
+}
+
+// Payload of the 'resource-alert' event: raised (active) or cleared
+export interface ResourceAlert {
+  rule: ResourceAlertRule;
+  value: number;
+  active: boolean;
+  message: string;
+}
+
+// ============================================================================
+// Ports
+// ============================================================================
+
+export type PortRole = 'amcp' | 'scanner' | 'osc' | 'test_server' | 'control_api' | 'metrics' | 'template_dev';
+
+export const PORT_ROLE_LABELS: Record<PortRole, string> = {
+  amcp: 'AMCP',
+  scanner: 'Media scanner',
+  osc: 'Watchdog OSC',
+  test_server: 'Test server',
+  control_api: 'Control API',
+  metrics: 'Metrics',
+  template_dev: 'Template dev server',
+};
+
+export type PortStatus = 'free' | 'held' | 'reassigned' | 'blocked';
+
+export interface PortCheck {
+  role: PortRole;
+  protocol: 'tcp' | 'udp';
+  requested: number;
+  // The port in use: `requested` unless reassigned
+  port: number;
+  status: PortStatus;
+  // Who holds `requested`, when it was taken
+  conflict?: string;
+}
+
+// Result of the port pre-flight; also the payload of the 'port-preflight' event
+export interface PortPreflight {
+  checks: PortCheck[];
+}
+
+// ============================================================================
+// Launch
+// ============================================================================
+
+// What the window needs from the command line the GUI was launched with
+export interface LaunchOptions {
+  // --read-only: the backend refuses saves, starts, stops and commands
+  read_only: boolean;
+  // A profile file named on the command line, opened unsaved
+  open_file: string | null;
+}
+
 // ============================================================================
 // Default Values
 // ============================================================================